@@ -0,0 +1,69 @@
+/// A raw token amount in its mint's base units, with the mint's decimals
+///
+/// Prices are quoted in SOL per whole token, so converting between a raw amount
+/// and its SOL value has to go through the decimals; lamport scaling only applies
+/// to the SOL side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Amount {
+    pub raw: u64,
+    pub decimals: u8,
+}
+
+impl Amount {
+    pub fn new(raw: u64, decimals: u8) -> Self {
+        Self { raw, decimals }
+    }
+
+    /// Tokens `sol` buys at `price` SOL per whole token
+    pub fn for_sol(sol: f64, price: f64, decimals: u8) -> Self {
+        Self::from_ui(sol / price, decimals)
+    }
+
+    /// Nearest raw amount to `ui` whole tokens
+    pub fn from_ui(ui: f64, decimals: u8) -> Self {
+        Self::new((ui * unit(decimals)).round() as u64, decimals)
+    }
+
+    /// Whole tokens, as wallets display them
+    pub fn ui(&self) -> f64 {
+        self.raw as f64 / unit(self.decimals)
+    }
+
+    /// Value in SOL at `price` SOL per whole token
+    pub fn sol_value(&self, price: f64) -> f64 {
+        self.ui() * price
+    }
+}
+
+/// Base units in one whole token
+fn unit(decimals: u8) -> f64 {
+    10f64.powi(decimals as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_six_decimal_token_value() {
+        // pump.fun mint: 1,500 tokens at 0.00003 SOL each
+        let amount = Amount::new(1_500_000_000, 6);
+
+        assert_eq!(amount.ui(), 1_500.0);
+        assert!((amount.sol_value(0.00003) - 0.045).abs() < 1e-12);
+        assert_eq!(Amount::for_sol(0.045, 0.00003, 6).raw, 1_500_000_000);
+    }
+
+    #[test]
+    fn test_nine_decimal_token_value() {
+        // The same 1,500 tokens of a 9-decimal mint have 1,000x the raw units
+        let amount = Amount::new(1_500_000_000_000, 9);
+
+        assert_eq!(amount.ui(), 1_500.0);
+        assert!((amount.sol_value(0.00003) - 0.045).abs() < 1e-12);
+        assert_eq!(Amount::for_sol(0.045, 0.00003, 9).raw, 1_500_000_000_000);
+
+        // Reading those raw units as a 6-decimal amount would overvalue them 1,000x
+        assert!((Amount::new(amount.raw, 6).sol_value(0.00003) - 45.0).abs() < 1e-9);
+    }
+}
@@ -1,22 +1,231 @@
-use crate::types::{TokenMetrics, TradingSignal, SignalType, StrategyType, StrategyExitParams};
-use crate::error::Result;
+use crate::types::{HolderThresholds, ReasonCode, TokenMetrics, TradingSignal, SignalType, StrategyType, StrategyExitParams};
+use crate::error::{BotError, Result};
+use crate::blacklist::Blacklist;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::sync::Arc;
 use tracing::{info, warn};
 
+/// Parse the scanned mint, rejecting malformed addresses from bad API payloads
+fn parse_mint(metrics: &TokenMetrics) -> Result<Pubkey> {
+    metrics.mint
+        .parse()
+        .map_err(|e| BotError::Analysis(format!("Invalid mint {:?}: {}", metrics.mint, e)))
+}
+
+/// Deal-breakers that force `Hold` whichever strategy analyzed the token: a
+/// blacklisted creator or a critical on-chain rug flag
+pub fn hard_rejection(metrics: &TokenMetrics, blacklist: Option<&Blacklist>) -> Option<(ReasonCode, String)> {
+    if let (Some(blacklist), Some(creator)) = (blacklist, &metrics.creator) {
+        if blacklist.is_creator_blocked(creator) {
+            return Some((ReasonCode::BlacklistedCreator, format!("blacklisted creator {}", creator)));
+        }
+    }
+
+    metrics.rug_risk
+        .as_ref()
+        .filter(|r| r.has_critical())
+        .map(|risk| (ReasonCode::RugRisk, format!("rug risk {:?}", risk.flags)))
+}
+
+/// Reasons behind a signal, each filed under the code it is aggregated by
+#[derive(Debug, Default)]
+struct Reasons {
+    text: Vec<String>,
+    codes: Vec<ReasonCode>,
+}
+
+impl Reasons {
+    fn push(&mut self, code: ReasonCode, reason: String) {
+        self.codes.push(code);
+        self.text.push(reason);
+    }
+
+    fn extend(&mut self, other: Reasons) {
+        self.codes.extend(other.codes);
+        self.text.extend(other.text);
+    }
+
+    fn insert(&mut self, index: usize, code: ReasonCode, reason: String) {
+        self.codes.insert(index, code);
+        self.text.insert(index, reason);
+    }
+}
+
 /// Trading Strategy Trait - All strategies must implement this
 pub trait TradingStrategy: Send + Sync {
     fn analyze(&self, metrics: &TokenMetrics) -> Result<TradingSignal>;
     fn get_exit_params(&self) -> StrategyExitParams;
     fn name(&self) -> &str;
+    fn strategy_type(&self) -> StrategyType;
+
+    /// Per-factor scores behind the signal, for strategies built from separable factors
+    fn factor_scores(&self, _metrics: &TokenMetrics) -> Option<FactorScores> {
+        None
+    }
+}
+
+/// Relative weight of each `TokenAnalyzer` factor; must sum to 1.0
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FactorWeights {
+    pub volume: f64,
+    pub liquidity: f64,
+    pub holders: f64,
+    pub momentum: f64,
+    pub pressure: f64,
+    pub bonding_curve: f64,
+}
+
+impl Default for FactorWeights {
+    fn default() -> Self {
+        Self {
+            volume: 0.25,
+            liquidity: 0.20,
+            holders: 0.15,
+            momentum: 0.20,
+            pressure: 0.10,
+            bonding_curve: 0.10,
+        }
+    }
+}
+
+impl FactorWeights {
+    const SUM_EPSILON: f64 = 1e-6;
+
+    pub fn validate(&self) -> Result<()> {
+        let weights = [
+            self.volume,
+            self.liquidity,
+            self.holders,
+            self.momentum,
+            self.pressure,
+            self.bonding_curve,
+        ];
+
+        if weights.iter().any(|w| w.is_nan() || *w < 0.0) {
+            return Err(BotError::Config(format!("Factor weights must be non-negative: {:?}", self)));
+        }
+
+        let sum: f64 = weights.iter().sum();
+        if (sum - 1.0).abs() > Self::SUM_EPSILON {
+            return Err(BotError::Config(format!("Factor weights must sum to 1.0, got {}", sum)));
+        }
+
+        Ok(())
+    }
+}
+
+/// Unweighted 0-1 score of each `TokenAnalyzer` factor
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FactorScores {
+    pub volume: f64,
+    pub liquidity: f64,
+    pub holders: f64,
+    pub momentum: f64,
+    pub pressure: f64,
+    pub bonding_curve: f64,
 }
 
+/// Absolute 5m volume (SOL) a token must trade before it can be bought at all
+pub const DEFAULT_VOLUME_FLOOR_5M: f64 = 0.5;
+
+/// Position size (SOL) liquidity is judged against when none is configured
+pub const DEFAULT_POSITION_SIZE_SOL: f64 = 1.0;
+
+/// Price impact at which a position's liquidity stops scoring; at the default size this
+/// matches the old 5 SOL liquidity minimum
+pub const DEFAULT_MAX_PRICE_IMPACT: f64 = 0.20;
+
+/// Fractional price impact of buying `sol_size` SOL of the token
+///
+/// On a constant-product curve with `S` virtual SOL reserves, the average fill of a
+/// buy of `x` SOL is `(S + x) / S` times the spot price, so the impact is `x / S`.
+/// Without known reserves the SOL side of the pool is taken to be `liquidity_sol`.
+pub fn estimate_price_impact(metrics: &TokenMetrics, sol_size: f64) -> f64 {
+    let sol_reserves = if metrics.virtual_sol_reserves > 0.0 {
+        metrics.virtual_sol_reserves
+    } else {
+        metrics.liquidity_sol
+    };
+    if sol_reserves.is_nan() || sol_reserves <= 0.0 {
+        return f64::INFINITY;
+    }
+
+    sol_size.max(0.0) / sol_reserves
+}
+
+/// A `TokenAnalyzer` preset by name, for users who'd rather not tune thresholds
+///
+/// | preset       | min liquidity | min 5m volume | 5m volume floor | min holders | max concentration | max price impact |
+/// |--------------|---------------|---------------|-----------------|-------------|-------------------|------------------|
+/// | `aggressive` | 2 SOL         | 5 SOL         | 0.2 SOL         | 25          | 0.45              | 0.30             |
+/// | `balanced`   | 5 SOL         | 10 SOL        | 0.5 SOL         | 50          | 0.30              | 0.20             |
+/// | `defensive`  | 10 SOL        | 20 SOL        | 1.0 SOL         | 100         | 0.20              | 0.10             |
+///
+/// Factor weights (volume / liquidity / holders / momentum / pressure / bonding curve):
+/// `aggressive` 0.25 / 0.10 / 0.10 / 0.30 / 0.15 / 0.10, `balanced` the defaults
+/// (0.25 / 0.20 / 0.15 / 0.20 / 0.10 / 0.10), `defensive` 0.20 / 0.30 / 0.25 / 0.10 / 0.10 / 0.05.
+/// `balanced` is the conservative strategy's built-in configuration.
+pub fn profile(name: &str) -> Result<TokenAnalyzer> {
+    let (analyzer, weights) = match name.trim().to_ascii_lowercase().as_str() {
+        "aggressive" => (
+            TokenAnalyzer::new(2.0, 5.0, 25, 0.45)
+                .with_volume_floor(0.2)
+                .with_price_impact(DEFAULT_POSITION_SIZE_SOL, 0.30),
+            FactorWeights {
+                volume: 0.25,
+                liquidity: 0.10,
+                holders: 0.10,
+                momentum: 0.30,
+                pressure: 0.15,
+                bonding_curve: 0.10,
+            },
+        ),
+        "balanced" => (TokenAnalyzer::new(5.0, 10.0, 50, 0.3), FactorWeights::default()),
+        "defensive" => (
+            TokenAnalyzer::new(10.0, 20.0, 100, 0.2)
+                .with_volume_floor(1.0)
+                .with_price_impact(DEFAULT_POSITION_SIZE_SOL, 0.10),
+            FactorWeights {
+                volume: 0.20,
+                liquidity: 0.30,
+                holders: 0.25,
+                momentum: 0.10,
+                pressure: 0.10,
+                bonding_curve: 0.05,
+            },
+        ),
+        _ => {
+            return Err(BotError::Config(format!(
+                "Unknown analyzer profile {:?} (expected aggressive, balanced or defensive)",
+                name
+            )))
+        }
+    };
+
+    analyzer.with_weights(weights)
+}
+
+/// Wash-trade score above which the volume factor is discounted by that score
+const WASH_TRADE_PENALTY_THRESHOLD: f64 = 0.5;
+
 /// Advanced Multi-Factor Token Analysis (Conservative Strategy)
 /// Based on 7 years of DeFi trading expertise
 pub struct TokenAnalyzer {
     // Configurable thresholds
     min_liquidity: f64,
     min_volume_5m: f64,
+    /// 5m volume below this is a dead token, however well it scores otherwise
+    volume_floor_5m: f64,
+    /// Size of the position liquidity is scored for
+    position_size_sol: f64,
+    /// Price impact of `position_size_sol` past which liquidity scores nothing
+    max_price_impact: f64,
     min_holder_count: u32,
     max_holder_concentration: f64,
+    weights: FactorWeights,
+    /// Tokens from blacklisted creators are always held
+    blacklist: Option<Arc<Blacklist>>,
 }
 
 impl TokenAnalyzer {
@@ -29,8 +238,50 @@ impl TokenAnalyzer {
         Self {
             min_liquidity,
             min_volume_5m,
+            volume_floor_5m: DEFAULT_VOLUME_FLOOR_5M,
+            position_size_sol: DEFAULT_POSITION_SIZE_SOL,
+            max_price_impact: DEFAULT_MAX_PRICE_IMPACT,
             min_holder_count,
             max_holder_concentration,
+            weights: FactorWeights::default(),
+            blacklist: None,
+        }
+    }
+
+    pub fn with_weights(mut self, weights: FactorWeights) -> Result<Self> {
+        weights.validate()?;
+        self.weights = weights;
+        Ok(self)
+    }
+
+    pub fn with_blacklist(mut self, blacklist: Arc<Blacklist>) -> Self {
+        self.blacklist = Some(blacklist);
+        self
+    }
+
+    /// Reject tokens whose 5m volume is below `floor` SOL; scoring still uses `min_volume_5m`
+    pub fn with_volume_floor(mut self, floor: f64) -> Self {
+        self.volume_floor_5m = floor;
+        self
+    }
+
+    /// Score liquidity by the price impact of a `position_size_sol` trade, scoring
+    /// nothing once it passes `max_price_impact`
+    pub fn with_price_impact(mut self, position_size_sol: f64, max_price_impact: f64) -> Self {
+        self.position_size_sol = position_size_sol;
+        self.max_price_impact = max_price_impact;
+        self
+    }
+
+    /// Score each factor on its own, before weighting
+    pub fn score_factors(&self, metrics: &TokenMetrics) -> FactorScores {
+        FactorScores {
+            volume: self.analyze_volume(metrics).0,
+            liquidity: self.analyze_liquidity(metrics).0,
+            holders: self.analyze_holders(metrics).0,
+            momentum: self.analyze_momentum(metrics).0,
+            pressure: self.analyze_pressure(metrics).0,
+            bonding_curve: self.analyze_bonding_curve(metrics).0,
         }
     }
 
@@ -38,49 +289,57 @@ impl TokenAnalyzer {
     pub fn analyze(&self, metrics: &TokenMetrics) -> Result<TradingSignal> {
         let mut score = 0.0;
         let mut max_score = 0.0;
-        let mut reasoning = Vec::new();
+        let mut reasoning = Reasons::default();
 
-        // Factor 1: Volume Analysis (Weight: 25%)
+        // Factor 1: Volume Analysis
         let (volume_score, volume_reason) = self.analyze_volume(metrics);
-        score += volume_score * 0.25;
-        max_score += 0.25;
+        score += volume_score * self.weights.volume;
+        max_score += self.weights.volume;
         reasoning.extend(volume_reason);
 
-        // Factor 2: Liquidity Analysis (Weight: 20%)
+        // Factor 2: Liquidity Analysis
         let (liquidity_score, liquidity_reason) = self.analyze_liquidity(metrics);
-        score += liquidity_score * 0.20;
-        max_score += 0.20;
+        score += liquidity_score * self.weights.liquidity;
+        max_score += self.weights.liquidity;
         reasoning.extend(liquidity_reason);
 
-        // Factor 3: Holder Distribution (Weight: 15%)
+        // Factor 3: Holder Distribution
         let (holder_score, holder_reason) = self.analyze_holders(metrics);
-        score += holder_score * 0.15;
-        max_score += 0.15;
+        score += holder_score * self.weights.holders;
+        max_score += self.weights.holders;
         reasoning.extend(holder_reason);
 
-        // Factor 4: Price Momentum (Weight: 20%)
+        // Factor 4: Price Momentum
         let (momentum_score, momentum_reason) = self.analyze_momentum(metrics);
-        score += momentum_score * 0.20;
-        max_score += 0.20;
+        score += momentum_score * self.weights.momentum;
+        max_score += self.weights.momentum;
         reasoning.extend(momentum_reason);
 
-        // Factor 5: Buy/Sell Pressure (Weight: 10%)
+        // Factor 5: Buy/Sell Pressure
         let (pressure_score, pressure_reason) = self.analyze_pressure(metrics);
-        score += pressure_score * 0.10;
-        max_score += 0.10;
+        score += pressure_score * self.weights.pressure;
+        max_score += self.weights.pressure;
         reasoning.extend(pressure_reason);
 
-        // Factor 6: Bonding Curve Progress (Weight: 10%)
+        // Factor 6: Bonding Curve Progress
         let (curve_score, curve_reason) = self.analyze_bonding_curve(metrics);
-        score += curve_score * 0.10;
-        max_score += 0.10;
+        score += curve_score * self.weights.bonding_curve;
+        max_score += self.weights.bonding_curve;
         reasoning.extend(curve_reason);
 
         // Normalize confidence score
         let confidence = score / max_score;
 
-        // Determine signal type based on confidence
-        let signal_type = self.determine_signal_type(confidence, metrics);
+        // Determine signal type based on confidence, unless a deal-breaker forces a hold
+        let rejection = self.rejection_reason(metrics);
+        let signal_type = match &rejection {
+            Some((code, reason)) => {
+                warn!("Token {} rejected: {}", metrics.symbol, reason);
+                reasoning.insert(0, *code, format!("REJECTED: {}", reason));
+                SignalType::Hold
+            }
+            None => self.determine_signal_type(confidence),
+        };
 
         info!(
             "Token {} analyzed: confidence={:.2}%, signal={:?}",
@@ -90,29 +349,31 @@ impl TokenAnalyzer {
         );
 
         Ok(TradingSignal {
-            token_mint: metrics.mint.parse().unwrap(),
+            token_mint: parse_mint(metrics)?,
             signal_type,
             confidence,
-            reasoning,
+            reasoning: reasoning.text,
+            reason_codes: reasoning.codes,
             timestamp: chrono::Utc::now().timestamp(),
+            rejected: rejection.is_some(),
         })
     }
 
     /// Factor 1: Volume Analysis
     /// Strong volume indicates real interest and reduces price impact
-    fn analyze_volume(&self, metrics: &TokenMetrics) -> (f64, Vec<String>) {
+    fn analyze_volume(&self, metrics: &TokenMetrics) -> (f64, Reasons) {
         let mut score = 0.0;
-        let mut reasons = Vec::new();
+        let mut reasons = Reasons::default();
 
         // 5-minute volume (most important for sniping)
         if metrics.volume_5m > self.min_volume_5m * 2.0 {
             score += 0.4;
-            reasons.push(format!("Exceptional 5m volume: {:.2} SOL", metrics.volume_5m));
+            reasons.push(ReasonCode::ExceptionalVolume, format!("Exceptional 5m volume: {:.2} SOL", metrics.volume_5m));
         } else if metrics.volume_5m > self.min_volume_5m {
             score += 0.2;
-            reasons.push(format!("Good 5m volume: {:.2} SOL", metrics.volume_5m));
+            reasons.push(ReasonCode::GoodVolume, format!("Good 5m volume: {:.2} SOL", metrics.volume_5m));
         } else {
-            reasons.push(format!("Low 5m volume: {:.2} SOL", metrics.volume_5m));
+            reasons.push(ReasonCode::LowVolume, format!("Low 5m volume: {:.2} SOL", metrics.volume_5m));
         }
 
         // Volume acceleration (1h vs 5m)
@@ -124,10 +385,10 @@ impl TokenAnalyzer {
 
         if volume_acceleration > 1.5 {
             score += 0.3;
-            reasons.push(format!("Volume accelerating: {:.2}x", volume_acceleration));
+            reasons.push(ReasonCode::VolumeAccelerating, format!("Volume accelerating: {:.2}x", volume_acceleration));
         } else if volume_acceleration > 1.0 {
             score += 0.15;
-            reasons.push(format!("Volume stable: {:.2}x", volume_acceleration));
+            reasons.push(ReasonCode::StableVolume, format!("Volume stable: {:.2}x", volume_acceleration));
         }
 
         // Unique buyers vs sellers ratio
@@ -139,32 +400,47 @@ impl TokenAnalyzer {
 
         if buyer_seller_ratio > 2.0 {
             score += 0.3;
-            reasons.push(format!("Strong buyer interest: {:.2}:1 ratio", buyer_seller_ratio));
+            reasons.push(ReasonCode::StrongBuyerInterest, format!("Strong buyer interest: {:.2}:1 ratio", buyer_seller_ratio));
         } else if buyer_seller_ratio > 1.2 {
             score += 0.15;
-            reasons.push(format!("Positive buyer/seller ratio: {:.2}:1", buyer_seller_ratio));
+            reasons.push(ReasonCode::BuyerInterest, format!("Positive buyer/seller ratio: {:.2}:1", buyer_seller_ratio));
+        }
+
+        // Volume from a few wallets trading with each other isn't real interest
+        if metrics.wash_trade_score > WASH_TRADE_PENALTY_THRESHOLD {
+            score *= 1.0 - metrics.wash_trade_score;
+            reasons.push(ReasonCode::WashTrading, format!("Suspected wash trading: score {:.2}", metrics.wash_trade_score));
         }
 
         (score, reasons)
     }
 
     /// Factor 2: Liquidity Analysis
-    /// Higher liquidity = lower slippage and easier exits
-    fn analyze_liquidity(&self, metrics: &TokenMetrics) -> (f64, Vec<String>) {
+    /// Scored by the price impact of the intended position: lower impact = cheaper
+    /// entries and easier exits
+    fn analyze_liquidity(&self, metrics: &TokenMetrics) -> (f64, Reasons) {
         let mut score = 0.0;
-        let mut reasons = Vec::new();
+        let mut reasons = Reasons::default();
+
+        let impact = estimate_price_impact(metrics, self.position_size_sol);
+        let detail = format!(
+            "{:.2} SOL, {:.1}% impact for {:.2} SOL",
+            metrics.liquidity_sol,
+            impact * 100.0,
+            self.position_size_sol
+        );
 
-        if metrics.liquidity_sol > self.min_liquidity * 3.0 {
+        if impact < self.max_price_impact / 3.0 {
             score += 1.0;
-            reasons.push(format!("Excellent liquidity: {:.2} SOL", metrics.liquidity_sol));
-        } else if metrics.liquidity_sol > self.min_liquidity * 1.5 {
+            reasons.push(ReasonCode::HighLiquidity, format!("Excellent liquidity: {}", detail));
+        } else if impact < self.max_price_impact / 1.5 {
             score += 0.7;
-            reasons.push(format!("Good liquidity: {:.2} SOL", metrics.liquidity_sol));
-        } else if metrics.liquidity_sol > self.min_liquidity {
+            reasons.push(ReasonCode::AdequateLiquidity, format!("Good liquidity: {}", detail));
+        } else if impact < self.max_price_impact {
             score += 0.4;
-            reasons.push(format!("Adequate liquidity: {:.2} SOL", metrics.liquidity_sol));
+            reasons.push(ReasonCode::AdequateLiquidity, format!("Adequate liquidity: {}", detail));
         } else {
-            reasons.push(format!("Low liquidity: {:.2} SOL (risky)", metrics.liquidity_sol));
+            reasons.push(ReasonCode::LowLiquidity, format!("Low liquidity: {} (risky)", detail));
         }
 
         (score, reasons)
@@ -172,36 +448,36 @@ impl TokenAnalyzer {
 
     /// Factor 3: Holder Distribution Analysis
     /// Well-distributed = less rug risk
-    fn analyze_holders(&self, metrics: &TokenMetrics) -> (f64, Vec<String>) {
+    fn analyze_holders(&self, metrics: &TokenMetrics) -> (f64, Reasons) {
         let mut score = 0.0;
-        let mut reasons = Vec::new();
+        let mut reasons = Reasons::default();
 
         // Holder count
         if metrics.holder_count > self.min_holder_count * 3 {
             score += 0.5;
-            reasons.push(format!("Strong holder base: {} holders", metrics.holder_count));
+            reasons.push(ReasonCode::StrongHolderBase, format!("Strong holder base: {} holders", metrics.holder_count));
         } else if metrics.holder_count > self.min_holder_count {
             score += 0.3;
-            reasons.push(format!("Good holder count: {}", metrics.holder_count));
+            reasons.push(ReasonCode::GoodHolderBase, format!("Good holder count: {}", metrics.holder_count));
         } else {
-            reasons.push(format!("Low holder count: {} (risky)", metrics.holder_count));
+            reasons.push(ReasonCode::LowHolderCount, format!("Low holder count: {} (risky)", metrics.holder_count));
         }
 
         // Concentration (lower is better - more distributed)
         if metrics.holder_concentration < self.max_holder_concentration * 0.5 {
             score += 0.5;
-            reasons.push(format!(
+            reasons.push(ReasonCode::WellDistributed, format!(
                 "Well distributed: {:.1}% top holder concentration",
                 metrics.holder_concentration * 100.0
             ));
         } else if metrics.holder_concentration < self.max_holder_concentration {
             score += 0.25;
-            reasons.push(format!(
+            reasons.push(ReasonCode::AcceptableDistribution, format!(
                 "Acceptable distribution: {:.1}% concentration",
                 metrics.holder_concentration * 100.0
             ));
         } else {
-            reasons.push(format!(
+            reasons.push(ReasonCode::HighConcentration, format!(
                 "High concentration risk: {:.1}% (whale risk)",
                 metrics.holder_concentration * 100.0
             ));
@@ -212,31 +488,31 @@ impl TokenAnalyzer {
 
     /// Factor 4: Price Momentum Analysis
     /// Positive momentum indicates trend strength
-    fn analyze_momentum(&self, metrics: &TokenMetrics) -> (f64, Vec<String>) {
+    fn analyze_momentum(&self, metrics: &TokenMetrics) -> (f64, Reasons) {
         let mut score = 0.0;
-        let mut reasons = Vec::new();
+        let mut reasons = Reasons::default();
 
         // 5-minute momentum (most important for entry timing)
         if metrics.price_change_5m > 0.20 {
             score += 0.5;
-            reasons.push(format!("Strong 5m momentum: +{:.1}%", metrics.price_change_5m * 100.0));
+            reasons.push(ReasonCode::StrongMomentum, format!("Strong 5m momentum: +{:.1}%", metrics.price_change_5m * 100.0));
         } else if metrics.price_change_5m > 0.10 {
             score += 0.3;
-            reasons.push(format!("Good 5m momentum: +{:.1}%", metrics.price_change_5m * 100.0));
+            reasons.push(ReasonCode::PositiveMomentum, format!("Good 5m momentum: +{:.1}%", metrics.price_change_5m * 100.0));
         } else if metrics.price_change_5m > 0.0 {
             score += 0.1;
-            reasons.push(format!("Positive 5m: +{:.1}%", metrics.price_change_5m * 100.0));
+            reasons.push(ReasonCode::PositiveMomentum, format!("Positive 5m: +{:.1}%", metrics.price_change_5m * 100.0));
         } else {
-            reasons.push(format!("Negative 5m: {:.1}%", metrics.price_change_5m * 100.0));
+            reasons.push(ReasonCode::WeakMomentum, format!("Negative 5m: {:.1}%", metrics.price_change_5m * 100.0));
         }
 
         // 1-hour momentum
         if metrics.price_change_1h > 0.50 {
             score += 0.5;
-            reasons.push(format!("Explosive 1h growth: +{:.1}%", metrics.price_change_1h * 100.0));
+            reasons.push(ReasonCode::StrongMomentum, format!("Explosive 1h growth: +{:.1}%", metrics.price_change_1h * 100.0));
         } else if metrics.price_change_1h > 0.25 {
             score += 0.3;
-            reasons.push(format!("Strong 1h growth: +{:.1}%", metrics.price_change_1h * 100.0));
+            reasons.push(ReasonCode::StrongMomentum, format!("Strong 1h growth: +{:.1}%", metrics.price_change_1h * 100.0));
         } else if metrics.price_change_1h > 0.0 {
             score += 0.1;
         }
@@ -245,27 +521,23 @@ impl TokenAnalyzer {
     }
 
     /// Factor 5: Buy/Sell Pressure Analysis
-    fn analyze_pressure(&self, metrics: &TokenMetrics) -> (f64, Vec<String>) {
+    fn analyze_pressure(&self, metrics: &TokenMetrics) -> (f64, Reasons) {
         let mut score = 0.0;
-        let mut reasons = Vec::new();
+        let mut reasons = Reasons::default();
 
-        let pressure_ratio = if metrics.sell_pressure > 0.0 {
-            metrics.buy_pressure / metrics.sell_pressure
-        } else {
-            metrics.buy_pressure
-        };
+        let pressure_ratio = metrics.pressure_ratio();
 
         if pressure_ratio > 3.0 {
             score += 1.0;
-            reasons.push(format!("Dominant buy pressure: {:.2}:1", pressure_ratio));
+            reasons.push(ReasonCode::StrongBuyPressure, format!("Dominant buy pressure: {:.2}:1", pressure_ratio));
         } else if pressure_ratio > 1.5 {
             score += 0.7;
-            reasons.push(format!("Strong buy pressure: {:.2}:1", pressure_ratio));
+            reasons.push(ReasonCode::StrongBuyPressure, format!("Strong buy pressure: {:.2}:1", pressure_ratio));
         } else if pressure_ratio > 1.0 {
             score += 0.4;
-            reasons.push(format!("Positive buy pressure: {:.2}:1", pressure_ratio));
+            reasons.push(ReasonCode::BuyPressure, format!("Positive buy pressure: {:.2}:1", pressure_ratio));
         } else {
-            reasons.push(format!("Sell pressure dominant: {:.2}:1", pressure_ratio));
+            reasons.push(ReasonCode::SellPressure, format!("Sell pressure dominant: {:.2}:1", pressure_ratio));
         }
 
         (score, reasons)
@@ -273,34 +545,34 @@ impl TokenAnalyzer {
 
     /// Factor 6: Bonding Curve Analysis
     /// Sweet spot: 30-70% - enough validation, but room to grow
-    fn analyze_bonding_curve(&self, metrics: &TokenMetrics) -> (f64, Vec<String>) {
+    fn analyze_bonding_curve(&self, metrics: &TokenMetrics) -> (f64, Reasons) {
         let mut score = 0.0;
-        let mut reasons = Vec::new();
+        let mut reasons = Reasons::default();
 
         if metrics.is_graduated {
             score += 0.5;
-            reasons.push("Token graduated to DEX".to_string());
+            reasons.push(ReasonCode::Graduated, "Token graduated to DEX".to_string());
         } else if metrics.bonding_curve_progress > 70.0 {
             score += 0.8;
-            reasons.push(format!(
+            reasons.push(ReasonCode::NearGraduation, format!(
                 "Near graduation: {:.1}% bonding curve",
                 metrics.bonding_curve_progress
             ));
         } else if metrics.bonding_curve_progress > 30.0 {
             score += 1.0;
-            reasons.push(format!(
+            reasons.push(ReasonCode::CurveSweetSpot, format!(
                 "Sweet spot: {:.1}% bonding curve (validated + room to grow)",
                 metrics.bonding_curve_progress
             ));
         } else if metrics.bonding_curve_progress > 10.0 {
             score += 0.6;
-            reasons.push(format!(
+            reasons.push(ReasonCode::EarlyCurve, format!(
                 "Early stage: {:.1}% bonding curve",
                 metrics.bonding_curve_progress
             ));
         } else {
             score += 0.2;
-            reasons.push(format!(
+            reasons.push(ReasonCode::EarlyCurve, format!(
                 "Very early: {:.1}% bonding curve (high risk)",
                 metrics.bonding_curve_progress
             ));
@@ -310,18 +582,40 @@ impl TokenAnalyzer {
     }
 
     /// Determine signal type based on confidence and other factors
-    fn determine_signal_type(&self, confidence: f64, metrics: &TokenMetrics) -> SignalType {
-        // Check for deal-breakers
+    /// Why `metrics` fails a deal-breaker check, or `None` when the token may be
+    /// traded on its confidence
+    pub fn rejection_reason(&self, metrics: &TokenMetrics) -> Option<(ReasonCode, String)> {
+        if let Some(rejection) = hard_rejection(metrics, self.blacklist.as_deref()) {
+            return Some(rejection);
+        }
+
         if metrics.liquidity_sol < self.min_liquidity {
-            return SignalType::Hold;
+            return Some((ReasonCode::LowLiquidity, format!(
+                "liquidity {:.1} SOL below minimum {:.1}",
+                metrics.liquidity_sol, self.min_liquidity
+            )));
         }
 
-        if metrics.holder_concentration > self.max_holder_concentration * 1.5 {
-            warn!("Token {} rejected: too concentrated", metrics.symbol);
-            return SignalType::Hold;
+        if metrics.volume_5m < self.volume_floor_5m {
+            return Some((ReasonCode::LowVolume, format!(
+                "5m volume {:.2} SOL below floor {:.2}",
+                metrics.volume_5m, self.volume_floor_5m
+            )));
         }
 
-        // Signal based on confidence
+        let max_concentration = self.max_holder_concentration * 1.5;
+        if metrics.holder_concentration > max_concentration {
+            return Some((ReasonCode::HighConcentration, format!(
+                "holder concentration {:.0}% above maximum {:.0}%",
+                metrics.holder_concentration * 100.0,
+                max_concentration * 100.0
+            )));
+        }
+
+        None
+    }
+
+    fn determine_signal_type(&self, confidence: f64) -> SignalType {
         if confidence >= 0.80 {
             SignalType::StrongBuy
         } else if confidence >= 0.65 {
@@ -362,12 +656,22 @@ impl TradingStrategy for TokenAnalyzer {
             use_trailing_stop: false,
             trailing_activation_pct: 0.0,
             trailing_distance_pct: 0.0,
+            loss_cooldown_seconds: 1800,
+            take_profit_ladder: Vec::new(),
         }
     }
 
     fn name(&self) -> &str {
         "Conservative Multi-Factor"
     }
+
+    fn strategy_type(&self) -> StrategyType {
+        StrategyType::Conservative
+    }
+
+    fn factor_scores(&self, metrics: &TokenMetrics) -> Option<FactorScores> {
+        Some(self.score_factors(metrics))
+    }
 }
 
 // ============================================================================
@@ -389,48 +693,48 @@ impl UltraEarlySniper {
     fn analyze_impl(&self, metrics: &TokenMetrics) -> Result<TradingSignal> {
         let mut score = 0.0;
         let mut max_score = 0.0;
-        let mut reasoning = Vec::new();
+        let mut reasoning = Reasons::default();
 
         // CRITICAL: Must be ultra-early (< 5 minutes old)
         if metrics.time_since_creation > 300 {
             return Ok(TradingSignal {
-                token_mint: metrics.mint.parse().unwrap(),
+                token_mint: parse_mint(metrics)?,
                 signal_type: SignalType::Hold,
                 confidence: 0.0,
                 reasoning: vec!["Too old for ultra-early strategy (>5min)".to_string()],
+                reason_codes: vec![ReasonCode::OutsideEntryWindow],
                 timestamp: chrono::Utc::now().timestamp(),
+                rejected: false,
             });
         }
 
         // CRITICAL: Must be very early bonding curve (< 10%)
         if metrics.bonding_curve_progress > 10.0 {
             return Ok(TradingSignal {
-                token_mint: metrics.mint.parse().unwrap(),
+                token_mint: parse_mint(metrics)?,
                 signal_type: SignalType::Hold,
                 confidence: 0.0,
                 reasoning: vec!["Bonding curve too advanced for ultra-early (>10%)".to_string()],
+                reason_codes: vec![ReasonCode::OutsideEntryWindow],
                 timestamp: chrono::Utc::now().timestamp(),
+                rejected: false,
             });
         }
 
         // Factor 1: Buy Pressure (35% weight) - MOST IMPORTANT
-        let pressure_ratio = if metrics.sell_pressure > 0.0 {
-            metrics.buy_pressure / metrics.sell_pressure
-        } else {
-            metrics.buy_pressure
-        };
+        let pressure_ratio = metrics.pressure_ratio();
 
         if pressure_ratio > 10.0 {
             score += 1.0 * 0.35;
-            reasoning.push(format!("EXCEPTIONAL buy pressure: {:.1}:1 ratio", pressure_ratio));
+            reasoning.push(ReasonCode::StrongBuyPressure, format!("EXCEPTIONAL buy pressure: {:.1}:1 ratio", pressure_ratio));
         } else if pressure_ratio > 5.0 {
             score += 0.8 * 0.35;
-            reasoning.push(format!("Dominant buy pressure: {:.1}:1 ratio", pressure_ratio));
+            reasoning.push(ReasonCode::StrongBuyPressure, format!("Dominant buy pressure: {:.1}:1 ratio", pressure_ratio));
         } else if pressure_ratio > 3.0 {
             score += 0.5 * 0.35;
-            reasoning.push(format!("Strong buy pressure: {:.1}:1 ratio", pressure_ratio));
+            reasoning.push(ReasonCode::StrongBuyPressure, format!("Strong buy pressure: {:.1}:1 ratio", pressure_ratio));
         } else {
-            reasoning.push(format!("Weak buy pressure: {:.1}:1 (risky)", pressure_ratio));
+            reasoning.push(ReasonCode::SellPressure, format!("Weak buy pressure: {:.1}:1 (risky)", pressure_ratio));
         }
         max_score += 0.35;
 
@@ -443,55 +747,55 @@ impl UltraEarlySniper {
 
         if volume_acceleration > 5.0 {
             score += 1.0 * 0.30;
-            reasoning.push(format!("EXPLOSIVE volume acceleration: {:.1}x", volume_acceleration));
+            reasoning.push(ReasonCode::VolumeAccelerating, format!("EXPLOSIVE volume acceleration: {:.1}x", volume_acceleration));
         } else if volume_acceleration > 3.0 {
             score += 0.8 * 0.30;
-            reasoning.push(format!("Strong volume acceleration: {:.1}x", volume_acceleration));
+            reasoning.push(ReasonCode::VolumeAccelerating, format!("Strong volume acceleration: {:.1}x", volume_acceleration));
         } else if volume_acceleration > 1.5 {
             score += 0.5 * 0.30;
-            reasoning.push(format!("Good volume acceleration: {:.1}x", volume_acceleration));
+            reasoning.push(ReasonCode::VolumeAccelerating, format!("Good volume acceleration: {:.1}x", volume_acceleration));
         } else {
-            reasoning.push(format!("Low volume acceleration: {:.1}x", volume_acceleration));
+            reasoning.push(ReasonCode::LowVolume, format!("Low volume acceleration: {:.1}x", volume_acceleration));
         }
         max_score += 0.30;
 
         // Factor 3: Price Momentum 5m (20% weight)
         if metrics.price_change_5m > 0.50 {
             score += 1.0 * 0.20;
-            reasoning.push(format!("EXPLOSIVE 5m momentum: +{:.1}%", metrics.price_change_5m * 100.0));
+            reasoning.push(ReasonCode::StrongMomentum, format!("EXPLOSIVE 5m momentum: +{:.1}%", metrics.price_change_5m * 100.0));
         } else if metrics.price_change_5m > 0.30 {
             score += 0.8 * 0.20;
-            reasoning.push(format!("Strong 5m momentum: +{:.1}%", metrics.price_change_5m * 100.0));
+            reasoning.push(ReasonCode::StrongMomentum, format!("Strong 5m momentum: +{:.1}%", metrics.price_change_5m * 100.0));
         } else if metrics.price_change_5m > 0.15 {
             score += 0.5 * 0.20;
-            reasoning.push(format!("Good 5m momentum: +{:.1}%", metrics.price_change_5m * 100.0));
+            reasoning.push(ReasonCode::PositiveMomentum, format!("Good 5m momentum: +{:.1}%", metrics.price_change_5m * 100.0));
         } else {
-            reasoning.push(format!("Weak 5m momentum: +{:.1}%", metrics.price_change_5m * 100.0));
+            reasoning.push(ReasonCode::WeakMomentum, format!("Weak 5m momentum: +{:.1}%", metrics.price_change_5m * 100.0));
         }
         max_score += 0.20;
 
         // Factor 4: Holder Growth (10% weight)
         if metrics.unique_buyers_5m > 50 {
             score += 1.0 * 0.10;
-            reasoning.push(format!("Viral growth: {} new buyers in 5m", metrics.unique_buyers_5m));
+            reasoning.push(ReasonCode::StrongBuyerInterest, format!("Viral growth: {} new buyers in 5m", metrics.unique_buyers_5m));
         } else if metrics.unique_buyers_5m > 30 {
             score += 0.7 * 0.10;
-            reasoning.push(format!("Strong growth: {} new buyers", metrics.unique_buyers_5m));
+            reasoning.push(ReasonCode::BuyerInterest, format!("Strong growth: {} new buyers", metrics.unique_buyers_5m));
         } else if metrics.unique_buyers_5m > 20 {
             score += 0.4 * 0.10;
-            reasoning.push(format!("Good growth: {} new buyers", metrics.unique_buyers_5m));
+            reasoning.push(ReasonCode::BuyerInterest, format!("Good growth: {} new buyers", metrics.unique_buyers_5m));
         }
         max_score += 0.10;
 
         // Factor 5: Minimal Liquidity Check (5% weight)
         if metrics.liquidity_sol > self.min_liquidity * 3.0 {
             score += 1.0 * 0.05;
-            reasoning.push(format!("Good early liquidity: {:.1} SOL", metrics.liquidity_sol));
+            reasoning.push(ReasonCode::AdequateLiquidity, format!("Good early liquidity: {:.1} SOL", metrics.liquidity_sol));
         } else if metrics.liquidity_sol > self.min_liquidity {
             score += 0.5 * 0.05;
-            reasoning.push(format!("Adequate liquidity: {:.1} SOL", metrics.liquidity_sol));
+            reasoning.push(ReasonCode::AdequateLiquidity, format!("Adequate liquidity: {:.1} SOL", metrics.liquidity_sol));
         } else {
-            reasoning.push(format!("Very low liquidity: {:.1} SOL (high risk)", metrics.liquidity_sol));
+            reasoning.push(ReasonCode::LowLiquidity, format!("Very low liquidity: {:.1} SOL (high risk)", metrics.liquidity_sol));
         }
         max_score += 0.05;
 
@@ -519,11 +823,13 @@ impl UltraEarlySniper {
         );
 
         Ok(TradingSignal {
-            token_mint: metrics.mint.parse().unwrap(),
+            token_mint: parse_mint(metrics)?,
             signal_type,
             confidence,
-            reasoning,
+            reasoning: reasoning.text,
+            reason_codes: reasoning.codes,
             timestamp: chrono::Utc::now().timestamp(),
+            rejected: false,
         })
     }
 }
@@ -541,12 +847,18 @@ impl TradingStrategy for UltraEarlySniper {
             use_trailing_stop: false,
             trailing_activation_pct: 0.0,
             trailing_distance_pct: 0.0,
+            loss_cooldown_seconds: 600,    // 10 minutes
+            take_profit_ladder: Vec::new(),
         }
     }
 
     fn name(&self) -> &str {
         "Ultra-Early Sniper (High Risk)"
     }
+
+    fn strategy_type(&self) -> StrategyType {
+        StrategyType::UltraEarlySniper
+    }
 }
 
 // ============================================================================
@@ -570,16 +882,18 @@ impl MomentumScalper {
     fn analyze_impl(&self, metrics: &TokenMetrics) -> Result<TradingSignal> {
         let mut score = 0.0;
         let mut max_score = 0.0;
-        let mut reasoning = Vec::new();
+        let mut reasoning = Reasons::default();
 
         // Must be in sweet spot for momentum (40-80% bonding curve)
         if metrics.bonding_curve_progress < 40.0 || metrics.bonding_curve_progress > 80.0 {
             return Ok(TradingSignal {
-                token_mint: metrics.mint.parse().unwrap(),
+                token_mint: parse_mint(metrics)?,
                 signal_type: SignalType::Hold,
                 confidence: 0.0,
                 reasoning: vec![format!("Bonding curve {:.1}% outside momentum zone (40-80%)", metrics.bonding_curve_progress)],
+                reason_codes: vec![ReasonCode::OutsideEntryWindow],
                 timestamp: chrono::Utc::now().timestamp(),
+                rejected: false,
             });
         }
 
@@ -600,18 +914,18 @@ impl MomentumScalper {
         max_score += 0.40;
 
         if metrics.price_change_1h > 0.50 {
-            reasoning.push(format!("EXPLOSIVE 1h growth: +{:.1}%", metrics.price_change_1h * 100.0));
+            reasoning.push(ReasonCode::StrongMomentum, format!("EXPLOSIVE 1h growth: +{:.1}%", metrics.price_change_1h * 100.0));
         } else {
-            reasoning.push(format!("Weak 1h momentum: +{:.1}%", metrics.price_change_1h * 100.0));
+            reasoning.push(ReasonCode::WeakMomentum, format!("Weak 1h momentum: +{:.1}%", metrics.price_change_1h * 100.0));
         }
 
         // 5m momentum continuation
         if metrics.price_change_5m > 0.20 {
             score += 0.5 * 0.40;
-            reasoning.push(format!("Strong 5m continuation: +{:.1}%", metrics.price_change_5m * 100.0));
+            reasoning.push(ReasonCode::StrongMomentum, format!("Strong 5m continuation: +{:.1}%", metrics.price_change_5m * 100.0));
         } else if metrics.price_change_5m > 0.10 {
             score += 0.3 * 0.40;
-            reasoning.push(format!("Good 5m momentum: +{:.1}%", metrics.price_change_5m * 100.0));
+            reasoning.push(ReasonCode::PositiveMomentum, format!("Good 5m momentum: +{:.1}%", metrics.price_change_5m * 100.0));
         }
 
         // Factor 2: Volume Analysis (30% weight)
@@ -627,36 +941,39 @@ impl MomentumScalper {
 
         score += volume_score * 0.30;
         max_score += 0.30;
-        reasoning.push(format!("5m volume: {:.1} SOL", metrics.volume_5m));
-
-        // Factor 3: Buy Pressure (20% weight)
-        let pressure_ratio = if metrics.sell_pressure > 0.0 {
-            metrics.buy_pressure / metrics.sell_pressure
+        let volume_code = if volume_score >= 1.0 {
+            ReasonCode::ExceptionalVolume
+        } else if volume_score > 0.0 {
+            ReasonCode::GoodVolume
         } else {
-            metrics.buy_pressure
+            ReasonCode::LowVolume
         };
+        reasoning.push(volume_code, format!("5m volume: {:.1} SOL", metrics.volume_5m));
+
+        // Factor 3: Buy Pressure (20% weight)
+        let pressure_ratio = metrics.pressure_ratio();
 
         if pressure_ratio > 3.0 {
             score += 1.0 * 0.20;
-            reasoning.push(format!("Dominant buy pressure: {:.1}:1", pressure_ratio));
+            reasoning.push(ReasonCode::StrongBuyPressure, format!("Dominant buy pressure: {:.1}:1", pressure_ratio));
         } else if pressure_ratio > 2.0 {
             score += 0.7 * 0.20;
-            reasoning.push(format!("Strong buy pressure: {:.1}:1", pressure_ratio));
+            reasoning.push(ReasonCode::StrongBuyPressure, format!("Strong buy pressure: {:.1}:1", pressure_ratio));
         } else if pressure_ratio > 1.5 {
             score += 0.4 * 0.20;
-            reasoning.push(format!("Positive pressure: {:.1}:1", pressure_ratio));
+            reasoning.push(ReasonCode::BuyPressure, format!("Positive pressure: {:.1}:1", pressure_ratio));
         }
         max_score += 0.20;
 
         // Factor 4: Liquidity (10% weight)
         if metrics.liquidity_sol > self.min_liquidity * 2.0 {
             score += 1.0 * 0.10;
-            reasoning.push(format!("Excellent liquidity: {:.1} SOL", metrics.liquidity_sol));
+            reasoning.push(ReasonCode::HighLiquidity, format!("Excellent liquidity: {:.1} SOL", metrics.liquidity_sol));
         } else if metrics.liquidity_sol > self.min_liquidity {
             score += 0.5 * 0.10;
-            reasoning.push(format!("Good liquidity: {:.1} SOL", metrics.liquidity_sol));
+            reasoning.push(ReasonCode::AdequateLiquidity, format!("Good liquidity: {:.1} SOL", metrics.liquidity_sol));
         } else {
-            reasoning.push(format!("Low liquidity: {:.1} SOL (risky exit)", metrics.liquidity_sol));
+            reasoning.push(ReasonCode::LowLiquidity, format!("Low liquidity: {:.1} SOL (risky exit)", metrics.liquidity_sol));
         }
         max_score += 0.10;
 
@@ -683,11 +1000,13 @@ impl MomentumScalper {
         );
 
         Ok(TradingSignal {
-            token_mint: metrics.mint.parse().unwrap(),
+            token_mint: parse_mint(metrics)?,
             signal_type,
             confidence,
-            reasoning,
+            reasoning: reasoning.text,
+            reason_codes: reasoning.codes,
             timestamp: chrono::Utc::now().timestamp(),
+            rejected: false,
         })
     }
 }
@@ -705,12 +1024,18 @@ impl TradingStrategy for MomentumScalper {
             use_trailing_stop: true,        // Use trailing stop
             trailing_activation_pct: 0.20,  // Activate at +20%
             trailing_distance_pct: 0.10,    // Trail by 10%
+            loss_cooldown_seconds: 900,     // 15 minutes
+            take_profit_ladder: Vec::new(),
         }
     }
 
     fn name(&self) -> &str {
         "Momentum Scalper (Quick Flips)"
     }
+
+    fn strategy_type(&self) -> StrategyType {
+        StrategyType::MomentumScalper
+    }
 }
 
 // ============================================================================
@@ -736,27 +1061,31 @@ impl GraduationAnticipator {
     fn analyze_impl(&self, metrics: &TokenMetrics) -> Result<TradingSignal> {
         let mut score = 0.0;
         let mut max_score = 0.0;
-        let mut reasoning = Vec::new();
+        let mut reasoning = Reasons::default();
 
-        // Must be in graduation zone (60-85% bonding curve)
-        if metrics.bonding_curve_progress < 60.0 || metrics.bonding_curve_progress > 85.0 {
+        // Already graduated? Skip
+        if metrics.is_graduated {
             return Ok(TradingSignal {
-                token_mint: metrics.mint.parse().unwrap(),
+                token_mint: parse_mint(metrics)?,
                 signal_type: SignalType::Hold,
                 confidence: 0.0,
-                reasoning: vec![format!("Bonding curve {:.1}% outside graduation zone (60-85%)", metrics.bonding_curve_progress)],
+                reasoning: vec!["Already graduated to DEX".to_string()],
+                reason_codes: vec![ReasonCode::Graduated],
                 timestamp: chrono::Utc::now().timestamp(),
+                rejected: false,
             });
         }
 
-        // Already graduated? Skip
-        if metrics.is_graduated {
+        // Must be in graduation zone (60-85% bonding curve)
+        if metrics.bonding_curve_progress < 60.0 || metrics.bonding_curve_progress > 85.0 {
             return Ok(TradingSignal {
-                token_mint: metrics.mint.parse().unwrap(),
+                token_mint: parse_mint(metrics)?,
                 signal_type: SignalType::Hold,
                 confidence: 0.0,
-                reasoning: vec!["Already graduated to DEX".to_string()],
+                reasoning: vec![format!("Bonding curve {:.1}% outside graduation zone (60-85%)", metrics.bonding_curve_progress)],
+                reason_codes: vec![ReasonCode::OutsideEntryWindow],
                 timestamp: chrono::Utc::now().timestamp(),
+                rejected: false,
             });
         }
 
@@ -771,55 +1100,55 @@ impl GraduationAnticipator {
 
         score += curve_score * 0.30;
         max_score += 0.30;
-        reasoning.push(format!("Near graduation: {:.1}% bonding curve", metrics.bonding_curve_progress));
+        reasoning.push(ReasonCode::NearGraduation, format!("Near graduation: {:.1}% bonding curve", metrics.bonding_curve_progress));
 
         // Factor 2: Liquidity (25% weight)
         if metrics.liquidity_sol > self.min_liquidity * 2.0 {
             score += 1.0 * 0.25;
-            reasoning.push(format!("Excellent DEX-ready liquidity: {:.1} SOL", metrics.liquidity_sol));
+            reasoning.push(ReasonCode::HighLiquidity, format!("Excellent DEX-ready liquidity: {:.1} SOL", metrics.liquidity_sol));
         } else if metrics.liquidity_sol > self.min_liquidity * 1.5 {
             score += 0.7 * 0.25;
-            reasoning.push(format!("Strong liquidity: {:.1} SOL", metrics.liquidity_sol));
+            reasoning.push(ReasonCode::HighLiquidity, format!("Strong liquidity: {:.1} SOL", metrics.liquidity_sol));
         } else if metrics.liquidity_sol > self.min_liquidity {
             score += 0.4 * 0.25;
-            reasoning.push(format!("Adequate liquidity: {:.1} SOL", metrics.liquidity_sol));
+            reasoning.push(ReasonCode::AdequateLiquidity, format!("Adequate liquidity: {:.1} SOL", metrics.liquidity_sol));
         } else {
-            reasoning.push(format!("Low liquidity: {:.1} SOL (risky)", metrics.liquidity_sol));
+            reasoning.push(ReasonCode::LowLiquidity, format!("Low liquidity: {:.1} SOL (risky)", metrics.liquidity_sol));
         }
         max_score += 0.25;
 
         // Factor 3: Holder Distribution (20% weight)
         if metrics.holder_count > self.min_holder_count * 2 {
             score += 0.5 * 0.20;
-            reasoning.push(format!("Strong community: {} holders", metrics.holder_count));
+            reasoning.push(ReasonCode::StrongHolderBase, format!("Strong community: {} holders", metrics.holder_count));
         } else if metrics.holder_count > self.min_holder_count {
             score += 0.3 * 0.20;
-            reasoning.push(format!("Good holder base: {} holders", metrics.holder_count));
+            reasoning.push(ReasonCode::GoodHolderBase, format!("Good holder base: {} holders", metrics.holder_count));
         } else {
-            reasoning.push(format!("Weak holder count: {}", metrics.holder_count));
+            reasoning.push(ReasonCode::LowHolderCount, format!("Weak holder count: {}", metrics.holder_count));
         }
 
         if metrics.holder_concentration < self.max_holder_concentration * 0.6 {
             score += 0.5 * 0.20;
-            reasoning.push(format!("Well distributed: {:.1}% concentration", metrics.holder_concentration * 100.0));
+            reasoning.push(ReasonCode::WellDistributed, format!("Well distributed: {:.1}% concentration", metrics.holder_concentration * 100.0));
         } else if metrics.holder_concentration < self.max_holder_concentration {
             score += 0.3 * 0.20;
-            reasoning.push(format!("Acceptable distribution: {:.1}%", metrics.holder_concentration * 100.0));
+            reasoning.push(ReasonCode::AcceptableDistribution, format!("Acceptable distribution: {:.1}%", metrics.holder_concentration * 100.0));
         } else {
-            reasoning.push(format!("High concentration risk: {:.1}%", metrics.holder_concentration * 100.0));
+            reasoning.push(ReasonCode::HighConcentration, format!("High concentration risk: {:.1}%", metrics.holder_concentration * 100.0));
         }
         max_score += 0.20;
 
         // Factor 4: Volume Sustained (15% weight)
         if metrics.volume_24h > 100.0 {
             score += 1.0 * 0.15;
-            reasoning.push(format!("Exceptional 24h volume: {:.1} SOL", metrics.volume_24h));
+            reasoning.push(ReasonCode::ExceptionalVolume, format!("Exceptional 24h volume: {:.1} SOL", metrics.volume_24h));
         } else if metrics.volume_24h > 50.0 {
             score += 0.7 * 0.15;
-            reasoning.push(format!("Strong 24h volume: {:.1} SOL", metrics.volume_24h));
+            reasoning.push(ReasonCode::GoodVolume, format!("Strong 24h volume: {:.1} SOL", metrics.volume_24h));
         } else if metrics.volume_24h > 25.0 {
             score += 0.4 * 0.15;
-            reasoning.push(format!("Good sustained volume: {:.1} SOL", metrics.volume_24h));
+            reasoning.push(ReasonCode::GoodVolume, format!("Good sustained volume: {:.1} SOL", metrics.volume_24h));
         }
         max_score += 0.15;
 
@@ -828,12 +1157,12 @@ impl GraduationAnticipator {
 
         if volatility < 0.20 {
             score += 1.0 * 0.10;
-            reasoning.push("Stable price action (low volatility)".to_string());
+            reasoning.push(ReasonCode::LowVolatility, "Stable price action (low volatility)".to_string());
         } else if volatility < 0.40 {
             score += 0.6 * 0.10;
-            reasoning.push("Moderate volatility".to_string());
+            reasoning.push(ReasonCode::ModerateVolatility, "Moderate volatility".to_string());
         } else {
-            reasoning.push("High volatility (risky)".to_string());
+            reasoning.push(ReasonCode::HighVolatility, "High volatility (risky)".to_string());
         }
         max_score += 0.10;
 
@@ -861,11 +1190,13 @@ impl GraduationAnticipator {
         );
 
         Ok(TradingSignal {
-            token_mint: metrics.mint.parse().unwrap(),
+            token_mint: parse_mint(metrics)?,
             signal_type,
             confidence,
-            reasoning,
+            reasoning: reasoning.text,
+            reason_codes: reasoning.codes,
             timestamp: chrono::Utc::now().timestamp(),
+            rejected: false,
         })
     }
 }
@@ -883,36 +1214,180 @@ impl TradingStrategy for GraduationAnticipator {
             use_trailing_stop: false,
             trailing_activation_pct: 0.0,
             trailing_distance_pct: 0.0,
+            loss_cooldown_seconds: 3600,     // 1 hour
+            take_profit_ladder: Vec::new(),
         }
     }
 
     fn name(&self) -> &str {
         "Graduation Anticipator (Low Risk)"
     }
+
+    fn strategy_type(&self) -> StrategyType {
+        StrategyType::GraduationAnticipator
+    }
 }
 
-/// Factory function to create strategy based on type
-pub fn create_strategy(strategy_type: StrategyType) -> Box<dyn TradingStrategy> {
+// ============================================================================
+// ENSEMBLE: best fit of all strategies
+// ============================================================================
+
+/// Run every strategy and keep the most confident signal.
+///
+/// Strategies whose curve/age gate rejects the token report zero confidence,
+/// so the winner is the best-fitting strategy whose gate passed. Ties go to the
+/// strategy listed first. A `hard_rejection` holds every strategy's signal, so
+/// whichever wins can't trade a blacklisted creator or a critical rug flag.
+pub fn analyze_ensemble(
+    metrics: &TokenMetrics,
+    strategies: &[Box<dyn TradingStrategy>],
+    blacklist: Option<&Blacklist>,
+) -> Result<(StrategyType, TradingSignal)> {
+    let rejection = hard_rejection(metrics, blacklist);
+    let mut best: Option<(StrategyType, TradingSignal)> = None;
+
+    for strategy in strategies {
+        let mut signal = strategy.analyze(metrics)?;
+        if let Some((code, reason)) = rejection.as_ref().filter(|_| !signal.rejected) {
+            signal.reject(*code, reason);
+        }
+        let better = match &best {
+            Some((_, current)) => signal.confidence > current.confidence,
+            None => true,
+        };
+        if better {
+            best = Some((strategy.strategy_type(), signal));
+        }
+    }
+
+    best.ok_or_else(|| BotError::Analysis("Ensemble has no strategies".to_string()))
+}
+
+/// Evaluates tokens with all four strategies and acts on the best fit
+pub struct EnsembleStrategy {
+    strategies: Vec<Box<dyn TradingStrategy>>,
+    /// Creators no member may trade, whether or not it checks the blacklist itself
+    blacklist: Option<Arc<Blacklist>>,
+}
+
+impl EnsembleStrategy {
+    pub fn new(blacklist: Option<Arc<Blacklist>>, thresholds: &HolderThresholds) -> Self {
+        Self {
+            strategies: vec![
+                create_strategy(StrategyType::Conservative, blacklist.clone(), thresholds),
+                create_strategy(StrategyType::UltraEarlySniper, None, thresholds),
+                create_strategy(StrategyType::MomentumScalper, None, thresholds),
+                create_strategy(StrategyType::GraduationAnticipator, None, thresholds),
+            ],
+            blacklist,
+        }
+    }
+
+    /// The strategies the ensemble picks between
+    pub fn strategies(&self) -> &[Box<dyn TradingStrategy>] {
+        &self.strategies
+    }
+
+    /// The winning strategy and its signal, held on any `hard_rejection`
+    pub fn best_fit(&self, metrics: &TokenMetrics) -> Result<(StrategyType, TradingSignal)> {
+        analyze_ensemble(metrics, &self.strategies, self.blacklist.as_deref())
+    }
+}
+
+impl TradingStrategy for EnsembleStrategy {
+    fn analyze(&self, metrics: &TokenMetrics) -> Result<TradingSignal> {
+        let (winner, mut signal) = self.best_fit(metrics)?;
+
+        info!(
+            "[ENSEMBLE] {} best fit: {:?} ({:.1}%, {:?})",
+            metrics.symbol,
+            winner,
+            signal.confidence * 100.0,
+            signal.signal_type
+        );
+        signal.reasoning.insert(0, format!("Ensemble winner: {:?}", winner));
+        signal.reason_codes.insert(0, ReasonCode::EnsembleWinner);
+
+        Ok(signal)
+    }
+
+    /// Positions are managed with the conservative exit rules regardless of the winner
+    fn get_exit_params(&self) -> StrategyExitParams {
+        self.strategies[0].get_exit_params()
+    }
+
+    fn name(&self) -> &str {
+        "Ensemble (Best Fit)"
+    }
+
+    fn strategy_type(&self) -> StrategyType {
+        StrategyType::Ensemble
+    }
+}
+
+/// Factory function to create strategy based on type, applying any holder
+/// threshold overrides over the strategy's defaults
+pub fn create_strategy(
+    strategy_type: StrategyType,
+    blacklist: Option<Arc<Blacklist>>,
+    thresholds: &HolderThresholds,
+) -> Box<dyn TradingStrategy> {
     match strategy_type {
-        StrategyType::Conservative => Box::new(TokenAnalyzer::new(5.0, 10.0, 50, 0.3)),
+        StrategyType::Conservative => {
+            let name = thresholds.analyzer_profile.as_deref().unwrap_or("balanced");
+            let mut analyzer = profile(name).unwrap_or_else(|e| {
+                warn!("{}, using the balanced profile", e);
+                TokenAnalyzer::new(5.0, 10.0, 50, 0.3)
+            });
+            if let Some(min_holders) = thresholds.conservative_min_holders {
+                analyzer.min_holder_count = min_holders;
+            }
+            if let Some(max_concentration) = thresholds.conservative_max_concentration {
+                analyzer.max_holder_concentration = max_concentration;
+            }
+            if let Some(floor) = thresholds.conservative_volume_floor_5m {
+                analyzer = analyzer.with_volume_floor(floor);
+            }
+            let max_price_impact = thresholds.conservative_max_price_impact.unwrap_or(analyzer.max_price_impact);
+            let analyzer = analyzer.with_price_impact(
+                thresholds.position_size_sol.unwrap_or(DEFAULT_POSITION_SIZE_SOL),
+                max_price_impact,
+            );
+            match blacklist {
+                Some(blacklist) => Box::new(analyzer.with_blacklist(blacklist)),
+                None => Box::new(analyzer),
+            }
+        }
         StrategyType::UltraEarlySniper => Box::new(UltraEarlySniper::new()),
         StrategyType::MomentumScalper => Box::new(MomentumScalper::new()),
-        StrategyType::GraduationAnticipator => Box::new(GraduationAnticipator::new()),
+        StrategyType::GraduationAnticipator => {
+            let mut strategy = GraduationAnticipator::new();
+            if let Some(min_holders) = thresholds.grad_min_holders {
+                strategy.min_holder_count = min_holders;
+            }
+            if let Some(max_concentration) = thresholds.grad_max_concentration {
+                strategy.max_holder_concentration = max_concentration;
+            }
+            Box::new(strategy)
+        }
+        StrategyType::Ensemble => Box::new(EnsembleStrategy::new(blacklist, thresholds)),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::{RugFlag, RugRisk};
 
     #[test]
     fn test_high_confidence_token() {
         let analyzer = TokenAnalyzer::new(5.0, 10.0, 50, 0.3);
 
         let metrics = TokenMetrics {
-            mint: "test123".to_string(),
+            mint: Pubkey::new_unique().to_string(),
             name: "Test Token".to_string(),
             symbol: "TEST".to_string(),
+            creator: None,
             volume_5m: 25.0,
             volume_1h: 200.0,
             volume_24h: 1000.0,
@@ -921,6 +1396,8 @@ mod tests {
             price_change_1h: 0.40,
             liquidity_sol: 20.0,
             liquidity_usd: 2000.0,
+            virtual_sol_reserves: 0.0,
+            virtual_token_reserves: 0.0,
             holder_count: 200,
             holder_concentration: 0.15,
             unique_buyers_5m: 50,
@@ -933,7 +1410,11 @@ mod tests {
             time_since_creation: 3600,
             buy_pressure: 3.0,
             sell_pressure: 1.0,
+            buy_count_5m: 0,
+            sell_count_5m: 0,
             volatility_score: 0.3,
+            wash_trade_score: 0.0,
+            rug_risk: None,
         };
 
         let signal = analyzer.analyze(&metrics).unwrap();
@@ -943,4 +1424,411 @@ mod tests {
             SignalType::StrongBuy | SignalType::Buy
         ));
     }
+
+    /// Token that scores as a strong buy with the default conservative thresholds
+    fn strong_metrics() -> TokenMetrics {
+        TokenMetrics {
+            mint: solana_sdk::pubkey::Pubkey::new_unique().to_string(),
+            name: "Test Token".to_string(),
+            symbol: "TEST".to_string(),
+            creator: Some("CreatorWallet".to_string()),
+            volume_5m: 25.0,
+            volume_1h: 400.0,
+            volume_24h: 1000.0,
+            current_price: 0.001,
+            price_change_5m: 0.25,
+            price_change_1h: 0.60,
+            liquidity_sol: 20.0,
+            liquidity_usd: 2000.0,
+            virtual_sol_reserves: 0.0,
+            virtual_token_reserves: 0.0,
+            holder_count: 200,
+            holder_concentration: 0.10,
+            unique_buyers_5m: 50,
+            unique_sellers_5m: 20,
+            market_cap: 100000.0,
+            fully_diluted_valuation: 100000.0,
+            bonding_curve_progress: 50.0,
+            is_graduated: false,
+            created_at: 0,
+            time_since_creation: 3600,
+            buy_pressure: 4.0,
+            sell_pressure: 1.0,
+            buy_count_5m: 0,
+            sell_count_5m: 0,
+            volatility_score: 0.3,
+            wash_trade_score: 0.0,
+            rug_risk: None,
+        }
+    }
+
+    #[test]
+    fn test_blacklisted_creator_is_held() {
+        let metrics = strong_metrics();
+        let analyzer = TokenAnalyzer::new(5.0, 10.0, 50, 0.3);
+        assert_eq!(analyzer.analyze(&metrics).unwrap().signal_type, SignalType::StrongBuy);
+
+        let blacklist = Arc::new(Blacklist::from_entries(&[], &["CreatorWallet"]));
+        let analyzer = TokenAnalyzer::new(5.0, 10.0, 50, 0.3).with_blacklist(blacklist);
+        assert_eq!(analyzer.analyze(&metrics).unwrap().signal_type, SignalType::Hold);
+    }
+
+    #[test]
+    fn test_critical_rug_risk_is_held() {
+        let analyzer = TokenAnalyzer::new(5.0, 10.0, 50, 0.3);
+        let mut metrics = strong_metrics();
+
+        // A whale alone is not a deal-breaker
+        metrics.rug_risk = Some(RugRisk {
+            score: 0.3,
+            flags: vec![RugFlag::WhaleHolder { account: "Whale".to_string(), share: 0.25 }],
+        });
+        assert_eq!(analyzer.analyze(&metrics).unwrap().signal_type, SignalType::StrongBuy);
+
+        metrics.rug_risk = Some(RugRisk { score: 0.5, flags: vec![RugFlag::MintAuthority] });
+        assert_eq!(analyzer.analyze(&metrics).unwrap().signal_type, SignalType::Hold);
+    }
+
+    #[test]
+    fn test_tiny_buys_dont_mask_large_sells() {
+        let analyzer = TokenAnalyzer::new(5.0, 10.0, 50, 0.3);
+
+        // 40 buys for 2 SOL against 3 sells for 30 SOL
+        let divergent = TokenMetrics {
+            buy_pressure: 2.0 / 30.0,
+            sell_pressure: 30.0 / 2.0,
+            buy_count_5m: 40,
+            sell_count_5m: 3,
+            ..strong_metrics()
+        };
+        let value_only = TokenMetrics { buy_count_5m: 0, sell_count_5m: 0, ..divergent.clone() };
+        let count_only = TokenMetrics { buy_pressure: 40.0 / 3.0, sell_pressure: 3.0 / 40.0, ..divergent.clone() };
+
+        let (blended, reasons) = analyzer.analyze_pressure(&divergent);
+        let (by_value, _) = analyzer.analyze_pressure(&value_only);
+        let (by_count, _) = analyzer.analyze_pressure(&count_only);
+
+        assert_eq!(by_count, 1.0);
+        assert!(blended <= by_value, "blended {} vs value {}", blended, by_value);
+        assert!(divergent.pressure_ratio() < 1.0);
+        assert!(reasons.text[0].starts_with("Sell pressure dominant"));
+    }
+
+    #[test]
+    fn test_low_liquidity_rejection_is_explained() {
+        let analyzer = TokenAnalyzer::new(5.0, 10.0, 50, 0.3);
+        let mut metrics = strong_metrics();
+        assert!(!analyzer.analyze(&metrics).unwrap().rejected);
+
+        metrics.liquidity_sol = 2.0;
+        let signal = analyzer.analyze(&metrics).unwrap();
+
+        assert_eq!(signal.signal_type, SignalType::Hold);
+        assert!(signal.rejected);
+        assert_eq!(signal.reasoning[0], "REJECTED: liquidity 2.0 SOL below minimum 5.0");
+    }
+
+    #[test]
+    fn test_conservative_reasons_carry_codes() {
+        let analyzer = TokenAnalyzer::new(5.0, 10.0, 50, 0.3);
+        let signal = analyzer.analyze(&strong_metrics()).unwrap();
+
+        assert_eq!(
+            signal.reason_codes,
+            vec![
+                ReasonCode::ExceptionalVolume,
+                ReasonCode::StableVolume,
+                ReasonCode::StrongBuyerInterest,
+                ReasonCode::HighLiquidity,
+                ReasonCode::StrongHolderBase,
+                ReasonCode::WellDistributed,
+                ReasonCode::StrongMomentum,
+                ReasonCode::StrongMomentum,
+                ReasonCode::StrongBuyPressure,
+                ReasonCode::CurveSweetSpot,
+            ]
+        );
+        assert_eq!(signal.reason_codes.len(), signal.reasoning.len());
+
+        // A rejection's code leads, like its text
+        let mut metrics = strong_metrics();
+        metrics.liquidity_sol = 2.0;
+        metrics.holder_concentration = 0.40;
+        let signal = analyzer.analyze(&metrics).unwrap();
+
+        assert_eq!(signal.reason_codes[0], ReasonCode::LowLiquidity);
+        assert!(signal.reason_codes.contains(&ReasonCode::HighConcentration));
+        assert_eq!(signal.reason_codes.len(), signal.reasoning.len());
+    }
+
+    #[test]
+    fn test_near_zero_volume_is_downgraded_to_hold() {
+        let analyzer = TokenAnalyzer::new(5.0, 10.0, 50, 0.3);
+        let metrics = TokenMetrics { volume_5m: 0.05, ..strong_metrics() };
+
+        let signal = analyzer.analyze(&metrics).unwrap();
+        assert_eq!(signal.signal_type, SignalType::Hold);
+        assert!(signal.rejected);
+        assert_eq!(signal.reasoning[0], "REJECTED: 5m volume 0.05 SOL below floor 0.50");
+
+        // The floor is configurable separately from the scoring threshold
+        let lenient = TokenAnalyzer::new(5.0, 10.0, 50, 0.3).with_volume_floor(0.01);
+        assert!(!lenient.analyze(&metrics).unwrap().rejected);
+    }
+
+    #[test]
+    fn test_liquidity_scored_by_price_impact_of_position() {
+        // Plenty of quoted liquidity, but a shallow curve
+        let metrics = TokenMetrics { virtual_sol_reserves: 30.0, ..strong_metrics() };
+        assert!((estimate_price_impact(&metrics, 1.5) - 0.05).abs() < 1e-9);
+
+        let small = TokenAnalyzer::new(5.0, 10.0, 50, 0.3).with_price_impact(0.5, 0.20);
+        assert_eq!(small.score_factors(&metrics).liquidity, 1.0);
+
+        let large = TokenAnalyzer::new(5.0, 10.0, 50, 0.3).with_price_impact(5.0, 0.20);
+        assert_eq!(large.score_factors(&metrics).liquidity, 0.4);
+
+        // Without reserves the pool's SOL liquidity stands in
+        let unknown = TokenMetrics { virtual_sol_reserves: 0.0, liquidity_sol: 4.0, ..strong_metrics() };
+        assert_eq!(estimate_price_impact(&unknown, 1.0), 0.25);
+        let default = TokenAnalyzer::new(1.0, 10.0, 50, 0.3);
+        assert_eq!(default.score_factors(&unknown).liquidity, 0.0);
+    }
+
+    #[test]
+    fn test_defensive_profile_is_stricter_than_aggressive() {
+        // Middling liquidity and a fairly concentrated holder base
+        let metrics = TokenMetrics { liquidity_sol: 8.0, holder_concentration: 0.32, ..strong_metrics() };
+
+        let aggressive = profile("aggressive").unwrap().analyze(&metrics).unwrap();
+        let defensive = profile("defensive").unwrap().analyze(&metrics).unwrap();
+
+        assert!(!aggressive.rejected);
+        assert!(matches!(aggressive.signal_type, SignalType::Buy | SignalType::StrongBuy));
+        assert!(defensive.rejected);
+        assert_eq!(defensive.signal_type, SignalType::Hold);
+        assert!(aggressive.confidence > defensive.confidence);
+
+        assert!(profile("Balanced").is_ok());
+        assert!(profile("yolo").is_err());
+    }
+
+    #[test]
+    fn test_momentum_only_weights() {
+        // Pumping hard, but nothing else going for it
+        let metrics = TokenMetrics {
+            volume_5m: 0.0,
+            volume_1h: 0.0,
+            price_change_5m: 0.30,
+            price_change_1h: 0.80,
+            liquidity_sol: 1.0,
+            holder_count: 5,
+            holder_concentration: 0.9,
+            unique_buyers_5m: 0,
+            unique_sellers_5m: 0,
+            bonding_curve_progress: 5.0,
+            buy_pressure: 0.5,
+            sell_pressure: 1.0,
+            buy_count_5m: 0,
+            sell_count_5m: 0,
+            ..strong_metrics()
+        };
+
+        let balanced = TokenAnalyzer::new(5.0, 10.0, 50, 0.3).analyze(&metrics).unwrap();
+
+        let weights = FactorWeights {
+            volume: 0.0,
+            liquidity: 0.0,
+            holders: 0.0,
+            momentum: 1.0,
+            pressure: 0.0,
+            bonding_curve: 0.0,
+        };
+        let momentum_only = TokenAnalyzer::new(5.0, 10.0, 50, 0.3)
+            .with_weights(weights)
+            .unwrap()
+            .analyze(&metrics)
+            .unwrap();
+
+        assert!(balanced.confidence < 0.3);
+        assert!(momentum_only.confidence > 0.95);
+    }
+
+    #[test]
+    fn test_weights_must_sum_to_one() {
+        assert!(FactorWeights::default().validate().is_ok());
+
+        let weights = FactorWeights { momentum: 0.5, ..FactorWeights::default() };
+        assert!(TokenAnalyzer::new(5.0, 10.0, 50, 0.3).with_weights(weights).is_err());
+    }
+
+    #[test]
+    fn test_ensemble_picks_best_fit_per_scenario() {
+        // Mid-curve token, 40 minutes old, steady momentum
+        let base = TokenMetrics {
+            volume_5m: 15.0,
+            volume_1h: 60.0,
+            volume_24h: 250.0,
+            price_change_5m: 0.18,
+            price_change_1h: 0.45,
+            liquidity_sol: 12.0,
+            holder_count: 120,
+            holder_concentration: 0.22,
+            unique_buyers_5m: 60,
+            unique_sellers_5m: 25,
+            bonding_curve_progress: 55.0,
+            time_since_creation: 2400,
+            buy_pressure: 2.2,
+            sell_pressure: 1.0,
+            buy_count_5m: 0,
+            sell_count_5m: 0,
+            ..strong_metrics()
+        };
+
+        let very_early = TokenMetrics {
+            time_since_creation: 180,
+            bonding_curve_progress: 6.0,
+            buy_pressure: 9.0,
+            volume_1h: 30.0,
+            ..base.clone()
+        };
+        let mid_curve = TokenMetrics {
+            bonding_curve_progress: 50.0,
+            time_since_creation: 3600,
+            ..base.clone()
+        };
+        let near_graduation = TokenMetrics {
+            bonding_curve_progress: 78.0,
+            liquidity_sol: 25.0,
+            holder_count: 250,
+            volume_24h: 150.0,
+            ..base.clone()
+        };
+        let explosive_momentum = TokenMetrics {
+            price_change_1h: 1.40,
+            price_change_5m: 0.20,
+            volume_5m: 50.0,
+            buy_pressure: 3.5,
+            ..base.clone()
+        };
+
+        let strategies: Vec<Box<dyn TradingStrategy>> = vec![
+            create_strategy(StrategyType::Conservative, None, &HolderThresholds::default()),
+            create_strategy(StrategyType::UltraEarlySniper, None, &HolderThresholds::default()),
+            create_strategy(StrategyType::MomentumScalper, None, &HolderThresholds::default()),
+            create_strategy(StrategyType::GraduationAnticipator, None, &HolderThresholds::default()),
+        ];
+
+        for (metrics, expected) in [
+            (very_early, StrategyType::UltraEarlySniper),
+            (mid_curve, StrategyType::Conservative),
+            (near_graduation, StrategyType::GraduationAnticipator),
+            (explosive_momentum, StrategyType::MomentumScalper),
+        ] {
+            let (winner, signal) = analyze_ensemble(&metrics, &strategies, None).unwrap();
+            assert_eq!(winner, expected, "curve {:.0}%", metrics.bonding_curve_progress);
+            assert!(signal.confidence > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_ensemble_ties_go_to_first_strategy() {
+        let strategies: Vec<Box<dyn TradingStrategy>> = vec![
+            create_strategy(StrategyType::MomentumScalper, None, &HolderThresholds::default()),
+            create_strategy(StrategyType::GraduationAnticipator, None, &HolderThresholds::default()),
+        ];
+        // Outside both gates: both report zero confidence
+        let metrics = TokenMetrics { bonding_curve_progress: 95.0, ..strong_metrics() };
+
+        let (winner, signal) = analyze_ensemble(&metrics, &strategies, None).unwrap();
+
+        assert_eq!(winner, StrategyType::MomentumScalper);
+        assert_eq!(signal.confidence, 0.0);
+    }
+
+    #[test]
+    fn test_ensemble_holds_blacklisted_creator_and_rug_flags() {
+        // Very early token: the sniper wins, and it checks neither the blacklist nor rug flags
+        let metrics = TokenMetrics {
+            time_since_creation: 180,
+            bonding_curve_progress: 6.0,
+            volume_5m: 15.0,
+            volume_1h: 30.0,
+            price_change_5m: 0.18,
+            liquidity_sol: 12.0,
+            holder_count: 120,
+            holder_concentration: 0.22,
+            unique_buyers_5m: 60,
+            unique_sellers_5m: 25,
+            buy_pressure: 9.0,
+            sell_pressure: 1.0,
+            ..strong_metrics()
+        };
+        let blacklist = Arc::new(Blacklist::from_entries(&[], &["CreatorWallet"]));
+        let ensemble = EnsembleStrategy::new(Some(blacklist), &HolderThresholds::default());
+        let open = EnsembleStrategy::new(None, &HolderThresholds::default());
+
+        let (winner, signal) = open.best_fit(&metrics).unwrap();
+        assert_eq!(winner, StrategyType::UltraEarlySniper);
+        assert_ne!(signal.signal_type, SignalType::Hold);
+
+        let (winner, signal) = ensemble.best_fit(&metrics).unwrap();
+        assert_eq!(winner, StrategyType::UltraEarlySniper);
+        assert_eq!(signal.signal_type, SignalType::Hold);
+        assert!(signal.rejected);
+        assert_eq!(signal.reason_codes[0], ReasonCode::BlacklistedCreator);
+
+        let flagged = TokenMetrics {
+            rug_risk: Some(RugRisk { score: 0.5, flags: vec![RugFlag::MintAuthority] }),
+            ..metrics
+        };
+        let signal = open.analyze(&flagged).unwrap();
+        assert_eq!(signal.signal_type, SignalType::Hold);
+        assert!(signal.rejected);
+        assert!(signal.reason_codes.contains(&ReasonCode::RugRisk));
+    }
+
+    #[test]
+    fn test_malformed_mint_is_analysis_error() {
+        let metrics = TokenMetrics { mint: "not-a-mint".to_string(), ..strong_metrics() };
+
+        for strategy_type in [
+            StrategyType::Conservative,
+            StrategyType::UltraEarlySniper,
+            StrategyType::MomentumScalper,
+            StrategyType::GraduationAnticipator,
+            StrategyType::Ensemble,
+        ] {
+            let result = create_strategy(strategy_type, None, &HolderThresholds::default()).analyze(&metrics);
+            assert!(matches!(result, Err(BotError::Analysis(_))), "{:?}", strategy_type);
+        }
+    }
+
+    #[test]
+    fn test_graduation_holder_thresholds_from_env() {
+        // In the graduation zone, but short of the default 100 holders / 25% concentration
+        let metrics = TokenMetrics {
+            bonding_curve_progress: 75.0,
+            volume_24h: 30.0,
+            price_change_5m: 0.05,
+            price_change_1h: 0.10,
+            holder_count: 80,
+            holder_concentration: 0.30,
+            ..strong_metrics()
+        };
+
+        let default = create_strategy(StrategyType::GraduationAnticipator, None, &HolderThresholds::default());
+        assert_eq!(default.analyze(&metrics).unwrap().signal_type, SignalType::Hold);
+
+        let thresholds = HolderThresholds::from_lookup(|key| match key {
+            "GRAD_MIN_HOLDERS" => Some("30".to_string()),
+            "GRAD_MAX_CONCENTRATION" => Some("0.6".to_string()),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(thresholds.conservative_min_holders, None);
+
+        let relaxed = create_strategy(StrategyType::GraduationAnticipator, None, &thresholds);
+        assert_eq!(relaxed.analyze(&metrics).unwrap().signal_type, SignalType::StrongBuy);
+    }
 }
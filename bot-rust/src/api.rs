@@ -1,37 +1,118 @@
 use axum::{
-    extract::{Path, State, ws::{WebSocket, WebSocketUpgrade}},
-    http::StatusCode,
-    response::{IntoResponse, Json},
-    routing::{get, post},
+    body::Body,
+    extract::{Path, Query, Request, State, ws::{Message, WebSocket, WebSocketUpgrade}},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post, put},
     Router,
 };
 use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, mpsc, oneshot, RwLock};
 use tower_http::cors::{Any, CorsLayer};
 use tracing::{info, warn};
 
-use crate::types::{StrategyType, SignalType};
+use crate::analyzer::{EnsembleStrategy, FactorScores};
+use crate::blacklist::Blacklist;
+use crate::export::{self, TradeRecord};
+use crate::health::{DependencyHealth, HealthChecker};
+use crate::types::{HolderThresholds, ReasonCode, SignalType, StrategyType, TokenMetrics, TradingSignal};
+use crate::vault::from_scaled_price;
 
 // ============================================================================
 // API State
 // ============================================================================
 
+/// Buffered events per WebSocket subscriber before it starts lagging
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Page size when `limit` is not given
+const DEFAULT_PAGE_LIMIT: usize = 50;
+
+/// Largest page a client may request
+const MAX_PAGE_LIMIT: usize = 200;
+
+/// Ping idle WebSocket clients so proxies don't drop the connection
+const HEARTBEAT_INTERVAL_SECS: u64 = 15;
+
 #[derive(Clone)]
 pub struct ApiState {
     pub delegations: Arc<RwLock<Vec<DelegationInfo>>>,
     pub positions: Arc<RwLock<Vec<PositionInfo>>>,
     pub stats: Arc<RwLock<BotStats>>,
+    pub events: broadcast::Sender<StreamEvent>,
+    /// Raised by the trader's drawdown limit; clearing it resumes new positions
+    pub trading_halted: Arc<AtomicBool>,
+    /// Bearer token required on per-user and mutating routes; those routes reject everything when unset
+    api_token: Option<Arc<str>>,
+    /// Dependency probes behind `/api/health`; only internal stats are reported when unset
+    health: Option<Arc<HealthChecker>>,
+    /// Commands for the trading loop; admin routes answer 503 when unset
+    commands: Option<mpsc::Sender<BotCommand>>,
+    /// Every strategy, for scoring hypothetical metrics
+    strategies: Arc<EnsembleStrategy>,
+}
+
+/// Requests only the trading loop can carry out, polled between cycles
+#[derive(Debug)]
+pub enum BotCommand {
+    /// Market-sell every held position in `mint`, replying with how many closed
+    ClosePositions { mint: Pubkey, reply: oneshot::Sender<usize> },
 }
 
 impl ApiState {
     pub fn new() -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+
         Self {
             delegations: Arc::new(RwLock::new(Vec::new())),
             positions: Arc::new(RwLock::new(Vec::new())),
             stats: Arc::new(RwLock::new(BotStats::default())),
+            events,
+            trading_halted: Arc::new(AtomicBool::new(false)),
+            api_token: None,
+            health: None,
+            commands: None,
+            strategies: Arc::new(EnsembleStrategy::new(None, &HolderThresholds::default())),
         }
     }
+
+    pub fn with_api_token(mut self, token: Option<String>) -> Self {
+        self.api_token = token.map(Into::into);
+        self
+    }
+
+    pub fn with_health_checker(mut self, checker: HealthChecker) -> Self {
+        self.health = Some(Arc::new(checker));
+        self
+    }
+
+    pub fn with_command_channel(mut self, commands: mpsc::Sender<BotCommand>) -> Self {
+        self.commands = Some(commands);
+        self
+    }
+
+    /// Score `/api/analyze` requests with the bot's own blacklist and holder thresholds
+    pub fn with_strategies(mut self, blacklist: Option<Arc<Blacklist>>, thresholds: &HolderThresholds) -> Self {
+        self.strategies = Arc::new(EnsembleStrategy::new(blacklist, thresholds));
+        self
+    }
+
+    /// Publish an event to all connected WebSocket clients
+    fn publish(&self, event: StreamEvent) {
+        // No subscribers is not an error
+        let _ = self.events.send(event);
+    }
+}
+
+impl Default for ApiState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 // ============================================================================
@@ -41,6 +122,9 @@ impl ApiState {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DelegationInfo {
     pub user: String,
+    /// Which of the user's vaults this is; a user may delegate several
+    #[serde(default)]
+    pub vault_index: u8,
     pub strategy: StrategyType,
     pub max_position_size_sol: f64,
     pub max_concurrent_trades: u8,
@@ -68,6 +152,15 @@ pub struct PositionInfo {
     pub pnl_percentage: f64,
     pub opened_at: i64,
     pub closed_at: Option<i64>,
+    /// On-chain strategy tag of the strategy that opened the position
+    #[serde(default)]
+    pub strategy: u8,
+    /// Signal confidence at entry, in basis points
+    #[serde(default)]
+    pub entry_confidence_bps: u16,
+    /// The live price couldn't be fetched; price and PnL are as of entry
+    #[serde(default)]
+    pub stale: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -79,13 +172,97 @@ pub struct BotStats {
     pub signals_generated: u64,
     pub trades_executed: u64,
     pub last_scan_at: Option<i64>,
+    /// Positions closed so far, and how many of them made money
+    #[serde(default)]
+    pub closed_trades: u64,
+    #[serde(default)]
+    pub winning_trades: u64,
+    #[serde(default)]
+    pub realized_pnl_lamports: i64,
+    /// `winning_trades / closed_trades`, 0 before the first close
+    #[serde(default)]
+    pub win_rate: f64,
+    /// New positions are blocked by the drawdown limit
+    #[serde(default)]
+    pub trading_halted: bool,
+}
+
+/// Live update pushed over `/api/stream`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum StreamEvent {
+    StatsUpdate(BotStats),
+    PositionOpened(PositionInfo),
+    PositionClosed(PositionInfo),
+    PriceUpdate {
+        position_id: String,
+        /// Wallet owning the position
+        user: String,
+        current_price: u64,
+        pnl: i64,
+        pnl_percentage: f64,
+    },
+    /// A held token crossed its trailing-stop activation price
+    TrailingActivated {
+        token_mint: String,
+        current_price: f64,
+    },
+}
+
+impl StreamEvent {
+    /// `WsMessage::kind` of the event
+    pub fn kind(&self) -> &'static str {
+        match self {
+            StreamEvent::StatsUpdate(_) => "stats_update",
+            StreamEvent::PositionOpened(_) => "position_opened",
+            StreamEvent::PositionClosed(_) => "position_closed",
+            StreamEvent::PriceUpdate { .. } => "price_update",
+            StreamEvent::TrailingActivated { .. } => "trailing_activated",
+        }
+    }
+
+    /// Wallet the event belongs to; `None` for events every client receives
+    fn wallet(&self) -> Option<&str> {
+        match self {
+            StreamEvent::PositionOpened(position) | StreamEvent::PositionClosed(position) => Some(&position.user),
+            StreamEvent::PriceUpdate { user, .. } => Some(user),
+            StreamEvent::StatsUpdate(_) | StreamEvent::TrailingActivated { .. } => None,
+        }
+    }
+}
+
+/// Frame sent over `/api/stream`: `kind` names the event and `data` is its payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WsMessage {
+    pub kind: String,
+    pub data: serde_json::Value,
+}
+
+impl From<&StreamEvent> for WsMessage {
+    fn from(event: &StreamEvent) -> Self {
+        let data = match serde_json::to_value(event).expect("stream events serialize") {
+            serde_json::Value::Object(mut fields) => fields.remove("data").unwrap_or_default(),
+            other => other,
+        };
+
+        Self { kind: event.kind().to_string(), data }
+    }
+}
+
+/// Client message on `/api/stream` limiting position events to one wallet
+#[derive(Debug, Deserialize)]
+struct SubscribeRequest {
+    subscribe: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct HealthResponse {
+    /// `healthy`, `degraded` (a dependency is unreachable) or `stopped`
     pub status: String,
     pub version: String,
     pub uptime_seconds: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dependencies: Option<DependencyHealth>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -110,6 +287,23 @@ pub struct UserStats {
     pub win_rate: f64,
     pub total_pnl_sol: f64,
     pub total_pnl_usd: f64,
+    /// SOL committed to positions still held
+    #[serde(default)]
+    pub open_exposure_sol: f64,
+    #[serde(default)]
+    pub largest_open_position_sol: f64,
+    #[serde(default)]
+    pub realized_vs_unrealized_pnl: PnlBreakdown,
+    /// Mean time from open to close over closed positions
+    #[serde(default)]
+    pub avg_hold_seconds: f64,
+}
+
+/// PnL already banked by closed positions vs. marked on open ones, in SOL
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PnlBreakdown {
+    pub realized_sol: f64,
+    pub unrealized_sol: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -117,34 +311,200 @@ pub struct ErrorResponse {
     pub error: String,
 }
 
+/// How one strategy reads a set of metrics
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StrategyScore {
+    pub strategy: StrategyType,
+    pub signal_type: SignalType,
+    pub confidence: f64,
+    pub reasoning: Vec<String>,
+    pub reason_codes: Vec<ReasonCode>,
+    pub rejected: bool,
+    /// Per-factor scores, for strategies built from separable factors
+    pub breakdown: Option<FactorScores>,
+}
+
+impl StrategyScore {
+    fn new(strategy: StrategyType, signal: TradingSignal, breakdown: Option<FactorScores>) -> Self {
+        Self {
+            strategy,
+            signal_type: signal.signal_type,
+            confidence: signal.confidence,
+            reasoning: signal.reasoning,
+            reason_codes: signal.reason_codes,
+            rejected: signal.rejected,
+            breakdown,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AnalyzeResponse {
+    /// Each strategy's score, then the ensemble's
+    pub scores: Vec<StrategyScore>,
+    /// Strategy whose signal the ensemble would act on
+    pub ensemble_winner: StrategyType,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CloseMintResponse {
+    pub mint: String,
+    /// Positions sold; fewer than were held if a sale failed
+    pub closed: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateDelegationRequest {
+    pub wallet: String,
+    #[serde(default)]
+    pub vault_index: u8,
+    pub strategy: String,
+    pub max_position_size_sol: f64,
+    pub max_concurrent_trades: u8,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateDelegationRequest {
+    /// Vault of the wallet to update
+    #[serde(default)]
+    pub vault_index: u8,
+    pub strategy: Option<String>,
+    pub max_position_size_sol: Option<f64>,
+    pub max_concurrent_trades: Option<u8>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PositionQuery {
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    /// `open` or `closed`
+    pub status: Option<String>,
+    pub mint: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PaginatedResponse<T> {
+    pub items: Vec<T>,
+    pub total: usize,
+    pub limit: usize,
+    pub offset: usize,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct HistoryQuery {
+    /// Inclusive lower bound on `closed_at` (unix seconds)
+    pub from: Option<i64>,
+    /// Inclusive upper bound on `closed_at` (unix seconds)
+    pub to: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClosedTrade {
+    pub position_id: String,
+    pub token_mint: String,
+    pub token_symbol: String,
+    pub amount_sol: f64,
+    pub entry_price: u64,
+    pub exit_price: u64,
+    pub pnl: i64,
+    pub pnl_percentage: f64,
+    pub opened_at: i64,
+    pub closed_at: i64,
+    pub hold_duration_seconds: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistorySummary {
+    pub total_trades: usize,
+    pub winning_trades: usize,
+    pub win_rate: f64,
+    pub realized_pnl: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistoryResponse {
+    pub trades: Vec<ClosedTrade>,
+    pub summary: HistorySummary,
+}
+
+type ApiError = (StatusCode, Json<ErrorResponse>);
+
 // ============================================================================
 // API Server
 // ============================================================================
 
 pub async fn start_api_server(state: ApiState, port: u16) -> anyhow::Result<()> {
+    let app = create_router(state);
+
+    let addr = format!("0.0.0.0:{}", port);
+    info!("🚀 API server starting on {}", addr);
+
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
+
+pub fn create_router(state: ApiState) -> Router {
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
         .allow_headers(Any);
 
-    let app = Router::new()
-        .route("/api/health", get(health_handler))
-        .route("/api/strategies", get(strategies_handler))
+    // Per-user data and anything that mutates state needs the bearer token; every
+    // position and the event stream belong to some user
+    let protected = Router::new()
+        .route("/api/positions", get(all_positions_handler))
+        .route("/api/positions/:position_id", get(position_handler))
+        .route("/api/stream", get(websocket_handler))
+        .route("/api/delegations", post(create_delegation_handler))
+        .route("/api/delegations/:wallet", put(update_delegation_handler))
         .route("/api/users/:wallet/positions", get(user_positions_handler))
         .route("/api/users/:wallet/stats", get(user_stats_handler))
-        .route("/api/positions", get(all_positions_handler))
+        .route("/api/users/:wallet/history", get(user_history_handler))
+        .route("/api/users/:wallet/export.csv", get(user_export_handler))
+        .route("/api/risk/reset", post(risk_reset_handler))
+        .route("/api/admin/close/:mint", post(admin_close_handler))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_bearer_token));
+
+    Router::new()
+        .route("/api/health", get(health_handler))
+        .route("/api/strategies", get(strategies_handler))
+        .route("/api/analyze", post(analyze_handler))
+        .merge(protected)
         .route("/api/stats", get(bot_stats_handler))
-        .route("/api/stream", get(websocket_handler))
+        .route("/metrics", get(metrics_handler))
         .layer(cors)
-        .with_state(state);
+        .with_state(state)
+}
 
-    let addr = format!("0.0.0.0:{}", port);
-    info!("🚀 API server starting on {}", addr);
+// ============================================================================
+// Middleware
+// ============================================================================
 
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
+async fn require_bearer_token(
+    State(state): State<ApiState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
 
-    Ok(())
+    match (state.api_token.as_deref(), provided) {
+        (Some(expected), Some(token)) if constant_time_eq(expected.as_bytes(), token.as_bytes()) => {
+            Ok(next.run(request).await)
+        }
+        _ => Err(error_response(StatusCode::UNAUTHORIZED, "Missing or invalid bearer token")),
+    }
+}
+
+/// Compare secrets without short-circuiting on the first differing byte
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
 }
 
 // ============================================================================
@@ -154,12 +514,25 @@ pub async fn start_api_server(state: ApiState, port: u16) -> anyhow::Result<()>
 async fn health_handler(
     State(state): State<ApiState>,
 ) -> Json<HealthResponse> {
+    let dependencies = match &state.health {
+        Some(checker) => Some(checker.check().await),
+        None => None,
+    };
     let stats = state.stats.read().await;
 
+    let status = if !stats.is_running {
+        "stopped"
+    } else if dependencies.is_some_and(|d| !d.all_ok()) {
+        "degraded"
+    } else {
+        "healthy"
+    };
+
     Json(HealthResponse {
-        status: if stats.is_running { "healthy" } else { "stopped" },
+        status: status.to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
         uptime_seconds: stats.uptime_seconds,
+        dependencies,
     })
 }
 
@@ -204,25 +577,133 @@ async fn strategies_handler() -> Json<Vec<StrategyInfo>> {
     ])
 }
 
+/// Score hypothetical metrics with every strategy, for tuning without waiting for a live token
+async fn analyze_handler(
+    State(state): State<ApiState>,
+    Json(metrics): Json<TokenMetrics>,
+) -> Result<Json<AnalyzeResponse>, ApiError> {
+    let invalid = |e: crate::error::BotError| error_response(StatusCode::BAD_REQUEST, &e.to_string());
+    let strategies = state.strategies.strategies();
+
+    let mut scores = Vec::with_capacity(strategies.len() + 1);
+    for strategy in strategies {
+        let signal = strategy.analyze(&metrics).map_err(invalid)?;
+        scores.push(StrategyScore::new(strategy.strategy_type(), signal, strategy.factor_scores(&metrics)));
+    }
+
+    let (ensemble_winner, signal) = state.strategies.best_fit(&metrics).map_err(invalid)?;
+    scores.push(StrategyScore::new(StrategyType::Ensemble, signal, None));
+
+    Ok(Json(AnalyzeResponse { scores, ensemble_winner }))
+}
+
+async fn create_delegation_handler(
+    State(state): State<ApiState>,
+    Json(request): Json<CreateDelegationRequest>,
+) -> Result<(StatusCode, Json<DelegationInfo>), ApiError> {
+    Pubkey::from_str(&request.wallet)
+        .map_err(|_| error_response(StatusCode::BAD_REQUEST, "Invalid wallet address"))?;
+    let strategy = parse_strategy(&request.strategy)?;
+    validate_limits(request.max_position_size_sol, request.max_concurrent_trades)?;
+
+    let delegation = DelegationInfo {
+        user: request.wallet,
+        vault_index: request.vault_index,
+        strategy,
+        max_position_size_sol: request.max_position_size_sol,
+        max_concurrent_trades: request.max_concurrent_trades,
+        is_active: true,
+        active_trades: 0,
+        total_trades: 0,
+        profitable_trades: 0,
+        total_pnl: 0,
+        created_at: chrono::Utc::now().timestamp(),
+    };
+
+    if !state.add_delegation(delegation.clone()).await {
+        return Err(error_response(StatusCode::CONFLICT, "Delegation already exists"));
+    }
+
+    info!(
+        "📝 Delegation registered for {} vault {} ({:?})",
+        delegation.user, delegation.vault_index, delegation.strategy
+    );
+    Ok((StatusCode::CREATED, Json(delegation)))
+}
+
+async fn update_delegation_handler(
+    State(state): State<ApiState>,
+    Path(wallet): Path<String>,
+    Json(request): Json<UpdateDelegationRequest>,
+) -> Result<Json<DelegationInfo>, ApiError> {
+    let strategy = request.strategy.as_deref().map(parse_strategy).transpose()?;
+    validate_limits(
+        request.max_position_size_sol.unwrap_or(1.0),
+        request.max_concurrent_trades.unwrap_or(1),
+    )?;
+
+    let mut delegations = state.delegations.write().await;
+    let delegation = delegations
+        .iter_mut()
+        .find(|d| d.user == wallet && d.vault_index == request.vault_index)
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "Delegation not found"))?;
+
+    if let Some(strategy) = strategy {
+        delegation.strategy = strategy;
+    }
+    if let Some(max_position_size_sol) = request.max_position_size_sol {
+        delegation.max_position_size_sol = max_position_size_sol;
+    }
+    if let Some(max_concurrent_trades) = request.max_concurrent_trades {
+        delegation.max_concurrent_trades = max_concurrent_trades;
+    }
+    if let Some(is_active) = request.is_active {
+        delegation.is_active = is_active;
+    }
+
+    Ok(Json(delegation.clone()))
+}
+
 async fn user_positions_handler(
     State(state): State<ApiState>,
     Path(wallet): Path<String>,
-) -> Result<Json<Vec<PositionInfo>>, (StatusCode, Json<ErrorResponse>)> {
+    Query(query): Query<PositionQuery>,
+) -> Result<Json<PaginatedResponse<PositionInfo>>, ApiError> {
     let positions = state.positions.read().await;
+    let user_positions = positions.iter().filter(|p| p.user == wallet);
 
-    let user_positions: Vec<PositionInfo> = positions
-        .iter()
-        .filter(|p| p.user == wallet)
-        .cloned()
-        .collect();
-
-    Ok(Json(user_positions))
+    Ok(Json(paginate_positions(user_positions, &query)?))
 }
 
 async fn user_stats_handler(
     State(state): State<ApiState>,
     Path(wallet): Path<String>,
 ) -> Result<Json<UserStats>, (StatusCode, Json<ErrorResponse>)> {
+    // Copied out so the positions lock isn't held across the delegations lock
+    let positions: Vec<PositionInfo> = state.positions.read().await
+        .iter()
+        .filter(|p| p.user == wallet)
+        .cloned()
+        .collect();
+    let (open, closed): (Vec<&PositionInfo>, Vec<&PositionInfo>) = positions
+        .iter()
+        .partition(|p| p.closed_at.is_none());
+
+    let lamports_to_sol = |pnl: i64| pnl as f64 / 1_000_000_000.0;
+    let realized_vs_unrealized_pnl = PnlBreakdown {
+        realized_sol: closed.iter().map(|p| lamports_to_sol(p.pnl)).sum(),
+        unrealized_sol: open.iter().map(|p| lamports_to_sol(p.pnl)).sum(),
+    };
+    let avg_hold_seconds = if closed.is_empty() {
+        0.0
+    } else {
+        let total_held: i64 = closed
+            .iter()
+            .filter_map(|p| p.closed_at.map(|closed_at| closed_at - p.opened_at))
+            .sum();
+        total_held as f64 / closed.len() as f64
+    };
+
     let delegations = state.delegations.read().await;
 
     let delegation = delegations
@@ -256,21 +737,202 @@ async fn user_stats_handler(
         win_rate,
         total_pnl_sol: pnl_sol,
         total_pnl_usd: pnl_usd,
+        open_exposure_sol: open.iter().map(|p| p.amount_sol).sum(),
+        largest_open_position_sol: open.iter().map(|p| p.amount_sol).fold(0.0, f64::max),
+        realized_vs_unrealized_pnl,
+        avg_hold_seconds,
     }))
 }
 
+async fn user_history_handler(
+    State(state): State<ApiState>,
+    Path(wallet): Path<String>,
+    Query(query): Query<HistoryQuery>,
+) -> Json<HistoryResponse> {
+    let positions = state.positions.read().await;
+
+    let mut trades: Vec<ClosedTrade> = positions
+        .iter()
+        .filter(|p| p.user == wallet && p.status == "closed")
+        .filter_map(|p| {
+            let closed_at = p.closed_at?;
+            let in_range = query.from.map_or(true, |from| closed_at >= from)
+                && query.to.map_or(true, |to| closed_at <= to);
+
+            in_range.then(|| ClosedTrade {
+                position_id: p.position_id.clone(),
+                token_mint: p.token_mint.clone(),
+                token_symbol: p.token_symbol.clone(),
+                amount_sol: p.amount_sol,
+                entry_price: p.entry_price,
+                exit_price: p.current_price,
+                pnl: p.pnl,
+                pnl_percentage: p.pnl_percentage,
+                opened_at: p.opened_at,
+                closed_at,
+                hold_duration_seconds: closed_at - p.opened_at,
+            })
+        })
+        .collect();
+
+    // Newest first
+    trades.sort_by(|a, b| b.closed_at.cmp(&a.closed_at));
+
+    let winning_trades = trades.iter().filter(|t| t.pnl > 0).count();
+    let win_rate = if trades.is_empty() {
+        0.0
+    } else {
+        (winning_trades as f64 / trades.len() as f64) * 100.0
+    };
+
+    let summary = HistorySummary {
+        total_trades: trades.len(),
+        winning_trades,
+        win_rate,
+        realized_pnl: trades.iter().map(|t| t.pnl).sum(),
+    };
+
+    Json(HistoryResponse { trades, summary })
+}
+
+/// The user's closed positions as CSV, sent a line at a time
+async fn user_export_handler(
+    State(state): State<ApiState>,
+    Path(wallet): Path<String>,
+) -> Response {
+    let records: Vec<TradeRecord> = state.positions.read().await
+        .iter()
+        .filter(|p| p.user == wallet && p.status == "closed")
+        .filter_map(|p| {
+            let closed_at = p.closed_at?;
+            let pnl = p.pnl as f64 / 1_000_000_000.0;
+
+            Some(TradeRecord {
+                mint: p.token_mint.clone(),
+                symbol: p.token_symbol.clone(),
+                entry_price: from_scaled_price(p.entry_price),
+                exit_price: from_scaled_price(p.current_price),
+                sol_invested: p.amount_sol,
+                sol_received: p.amount_sol + pnl,
+                pnl,
+                pnl_pct: p.pnl_percentage,
+                opened_at: p.opened_at,
+                closed_at,
+                hold_seconds: closed_at - p.opened_at,
+                strategy: StrategyType::from_on_chain_id(p.strategy),
+            })
+        })
+        .collect();
+
+    let lines = std::iter::once(export::header_chunk())
+        .chain(records.into_iter().map(|record| export::row_chunk(&record)));
+
+    (
+        [
+            (header::CONTENT_TYPE, "text/csv"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"trades.csv\""),
+        ],
+        Body::from_stream(futures::stream::iter(lines)),
+    )
+        .into_response()
+}
+
 async fn all_positions_handler(
     State(state): State<ApiState>,
-) -> Json<Vec<PositionInfo>> {
+    Query(query): Query<PositionQuery>,
+) -> Result<Json<PaginatedResponse<PositionInfo>>, ApiError> {
     let positions = state.positions.read().await;
-    Json(positions.clone())
+
+    Ok(Json(paginate_positions(positions.iter(), &query)?))
+}
+
+/// One position by id; an open one carries the price and PnL of the trading loop's
+/// latest live snapshot
+async fn position_handler(
+    State(state): State<ApiState>,
+    Path(position_id): Path<String>,
+) -> Result<Json<PositionInfo>, ApiError> {
+    let positions = state.positions.read().await;
+
+    positions
+        .iter()
+        .find(|p| p.position_id == position_id)
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "Position not found"))
 }
 
 async fn bot_stats_handler(
     State(state): State<ApiState>,
 ) -> Json<BotStats> {
-    let stats = state.stats.read().await;
-    Json(stats.clone())
+    let mut stats = state.stats.read().await.clone();
+    stats.trading_halted = state.trading_halted.load(Ordering::SeqCst);
+    Json(stats)
+}
+
+/// Resume opening positions after the drawdown limit halted trading
+async fn risk_reset_handler(
+    State(state): State<ApiState>,
+) -> Json<BotStats> {
+    if state.trading_halted.swap(false, Ordering::SeqCst) {
+        info!("Trading halt cleared via API");
+    }
+
+    let mut stats = state.stats.read().await.clone();
+    stats.trading_halted = false;
+    Json(stats)
+}
+
+/// Force-exit every position in a mint, e.g. after a confirmed rug
+///
+/// Waits for the trading loop to pick the command up and finish selling.
+async fn admin_close_handler(
+    State(state): State<ApiState>,
+    Path(mint): Path<String>,
+) -> Result<Json<CloseMintResponse>, ApiError> {
+    let token_mint = Pubkey::from_str(&mint)
+        .map_err(|_| error_response(StatusCode::BAD_REQUEST, "Invalid mint address"))?;
+    let unavailable = || error_response(StatusCode::SERVICE_UNAVAILABLE, "Trading loop is not accepting commands");
+    let commands = state.commands.as_ref().ok_or_else(unavailable)?;
+
+    let (reply, closed) = oneshot::channel();
+    commands
+        .send(BotCommand::ClosePositions { mint: token_mint, reply })
+        .await
+        .map_err(|_| unavailable())?;
+    let closed = closed.await.map_err(|_| unavailable())?;
+
+    warn!("🚨 Force-closed {} position(s) in {} via API", closed, mint);
+    Ok(Json(CloseMintResponse { mint, closed }))
+}
+
+/// Prometheus text exposition of bot and delegation metrics
+async fn metrics_handler(
+    State(state): State<ApiState>,
+) -> impl IntoResponse {
+    let stats = state.stats.read().await.clone();
+    let active_positions = state.positions.read().await
+        .iter()
+        .filter(|p| p.closed_at.is_none())
+        .count();
+    let total_pnl: i64 = state.delegations.read().await
+        .iter()
+        .map(|d| d.total_pnl)
+        .sum();
+
+    let mut body = String::new();
+    write_metric(&mut body, "bot_total_scans", "counter", "Token scans performed", stats.total_scans as f64);
+    write_metric(&mut body, "bot_tokens_analyzed", "counter", "Tokens run through the strategy", stats.tokens_analyzed as f64);
+    write_metric(&mut body, "bot_signals_generated", "counter", "Trading signals generated", stats.signals_generated as f64);
+    write_metric(&mut body, "bot_trades_executed", "counter", "Trades executed", stats.trades_executed as f64);
+    write_metric(&mut body, "bot_active_positions", "gauge", "Currently open positions", active_positions as f64);
+    write_metric(&mut body, "bot_total_pnl_lamports", "gauge", "Realized PnL across all delegations", total_pnl as f64);
+    write_metric(&mut body, "bot_uptime_seconds", "gauge", "Seconds since the bot started", stats.uptime_seconds as f64);
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        body,
+    )
 }
 
 async fn websocket_handler(
@@ -280,27 +942,151 @@ async fn websocket_handler(
     ws.on_upgrade(|socket| handle_websocket(socket, state))
 }
 
+/// Stream events to one client as `WsMessage` frames
+///
+/// Everything is sent until the client sends `{"subscribe":"<wallet>"}`; from then on
+/// position events for other wallets are dropped, while stats and trailing-stop events
+/// stay global. The subscription is acknowledged with a `subscribed` frame.
 async fn handle_websocket(mut socket: WebSocket, state: ApiState) {
     info!("WebSocket connection established");
 
-    loop {
-        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+    // Subscribe before the snapshot so nothing published in between is missed
+    let mut events = state.events.subscribe();
+
+    let snapshot = StreamEvent::StatsUpdate(state.stats.read().await.clone());
+    if send_event(&mut socket, &snapshot).await.is_err() {
+        warn!("WebSocket connection closed");
+        return;
+    }
 
-        // Send stats update
-        let stats = state.stats.read().await;
-        let message = serde_json::to_string(&*stats).unwrap();
+    let mut heartbeat = tokio::time::interval(tokio::time::Duration::from_secs(HEARTBEAT_INTERVAL_SECS));
+    let mut wallet: Option<String> = None;
 
-        if socket.send(axum::extract::ws::Message::Text(message)).await.is_err() {
-            warn!("WebSocket connection closed");
-            break;
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("WebSocket client lagging, skipped {} events", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                if matches!((event.wallet(), &wallet), (Some(owner), Some(wallet)) if owner != wallet) {
+                    continue;
+                }
+                if send_event(&mut socket, &event).await.is_err() {
+                    break;
+                }
+            }
+            _ = heartbeat.tick() => {
+                if socket.send(Message::Ping(Vec::new())).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.recv() => {
+                match incoming {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(Message::Text(text))) => match serde_json::from_str::<SubscribeRequest>(&text) {
+                        Ok(request) => {
+                            let ack = WsMessage {
+                                kind: "subscribed".to_string(),
+                                data: serde_json::Value::String(request.subscribe.clone()),
+                            };
+                            wallet = Some(request.subscribe);
+                            if send_message(&mut socket, &ack).await.is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => warn!("Ignoring WebSocket message: {}", e),
+                    },
+                    Some(Ok(_)) => {}
+                }
+            }
         }
     }
+
+    warn!("WebSocket connection closed");
+}
+
+async fn send_event(socket: &mut WebSocket, event: &StreamEvent) -> Result<(), axum::Error> {
+    send_message(socket, &WsMessage::from(event)).await
+}
+
+async fn send_message(socket: &mut WebSocket, message: &WsMessage) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(message).expect("stream frames serialize");
+    socket.send(Message::Text(text)).await
 }
 
 // ============================================================================
 // Helper Functions
 // ============================================================================
 
+fn write_metric(out: &mut String, name: &str, kind: &str, help: &str, value: f64) {
+    use std::fmt::Write;
+
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+fn error_response(status: StatusCode, message: &str) -> ApiError {
+    (
+        status,
+        Json(ErrorResponse {
+            error: message.to_string(),
+        }),
+    )
+}
+
+/// Apply status/mint filters, then the offset/limit window
+fn paginate_positions<'a>(
+    positions: impl Iterator<Item = &'a PositionInfo>,
+    query: &PositionQuery,
+) -> Result<PaginatedResponse<PositionInfo>, ApiError> {
+    let want_open = match query.status.as_deref() {
+        None => None,
+        Some("open") => Some(true),
+        Some("closed") => Some(false),
+        Some(other) => {
+            return Err(error_response(StatusCode::BAD_REQUEST, &format!("Unknown status filter: {}", other)));
+        }
+    };
+
+    let filtered: Vec<&PositionInfo> = positions
+        .filter(|p| want_open.map_or(true, |open| p.closed_at.is_none() == open))
+        .filter(|p| query.mint.as_ref().map_or(true, |mint| &p.token_mint == mint))
+        .collect();
+
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
+    let offset = query.offset.unwrap_or(0);
+
+    Ok(PaginatedResponse {
+        total: filtered.len(),
+        items: filtered.into_iter().skip(offset).take(limit).cloned().collect(),
+        limit,
+        offset,
+    })
+}
+
+fn parse_strategy(strategy: &str) -> Result<StrategyType, ApiError> {
+    strategy
+        .parse()
+        .map_err(|_| error_response(StatusCode::BAD_REQUEST, &format!("Unknown strategy: {}", strategy)))
+}
+
+fn validate_limits(max_position_size_sol: f64, max_concurrent_trades: u8) -> Result<(), ApiError> {
+    if !max_position_size_sol.is_finite() || max_position_size_sol <= 0.0 {
+        return Err(error_response(StatusCode::BAD_REQUEST, "max_position_size_sol must be positive"));
+    }
+    if max_concurrent_trades == 0 {
+        return Err(error_response(StatusCode::BAD_REQUEST, "max_concurrent_trades must be at least 1"));
+    }
+    Ok(())
+}
+
 impl ApiState {
     pub async fn update_bot_stats(&self, is_running: bool, uptime: u64, scans: u64, analyzed: u64, signals: u64, trades: u64) {
         let mut stats = self.stats.write().await;
@@ -311,16 +1097,38 @@ impl ApiState {
         stats.signals_generated = signals;
         stats.trades_executed = trades;
         stats.last_scan_at = Some(chrono::Utc::now().timestamp());
+
+        self.publish(StreamEvent::StatsUpdate(stats.clone()));
+    }
+
+    /// Fold a closed position's realized PnL into the outcome stats
+    pub async fn record_close(&self, pnl_lamports: i64) {
+        let mut stats = self.stats.write().await;
+        stats.closed_trades += 1;
+        if pnl_lamports > 0 {
+            stats.winning_trades += 1;
+        }
+        stats.realized_pnl_lamports = stats.realized_pnl_lamports.saturating_add(pnl_lamports);
+        stats.win_rate = stats.winning_trades as f64 / stats.closed_trades as f64;
+
+        self.publish(StreamEvent::StatsUpdate(stats.clone()));
     }
 
-    pub async fn add_delegation(&self, delegation: DelegationInfo) {
+    /// Register a delegation; returns false if the wallet already has one
+    /// Register a delegation; false if that user already has one at the same vault index
+    pub async fn add_delegation(&self, delegation: DelegationInfo) -> bool {
         let mut delegations = self.delegations.write().await;
+        if delegations.iter().any(|d| d.user == delegation.user && d.vault_index == delegation.vault_index) {
+            return false;
+        }
         delegations.push(delegation);
+        true
     }
 
-    pub async fn update_delegation(&self, user: &str, is_active: bool, active_trades: u8, total_trades: u64, profitable_trades: u64, total_pnl: i64) {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_delegation(&self, user: &str, vault_index: u8, is_active: bool, active_trades: u8, total_trades: u64, profitable_trades: u64, total_pnl: i64) {
         let mut delegations = self.delegations.write().await;
-        if let Some(delegation) = delegations.iter_mut().find(|d| d.user == user) {
+        if let Some(delegation) = delegations.iter_mut().find(|d| d.user == user && d.vault_index == vault_index) {
             delegation.is_active = is_active;
             delegation.active_trades = active_trades;
             delegation.total_trades = total_trades;
@@ -331,7 +1139,9 @@ impl ApiState {
 
     pub async fn add_position(&self, position: PositionInfo) {
         let mut positions = self.positions.write().await;
-        positions.push(position);
+        positions.push(position.clone());
+
+        self.publish(StreamEvent::PositionOpened(position));
     }
 
     pub async fn update_position(&self, position_id: &str, current_price: u64, status: &str, pnl: i64, closed_at: Option<i64>) {
@@ -346,6 +1156,883 @@ impl ApiState {
             if position.entry_price > 0 {
                 position.pnl_percentage = ((current_price as f64 - position.entry_price as f64) / position.entry_price as f64) * 100.0;
             }
+
+            let event = if position.closed_at.is_some() {
+                StreamEvent::PositionClosed(position.clone())
+            } else {
+                StreamEvent::PriceUpdate {
+                    position_id: position.position_id.clone(),
+                    user: position.user.clone(),
+                    current_price: position.current_price,
+                    pnl: position.pnl,
+                    pnl_percentage: position.pnl_percentage,
+                }
+            };
+            self.publish(event);
+        }
+    }
+
+    /// Bring open positions up to date with the trader's live snapshot
+    ///
+    /// Entries are matched by owner and mint: the trader holds one position per mint,
+    /// and the vault records it under its own position id.
+    pub async fn refresh_live_positions(&self, snapshot: Vec<PositionInfo>) {
+        let mut positions = self.positions.write().await;
+        for live in snapshot {
+            let held = positions
+                .iter_mut()
+                .filter(|p| p.closed_at.is_none() && p.user == live.user && p.token_mint == live.token_mint);
+            for position in held {
+                position.current_price = live.current_price;
+                position.status = live.status.clone();
+                position.pnl = live.pnl;
+                position.pnl_percentage = live.pnl_percentage;
+                position.stale = live.stale;
+
+                self.publish(StreamEvent::PriceUpdate {
+                    position_id: position.position_id.clone(),
+                    user: position.user.clone(),
+                    current_price: position.current_price,
+                    pnl: position.pnl,
+                    pnl_percentage: position.pnl_percentage,
+                });
+            }
+        }
+    }
+
+    /// Record a position closed on-chain, taking its PnL percentage from the vault's
+    /// `pnl_bps` rather than recomputing it from prices
+    pub async fn close_position(&self, position_id: &str, exit_price: u64, pnl: i64, pnl_bps: i32, closed_at: i64) {
+        let mut positions = self.positions.write().await;
+        if let Some(position) = positions.iter_mut().find(|p| p.position_id == position_id) {
+            position.current_price = exit_price;
+            position.status = "closed".to_string();
+            position.pnl = pnl;
+            position.pnl_percentage = pnl_bps as f64 / 100.0;
+            position.closed_at = Some(closed_at);
+
+            self.publish(StreamEvent::PositionClosed(position.clone()));
+        }
+    }
+
+    /// Close a position force-liquidated at an unknown price, with zero PnL
+    pub async fn liquidate_position(&self, position_id: &str, closed_at: i64) {
+        let mut positions = self.positions.write().await;
+        if let Some(position) = positions.iter_mut().find(|p| p.position_id == position_id) {
+            position.status = "liquidated".to_string();
+            position.pnl = 0;
+            position.pnl_percentage = 0.0;
+            position.closed_at = Some(closed_at);
+
+            self.publish(StreamEvent::PositionClosed(position.clone()));
+        }
+    }
+
+    /// Mark open positions in `token_mint` as trailing and notify stream clients
+    pub async fn mark_trailing_active(&self, token_mint: &str, current_price: f64) {
+        let mut positions = self.positions.write().await;
+        for position in positions.iter_mut().filter(|p| p.token_mint == token_mint && p.closed_at.is_none()) {
+            position.status = "monitoring".to_string();
+        }
+
+        self.publish(StreamEvent::TrailingActivated {
+            token_mint: token_mint.to_string(),
+            current_price,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Method, Request};
+    use futures::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::Message as ClientMessage;
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+    use tower::ServiceExt;
+
+    const WALLET: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    const API_TOKEN: &str = "test-api-token";
+
+    fn test_state() -> ApiState {
+        ApiState::new().with_api_token(Some(API_TOKEN.to_string()))
+    }
+
+    fn get(uri: &str) -> Request<Body> {
+        Request::builder()
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {}", API_TOKEN))
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    fn json_request(method: Method, uri: &str, body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::AUTHORIZATION, format!("Bearer {}", API_TOKEN))
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    fn create_body() -> serde_json::Value {
+        serde_json::json!({
+            "wallet": WALLET,
+            "strategy": "momentum_scalper",
+            "max_position_size_sol": 0.5,
+            "max_concurrent_trades": 3
+        })
+    }
+
+    #[tokio::test]
+    async fn test_create_delegation() {
+        let state = test_state();
+        let response = create_router(state.clone())
+            .oneshot(json_request(Method::POST, "/api/delegations", create_body()))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let delegations = state.delegations.read().await;
+        assert_eq!(delegations.len(), 1);
+        assert_eq!(delegations[0].user, WALLET);
+        assert_eq!(delegations[0].strategy, StrategyType::MomentumScalper);
+        assert!(delegations[0].is_active);
+    }
+
+    #[tokio::test]
+    async fn test_create_duplicate_delegation_conflicts() {
+        let state = test_state();
+        let app = create_router(state.clone());
+
+        let first = app.clone()
+            .oneshot(json_request(Method::POST, "/api/delegations", create_body()))
+            .await
+            .unwrap();
+        let second = app
+            .oneshot(json_request(Method::POST, "/api/delegations", create_body()))
+            .await
+            .unwrap();
+
+        assert_eq!(first.status(), StatusCode::CREATED);
+        assert_eq!(second.status(), StatusCode::CONFLICT);
+        assert_eq!(state.delegations.read().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_second_vault_of_a_wallet_is_its_own_delegation() {
+        let state = test_state();
+        let app = create_router(state.clone());
+
+        let mut body = create_body();
+        body["vault_index"] = 1.into();
+        for body in [create_body(), body] {
+            let response = app.clone()
+                .oneshot(json_request(Method::POST, "/api/delegations", body))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+        }
+
+        let update = serde_json::json!({ "vault_index": 1, "is_active": false });
+        let response = app
+            .oneshot(json_request(Method::PUT, &format!("/api/delegations/{}", WALLET), update))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let delegations = state.delegations.read().await;
+        assert_eq!(delegations.len(), 2);
+        assert!(delegations.iter().find(|d| d.vault_index == 0).unwrap().is_active);
+        assert!(!delegations.iter().find(|d| d.vault_index == 1).unwrap().is_active);
+    }
+
+    #[tokio::test]
+    async fn test_create_delegation_rejects_invalid_input() {
+        let state = test_state();
+        let app = create_router(state.clone());
+
+        let mut bad_strategy = create_body();
+        bad_strategy["strategy"] = "yolo".into();
+        let mut zero_size = create_body();
+        zero_size["max_position_size_sol"] = 0.0.into();
+        let mut zero_trades = create_body();
+        zero_trades["max_concurrent_trades"] = 0.into();
+
+        for body in [bad_strategy, zero_size, zero_trades] {
+            let response = app.clone()
+                .oneshot(json_request(Method::POST, "/api/delegations", body))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+
+        assert!(state.delegations.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_update_delegation() {
+        let state = test_state();
+        let app = create_router(state.clone());
+        app.clone()
+            .oneshot(json_request(Method::POST, "/api/delegations", create_body()))
+            .await
+            .unwrap();
+
+        let update = serde_json::json!({ "strategy": "conservative", "max_concurrent_trades": 1, "is_active": false });
+        let response = app
+            .oneshot(json_request(Method::PUT, &format!("/api/delegations/{}", WALLET), update))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let delegations = state.delegations.read().await;
+        assert_eq!(delegations[0].strategy, StrategyType::Conservative);
+        assert_eq!(delegations[0].max_concurrent_trades, 1);
+        assert_eq!(delegations[0].max_position_size_sol, 0.5);
+        assert!(!delegations[0].is_active);
+    }
+
+    #[tokio::test]
+    async fn test_update_delegation_validation() {
+        let state = test_state();
+        let app = create_router(state);
+
+        let missing = app.clone()
+            .oneshot(json_request(Method::PUT, &format!("/api/delegations/{}", WALLET), serde_json::json!({ "is_active": false })))
+            .await
+            .unwrap();
+        let zero_limit = app
+            .oneshot(json_request(Method::PUT, &format!("/api/delegations/{}", WALLET), serde_json::json!({ "max_concurrent_trades": 0 })))
+            .await
+            .unwrap();
+
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+        assert_eq!(zero_limit.status(), StatusCode::BAD_REQUEST);
+    }
+
+    fn position(id: &str) -> PositionInfo {
+        PositionInfo {
+            position_id: id.to_string(),
+            user: WALLET.to_string(),
+            token_mint: "Mint111".to_string(),
+            token_symbol: "MINT".to_string(),
+            amount_sol: 0.5,
+            entry_price: 1_000,
+            current_price: 1_000,
+            take_profit_price: 2_000,
+            stop_loss_price: 500,
+            status: "open".to_string(),
+            pnl: 0,
+            pnl_percentage: 0.0,
+            opened_at: 1_700_000_000,
+            closed_at: None,
+            strategy: 0,
+            entry_confidence_bps: 8_000,
+            stale: false,
+        }
+    }
+
+    fn delegation_info() -> DelegationInfo {
+        DelegationInfo {
+            user: WALLET.to_string(),
+            vault_index: 0,
+            strategy: StrategyType::Conservative,
+            max_position_size_sol: 1.0,
+            max_concurrent_trades: 3,
+            is_active: true,
+            active_trades: 0,
+            total_trades: 4,
+            profitable_trades: 3,
+            total_pnl: 250_000_000,
+            created_at: 1_700_000_000,
+        }
+    }
+
+    /// Next JSON frame from the client side of `/api/stream`, skipping pings
+    async fn next_event<S>(ws: &mut S) -> WsMessage
+    where
+        S: futures::Stream<Item = Result<ClientMessage, tokio_tungstenite::tungstenite::Error>> + Unpin,
+    {
+        let frame = async {
+            loop {
+                if let ClientMessage::Text(text) = ws.next().await.unwrap().unwrap() {
+                    return serde_json::from_str(&text).unwrap();
+                }
+            }
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(5), frame).await.unwrap()
+    }
+
+    async fn connect_stream(state: &ApiState) -> tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = create_router(state.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let mut request = format!("ws://{}/api/stream", addr).into_client_request().unwrap();
+        request
+            .headers_mut()
+            .insert("Authorization", format!("Bearer {}", API_TOKEN).parse().unwrap());
+        let (ws, _) = tokio_tungstenite::connect_async(request).await.unwrap();
+        ws
+    }
+
+    #[tokio::test]
+    async fn test_websocket_streams_position_opened() {
+        let state = test_state();
+        let mut ws = connect_stream(&state).await;
+
+        // The snapshot arrives once the server has subscribed
+        let snapshot = next_event(&mut ws).await;
+        assert_eq!(snapshot.kind, "stats_update");
+
+        state.add_position(position("pos-1")).await;
+
+        let event = next_event(&mut ws).await;
+        assert_eq!(event.kind, "position_opened");
+        assert_eq!(event.data["position_id"], "pos-1");
+    }
+
+    #[tokio::test]
+    async fn test_websocket_subscription_filters_other_wallets() {
+        let state = test_state();
+        let mut ws = connect_stream(&state).await;
+
+        assert_eq!(next_event(&mut ws).await.kind, "stats_update");
+        ws.send(ClientMessage::Text(serde_json::json!({ "subscribe": WALLET }).to_string())).await.unwrap();
+        let ack = next_event(&mut ws).await;
+        assert_eq!(ack.kind, "subscribed");
+        assert_eq!(ack.data, WALLET);
+
+        let mut other = position("pos-other");
+        other.user = "OtherWallet111".to_string();
+        state.add_position(other).await;
+        state.add_position(position("pos-1")).await;
+        state.record_close(1_000).await;
+
+        // The other wallet's position never arrives; stats still do
+        let event = next_event(&mut ws).await;
+        assert_eq!(event.kind, "position_opened");
+        assert_eq!(event.data["position_id"], "pos-1");
+        assert_eq!(next_event(&mut ws).await.kind, "stats_update");
+    }
+
+    #[tokio::test]
+    async fn test_closed_position_percentage_comes_from_pnl_bps() {
+        let state = test_state();
+        state.add_position(position("pos-1")).await;
+
+        // Exit price alone would say +40%; the vault realized +33.33% after fees
+        state.close_position("pos-1", 1_400, 166_650_000, 3_333, 1_700_000_500).await;
+
+        let positions = state.positions.read().await;
+        assert_eq!(positions[0].status, "closed");
+        assert_eq!(positions[0].closed_at, Some(1_700_000_500));
+        assert!((positions[0].pnl_percentage - 33.33).abs() < 1e-9);
+    }
+
+    async fn get_positions(app: Router, uri: &str) -> (StatusCode, PaginatedResponse<PositionInfo>) {
+        let response = app
+            .oneshot(get(uri))
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    async fn seeded_state() -> ApiState {
+        let state = test_state();
+        for i in 0..10 {
+            let mut p = position(&format!("pos-{}", i));
+            if i % 2 == 1 {
+                p.status = "closed".to_string();
+                p.closed_at = Some(1_700_000_100);
+            }
+            if i == 4 {
+                p.token_mint = "OtherMint".to_string();
+            }
+            state.positions.write().await.push(p);
+        }
+        state
+    }
+
+    #[tokio::test]
+    async fn test_positions_filter_by_status_and_mint() {
+        let app = create_router(seeded_state().await);
+
+        let (status, open) = get_positions(app.clone(), "/api/positions?status=open").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(open.total, 5);
+        assert!(open.items.iter().all(|p| p.closed_at.is_none()));
+
+        let (_, closed) = get_positions(app.clone(), &format!("/api/users/{}/positions?status=closed", WALLET)).await;
+        assert_eq!(closed.total, 5);
+        assert!(closed.items.iter().all(|p| p.closed_at.is_some()));
+
+        let (_, by_mint) = get_positions(app.clone(), "/api/positions?status=open&mint=OtherMint").await;
+        assert_eq!(by_mint.total, 1);
+        assert_eq!(by_mint.items[0].position_id, "pos-4");
+
+        let response = app
+            .oneshot(get("/api/positions?status=pending"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_positions_offset_limit_window() {
+        let app = create_router(seeded_state().await);
+
+        let (_, page) = get_positions(app.clone(), "/api/positions?offset=3&limit=4").await;
+        let ids: Vec<&str> = page.items.iter().map(|p| p.position_id.as_str()).collect();
+        assert_eq!(ids, vec!["pos-3", "pos-4", "pos-5", "pos-6"]);
+        assert_eq!((page.total, page.limit, page.offset), (10, 4, 3));
+
+        let (_, past_end) = get_positions(app.clone(), "/api/positions?offset=8&limit=5").await;
+        assert_eq!(past_end.items.len(), 2);
+
+        let (_, capped) = get_positions(app, "/api/positions?limit=1000").await;
+        assert_eq!(capped.limit, MAX_PAGE_LIMIT);
+        assert_eq!(capped.items.len(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_position_by_id_carries_live_pnl() {
+        let state = seeded_state().await;
+        let app = create_router(state.clone());
+        let fetch = |app: Router| async move {
+            let response = app.oneshot(get("/api/positions/pos-4")).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<PositionInfo>(&body).unwrap()
+        };
+        assert_eq!(fetch(app.clone()).await.current_price, 1_000);
+
+        // The trading loop's snapshot of the mint, priced 50% up since entry
+        let live = PositionInfo {
+            position_id: "TraderSideId".to_string(),
+            token_mint: "OtherMint".to_string(),
+            current_price: 1_500,
+            pnl: 250_000_000,
+            pnl_percentage: 50.0,
+            ..position("live")
+        };
+        state.refresh_live_positions(vec![live]).await;
+
+        let found = fetch(app.clone()).await;
+        assert_eq!(found.position_id, "pos-4");
+        assert_eq!(found.current_price, 1_500);
+        assert_eq!(found.pnl, 250_000_000);
+        assert!((found.pnl_percentage - 50.0).abs() < 1e-9);
+        // Positions in other mints keep their prices
+        assert_eq!(state.positions.read().await[2].current_price, 1_000);
+
+        let response = app.oneshot(get("/api/positions/pos-missing")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error.error, "Position not found");
+    }
+
+    #[tokio::test]
+    async fn test_stats_aggregate_closed_trades() {
+        let state = test_state();
+        for pnl in [500_000_000, -200_000_000, 300_000_000, -100_000_000, 0] {
+            state.record_close(pnl).await;
+        }
+
+        let response = create_router(state)
+            .oneshot(get("/api/stats"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let stats: BotStats = serde_json::from_slice(&body).unwrap();
+        assert_eq!(stats.closed_trades, 5);
+        assert_eq!(stats.winning_trades, 2);
+        assert_eq!(stats.realized_pnl_lamports, 500_000_000);
+        assert!((stats.win_rate - 0.4).abs() < 1e-9);
+    }
+
+    async fn get_user_stats(state: ApiState) -> UserStats {
+        let response = create_router(state)
+            .oneshot(get(&format!("/api/users/{}/stats", WALLET)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_user_stats_summarize_risk_exposure() {
+        let state = test_state();
+        state.add_delegation(delegation_info()).await;
+        {
+            let mut positions = state.positions.write().await;
+            // (id, amount_sol, pnl, closed_at); opened at 1_000
+            for (id, amount_sol, pnl, closed_at) in [
+                ("open-small", 0.25, 50_000_000, None),
+                ("open-large", 0.75, -100_000_000, None),
+                ("closed-win", 0.5, 200_000_000, Some(1_600)),
+                ("closed-loss", 1.0, -50_000_000, Some(2_000)),
+            ] {
+                let mut p = position(id);
+                p.amount_sol = amount_sol;
+                p.pnl = pnl;
+                p.opened_at = 1_000;
+                if closed_at.is_some() {
+                    p.status = "closed".to_string();
+                    p.closed_at = closed_at;
+                }
+                positions.push(p);
+            }
+            // Another user's position doesn't count
+            let mut other = position("other");
+            other.user = "OtherWallet".to_string();
+            positions.push(other);
+        }
+
+        let stats = get_user_stats(state).await;
+        assert!((stats.open_exposure_sol - 1.0).abs() < 1e-9);
+        assert!((stats.largest_open_position_sol - 0.75).abs() < 1e-9);
+        assert!((stats.realized_vs_unrealized_pnl.realized_sol - 0.15).abs() < 1e-9);
+        assert!((stats.realized_vs_unrealized_pnl.unrealized_sol + 0.05).abs() < 1e-9);
+        assert!((stats.avg_hold_seconds - 800.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_user_stats_without_positions_are_zero() {
+        let state = test_state();
+        state.add_delegation(delegation_info()).await;
+
+        let stats = get_user_stats(state).await;
+        assert_eq!(stats.open_exposure_sol, 0.0);
+        assert_eq!(stats.largest_open_position_sol, 0.0);
+        assert_eq!(stats.realized_vs_unrealized_pnl, PnlBreakdown::default());
+        assert_eq!(stats.avg_hold_seconds, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_risk_reset_clears_trading_halt() {
+        let state = test_state();
+        state.trading_halted.store(true, Ordering::SeqCst);
+        let app = create_router(state.clone());
+
+        let response = app.clone().oneshot(get("/api/stats")).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(serde_json::from_slice::<BotStats>(&body).unwrap().trading_halted);
+
+        let response = app
+            .oneshot(json_request(Method::POST, "/api/risk/reset", serde_json::json!({})))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!state.trading_halted.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_analyze_scores_posted_metrics_with_every_strategy() {
+        let payload = serde_json::json!({
+            "mint": Pubkey::new_unique().to_string(),
+            "name": "Hypothetical",
+            "symbol": "HYPO",
+            "volume_5m": 25.0,
+            "volume_1h": 120.0,
+            "volume_24h": 400.0,
+            "current_price": 0.00000004,
+            "price_change_5m": 0.15,
+            "price_change_1h": 0.6,
+            "liquidity_sol": 30.0,
+            "liquidity_usd": 4500.0,
+            "holder_count": 180,
+            "holder_concentration": 0.18,
+            "unique_buyers_5m": 40,
+            "unique_sellers_5m": 12,
+            "market_cap": 60000.0,
+            "fully_diluted_valuation": 60000.0,
+            "bonding_curve_progress": 55.0,
+            "is_graduated": false,
+            "created_at": 0,
+            "time_since_creation": 1800,
+            "buy_pressure": 3.0,
+            "sell_pressure": 0.33,
+            "volatility_score": 0.3
+        });
+        let metrics: TokenMetrics = serde_json::from_value(payload.clone()).unwrap();
+
+        let response = create_router(test_state())
+            .oneshot(json_request(Method::POST, "/api/analyze", payload))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let analysis: AnalyzeResponse = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(analysis.scores.len(), 5);
+        let conservative = &analysis.scores[0];
+        assert_eq!(conservative.strategy, StrategyType::Conservative);
+        assert!(conservative.breakdown.is_some());
+
+        let direct = crate::analyzer::create_strategy(StrategyType::Conservative, None, &HolderThresholds::default())
+            .analyze(&metrics)
+            .unwrap();
+        assert_eq!(conservative.confidence, direct.confidence);
+        assert_eq!(conservative.signal_type, direct.signal_type);
+
+        let ensemble = analysis.scores.last().unwrap();
+        assert_eq!(ensemble.strategy, StrategyType::Ensemble);
+        let best = analysis.scores[..4].iter().map(|s| s.confidence).fold(f64::MIN, f64::max);
+        assert_eq!(ensemble.confidence, best);
+    }
+
+    #[tokio::test]
+    async fn test_admin_close_enqueues_command_and_returns_count() {
+        let mint = Pubkey::new_unique();
+        let (commands, mut received) = mpsc::channel(1);
+        let app = create_router(test_state().with_command_channel(commands));
+
+        // Stub trading loop: closes two positions in whatever mint it's asked for
+        let trader = tokio::spawn(async move {
+            let BotCommand::ClosePositions { mint, reply } = received.recv().await.unwrap();
+            reply.send(2).unwrap();
+            mint
+        });
+
+        let uri = format!("/api/admin/close/{}", mint);
+        let response = app
+            .oneshot(json_request(Method::POST, &uri, serde_json::json!({})))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let closed: CloseMintResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(closed.closed, 2);
+        assert_eq!(closed.mint, mint.to_string());
+        assert_eq!(trader.await.unwrap(), mint);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint() {
+        let state = seeded_state().await;
+        state.update_bot_stats(true, 120, 42, 30, 5, 2).await;
+        state.add_delegation(delegation_info()).await;
+
+        let response = create_router(state)
+            .oneshot(get("/metrics"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+
+        let value = |name: &str| -> f64 {
+            text.lines()
+                .find(|l| l.starts_with(&format!("{} ", name)))
+                .and_then(|l| l.split_whitespace().nth(1))
+                .unwrap_or_else(|| panic!("missing metric {}", name))
+                .parse()
+                .unwrap()
+        };
+
+        assert_eq!(value("bot_total_scans"), 42.0);
+        assert_eq!(value("bot_tokens_analyzed"), 30.0);
+        assert_eq!(value("bot_signals_generated"), 5.0);
+        assert_eq!(value("bot_trades_executed"), 2.0);
+        assert_eq!(value("bot_active_positions"), 5.0);
+        assert_eq!(value("bot_total_pnl_lamports"), 250_000_000.0);
+        assert_eq!(value("bot_uptime_seconds"), 120.0);
+        assert!(text.contains("# TYPE bot_total_scans counter"));
+    }
+
+    #[tokio::test]
+    async fn test_history_returns_closed_positions_in_range() {
+        let state = test_state();
+        {
+            let mut positions = state.positions.write().await;
+            // (id, closed_at, pnl); None stays open
+            for (id, closed_at, pnl) in [
+                ("open", None, 0),
+                ("early", Some(1_000), 10),
+                ("mid-win", Some(2_000), 50),
+                ("mid-loss", Some(3_000), -20),
+                ("late", Some(9_000), 5),
+            ] {
+                let mut p = position(id);
+                p.opened_at = 500;
+                p.pnl = pnl;
+                if closed_at.is_some() {
+                    p.status = "closed".to_string();
+                    p.closed_at = closed_at;
+                }
+                positions.push(p);
+            }
         }
+
+        let response = create_router(state)
+            .oneshot(
+                get(&format!("/api/users/{}/history?from=1500&to=5000", WALLET)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let history: HistoryResponse = serde_json::from_slice(&body).unwrap();
+
+        let ids: Vec<&str> = history.trades.iter().map(|t| t.position_id.as_str()).collect();
+        assert_eq!(ids, vec!["mid-loss", "mid-win"]);
+        assert_eq!(history.trades[1].hold_duration_seconds, 1_500);
+        assert_eq!(history.summary.total_trades, 2);
+        assert_eq!(history.summary.winning_trades, 1);
+        assert_eq!(history.summary.win_rate, 50.0);
+        assert_eq!(history.summary.realized_pnl, 30);
+    }
+
+    #[tokio::test]
+    async fn test_export_csv_lists_closed_positions() {
+        let state = test_state();
+        {
+            let mut positions = state.positions.write().await;
+            positions.push(position("still-open"));
+
+            let mut closed = position("closed");
+            closed.entry_price = crate::vault::to_scaled_price(0.5);
+            closed.current_price = crate::vault::to_scaled_price(0.75);
+            closed.status = "closed".to_string();
+            closed.pnl = 250_000_000;
+            closed.pnl_percentage = 50.0;
+            closed.opened_at = 1_000;
+            closed.closed_at = Some(1_600);
+            positions.push(closed);
+        }
+
+        let response = create_router(state)
+            .oneshot(get(&format!("/api/users/{}/export.csv", WALLET)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/csv");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let csv = String::from_utf8(body.to_vec()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines, vec![
+            "mint,symbol,entry_price,exit_price,sol_invested,sol_received,pnl,pnl_pct,opened_at,closed_at,hold_seconds,strategy",
+            "Mint111,MINT,0.5,0.75,0.5,0.75,0.25,50.0,1000,1600,600,conservative",
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_protected_routes_require_bearer_token() {
+        let app = create_router(test_state());
+        let uri = format!("/api/users/{}/positions", WALLET);
+
+        let missing = app.clone()
+            .oneshot(Request::builder().uri(&uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let wrong = app.clone()
+            .oneshot(
+                Request::builder()
+                    .uri(&uri)
+                    .header(header::AUTHORIZATION, "Bearer not-the-token")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let valid = app.clone().oneshot(get(&uri)).await.unwrap();
+
+        assert_eq!(missing.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(wrong.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(valid.status(), StatusCode::OK);
+
+        // Public routes stay open
+        let health = app
+            .oneshot(Request::builder().uri("/api/health").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(health.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_positions_and_stream_require_bearer_token() {
+        let app = create_router(seeded_state().await);
+
+        for uri in ["/api/positions", "/api/positions/pos-2", "/api/stream"] {
+            let missing = app.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(missing.status(), StatusCode::UNAUTHORIZED, "{}", uri);
+        }
+
+        let valid = app.oneshot(get("/api/positions/pos-2")).await.unwrap();
+        assert_eq!(valid.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_stream_rejects_unauthenticated_upgrade() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = create_router(test_state());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let result = tokio_tungstenite::connect_async(format!("ws://{}/api/stream", addr)).await;
+        match result {
+            Err(tokio_tungstenite::tungstenite::Error::Http(response)) => {
+                assert_eq!(response.status().as_u16(), 401);
+            }
+            other => panic!("expected a 401 upgrade response, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mutating_routes_reject_without_configured_token() {
+        let state = ApiState::new();
+        let response = create_router(state.clone())
+            .oneshot(json_request(Method::POST, "/api/delegations", create_body()))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(state.delegations.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_health_reports_degraded_when_rpc_is_down() {
+        let mut server = mockito::Server::new_async().await;
+        let _metrics = server.mock("GET", "/tokens/trending")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"tokens":[]}"#)
+            .create_async()
+            .await;
+        let mut config = crate::types::BotConfig::for_tests(&server.url());
+        config.rpc_url = "http://127.0.0.1:1".to_string();
+
+        let state = ApiState::new().with_health_checker(HealthChecker::new(&config));
+        state.update_bot_stats(true, 60, 1, 1, 0, 0).await;
+
+        let response = create_router(state)
+            .oneshot(Request::builder().uri("/api/health").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let health: HealthResponse = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(health.status, "degraded");
+        assert_eq!(health.dependencies, Some(DependencyHealth { rpc: false, metrics_api: true }));
     }
 }
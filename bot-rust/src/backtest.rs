@@ -0,0 +1,367 @@
+use crate::analyzer::TradingStrategy;
+use crate::error::{BotError, Result};
+use crate::types::{SignalType, StrategyExitParams, TokenMetrics};
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::debug;
+
+// ============================================================================
+// Backtest Report
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExitReason {
+    TakeProfit,
+    StopLoss,
+    TrailingStop,
+    Timeout,
+    /// Still open when the series ran out; closed at the last seen price
+    EndOfData,
+}
+
+#[derive(Debug, Clone)]
+pub struct BacktestTrade {
+    pub mint: String,
+    pub entry_price: f64,
+    pub exit_price: f64,
+    pub entry_time: i64,
+    pub exit_time: i64,
+    pub exit_reason: ExitReason,
+}
+
+impl BacktestTrade {
+    /// Fractional return, e.g. 0.5 for +50%
+    pub fn return_pct(&self) -> f64 {
+        (self.exit_price - self.entry_price) / self.entry_price
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BacktestReport {
+    pub trades: Vec<BacktestTrade>,
+    pub total_trades: usize,
+    pub winning_trades: usize,
+    /// 0-1
+    pub win_rate: f64,
+    /// Sum of per-trade fractional returns, assuming equal position sizes
+    pub total_return: f64,
+    /// Largest peak-to-trough fall of the equity curve, as a fraction of the peak
+    pub max_drawdown: f64,
+    pub avg_hold_seconds: f64,
+}
+
+impl BacktestReport {
+    fn from_trades(trades: Vec<BacktestTrade>) -> Self {
+        if trades.is_empty() {
+            return Self::default();
+        }
+
+        let total_trades = trades.len();
+        let winning_trades = trades.iter().filter(|t| t.return_pct() > 0.0).count();
+        let total_return: f64 = trades.iter().map(|t| t.return_pct()).sum();
+        let avg_hold_seconds = trades.iter()
+            .map(|t| (t.exit_time - t.entry_time) as f64)
+            .sum::<f64>() / total_trades as f64;
+
+        // Equity starts at 1.0 and moves by each trade's return in exit order
+        let mut by_exit: Vec<&BacktestTrade> = trades.iter().collect();
+        by_exit.sort_by_key(|t| t.exit_time);
+
+        let mut equity = 1.0;
+        let mut peak = 1.0;
+        let mut max_drawdown: f64 = 0.0;
+        for trade in by_exit {
+            equity += trade.return_pct();
+            peak = f64::max(peak, equity);
+            max_drawdown = max_drawdown.max((peak - equity) / peak);
+        }
+
+        Self {
+            total_trades,
+            winning_trades,
+            win_rate: winning_trades as f64 / total_trades as f64,
+            total_return,
+            max_drawdown,
+            avg_hold_seconds,
+            trades,
+        }
+    }
+}
+
+// ============================================================================
+// Simulation
+// ============================================================================
+
+struct SimPosition {
+    entry_price: f64,
+    entry_time: i64,
+    peak_price: f64,
+    last_price: f64,
+    last_time: i64,
+}
+
+/// Snapshot time: creation time plus the token's age when it was recorded
+fn snapshot_time(metrics: &TokenMetrics) -> i64 {
+    metrics.created_at + metrics.time_since_creation as i64
+}
+
+/// Replay `strategy` over a time-ordered series of metric snapshots
+///
+/// Snapshots may mix several tokens; each token holds at most one position at a time.
+/// Entries happen at the snapshot price on a StrongBuy, and exits use the same
+/// TP/SL/trailing/timeout rules as live trading.
+pub fn run_backtest(
+    strategy: &dyn TradingStrategy,
+    series: &[TokenMetrics],
+    exit: &StrategyExitParams,
+) -> BacktestReport {
+    let mut open: HashMap<String, SimPosition> = HashMap::new();
+    let mut trades = Vec::new();
+
+    for metrics in series {
+        let now = snapshot_time(metrics);
+        let price = metrics.current_price;
+
+        if let Some(position) = open.get_mut(&metrics.mint) {
+            position.peak_price = position.peak_price.max(price);
+            position.last_price = price;
+            position.last_time = now;
+
+            if let Some(reason) = exit_reason(position, price, now, exit) {
+                let position = open.remove(&metrics.mint).unwrap();
+                trades.push(BacktestTrade {
+                    mint: metrics.mint.clone(),
+                    entry_price: position.entry_price,
+                    exit_price: price,
+                    entry_time: position.entry_time,
+                    exit_time: now,
+                    exit_reason: reason,
+                });
+            }
+            continue;
+        }
+
+        if price <= 0.0 {
+            continue;
+        }
+
+        let signal = match strategy.analyze(metrics) {
+            Ok(signal) => signal,
+            Err(e) => {
+                debug!("Backtest: failed to analyze {}: {}", metrics.mint, e);
+                continue;
+            }
+        };
+
+        if signal.signal_type == SignalType::StrongBuy {
+            open.insert(metrics.mint.clone(), SimPosition {
+                entry_price: price,
+                entry_time: now,
+                peak_price: price,
+                last_price: price,
+                last_time: now,
+            });
+        }
+    }
+
+    for (mint, position) in open {
+        trades.push(BacktestTrade {
+            mint,
+            entry_price: position.entry_price,
+            exit_price: position.last_price,
+            entry_time: position.entry_time,
+            exit_time: position.last_time,
+            exit_reason: ExitReason::EndOfData,
+        });
+    }
+
+    BacktestReport::from_trades(trades)
+}
+
+fn exit_reason(position: &SimPosition, price: f64, now: i64, exit: &StrategyExitParams) -> Option<ExitReason> {
+    let entry = position.entry_price;
+
+    if price >= entry * exit.take_profit_multiplier {
+        return Some(ExitReason::TakeProfit);
+    }
+    if price <= entry * (1.0 - exit.stop_loss_percentage) {
+        return Some(ExitReason::StopLoss);
+    }
+    if exit.use_trailing_stop
+        && position.peak_price >= entry * (1.0 + exit.trailing_activation_pct)
+        && price <= position.peak_price * (1.0 - exit.trailing_distance_pct)
+    {
+        return Some(ExitReason::TrailingStop);
+    }
+    if now - position.entry_time > exit.position_timeout_seconds as i64 {
+        return Some(ExitReason::Timeout);
+    }
+
+    None
+}
+
+// ============================================================================
+// Loading Recorded Series
+// ============================================================================
+
+/// Load `TokenMetrics` snapshots from a JSON array or a JSON-lines file,
+/// sorted by snapshot time
+pub fn load_series(path: &Path) -> Result<Vec<TokenMetrics>> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| BotError::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+
+    let mut series: Vec<TokenMetrics> = if contents.trim_start().starts_with('[') {
+        serde_json::from_str(&contents)?
+    } else {
+        contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<std::result::Result<_, _>>()?
+    };
+
+    series.sort_by_key(snapshot_time);
+    Ok(series)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TradingSignal;
+    use solana_sdk::pubkey::Pubkey;
+
+    /// Buys every token it sees
+    struct AlwaysBuy;
+
+    impl TradingStrategy for AlwaysBuy {
+        fn analyze(&self, _metrics: &TokenMetrics) -> Result<TradingSignal> {
+            Ok(TradingSignal {
+                token_mint: Pubkey::default(),
+                signal_type: SignalType::StrongBuy,
+                confidence: 1.0,
+                reasoning: Vec::new(),
+                reason_codes: Vec::new(),
+                timestamp: 0,
+                rejected: false,
+            })
+        }
+
+        fn get_exit_params(&self) -> StrategyExitParams {
+            exit_params()
+        }
+
+        fn name(&self) -> &str {
+            "Always Buy"
+        }
+
+        fn strategy_type(&self) -> crate::types::StrategyType {
+            crate::types::StrategyType::Conservative
+        }
+    }
+
+    fn exit_params() -> StrategyExitParams {
+        StrategyExitParams {
+            take_profit_multiplier: 2.0,
+            stop_loss_percentage: 0.5,
+            position_timeout_seconds: 3600,
+            use_trailing_stop: false,
+            trailing_activation_pct: 0.0,
+            trailing_distance_pct: 0.0,
+            loss_cooldown_seconds: 0,
+            take_profit_ladder: Vec::new(),
+        }
+    }
+
+    fn snapshot(mint: &str, seconds: u64, price: f64) -> TokenMetrics {
+        TokenMetrics {
+            mint: mint.to_string(),
+            name: mint.to_string(),
+            symbol: mint.to_string(),
+            creator: None,
+            volume_5m: 0.0,
+            volume_1h: 0.0,
+            volume_24h: 0.0,
+            current_price: price,
+            price_change_5m: 0.0,
+            price_change_1h: 0.0,
+            liquidity_sol: 0.0,
+            liquidity_usd: 0.0,
+            virtual_sol_reserves: 0.0,
+            virtual_token_reserves: 0.0,
+            holder_count: 0,
+            holder_concentration: 0.0,
+            unique_buyers_5m: 0,
+            unique_sellers_5m: 0,
+            market_cap: 0.0,
+            fully_diluted_valuation: 0.0,
+            bonding_curve_progress: 0.0,
+            is_graduated: false,
+            created_at: 1_700_000_000,
+            time_since_creation: seconds,
+            buy_pressure: 0.0,
+            sell_pressure: 0.0,
+            buy_count_5m: 0,
+            sell_count_5m: 0,
+            volatility_score: 0.0,
+            wash_trade_score: 0.0,
+            rug_risk: None,
+        }
+    }
+
+    #[test]
+    fn test_profitable_series() {
+        let series = vec![
+            snapshot("WIN", 0, 1.0),
+            snapshot("WIN", 60, 1.5),
+            snapshot("WIN", 120, 2.2), // take profit
+        ];
+
+        let report = run_backtest(&AlwaysBuy, &series, &exit_params());
+
+        assert_eq!(report.total_trades, 1);
+        assert_eq!(report.winning_trades, 1);
+        assert_eq!(report.win_rate, 1.0);
+        assert!((report.total_return - 1.2).abs() < 1e-9);
+        assert_eq!(report.max_drawdown, 0.0);
+        assert_eq!(report.avg_hold_seconds, 120.0);
+        assert_eq!(report.trades[0].exit_reason, ExitReason::TakeProfit);
+    }
+
+    #[test]
+    fn test_losing_series() {
+        let series = vec![
+            snapshot("LOSE", 0, 1.0),
+            snapshot("LOSE", 30, 0.4), // stop loss
+            snapshot("LOSE", 60, 0.4), // re-entry
+            snapshot("LOSE", 4000, 0.3), // timeout, not below the new stop
+        ];
+
+        let report = run_backtest(&AlwaysBuy, &series, &exit_params());
+
+        assert_eq!(report.total_trades, 2);
+        assert_eq!(report.winning_trades, 0);
+        assert_eq!(report.win_rate, 0.0);
+        assert!((report.total_return - (-0.6 - 0.25)).abs() < 1e-9);
+        assert!((report.max_drawdown - 0.85).abs() < 1e-9);
+        assert_eq!(report.avg_hold_seconds, (30.0 + 3940.0) / 2.0);
+        assert_eq!(report.trades[0].exit_reason, ExitReason::StopLoss);
+        assert_eq!(report.trades[1].exit_reason, ExitReason::Timeout);
+    }
+
+    #[test]
+    fn test_load_series_from_json_lines() {
+        let path = std::env::temp_dir().join(format!("curverider-series-{}.jsonl", std::process::id()));
+        let lines: Vec<String> = [snapshot("A", 60, 2.0), snapshot("A", 0, 1.0)]
+            .iter()
+            .map(|m| serde_json::to_string(m).unwrap())
+            .collect();
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let series = load_series(&path);
+        std::fs::remove_file(&path).ok();
+        let series = series.unwrap();
+
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].current_price, 1.0);
+    }
+}
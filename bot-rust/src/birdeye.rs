@@ -0,0 +1,236 @@
+use crate::error::{BotError, Result};
+use crate::scanner::MetricsSource;
+use crate::types::{BotConfig, TokenMetrics};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::debug;
+
+// ============================================================================
+// Birdeye API Types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct OverviewResponse {
+    #[serde(default)]
+    success: bool,
+    #[serde(default)]
+    data: Option<TokenOverview>,
+}
+
+/// `/defi/token_overview` payload; every USD figure is converted to SOL on mapping
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenOverview {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    symbol: String,
+    /// USD; absent for tokens Birdeye has not indexed
+    #[serde(default)]
+    price: Option<f64>,
+    /// USD
+    #[serde(default)]
+    liquidity: f64,
+    #[serde(default, rename = "v1hUSD")]
+    v1h_usd: f64,
+    #[serde(default, rename = "v24hUSD")]
+    v24h_usd: f64,
+    /// Percent, e.g. -4.0 for -4%
+    #[serde(default)]
+    price_change1h_percent: f64,
+    #[serde(default)]
+    mc: f64,
+    #[serde(default)]
+    holder: u32,
+    #[serde(default, rename = "buy1h")]
+    buy_1h: u32,
+    #[serde(default, rename = "sell1h")]
+    sell_1h: u32,
+}
+
+// ============================================================================
+// Birdeye Source
+// ============================================================================
+
+/// Token metrics from the Birdeye token overview, authenticated with `X-API-KEY`
+///
+/// Birdeye indexes DEX pools, so this covers tokens after graduation. It has no
+/// 5-minute windows or creation time: those fields are 0, and the age is left
+/// unknown so age-gated strategies skip the token.
+pub struct BirdeyeSource {
+    client: Client,
+    api_url: String,
+    api_key: Option<String>,
+}
+
+impl BirdeyeSource {
+    pub fn new(config: &BotConfig) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            api_url: config.birdeye_api_url.clone(),
+            api_key: config.birdeye_api_key.clone(),
+        }
+    }
+
+    /// Fetch the overview of `address`; `TokenNotFound` when Birdeye has no price for it
+    async fn overview(&self, address: &str) -> Result<TokenOverview> {
+        let api_key = self.api_key.as_deref()
+            .ok_or_else(|| BotError::Config("BIRDEYE_API_KEY is required for the Birdeye source".to_string()))?;
+        let url = format!("{}/defi/token_overview", self.api_url);
+
+        debug!("Fetching Birdeye overview for {}", address);
+
+        let response: OverviewResponse = self.client
+            .get(&url)
+            .query(&[("address", address)])
+            .header("X-API-KEY", api_key)
+            .header("x-chain", "solana")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        match response.data {
+            Some(overview) if response.success && overview.price.is_some_and(|p| p > 0.0) => Ok(overview),
+            _ => Err(BotError::TokenNotFound(address.to_string())),
+        }
+    }
+
+    /// Map a token overview into `TokenMetrics`, pricing in SOL at `sol_usd`
+    fn metrics_from_overview(mint: &str, token: TokenOverview, sol_usd: f64) -> Result<TokenMetrics> {
+        if sol_usd <= 0.0 {
+            return Err(BotError::Unknown("Birdeye returned no SOL price".to_string()));
+        }
+        let to_sol = |usd: f64| usd / sol_usd;
+
+        let buys = token.buy_1h;
+        let sells = token.sell_1h;
+        let buy_pressure = if sells > 0 { buys as f64 / sells as f64 } else { buys as f64 };
+        let sell_pressure = if buys > 0 { sells as f64 / buys as f64 } else { 1.0 };
+
+        let now = chrono::Utc::now().timestamp();
+
+        Ok(TokenMetrics {
+            mint: mint.to_string(),
+            name: token.name,
+            symbol: token.symbol,
+            creator: None,
+            volume_5m: 0.0,
+            volume_1h: to_sol(token.v1h_usd),
+            volume_24h: to_sol(token.v24h_usd),
+            current_price: to_sol(token.price.unwrap_or(0.0)),
+            price_change_5m: 0.0,
+            price_change_1h: token.price_change1h_percent / 100.0,
+            liquidity_sol: to_sol(token.liquidity),
+            liquidity_usd: token.liquidity,
+            virtual_sol_reserves: 0.0,
+            virtual_token_reserves: 0.0,
+            holder_count: token.holder,
+            holder_concentration: 0.0,
+            unique_buyers_5m: 0,
+            unique_sellers_5m: 0,
+            market_cap: token.mc,
+            fully_diluted_valuation: token.mc,
+            bonding_curve_progress: 100.0,
+            is_graduated: true,
+            created_at: 0,
+            time_since_creation: now.max(0) as u64,
+            buy_pressure,
+            sell_pressure,
+            // Birdeye only reports hourly trade counts, which `buy_pressure` already reflects
+            buy_count_5m: 0,
+            sell_count_5m: 0,
+            volatility_score: 0.0,
+            wash_trade_score: 0.0,
+            rug_risk: None,
+        })
+    }
+}
+
+#[async_trait]
+impl MetricsSource for BirdeyeSource {
+    async fn get_metrics(&self, mint: &str) -> Result<TokenMetrics> {
+        let native_mint = spl_token::native_mint::id().to_string();
+        let (token, sol) = futures::try_join!(self.overview(mint), self.overview(&native_mint))?;
+
+        Self::metrics_from_overview(mint, token, sol.price.unwrap_or(0.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINT: &str = "7GCihgDB8fe6KNjn2MYtkzZcRjQy3t9GHdC8uHYmW2hr";
+
+    const SAMPLE_PAYLOAD: &str = r#"{
+        "success": true,
+        "data": {
+            "address": "7GCihgDB8fe6KNjn2MYtkzZcRjQy3t9GHdC8uHYmW2hr",
+            "decimals": 6,
+            "symbol": "CCAT",
+            "name": "Curve Cat",
+            "price": 0.0003,
+            "liquidity": 30000,
+            "priceChange1hPercent": -4,
+            "priceChange24hPercent": 80,
+            "v1hUSD": 15000,
+            "v24hUSD": 150000,
+            "mc": 300000,
+            "holder": 1250,
+            "buy1h": 200,
+            "sell1h": 100
+        }
+    }"#;
+
+    #[test]
+    fn test_overview_payload_maps_to_metrics() {
+        let response: OverviewResponse = serde_json::from_str(SAMPLE_PAYLOAD).unwrap();
+        let metrics = BirdeyeSource::metrics_from_overview(MINT, response.data.unwrap(), 150.0).unwrap();
+
+        assert_eq!(metrics.symbol, "CCAT");
+        assert!((metrics.current_price - 0.000002).abs() < 1e-12);
+        assert!((metrics.liquidity_sol - 200.0).abs() < 1e-9);
+        assert_eq!(metrics.liquidity_usd, 30000.0);
+        assert!((metrics.volume_24h - 1000.0).abs() < 1e-9);
+        assert!((metrics.volume_1h - 100.0).abs() < 1e-9);
+        assert!((metrics.price_change_1h + 0.04).abs() < 1e-9);
+        assert_eq!(metrics.holder_count, 1250);
+        assert_eq!(metrics.buy_pressure, 2.0);
+        assert!(metrics.is_graduated);
+    }
+
+    #[test]
+    fn test_missing_api_key_is_config_error() {
+        let source = BirdeyeSource::new(&BotConfig::for_tests("http://127.0.0.1:1"));
+        let result = futures::executor::block_on(source.get_metrics(MINT));
+
+        assert!(matches!(result, Err(BotError::Config(_))));
+    }
+
+    #[tokio::test]
+    async fn test_unindexed_token_is_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server.mock("GET", "/defi/token_overview")
+            .match_header("x-api-key", "test-key")
+            .match_query(mockito::Matcher::UrlEncoded("address".into(), MINT.into()))
+            .with_body(r#"{"success": true, "data": {}}"#)
+            .create_async()
+            .await;
+
+        let mut config = BotConfig::for_tests(&server.url());
+        config.birdeye_api_key = Some("test-key".to_string());
+        let source = BirdeyeSource::new(&config);
+        let result = source.overview(MINT).await;
+
+        assert!(matches!(result, Err(BotError::TokenNotFound(_))));
+    }
+}
@@ -0,0 +1,124 @@
+use crate::error::{BotError, Result};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::SystemTime;
+use tracing::{info, warn};
+
+/// On-disk format: `{ "mints": [...], "creators": [...] }`
+#[derive(Debug, Default, Deserialize)]
+struct BlacklistFile {
+    #[serde(default)]
+    mints: HashSet<String>,
+    #[serde(default)]
+    creators: HashSet<String>,
+}
+
+/// Known-bad mints and creator wallets that are never traded
+///
+/// When loaded from a file, `reload_if_changed` picks up edits without a restart.
+#[derive(Debug, Default)]
+pub struct Blacklist {
+    path: Option<PathBuf>,
+    entries: RwLock<BlacklistFile>,
+    modified: Mutex<Option<SystemTime>>,
+}
+
+impl Blacklist {
+    pub fn load(path: &Path) -> Result<Self> {
+        let blacklist = Self {
+            path: Some(path.to_path_buf()),
+            ..Self::default()
+        };
+        blacklist.reload()?;
+        Ok(blacklist)
+    }
+
+    #[cfg(test)]
+    pub fn from_entries(mints: &[&str], creators: &[&str]) -> Self {
+        Self {
+            entries: RwLock::new(BlacklistFile {
+                mints: mints.iter().map(|m| m.to_string()).collect(),
+                creators: creators.iter().map(|c| c.to_string()).collect(),
+            }),
+            ..Self::default()
+        }
+    }
+
+    pub fn is_mint_blocked(&self, mint: &str) -> bool {
+        self.entries.read().unwrap().mints.contains(mint)
+    }
+
+    pub fn is_creator_blocked(&self, creator: &str) -> bool {
+        self.entries.read().unwrap().creators.contains(creator)
+    }
+
+    /// Re-read the file if its modification time changed. Returns whether it reloaded.
+    ///
+    /// A file that fails to parse leaves the previous entries in place.
+    pub fn reload_if_changed(&self) -> Result<bool> {
+        let Some(path) = &self.path else {
+            return Ok(false);
+        };
+
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        if modified.is_some() && modified == *self.modified.lock().unwrap() {
+            return Ok(false);
+        }
+
+        self.reload()?;
+        Ok(true)
+    }
+
+    fn reload(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| BotError::Config(format!("Failed to read blacklist {}: {}", path.display(), e)))?;
+        let entries: BlacklistFile = serde_json::from_str(&contents).map_err(|e| {
+            warn!("Ignoring invalid blacklist {}: {}", path.display(), e);
+            BotError::Config(format!("Invalid blacklist {}: {}", path.display(), e))
+        })?;
+
+        info!(
+            "🚫 Blacklist loaded: {} mints, {} creators",
+            entries.mints.len(),
+            entries.creators.len()
+        );
+
+        *self.entries.write().unwrap() = entries;
+        *self.modified.lock().unwrap() = modified;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reload_picks_up_file_changes() {
+        let path = std::env::temp_dir().join(format!("curverider-blacklist-{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"mints":["BadMint"]}"#).unwrap();
+
+        let blacklist = Blacklist::load(&path).unwrap();
+        assert!(blacklist.is_mint_blocked("BadMint"));
+        assert!(!blacklist.is_creator_blocked("Rugger"));
+        assert!(!blacklist.reload_if_changed().unwrap());
+
+        std::fs::write(&path, r#"{"mints":[],"creators":["Rugger"]}"#).unwrap();
+        // Make sure the mtime moves even on coarse-grained filesystems
+        let later = SystemTime::now() + std::time::Duration::from_secs(5);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+
+        assert!(blacklist.reload_if_changed().unwrap());
+        assert!(!blacklist.is_mint_blocked("BadMint"));
+        assert!(blacklist.is_creator_blocked("Rugger"));
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,271 @@
+use crate::error::{BotError, Result};
+use crate::scanner::MetricsSource;
+use crate::types::{BotConfig, TokenMetrics};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::debug;
+
+// ============================================================================
+// DexScreener API Types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct PairsResponse {
+    #[serde(default)]
+    pairs: Option<Vec<Pair>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Pair {
+    #[serde(default)]
+    dex_id: String,
+    base_token: PairToken,
+    quote_token: PairToken,
+    /// Price in quote token, as a decimal string
+    #[serde(default)]
+    price_native: Option<String>,
+    #[serde(default)]
+    price_usd: Option<String>,
+    #[serde(default)]
+    txns: Txns,
+    #[serde(default)]
+    volume: Windows,
+    #[serde(default)]
+    price_change: Windows,
+    #[serde(default)]
+    liquidity: Liquidity,
+    #[serde(default)]
+    fdv: f64,
+    #[serde(default)]
+    market_cap: f64,
+    /// Milliseconds since epoch
+    #[serde(default)]
+    pair_created_at: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PairToken {
+    address: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    symbol: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Txns {
+    #[serde(default)]
+    m5: TxnCount,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TxnCount {
+    #[serde(default)]
+    buys: u32,
+    #[serde(default)]
+    sells: u32,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Windows {
+    #[serde(default)]
+    m5: f64,
+    #[serde(default)]
+    h1: f64,
+    #[serde(default)]
+    h24: f64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Liquidity {
+    #[serde(default)]
+    usd: f64,
+    /// Quote-side reserves (SOL for pump.fun pairs)
+    #[serde(default)]
+    quote: f64,
+}
+
+// ============================================================================
+// DexScreener Source
+// ============================================================================
+
+/// Token metrics from the DexScreener pairs API
+///
+/// Reports price, liquidity and volume after graduation, which pump.fun does not.
+/// Holder data is not available, so `holder_count` is always 0.
+pub struct DexScreenerSource {
+    client: Client,
+    api_url: String,
+}
+
+impl DexScreenerSource {
+    pub fn new(config: &BotConfig) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            api_url: config.dexscreener_api_url.clone(),
+        }
+    }
+
+    /// Map the deepest SOL-quoted pair for `mint` into `TokenMetrics`
+    fn metrics_from_response(mint: &str, response: PairsResponse) -> Result<TokenMetrics> {
+        let native_mint = spl_token::native_mint::id().to_string();
+
+        let pair = response.pairs.unwrap_or_default()
+            .into_iter()
+            .filter(|p| p.base_token.address == mint && p.quote_token.address == native_mint)
+            .max_by(|a, b| a.liquidity.usd.total_cmp(&b.liquidity.usd))
+            .ok_or_else(|| BotError::TokenNotFound(mint.to_string()))?;
+
+        let parse_price = |s: &Option<String>| {
+            s.as_deref().and_then(|p| p.parse::<f64>().ok()).unwrap_or(0.0)
+        };
+        let price_sol = parse_price(&pair.price_native);
+        let price_usd = parse_price(&pair.price_usd);
+
+        // Volumes are reported in USD; convert with the pair's implied SOL price
+        let sol_usd = if price_sol > 0.0 { price_usd / price_sol } else { 0.0 };
+        let to_sol = |usd: f64| if sol_usd > 0.0 { usd / sol_usd } else { 0.0 };
+
+        let buys = pair.txns.m5.buys;
+        let sells = pair.txns.m5.sells;
+        let buy_pressure = if sells > 0 { buys as f64 / sells as f64 } else { buys as f64 };
+        let sell_pressure = if buys > 0 { sells as f64 / buys as f64 } else { 1.0 };
+
+        // Any pair outside the pump.fun curve means the token has migrated
+        let is_graduated = pair.dex_id != "pumpfun";
+
+        let now = chrono::Utc::now().timestamp();
+        let created_at = pair.pair_created_at.map(|ms| ms / 1000).unwrap_or(now);
+
+        Ok(TokenMetrics {
+            mint: mint.to_string(),
+            name: pair.base_token.name,
+            symbol: pair.base_token.symbol,
+            creator: None,
+            volume_5m: to_sol(pair.volume.m5),
+            volume_1h: to_sol(pair.volume.h1),
+            volume_24h: to_sol(pair.volume.h24),
+            current_price: price_sol,
+            price_change_5m: pair.price_change.m5 / 100.0,
+            price_change_1h: pair.price_change.h1 / 100.0,
+            liquidity_sol: pair.liquidity.quote,
+            liquidity_usd: pair.liquidity.usd,
+            virtual_sol_reserves: 0.0,
+            virtual_token_reserves: 0.0,
+            holder_count: 0,
+            holder_concentration: 0.0,
+            unique_buyers_5m: buys,
+            unique_sellers_5m: sells,
+            market_cap: pair.market_cap,
+            fully_diluted_valuation: pair.fdv,
+            bonding_curve_progress: if is_graduated { 100.0 } else { 0.0 },
+            is_graduated,
+            created_at,
+            time_since_creation: (now - created_at).max(0) as u64,
+            buy_pressure,
+            sell_pressure,
+            buy_count_5m: buys,
+            sell_count_5m: sells,
+            volatility_score: 0.0,
+            wash_trade_score: 0.0,
+            rug_risk: None,
+        })
+    }
+}
+
+#[async_trait]
+impl MetricsSource for DexScreenerSource {
+    async fn get_metrics(&self, mint: &str) -> Result<TokenMetrics> {
+        let url = format!("{}/latest/dex/tokens/{}", self.api_url, mint);
+
+        debug!("Fetching DexScreener pairs for {}", mint);
+
+        let response: PairsResponse = self.client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Self::metrics_from_response(mint, response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINT: &str = "7GCihgDB8fe6KNjn2MYtkzZcRjQy3t9GHdC8uHYmW2hr";
+
+    fn sample_payload() -> String {
+        format!(r#"{{
+            "schemaVersion": "1.0.0",
+            "pairs": [
+                {{
+                    "chainId": "solana",
+                    "dexId": "raydium",
+                    "pairAddress": "Pair1111111111111111111111111111111111111111",
+                    "baseToken": {{ "address": "{MINT}", "name": "Curve Cat", "symbol": "CCAT" }},
+                    "quoteToken": {{ "address": "So11111111111111111111111111111111111111112", "name": "Wrapped SOL", "symbol": "SOL" }},
+                    "priceNative": "0.0000020",
+                    "priceUsd": "0.0003",
+                    "txns": {{ "m5": {{ "buys": 30, "sells": 10 }}, "h1": {{ "buys": 200, "sells": 150 }} }},
+                    "volume": {{ "h24": 150000, "h6": 60000, "h1": 15000, "m5": 1500 }},
+                    "priceChange": {{ "m5": 12.5, "h1": -4, "h6": 30, "h24": 80 }},
+                    "liquidity": {{ "usd": 30000, "base": 50000000, "quote": 100 }},
+                    "fdv": 300000,
+                    "marketCap": 300000,
+                    "pairCreatedAt": 1700000000000
+                }},
+                {{
+                    "chainId": "solana",
+                    "dexId": "meteora",
+                    "baseToken": {{ "address": "{MINT}", "name": "Curve Cat", "symbol": "CCAT" }},
+                    "quoteToken": {{ "address": "So11111111111111111111111111111111111111112", "name": "Wrapped SOL", "symbol": "SOL" }},
+                    "priceNative": "0.0000021",
+                    "liquidity": {{ "usd": 500, "quote": 1.5 }}
+                }}
+            ]
+        }}"#)
+    }
+
+    #[test]
+    fn test_pairs_payload_maps_to_metrics() {
+        let response: PairsResponse = serde_json::from_str(&sample_payload()).unwrap();
+        let metrics = DexScreenerSource::metrics_from_response(MINT, response).unwrap();
+
+        // Deepest pair wins
+        assert_eq!(metrics.symbol, "CCAT");
+        assert_eq!(metrics.liquidity_sol, 100.0);
+        assert_eq!(metrics.liquidity_usd, 30000.0);
+        assert!((metrics.current_price - 0.000002).abs() < 1e-12);
+
+        // SOL at $150: 1500 USD => 10 SOL
+        assert!((metrics.volume_5m - 10.0).abs() < 1e-6);
+        assert!((metrics.volume_24h - 1000.0).abs() < 1e-6);
+
+        assert!((metrics.price_change_5m - 0.125).abs() < 1e-9);
+        assert!((metrics.price_change_1h + 0.04).abs() < 1e-9);
+        assert_eq!(metrics.unique_buyers_5m, 30);
+        assert_eq!(metrics.buy_pressure, 3.0);
+        assert!(metrics.is_graduated);
+        assert_eq!(metrics.created_at, 1_700_000_000);
+    }
+
+    #[test]
+    fn test_no_sol_pair_is_token_not_found() {
+        let response: PairsResponse = serde_json::from_str(r#"{"schemaVersion":"1.0.0","pairs":null}"#).unwrap();
+        let result = DexScreenerSource::metrics_from_response(MINT, response);
+
+        assert!(matches!(result, Err(BotError::TokenNotFound(_))));
+    }
+}
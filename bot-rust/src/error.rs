@@ -29,9 +29,27 @@ pub enum BotError {
     #[error("Position limit reached: {0}/{1}")]
     PositionLimitReached(usize, usize),
 
+    #[error("Scale-in limit reached for {mint}: {max} adds")]
+    ScaleInLimitReached { mint: String, max: u32 },
+
+    #[error("Cooling down after a loss on {mint}: {remaining_secs}s left")]
+    CoolingDown { mint: String, remaining_secs: i64 },
+
+    #[error("Trading halted: max drawdown exceeded")]
+    TradingHalted,
+
     #[error("Trade timeout")]
     TradeTimeout,
 
+    #[error("Transaction failed: {0}")]
+    TransactionFailed(String),
+
+    #[error("Simulation failed: {err}; program log: {}", .logs.join(" | "))]
+    SimulationFailed { err: String, logs: Vec<String> },
+
+    #[error("No swap route: {0}")]
+    NoRoute(String),
+
     #[error("Invalid keypair")]
     InvalidKeypair,
 
@@ -0,0 +1,68 @@
+use crate::types::StrategyType;
+use serde::Serialize;
+use std::io::Write;
+
+/// Columns of a trade export, in `TradeRecord` field order
+pub const TRADE_CSV_HEADER: [&str; 12] = [
+    "mint",
+    "symbol",
+    "entry_price",
+    "exit_price",
+    "sol_invested",
+    "sol_received",
+    "pnl",
+    "pnl_pct",
+    "opened_at",
+    "closed_at",
+    "hold_seconds",
+    "strategy",
+];
+
+/// One closed position in a trade export; prices are SOL per whole token and
+/// amounts are SOL
+#[derive(Debug, Clone, Serialize)]
+pub struct TradeRecord {
+    pub mint: String,
+    pub symbol: String,
+    pub entry_price: f64,
+    pub exit_price: f64,
+    pub sol_invested: f64,
+    pub sol_received: f64,
+    pub pnl: f64,
+    pub pnl_pct: f64,
+    pub opened_at: i64,
+    pub closed_at: i64,
+    pub hold_seconds: i64,
+    /// Empty when the position's strategy tag is unknown
+    pub strategy: Option<StrategyType>,
+}
+
+/// Encode into a fresh buffer, so rows can be sent as they are produced
+fn encode(write: impl FnOnce(&mut csv::Writer<Vec<u8>>) -> csv::Result<()>) -> csv::Result<Vec<u8>> {
+    let mut writer = csv::WriterBuilder::new().has_headers(false).from_writer(Vec::new());
+    write(&mut writer)?;
+    writer.into_inner().map_err(|e| e.into_error().into())
+}
+
+/// The header line of a trade export
+pub fn header_chunk() -> csv::Result<Vec<u8>> {
+    encode(|writer| writer.write_record(TRADE_CSV_HEADER))
+}
+
+/// One CSV line for `record`
+pub fn row_chunk(record: &TradeRecord) -> csv::Result<Vec<u8>> {
+    encode(|writer| writer.serialize(record))
+}
+
+/// Write a header and then `records` to `out`, one line at a time
+pub fn write_trades(
+    mut out: impl Write,
+    records: impl IntoIterator<Item = TradeRecord>,
+) -> csv::Result<()> {
+    out.write_all(&header_chunk()?)?;
+    for record in records {
+        out.write_all(&row_chunk(&record)?)?;
+    }
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use crate::types::BotConfig;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+/// How long a dependency check result is reused before probing again
+const HEALTH_CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// Probes must answer well inside a load balancer's health-check timeout
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Reachability of the services the bot trades through
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DependencyHealth {
+    /// Solana RPC answered `getHealth` with "ok"
+    pub rpc: bool,
+    /// pump.fun API answered a one-token listing
+    pub metrics_api: bool,
+}
+
+impl DependencyHealth {
+    pub fn all_ok(&self) -> bool {
+        self.rpc && self.metrics_api
+    }
+}
+
+/// Pings the RPC and metrics API, caching the result so frequent health
+/// polling doesn't turn into load on either
+pub struct HealthChecker {
+    client: Client,
+    rpc_url: String,
+    metrics_api_url: String,
+    cached: Mutex<Option<(Instant, DependencyHealth)>>,
+}
+
+impl HealthChecker {
+    pub fn new(config: &BotConfig) -> Self {
+        let client = Client::builder()
+            .timeout(PROBE_TIMEOUT)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            rpc_url: config.rpc_url.clone(),
+            metrics_api_url: config.pump_fun_api_url.clone(),
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Current dependency health, probing at most once per `HEALTH_CACHE_TTL`
+    pub async fn check(&self) -> DependencyHealth {
+        // Held across the probes so concurrent callers wait for one result
+        let mut cached = self.cached.lock().await;
+        if let Some((checked_at, health)) = *cached {
+            if checked_at.elapsed() < HEALTH_CACHE_TTL {
+                return health;
+            }
+        }
+
+        let (rpc, metrics_api) = futures::join!(self.probe_rpc(), self.probe_metrics_api());
+        let health = DependencyHealth { rpc, metrics_api };
+        if !health.all_ok() {
+            warn!("Dependency health degraded: {:?}", health);
+        }
+
+        *cached = Some((Instant::now(), health));
+        health
+    }
+
+    async fn probe_rpc(&self) -> bool {
+        let request = serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "getHealth"});
+        let response = match self.client.post(&self.rpc_url).json(&request).send().await {
+            Ok(response) if response.status().is_success() => response,
+            _ => return false,
+        };
+
+        match response.json::<serde_json::Value>().await {
+            Ok(body) => body["result"] == "ok",
+            Err(_) => false,
+        }
+    }
+
+    async fn probe_metrics_api(&self) -> bool {
+        let url = format!("{}/tokens/trending?limit=1", self.metrics_api_url);
+        matches!(self.client.get(&url).send().await, Ok(response) if response.status().is_success())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_results_are_cached() {
+        let mut server = mockito::Server::new_async().await;
+        let rpc = server.mock("POST", "/")
+            .with_body(r#"{"jsonrpc":"2.0","result":"ok","id":1}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let _metrics = server.mock("GET", "/tokens/trending")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"tokens":[]}"#)
+            .create_async()
+            .await;
+
+        let checker = HealthChecker::new(&BotConfig::for_tests(&server.url()));
+        let first = checker.check().await;
+        let second = checker.check().await;
+
+        assert!(first.all_ok());
+        assert_eq!(first, second);
+        rpc.assert_async().await;
+    }
+}
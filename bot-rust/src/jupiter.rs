@@ -0,0 +1,236 @@
+use crate::error::{BotError, Result};
+use crate::types::BotConfig;
+use base64::Engine;
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::VersionedTransaction,
+};
+use std::time::Duration;
+use tracing::debug;
+
+// ============================================================================
+// Jupiter API Types
+// ============================================================================
+
+/// `/quote` response; passed back unchanged to `/swap`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteResponse {
+    pub input_mint: String,
+    /// Raw input amount, as a decimal string
+    pub in_amount: String,
+    pub output_mint: String,
+    /// Raw expected output amount, as a decimal string
+    pub out_amount: String,
+    /// Minimum output after slippage
+    pub other_amount_threshold: String,
+    pub slippage_bps: u16,
+    #[serde(default)]
+    pub price_impact_pct: String,
+    #[serde(default)]
+    pub route_plan: Vec<serde_json::Value>,
+    /// Fields we don't read but the swap endpoint expects back
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl QuoteResponse {
+    pub fn out_amount(&self) -> u64 {
+        self.out_amount.parse().unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SwapRequest<'a> {
+    quote_response: &'a QuoteResponse,
+    user_public_key: String,
+    wrap_and_unwrap_sol: bool,
+    dynamic_compute_unit_limit: bool,
+    compute_unit_price_micro_lamports: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SwapResponse {
+    /// Base64-encoded, unsigned versioned transaction
+    swap_transaction: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ErrorBody {
+    #[serde(default)]
+    error: String,
+    #[serde(default)]
+    error_code: Option<String>,
+}
+
+// ============================================================================
+// Jupiter Swap
+// ============================================================================
+
+/// Token swaps through the Jupiter aggregator, used once a token has left the bonding curve
+pub struct JupiterSwap {
+    client: Client,
+    api_url: String,
+    slippage_bps: u16,
+    priority_fee_micro_lamports: u64,
+}
+
+impl JupiterSwap {
+    pub fn new(config: &BotConfig) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            api_url: config.jupiter_api_url.clone(),
+            slippage_bps: config.max_slippage_bps,
+            priority_fee_micro_lamports: config.priority_fee_micro_lamports,
+        }
+    }
+
+    /// Best route for selling `amount` raw units of `input_mint`
+    pub async fn quote(&self, input_mint: &Pubkey, output_mint: &Pubkey, amount: u64) -> Result<QuoteResponse> {
+        let url = format!("{}/quote", self.api_url);
+        debug!("Fetching Jupiter quote for {} {} -> {}", amount, input_mint, output_mint);
+
+        let response = self.client
+            .get(&url)
+            .query(&[
+                ("inputMint", input_mint.to_string()),
+                ("outputMint", output_mint.to_string()),
+                ("amount", amount.to_string()),
+                ("slippageBps", self.slippage_bps.to_string()),
+            ])
+            .send()
+            .await?;
+
+        if response.status() == StatusCode::BAD_REQUEST {
+            let body: ErrorBody = response.json().await?;
+            return Err(BotError::NoRoute(format!(
+                "{} -> {}: {} ({})",
+                input_mint,
+                output_mint,
+                body.error,
+                body.error_code.unwrap_or_default()
+            )));
+        }
+
+        let quote: QuoteResponse = response.error_for_status()?.json().await?;
+        if quote.route_plan.is_empty() || quote.out_amount() == 0 {
+            return Err(BotError::NoRoute(format!("{} -> {}: empty route", input_mint, output_mint)));
+        }
+
+        Ok(quote)
+    }
+
+    /// Signed swap transaction for a quote, paying fees from `payer`
+    pub async fn swap_transaction(&self, quote: &QuoteResponse, payer: &Keypair) -> Result<VersionedTransaction> {
+        let url = format!("{}/swap", self.api_url);
+        let request = SwapRequest {
+            quote_response: quote,
+            user_public_key: payer.pubkey().to_string(),
+            wrap_and_unwrap_sol: true,
+            dynamic_compute_unit_limit: true,
+            compute_unit_price_micro_lamports: self.priority_fee_micro_lamports,
+        };
+
+        let response: SwapResponse = self.client
+            .post(&url)
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(&response.swap_transaction)
+            .map_err(|e| BotError::Unknown(format!("Invalid swap transaction encoding: {}", e)))?;
+        let unsigned: VersionedTransaction = bincode::deserialize(&bytes)
+            .map_err(|e| BotError::Unknown(format!("Invalid swap transaction: {}", e)))?;
+
+        VersionedTransaction::try_new(unsigned.message, &[payer])
+            .map_err(|e| BotError::Unknown(format!("Failed to sign swap transaction: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_QUOTE: &str = r#"{
+        "inputMint": "7GCihgDB8fe6KNjn2MYtkzZcRjQy3t9GHdC8uHYmW2hr",
+        "inAmount": "1000000000",
+        "outputMint": "So11111111111111111111111111111111111111112",
+        "outAmount": "48213957",
+        "otherAmountThreshold": "45803259",
+        "swapMode": "ExactIn",
+        "slippageBps": 500,
+        "platformFee": null,
+        "priceImpactPct": "0.0123",
+        "routePlan": [
+            {
+                "swapInfo": {
+                    "ammKey": "58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2",
+                    "label": "Raydium",
+                    "inputMint": "7GCihgDB8fe6KNjn2MYtkzZcRjQy3t9GHdC8uHYmW2hr",
+                    "outputMint": "So11111111111111111111111111111111111111112",
+                    "inAmount": "1000000000",
+                    "outAmount": "48213957",
+                    "feeAmount": "2500000",
+                    "feeMint": "7GCihgDB8fe6KNjn2MYtkzZcRjQy3t9GHdC8uHYmW2hr"
+                },
+                "percent": 100
+            }
+        ],
+        "contextSlot": 256110000,
+        "timeTaken": 0.012
+    }"#;
+
+    #[test]
+    fn test_quote_response_deserializes() {
+        let quote: QuoteResponse = serde_json::from_str(SAMPLE_QUOTE).unwrap();
+
+        assert_eq!(quote.out_amount(), 48_213_957);
+        assert_eq!(quote.other_amount_threshold, "45803259");
+        assert_eq!(quote.slippage_bps, 500);
+        assert_eq!(quote.route_plan.len(), 1);
+
+        // Unread fields survive the trip back to /swap
+        let echoed = serde_json::to_value(&quote).unwrap();
+        assert_eq!(echoed["swapMode"], "ExactIn");
+        assert_eq!(echoed["contextSlot"], 256_110_000);
+    }
+
+    #[tokio::test]
+    async fn test_missing_route_is_descriptive_error() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server.mock("GET", "/quote")
+            .match_query(mockito::Matcher::Any)
+            .with_status(400)
+            .with_body(r#"{"error":"Could not find any route","errorCode":"COULD_NOT_FIND_ANY_ROUTE"}"#)
+            .create_async()
+            .await;
+
+        let mut config = BotConfig::for_tests(&server.url());
+        config.jupiter_api_url = server.url();
+        let jupiter = JupiterSwap::new(&config);
+
+        let result = jupiter
+            .quote(&Pubkey::new_unique(), &spl_token::native_mint::id(), 1_000)
+            .await;
+
+        match result {
+            Err(BotError::NoRoute(message)) => assert!(message.contains("COULD_NOT_FIND_ANY_ROUTE")),
+            other => panic!("expected NoRoute, got {:?}", other),
+        }
+    }
+}
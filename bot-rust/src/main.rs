@@ -1,51 +1,102 @@
 use solana_sdk::signature::Signer;
+mod api;
 mod error;
 mod types;
+mod amount;
 mod config;
 mod analyzer;
 mod scanner;
 mod trader;
+mod pump_fun;
+mod raydium;
+mod dexscreener;
+mod birdeye;
+mod jupiter;
+mod backtest;
+mod blacklist;
+mod risk;
+mod rugcheck;
+mod vault;
+mod replay;
+mod health;
+mod ratelimit;
+mod export;
+mod rpc;
 
 use error::Result;
-use types::{BotConfig, SignalType};
-use analyzer::{TradingStrategy, create_strategy};
-use scanner::PumpFunScanner;
+use types::{BotConfig, LogFormat, SignalType, TokenMetrics, TradingSignal};
+use analyzer::{TradingStrategy, create_strategy, hard_rejection};
+use blacklist::Blacklist;
+use rugcheck::RugCheck;
+use scanner::{MetricsSource, PumpFunScanner, create_metrics_source};
 use trader::Trader;
+use vault::VaultClient;
 
 use tracing::{info, warn, error, debug};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{fmt::MakeWriter, layer::SubscriberExt, registry::LookupSpan, util::SubscriberInitExt, Layer};
+use futures::StreamExt;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::{mpsc, Semaphore};
 use tokio::time;
 
+/// Admin commands queued for the trading loop before the API waits for room
+const COMMAND_CHANNEL_CAPACITY: usize = 16;
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize logging
+    // Initialize logging; LOG_FORMAT comes straight from the environment so it applies from the first line
+    let log_format: LogFormat = std::env::var("LOG_FORMAT")
+        .unwrap_or_else(|_| "pretty".to_string())
+        .parse()?;
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "info".into()),
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(log_layer(log_format, std::io::stdout))
         .init();
 
     info!("🚀 Starting Curverider Vault Bot");
     info!("⚡ High-Performance Rust Trading Bot for pump.fun");
     info!("═══════════════════════════════════════════════════");
 
-    // Load configuration
-    let config = BotConfig::from_env()?;
+    // `curverider-bot backtest <series.json>` replays recorded metrics instead of trading
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() == 3 && args[1] == "backtest" {
+        return run_backtest_command(std::path::Path::new(&args[2]));
+    }
+
+    // Load configuration: BOT_CONFIG_FILE (TOML) if set, env overrides either way
+    let config = match std::env::var("BOT_CONFIG_FILE") {
+        Ok(path) => BotConfig::from_file(std::path::Path::new(&path))?,
+        Err(_) => BotConfig::from_env()?,
+    };
     info!("✅ Configuration loaded");
     info!("📊 Wallet: {}", config.wallet_keypair.pubkey());
     info!("💰 Max position size: {} SOL", config.max_position_size_sol);
     info!("🌐 RPC: {}", config.rpc_url);
+    if !config.rpc_fallback_urls.is_empty() {
+        info!("   Fallback RPCs: {}", config.rpc_fallback_urls.join(", "));
+    }
+    info!("📡 Metrics source: {:?}", config.metrics_source);
     if config.dry_run {
         info!("🧪 DRY RUN MODE - No real trades will be executed");
         info!("   (Using mock data instead of pump.fun API)");
     }
 
+    // Known-bad mints and creators
+    let blacklist = Arc::new(match &config.blacklist_path {
+        Some(path) => Blacklist::load(std::path::Path::new(path))?,
+        None => Blacklist::default(),
+    });
+
     // Initialize strategy
-    let strategy = create_strategy(config.strategy_type);
-    let exit_params = strategy.get_exit_params();
+    let strategy = create_strategy(config.strategy_type, Some(blacklist.clone()), &config.holder_thresholds);
+    let exit_params = strategy.get_exit_params().with_take_profit_ladder(&config.take_profit_ladder);
+    exit_params.validate()?;
 
     info!("🎲 Strategy: {}", strategy.name());
     info!("🎯 Take profit: {}x", exit_params.take_profit_multiplier);
@@ -59,17 +110,55 @@ async fn main() -> anyhow::Result<()> {
 
     // Initialize components
     let scanner = PumpFunScanner::new(&config);
-    let mut trader = Trader::new(&config);
+    let metrics_source = create_metrics_source(&config);
+    let rug_check = RugCheck::new(&config);
+
+    // Start HTTP API for the frontend
+    if config.api_auth_token.is_none() {
+        warn!("🔒 API_AUTH_TOKEN not set - per-user and mutating API routes are disabled");
+    }
+    let (command_tx, mut commands) = mpsc::channel(COMMAND_CHANNEL_CAPACITY);
+    let api_state = api::ApiState::new()
+        .with_api_token(config.api_auth_token.clone())
+        .with_health_checker(health::HealthChecker::new(&config))
+        .with_command_channel(command_tx)
+        .with_strategies(Some(blacklist.clone()), &config.holder_thresholds);
+    let mut trader = Trader::new(config.clone()).with_api_state(api_state.clone());
+    let mut loop_stats = LoopStats::new(api_state.clone());
+    let vault_client = VaultClient::new(&config);
+    let mut debouncer = SignalDebouncer::new(config.signal_confirmations);
+    let rebuilder = replay::StateRebuilder::new(api_state.clone(), config.vault_program, config.commitment.config());
+    let replay_rpc = rpc::FailoverRpc::new(&config.rpc_urls(), config.commitment.config());
+    let rpc_ws_url = config.rpc_ws_url.clone();
+    tokio::spawn(async move {
+        if let Err(e) = rebuilder.run(&replay_rpc, &rpc_ws_url).await {
+            warn!("Vault event replay stopped: {}", e);
+        }
+    });
+    let api_port = config.api_port;
+    tokio::spawn(async move {
+        if let Err(e) = api::start_api_server(api_state, api_port).await {
+            error!("API server stopped: {}", e);
+        }
+    });
 
     info!("✅ Bot initialized successfully");
     info!("🔍 Starting main trading loop...\n");
 
-    // Main trading loop
+    if config.stream_new_tokens {
+        info!("📡 Streaming new tokens over WebSocket\n");
+        run_streaming_loop(&scanner, metrics_source.as_ref(), strategy.as_ref(), &blacklist, &rug_check, &mut trader, &mut debouncer, &mut loop_stats, &mut commands, &config).await;
+        return graceful_shutdown(&mut trader, &config).await;
+    }
+
+    // Main trading loop, until Ctrl-C/SIGTERM
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
     let mut iteration = 0;
     loop {
         iteration += 1;
 
-        match run_trading_cycle(&scanner, strategy.as_ref(), &mut trader, &config).await {
+        match run_trading_cycle(&scanner, metrics_source.as_ref(), strategy.as_ref(), &blacklist, &rug_check, &mut trader, &mut debouncer, &mut loop_stats, &config).await {
             Ok(_) => {
                 debug!("Iteration {} completed successfully", iteration);
             }
@@ -79,25 +168,178 @@ async fn main() -> anyhow::Result<()> {
         }
 
         // Monitor existing positions
-        if let Err(e) = trader.monitor_positions().await {
+        if let Err(e) = trader.monitor_positions(metrics_source.as_ref(), strategy.as_ref()).await {
             error!("Error monitoring positions: {}", e);
         }
+        run_commands(&mut commands, &mut trader).await;
+        trader.publish_live_positions().await;
+        loop_stats.publish().await;
 
         // Display status
         if iteration % 10 == 0 {
             display_status(&trader, &config);
+            if let Some(vault_client) = &vault_client {
+                reconcile_positions(&mut trader, vault_client);
+            }
+        }
+
+        // Wait before next cycle; a pending shutdown stops scanning here
+        tokio::select! {
+            _ = time::sleep(Duration::from_millis(config.scan_interval_ms)) => {}
+            _ = &mut shutdown => break,
+        }
+    }
+
+    graceful_shutdown(&mut trader, &config).await
+}
+
+/// Formatting layer writing to `writer`, as JSON lines when `format` is `Json`
+fn log_layer<S, W>(format: LogFormat, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    match format {
+        LogFormat::Pretty => tracing_subscriber::fmt::layer().with_writer(writer).boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer().json().with_writer(writer).boxed(),
+    }
+}
+
+/// Resolves on Ctrl-C or SIGTERM
+///
+/// The handlers are installed before this returns, so a signal that arrives
+/// mid-cycle is picked up at the next wait instead of killing the process.
+#[cfg(unix)]
+fn shutdown_signal() -> impl std::future::Future<Output = ()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut interrupt = signal(SignalKind::interrupt()).expect("Failed to install SIGINT handler");
+    let mut terminate = signal(SignalKind::terminate()).expect("Failed to install SIGTERM handler");
+    async move {
+        tokio::select! {
+            _ = interrupt.recv() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn shutdown_signal() -> impl std::future::Future<Output = ()> {
+    async {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// Close (if configured) or persist open positions, then flush output
+async fn graceful_shutdown(trader: &mut Trader, config: &BotConfig) -> anyhow::Result<()> {
+    info!("🛑 Shutdown requested - no longer scanning");
+    if config.close_on_shutdown {
+        info!("💸 Closing {} open position(s)", trader.position_count());
+    }
+
+    let persisted = trader.shutdown(config.close_on_shutdown).await?;
+    info!("💾 Persisted {} open position(s) to {}", persisted, config.positions_file);
+    if let Some(path) = &config.trades_csv_file {
+        match trader.export_positions_csv(std::path::Path::new(path)) {
+            Ok(exported) => info!("🧾 Exported {} closed trade(s) to {}", exported, path),
+            Err(e) => warn!("Failed to export closed trades: {}", e),
         }
+    }
+    info!("👋 Curverider bot stopped");
+
+    std::io::Write::flush(&mut std::io::stdout())?;
+    Ok(())
+}
 
-        // Wait before next cycle
-        time::sleep(Duration::from_millis(config.scan_interval_ms)).await;
+/// Bring held positions back in line with their vault records
+fn reconcile_positions(trader: &mut Trader, vault_client: &VaultClient) {
+    match trader.reconcile_with_chain(vault_client) {
+        Ok(report) if report.is_clean() => debug!("{} position(s) match the vault", report.checked),
+        Ok(report) => warn!(
+            "🔄 Reconciled with the vault: {} closed on-chain, {} missing on-chain",
+            report.closed.len(),
+            report.dropped.len()
+        ),
+        Err(e) => warn!("Failed to reconcile positions with the vault: {}", e),
+    }
+}
+
+/// Carry out commands queued by the API since the last cycle
+async fn run_commands(commands: &mut mpsc::Receiver<api::BotCommand>, trader: &mut Trader) {
+    while let Ok(command) = commands.try_recv() {
+        match command {
+            api::BotCommand::ClosePositions { mint, reply } => {
+                info!("🚨 Admin close requested for {}", mint);
+                let closed = trader.close_mint(&mint).await;
+                // The request may have been abandoned; the sells stand either way
+                let _ = reply.send(closed);
+            }
+        }
+    }
+}
+
+/// Analyze tokens the moment they are created, monitoring positions between events,
+/// until Ctrl-C/SIGTERM
+#[allow(clippy::too_many_arguments)]
+async fn run_streaming_loop(
+    scanner: &PumpFunScanner,
+    metrics_source: &dyn MetricsSource,
+    strategy: &dyn TradingStrategy,
+    blacklist: &Blacklist,
+    rug_check: &RugCheck,
+    trader: &mut Trader,
+    debouncer: &mut SignalDebouncer,
+    loop_stats: &mut LoopStats,
+    commands: &mut mpsc::Receiver<api::BotCommand>,
+    config: &BotConfig,
+) {
+    let mut new_tokens = Box::pin(scanner.subscribe_new_tokens());
+    let mut monitor_interval = time::interval(Duration::from_millis(config.scan_interval_ms));
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => return,
+            Some(mint) = new_tokens.next() => {
+                if trader.position_count() >= config.max_concurrent_positions {
+                    debug!("At position limit, ignoring new token {}", mint);
+                    continue;
+                }
+
+                loop_stats.scans += 1;
+                if let Err(e) = evaluate_token(&mint, metrics_source, strategy, blacklist, rug_check, trader, debouncer, loop_stats, config).await {
+                    error!("Error evaluating new token {}: {}", mint, e);
+                }
+                loop_stats.publish().await;
+            }
+            _ = monitor_interval.tick() => {
+                if let Err(e) = blacklist.reload_if_changed() {
+                    warn!("Keeping previous blacklist: {}", e);
+                }
+
+                if let Err(e) = trader.monitor_positions(metrics_source, strategy).await {
+                    error!("Error monitoring positions: {}", e);
+                }
+                run_commands(commands, trader).await;
+                trader.publish_live_positions().await;
+                loop_stats.publish().await;
+            }
+        }
     }
 }
 
 /// Run a single trading cycle
+#[allow(clippy::too_many_arguments)]
 async fn run_trading_cycle(
     scanner: &PumpFunScanner,
+    metrics_source: &dyn MetricsSource,
     strategy: &dyn TradingStrategy,
+    blacklist: &Blacklist,
+    rug_check: &RugCheck,
     trader: &mut Trader,
+    debouncer: &mut SignalDebouncer,
+    loop_stats: &mut LoopStats,
     config: &BotConfig,
 ) -> Result<()> {
     // Skip if at position limit
@@ -107,84 +349,320 @@ async fn run_trading_cycle(
         return Ok(());
     }
 
+    // Held positions keep being monitored; only new entries stop
+    if trader.trading_halted() {
+        debug!("Trading halted by max drawdown, skipping scan");
+        return Ok(());
+    }
+
+    // Pick up blacklist edits without a restart
+    if let Err(e) = blacklist.reload_if_changed() {
+        warn!("Keeping previous blacklist: {}", e);
+    }
+
     // Scan for tokens
-    let token_mints = scanner.scan_trending_tokens(20).await?;
+    let token_mints = scanner.scan_tokens(config.scan_mode, 20).await?;
+    loop_stats.scans += 1;
 
     if token_mints.is_empty() {
         debug!("No tokens found in scan");
         return Ok(());
     }
 
-    // Analyze each token
-    for mint in token_mints {
+    // Analyze concurrently, then trade the most confident signals first
+    let mut analyzed = analyze_tokens(token_mints, metrics_source, strategy, blacklist, rug_check, config).await;
+    analyzed.sort_by(|a, b| b.1.confidence.total_cmp(&a.1.confidence));
+
+    for (metrics, signal) in analyzed {
+        let traded = if trader.position_count() >= config.max_concurrent_positions {
+            debug!("At position limit, not acting on {}", metrics.mint);
+            false
+        } else {
+            execute_signal(&metrics, &signal, trader, debouncer, config).await
+        };
+        loop_stats.record(&signal, traded);
+    }
+
+    Ok(())
+}
+
+/// Fetch metrics for a single token, analyze it and buy on a strong signal
+#[allow(clippy::too_many_arguments)]
+async fn evaluate_token(
+    mint: &str,
+    metrics_source: &dyn MetricsSource,
+    strategy: &dyn TradingStrategy,
+    blacklist: &Blacklist,
+    rug_check: &RugCheck,
+    trader: &mut Trader,
+    debouncer: &mut SignalDebouncer,
+    loop_stats: &mut LoopStats,
+    config: &BotConfig,
+) -> Result<()> {
+    let api_permits = Semaphore::new(config.max_concurrent_requests);
+    if let Some((metrics, signal)) = analyze_token(mint, metrics_source, strategy, blacklist, rug_check, config, &api_permits).await {
+        let traded = execute_signal(&metrics, &signal, trader, debouncer, config).await;
+        loop_stats.record(&signal, traded);
+    }
+
+    Ok(())
+}
+
+/// Analyze up to `analysis_concurrency` tokens at a time
+///
+/// Metric fetches and rug checks share `max_concurrent_requests` permits so a
+/// large scan doesn't trip the API rate limit. Results arrive in completion order.
+async fn analyze_tokens(
+    mints: Vec<String>,
+    metrics_source: &dyn MetricsSource,
+    strategy: &dyn TradingStrategy,
+    blacklist: &Blacklist,
+    rug_check: &RugCheck,
+    config: &BotConfig,
+) -> Vec<(TokenMetrics, TradingSignal)> {
+    let api_permits = Semaphore::new(config.max_concurrent_requests);
+    let api_permits = &api_permits;
+
+    futures::stream::iter(mints)
+        .map(|mint| async move {
+            analyze_token(&mint, metrics_source, strategy, blacklist, rug_check, config, api_permits).await
+        })
+        .buffer_unordered(config.analysis_concurrency)
+        .filter_map(futures::future::ready)
+        .collect()
+        .await
+}
+
+/// Fetch metrics for a single token and analyze it; `None` when it was skipped
+async fn analyze_token(
+    mint: &str,
+    metrics_source: &dyn MetricsSource,
+    strategy: &dyn TradingStrategy,
+    blacklist: &Blacklist,
+    rug_check: &RugCheck,
+    config: &BotConfig,
+    api_permits: &Semaphore,
+) -> Option<(TokenMetrics, TradingSignal)> {
+    if blacklist.is_mint_blocked(mint) {
+        debug!("Skipping blacklisted mint {}", mint);
+        return None;
+    }
+
+    let metrics = {
+        let _permit = api_permits.acquire().await.expect("API permits are never closed");
+
         // Get metrics
-        let metrics = match scanner.get_token_metrics(&mint).await {
+        let mut metrics = match metrics_source.get_metrics(mint).await {
             Ok(m) => m,
             Err(e) => {
                 warn!("Failed to get metrics for {}: {}", mint, e);
-                continue;
+                return None;
             }
         };
 
-        // Analyze using selected strategy
-        let signal = match strategy.analyze(&metrics) {
-            Ok(s) => s,
-            Err(e) => {
-                warn!("Failed to analyze {}: {}", mint, e);
-                continue;
+        // On-chain rug checks; mock tokens in dry run have no mint account
+        if !config.dry_run {
+            match mint.parse() {
+                Ok(mint_pubkey) => match rug_check.assess(&mint_pubkey) {
+                    Ok(risk) => metrics.rug_risk = Some(risk),
+                    Err(e) => warn!("Rug check failed for {}: {}", mint, e),
+                },
+                Err(e) => warn!("Skipping rug check for invalid mint {}: {}", mint, e),
             }
-        };
+        }
+
+        metrics
+    };
+
+    // Analyze using selected strategy
+    let mut signal = match strategy.analyze(&metrics) {
+        Ok(s) => s,
+        Err(e) => {
+            warn!("Failed to analyze {}: {}", mint, e);
+            return None;
+        }
+    };
+
+    // Not every strategy checks the creator and rug flags itself
+    if !signal.rejected {
+        if let Some((code, reason)) = hard_rejection(&metrics, Some(blacklist)) {
+            warn!("Token {} rejected: {}", metrics.symbol, reason);
+            signal.reject(code, &reason);
+        }
+    }
+
+    // Log signal
+    info!(
+        mint = %metrics.mint,
+        signal_type = ?signal.signal_type,
+        confidence = signal.confidence,
+        "📊 {} ({}): {:?} - {:.1}% confidence",
+        metrics.symbol,
+        metrics.mint,
+        signal.signal_type,
+        signal.confidence * 100.0
+    );
+
+    if !signal.reasoning.is_empty() {
+        for reason in &signal.reasoning {
+            debug!("   └─ {}", reason);
+        }
+    }
+
+    Some((metrics, signal))
+}
+
+/// Loop counters behind `/api/stats`, `/metrics` and the running flag `/api/health` reports
+struct LoopStats {
+    api_state: api::ApiState,
+    started: std::time::Instant,
+    scans: u64,
+    analyzed: u64,
+    signals: u64,
+    trades: u64,
+}
+
+impl LoopStats {
+    fn new(api_state: api::ApiState) -> Self {
+        Self {
+            api_state,
+            started: std::time::Instant::now(),
+            scans: 0,
+            analyzed: 0,
+            signals: 0,
+            trades: 0,
+        }
+    }
+
+    /// Count an analyzed token, and whether acting on its signal bought anything
+    fn record(&mut self, signal: &TradingSignal, traded: bool) {
+        self.analyzed += 1;
+        if !matches!(signal.signal_type, SignalType::Hold) {
+            self.signals += 1;
+        }
+        if traded {
+            self.trades += 1;
+        }
+    }
+
+    /// Report the loop as running with the counts so far
+    async fn publish(&self) {
+        self.api_state
+            .update_bot_stats(true, self.started.elapsed().as_secs(), self.scans, self.analyzed, self.signals, self.trades)
+            .await;
+    }
+}
 
-        // Log signal
-        info!(
-            "📊 {} ({}): {:?} - {:.1}% confidence",
-            metrics.symbol,
-            metrics.mint,
-            signal.signal_type,
-            signal.confidence * 100.0
+/// Counts consecutive qualifying StrongBuy analyses per mint, so a token whose
+/// confidence flickers around the threshold isn't bought on a single read
+struct SignalDebouncer {
+    required: u32,
+    streaks: HashMap<Pubkey, u32>,
+}
+
+impl SignalDebouncer {
+    fn new(required: u32) -> Self {
+        Self {
+            required,
+            streaks: HashMap::new(),
+        }
+    }
+
+    /// Record one analysis of `mint`; true while it has qualified at least
+    /// `required` times in a row. Any analysis that doesn't qualify resets the streak.
+    fn confirm(&mut self, mint: Pubkey, qualifies: bool) -> bool {
+        if !qualifies {
+            self.streaks.remove(&mint);
+            return false;
+        }
+
+        let streak = self.streaks.entry(mint).or_insert(0);
+        *streak = streak.saturating_add(1);
+        *streak >= self.required
+    }
+}
+
+/// Buy on a strong signal once `signal_confirmations` analyses in a row agree;
+/// true when a buy went through
+async fn execute_signal(
+    metrics: &TokenMetrics,
+    signal: &TradingSignal,
+    trader: &mut Trader,
+    debouncer: &mut SignalDebouncer,
+    config: &BotConfig,
+) -> bool {
+    let strong_buy = matches!(signal.signal_type, SignalType::StrongBuy)
+        && signal.confidence >= config.strong_buy_confidence;
+    let confirmed = debouncer.confirm(signal.token_mint, strong_buy);
+
+    // Execute trade if strong buy signal
+    if strong_buy && !confirmed {
+        debug!(
+            "Strong buy on {} awaiting {} consecutive confirmations",
+            metrics.mint, config.signal_confirmations
         );
+    } else if strong_buy {
+        info!("🎯 STRONG BUY SIGNAL DETECTED!");
+        info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        info!("Token: {} ({})", metrics.symbol, metrics.name);
+        info!("Confidence: {:.1}%", signal.confidence * 100.0);
+        info!("Reasons:");
+        for reason in &signal.reasoning {
+            info!("  • {}", reason);
+        }
+        info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
 
-        if !signal.reasoning.is_empty() {
-            for reason in &signal.reasoning {
-                debug!("   └─ {}", reason);
-            }
+        // Paper trades fill at the analyzed price
+        if config.dry_run {
+            trader.set_paper_price(signal.token_mint, metrics.current_price);
         }
 
-        // Execute trade if strong buy signal
-        if matches!(signal.signal_type, SignalType::StrongBuy) 
-            && signal.confidence >= 0.75 {
-            
-            info!("🎯 STRONG BUY SIGNAL DETECTED!");
-            info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-            info!("Token: {} ({})", metrics.symbol, metrics.name);
-            info!("Confidence: {:.1}%", signal.confidence * 100.0);
-            info!("Reasons:");
-            for reason in &signal.reasoning {
-                info!("  • {}", reason);
+        // Execute buy
+        match trader.buy_token(&signal.token_mint, config.max_position_size_sol, signal.confidence, metrics.liquidity_sol).await {
+            Ok(position) => {
+                info!("✅ Position opened successfully!");
+                info!("📍 Entry: ${:.6}", position.entry_price);
+                info!("🎯 Take Profit: ${:.6}", position.take_profit_price);
+                info!("🛑 Stop Loss: ${:.6}\n", position.stop_loss_price);
+                return true;
             }
-            info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
-
-            // Execute buy
-            match trader.buy_token(&signal.token_mint, config.max_position_size_sol).await {
-                Ok(position) => {
-                    info!("✅ Position opened successfully!");
-                    info!("📍 Entry: ${:.6}", position.entry_price);
-                    info!("🎯 Take Profit: ${:.6}", position.take_profit_price);
-                    info!("🛑 Stop Loss: ${:.6}\n", position.stop_loss_price);
-                }
-                Err(e) => {
-                    error!("❌ Failed to open position: {}\n", e);
-                }
+            Err(e) => {
+                error!("❌ Failed to open position: {}\n", e);
             }
-        } else if matches!(signal.signal_type, SignalType::Buy) 
-            && signal.confidence >= 0.65 {
-            
-            info!("📈 Buy signal detected (moderate confidence)");
-            // Could implement smaller position sizing for lower confidence
         }
+    } else if matches!(signal.signal_type, SignalType::Buy) 
+        && signal.confidence >= config.buy_confidence {
+        
+        info!("📈 Buy signal detected (moderate confidence)");
+        // Could implement smaller position sizing for lower confidence
+    }
+
+    false
+}
+
+/// Replay the configured strategy over a recorded series and print the report
+fn run_backtest_command(path: &std::path::Path) -> anyhow::Result<()> {
+    let strategy_type: types::StrategyType = std::env::var("STRATEGY_TYPE")
+        .unwrap_or_else(|_| "conservative".to_string())
+        .parse()?;
+    let thresholds = types::HolderThresholds::from_lookup(|key| std::env::var(key).ok())?;
+    thresholds.validate()?;
+    let strategy = create_strategy(strategy_type, None, &thresholds);
+    let series = backtest::load_series(path)?;
+
+    info!("🧪 Backtesting {} over {} snapshots", strategy.name(), series.len());
+    let report = backtest::run_backtest(strategy.as_ref(), &series, &strategy.get_exit_params());
 
-        // Small delay between token analyses
-        time::sleep(Duration::from_millis(100)).await;
+    info!("═══════════════════════════════════════════════");
+    info!("📊 BACKTEST REPORT");
+    info!("═══════════════════════════════════════════════");
+    info!("Trades: {} ({} winning)", report.total_trades, report.winning_trades);
+    info!("Win rate: {:.1}%", report.win_rate * 100.0);
+    info!("Total return: {:+.1}%", report.total_return * 100.0);
+    info!("Max drawdown: {:.1}%", report.max_drawdown * 100.0);
+    info!("Avg hold: {:.0}s", report.avg_hold_seconds);
+    for trade in &report.trades {
+        debug!("  {} {:?}: {:.6} -> {:.6}", trade.mint, trade.exit_reason, trade.entry_price, trade.exit_price);
     }
 
     Ok(())
@@ -218,3 +696,343 @@ fn display_status(trader: &Trader, config: &BotConfig) {
 
     info!("═══════════════════════════════════════════════\n");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{StrategyExitParams, TokenMetrics, TradingSignal};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts lookups; every lookup fails so nothing gets traded
+    #[derive(Default)]
+    struct CountingSource {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl MetricsSource for CountingSource {
+        async fn get_metrics(&self, mint: &str) -> Result<TokenMetrics> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Err(error::BotError::TokenNotFound(mint.to_string()))
+        }
+    }
+
+    struct NeverCalled;
+
+    impl TradingStrategy for NeverCalled {
+        fn analyze(&self, metrics: &TokenMetrics) -> Result<TradingSignal> {
+            panic!("blacklisted token {} was analyzed", metrics.mint);
+        }
+
+        fn get_exit_params(&self) -> StrategyExitParams {
+            unreachable!()
+        }
+
+        fn name(&self) -> &str {
+            "Never Called"
+        }
+
+        fn strategy_type(&self) -> crate::types::StrategyType {
+            unreachable!()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_blacklisted_mint_is_never_analyzed() {
+        let config = BotConfig::for_tests("http://localhost");
+        let mut trader = Trader::new(config.clone());
+        let source = CountingSource::default();
+        let blacklist = Blacklist::from_entries(&["BadMint"], &[]);
+        let rug_check = RugCheck::new(&config);
+        let mut debouncer = SignalDebouncer::new(1);
+        let mut loop_stats = LoopStats::new(api::ApiState::new());
+
+        evaluate_token("BadMint", &source, &NeverCalled, &blacklist, &rug_check, &mut trader, &mut debouncer, &mut loop_stats, &config)
+            .await
+            .unwrap();
+        assert_eq!(source.calls.load(Ordering::SeqCst), 0);
+
+        evaluate_token("GoodMint", &source, &NeverCalled, &blacklist, &rug_check, &mut trader, &mut debouncer, &mut loop_stats, &config)
+            .await
+            .unwrap();
+        assert_eq!(source.calls.load(Ordering::SeqCst), 1);
+    }
+
+    async fn health_status(state: &api::ApiState) -> String {
+        use tower::ServiceExt;
+
+        let request = axum::http::Request::builder()
+            .uri("/api/health")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = api::create_router(state.clone()).oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let health: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        health["status"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_running_loop_reports_healthy() {
+        let mut config = BotConfig::for_tests("http://localhost");
+        config.dry_run = true;
+        let api_state = api::ApiState::new();
+        let mut trader = Trader::new(config.clone()).with_api_state(api_state.clone());
+        let mut loop_stats = LoopStats::new(api_state.clone());
+        let source = CountingSource::default();
+        let rug_check = RugCheck::new(&config);
+        assert_eq!(health_status(&api_state).await, "stopped");
+
+        // Dry-run scans return three mock tokens; every lookup fails, so none is analyzed
+        run_trading_cycle(
+            &PumpFunScanner::new(&config),
+            &source,
+            &NeverCalled,
+            &Blacklist::default(),
+            &rug_check,
+            &mut trader,
+            &mut SignalDebouncer::new(1),
+            &mut loop_stats,
+            &config,
+        )
+        .await
+        .unwrap();
+        loop_stats.publish().await;
+
+        assert_eq!(health_status(&api_state).await, "healthy");
+        let stats = api_state.stats.read().await;
+        assert!(stats.is_running);
+        assert_eq!(stats.total_scans, 1);
+        assert_eq!(source.calls.load(Ordering::SeqCst), 3);
+        assert!(stats.last_scan_at.is_some());
+    }
+
+    /// Serves the same metrics for every mint
+    struct FixedSource(TokenMetrics);
+
+    #[async_trait::async_trait]
+    impl MetricsSource for FixedSource {
+        async fn get_metrics(&self, mint: &str) -> Result<TokenMetrics> {
+            Ok(TokenMetrics { mint: mint.to_string(), ..self.0.clone() })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_critical_rug_flag_holds_every_strategy() {
+        let mut config = BotConfig::for_tests("http://localhost");
+        config.dry_run = true;
+        let mint = solana_sdk::pubkey::Pubkey::new_unique().to_string();
+        let metrics = SlowSource::default().get_metrics(&mint).await.unwrap();
+        // Mid-curve token in a steep, well-bought run: the momentum scalper's entry
+        let mut metrics = TokenMetrics {
+            creator: Some("CreatorWallet".to_string()),
+            volume_5m: 50.0,
+            volume_1h: 60.0,
+            volume_24h: 250.0,
+            current_price: 0.001,
+            price_change_5m: 0.20,
+            price_change_1h: 1.40,
+            liquidity_sol: 12.0,
+            holder_count: 120,
+            holder_concentration: 0.22,
+            unique_buyers_5m: 60,
+            unique_sellers_5m: 25,
+            bonding_curve_progress: 55.0,
+            time_since_creation: 2400,
+            buy_pressure: 3.5,
+            sell_pressure: 1.0,
+            volatility_score: 0.3,
+            ..metrics
+        };
+        let strategy = create_strategy(types::StrategyType::MomentumScalper, None, &types::HolderThresholds::default());
+        let rug_check = RugCheck::new(&config);
+        let api_permits = Semaphore::new(1);
+
+        let source = FixedSource(metrics.clone());
+        let (_, signal) = analyze_token(&mint, &source, strategy.as_ref(), &Blacklist::default(), &rug_check, &config, &api_permits)
+            .await
+            .unwrap();
+        assert_ne!(signal.signal_type, SignalType::Hold);
+        assert!(!signal.rejected);
+
+        metrics.rug_risk = Some(types::RugRisk { score: 0.5, flags: vec![types::RugFlag::MintAuthority] });
+        let source = FixedSource(metrics.clone());
+        let (_, signal) = analyze_token(&mint, &source, strategy.as_ref(), &Blacklist::default(), &rug_check, &config, &api_permits)
+            .await
+            .unwrap();
+        assert_eq!(signal.signal_type, SignalType::Hold);
+        assert!(signal.rejected);
+        assert_eq!(signal.reason_codes[0], types::ReasonCode::RugRisk);
+
+        // A blacklisted creator is held the same way
+        metrics.rug_risk = None;
+        let blacklist = Blacklist::from_entries(&[], &["CreatorWallet"]);
+        let source = FixedSource(metrics);
+        let (_, signal) = analyze_token(&mint, &source, strategy.as_ref(), &blacklist, &rug_check, &config, &api_permits)
+            .await
+            .unwrap();
+        assert_eq!(signal.signal_type, SignalType::Hold);
+        assert_eq!(signal.reason_codes[0], types::ReasonCode::BlacklistedCreator);
+    }
+
+    /// Takes a while per lookup and records how many lookups overlapped
+    #[derive(Default)]
+    struct SlowSource {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl MetricsSource for SlowSource {
+        async fn get_metrics(&self, mint: &str) -> Result<TokenMetrics> {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            time::sleep(Duration::from_millis(50)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            Ok(TokenMetrics {
+                mint: mint.to_string(),
+                name: "Slow Token".to_string(),
+                symbol: "SLOW".to_string(),
+                creator: None,
+                volume_5m: 0.0,
+                volume_1h: 0.0,
+                volume_24h: 0.0,
+                current_price: 0.0,
+                price_change_5m: 0.0,
+                price_change_1h: 0.0,
+                liquidity_sol: 0.0,
+                liquidity_usd: 0.0,
+                virtual_sol_reserves: 0.0,
+                virtual_token_reserves: 0.0,
+                holder_count: 0,
+                holder_concentration: 0.0,
+                unique_buyers_5m: 0,
+                unique_sellers_5m: 0,
+                market_cap: 0.0,
+                fully_diluted_valuation: 0.0,
+                bonding_curve_progress: 0.0,
+                is_graduated: false,
+                created_at: 0,
+                time_since_creation: 0,
+                buy_pressure: 0.0,
+                sell_pressure: 0.0,
+                buy_count_5m: 0,
+                sell_count_5m: 0,
+                volatility_score: 0.0,
+                wash_trade_score: 0.0,
+                rug_risk: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tokens_are_analyzed_concurrently() {
+        let mut config = BotConfig::for_tests("http://localhost");
+        config.dry_run = true;
+        config.analysis_concurrency = 8;
+        config.max_concurrent_requests = 4;
+        let source = SlowSource::default();
+        let strategy = create_strategy(types::StrategyType::MomentumScalper, None, &types::HolderThresholds::default());
+        let blacklist = Blacklist::default();
+        let rug_check = RugCheck::new(&config);
+        let mints: Vec<String> = (0..20).map(|_| solana_sdk::pubkey::Pubkey::new_unique().to_string()).collect();
+
+        let started = std::time::Instant::now();
+        let analyzed = analyze_tokens(mints, &source, strategy.as_ref(), &blacklist, &rug_check, &config).await;
+        let elapsed = started.elapsed();
+
+        // One at a time this is 20 x 50ms of lookups alone
+        assert_eq!(analyzed.len(), 20);
+        assert!(elapsed < Duration::from_millis(500), "took {:?}", elapsed);
+        assert_eq!(source.max_in_flight.load(Ordering::SeqCst), 4);
+    }
+
+    async fn strong_buy(confidence: f64) -> (TokenMetrics, TradingSignal) {
+        let mint = solana_sdk::pubkey::Pubkey::new_unique();
+        let mut metrics = SlowSource::default().get_metrics(&mint.to_string()).await.unwrap();
+        metrics.current_price = 0.00000003;
+        let signal = TradingSignal {
+            token_mint: mint,
+            signal_type: SignalType::StrongBuy,
+            confidence,
+            reasoning: Vec::new(),
+            reason_codes: Vec::new(),
+            timestamp: 0,
+            rejected: false,
+        };
+        (metrics, signal)
+    }
+
+    #[tokio::test]
+    async fn test_strong_buy_threshold_is_configurable() {
+        let mut config = BotConfig::for_tests("http://127.0.0.1:1");
+        config.dry_run = true;
+        let (metrics, signal) = strong_buy(0.70).await;
+
+        let mut trader = Trader::new(config.clone());
+        execute_signal(&metrics, &signal, &mut trader, &mut SignalDebouncer::new(1), &config).await;
+        assert!(trader.get_active_positions().is_empty());
+
+        config.strong_buy_confidence = 0.65;
+        let mut trader = Trader::new(config.clone());
+        execute_signal(&metrics, &signal, &mut trader, &mut SignalDebouncer::new(1), &config).await;
+        assert_eq!(trader.get_active_positions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_strong_buy_needs_consecutive_confirmations() {
+        let mut config = BotConfig::for_tests("http://127.0.0.1:1");
+        config.dry_run = true;
+        config.signal_confirmations = 3;
+        let mut trader = Trader::new(config.clone());
+        let mut debouncer = SignalDebouncer::new(config.signal_confirmations);
+        let (metrics, above) = strong_buy(0.80).await;
+        let below = TradingSignal { confidence: 0.74, ..above.clone() };
+
+        // Flickering around the threshold never builds a streak
+        for signal in [&above, &below, &above, &above, &below, &above, &above, &below] {
+            execute_signal(&metrics, signal, &mut trader, &mut debouncer, &config).await;
+            assert!(trader.get_active_positions().is_empty());
+        }
+
+        execute_signal(&metrics, &above, &mut trader, &mut debouncer, &config).await;
+        execute_signal(&metrics, &above, &mut trader, &mut debouncer, &config).await;
+        assert!(trader.get_active_positions().is_empty());
+        execute_signal(&metrics, &above, &mut trader, &mut debouncer, &config).await;
+        assert_eq!(trader.get_active_positions().len(), 1);
+    }
+
+    /// Buffer a log layer writes into
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_logs_carry_signal_fields() {
+        let captured = CapturedLogs::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::registry()
+            .with(log_layer(LogFormat::Json, move || writer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            info!(mint = "TestMint111", signal_type = ?SignalType::StrongBuy, confidence = 0.82, "📊 signal");
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let event: serde_json::Value = serde_json::from_str(output.lines().next().unwrap()).unwrap();
+        assert_eq!(event["level"], "INFO");
+        assert_eq!(event["fields"]["mint"], "TestMint111");
+        assert_eq!(event["fields"]["signal_type"], "StrongBuy");
+        assert_eq!(event["fields"]["confidence"], 0.82);
+    }
+}
@@ -0,0 +1,231 @@
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+    sysvar,
+};
+use std::str::FromStr;
+
+// ============================================================================
+// pump.fun Program Constants
+// ============================================================================
+
+/// pump.fun bonding curve program (mainnet)
+pub const PUMP_FUN_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+
+/// Protocol fee recipient required by the buy/sell instructions
+pub const PUMP_FUN_FEE_RECIPIENT: &str = "CebN5WGQ4jvEPvsVU4EoHEpgzq1VV7AbicfhtW4xC9iM";
+
+const GLOBAL_SEED: &[u8] = b"global";
+const BONDING_CURVE_SEED: &[u8] = b"bonding-curve";
+const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+/// All pump.fun mints use 6 decimals
+pub const PUMP_FUN_TOKEN_DECIMALS: u8 = 6;
+
+/// Anchor discriminator for `global:buy`
+pub const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+
+// ============================================================================
+// PDA Derivation
+// ============================================================================
+
+pub fn derive_global(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[GLOBAL_SEED], program_id).0
+}
+
+pub fn derive_bonding_curve(program_id: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[BONDING_CURVE_SEED, mint.as_ref()], program_id).0
+}
+
+/// The bonding curve's token account is a regular ATA owned by the curve PDA
+pub fn derive_associated_bonding_curve(bonding_curve: &Pubkey, mint: &Pubkey) -> Pubkey {
+    spl_associated_token_account::get_associated_token_address(bonding_curve, mint)
+}
+
+pub fn derive_event_authority(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[EVENT_AUTHORITY_SEED], program_id).0
+}
+
+pub fn fee_recipient() -> Pubkey {
+    Pubkey::from_str(PUMP_FUN_FEE_RECIPIENT).expect("valid fee recipient pubkey")
+}
+
+// ============================================================================
+// Bonding Curve State
+// ============================================================================
+
+/// On-chain bonding curve account (after the 8-byte Anchor discriminator)
+#[derive(Debug, Clone, PartialEq)]
+pub struct BondingCurveState {
+    pub virtual_token_reserves: u64,
+    pub virtual_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub token_total_supply: u64,
+    pub complete: bool,
+}
+
+impl BondingCurveState {
+    /// Parse raw account data as returned by `get_account_data`
+    pub fn from_account_data(data: &[u8]) -> Option<Self> {
+        // 8 discriminator + 5 * u64 + bool
+        if data.len() < 8 + 5 * 8 + 1 {
+            return None;
+        }
+
+        let read_u64 = |offset: usize| {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(&data[offset..offset + 8]);
+            u64::from_le_bytes(buf)
+        };
+
+        Some(Self {
+            virtual_token_reserves: read_u64(8),
+            virtual_sol_reserves: read_u64(16),
+            real_token_reserves: read_u64(24),
+            real_sol_reserves: read_u64(32),
+            token_total_supply: read_u64(40),
+            complete: data[48] != 0,
+        })
+    }
+
+    /// Spot price in SOL per whole token, from the virtual reserves
+    pub fn spot_price(&self) -> f64 {
+        if self.virtual_token_reserves == 0 {
+            return 0.0;
+        }
+
+        let sol = self.virtual_sol_reserves as f64 / 1e9;
+        let tokens = self.virtual_token_reserves as f64 / 10f64.powi(PUMP_FUN_TOKEN_DECIMALS as i32);
+        sol / tokens
+    }
+
+    /// Tokens received for `sol_in` lamports using the constant-product curve
+    pub fn buy_token_amount(&self, sol_in: u64) -> u64 {
+        if sol_in == 0 || self.virtual_sol_reserves == 0 {
+            return 0;
+        }
+
+        let k = self.virtual_sol_reserves as u128 * self.virtual_token_reserves as u128;
+        let new_sol_reserves = self.virtual_sol_reserves as u128 + sol_in as u128;
+        let new_token_reserves = k / new_sol_reserves + 1;
+        let tokens_out = (self.virtual_token_reserves as u128).saturating_sub(new_token_reserves);
+
+        tokens_out.min(self.real_token_reserves as u128) as u64
+    }
+
+    /// Fractional price impact of buying with `sol_in` lamports: the average fill
+    /// price relative to the spot price, minus one
+    pub fn buy_price_impact(&self, sol_in: u64) -> f64 {
+        let tokens_out = self.buy_token_amount(sol_in);
+        if tokens_out == 0 || self.virtual_token_reserves == 0 {
+            return f64::INFINITY;
+        }
+
+        let spot = self.virtual_sol_reserves as f64 / self.virtual_token_reserves as f64;
+        let effective = sol_in as f64 / tokens_out as f64;
+        effective / spot - 1.0
+    }
+}
+
+/// Maximum SOL the program may charge, padded by the allowed slippage
+pub fn max_sol_cost_with_slippage(sol_amount: u64, max_slippage_bps: u16) -> u64 {
+    let padded = sol_amount as u128 * (10_000 + max_slippage_bps as u128) / 10_000;
+    padded.min(u64::MAX as u128) as u64
+}
+
+// ============================================================================
+// Instruction Builders
+// ============================================================================
+
+/// Build the pump.fun `buy` instruction
+///
+/// Data layout: discriminator (8) | amount: u64 LE | max_sol_cost: u64 LE
+pub fn build_buy_instruction(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    mint: &Pubkey,
+    user_token_account: &Pubkey,
+    token_amount: u64,
+    max_sol_cost: u64,
+) -> Instruction {
+    let bonding_curve = derive_bonding_curve(program_id, mint);
+    let associated_bonding_curve = derive_associated_bonding_curve(&bonding_curve, mint);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(derive_global(program_id), false),
+        AccountMeta::new(fee_recipient(), false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new(bonding_curve, false),
+        AccountMeta::new(associated_bonding_curve, false),
+        AccountMeta::new(*user_token_account, false),
+        AccountMeta::new(*user, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(derive_event_authority(program_id), false),
+        AccountMeta::new_readonly(*program_id, false),
+    ];
+
+    let mut data = Vec::with_capacity(24);
+    data.extend_from_slice(&BUY_DISCRIMINATOR);
+    data.extend_from_slice(&token_amount.to_le_bytes());
+    data.extend_from_slice(&max_sol_cost.to_le_bytes());
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buy_instruction_layout() {
+        let program_id = Pubkey::from_str(PUMP_FUN_PROGRAM_ID).unwrap();
+        let user = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let user_ata = spl_associated_token_account::get_associated_token_address(&user, &mint);
+
+        let ix = build_buy_instruction(&program_id, &user, &mint, &user_ata, 1_000_000, 105_000_000);
+
+        let bonding_curve = derive_bonding_curve(&program_id, &mint);
+        let expected = vec![
+            derive_global(&program_id),
+            fee_recipient(),
+            mint,
+            bonding_curve,
+            derive_associated_bonding_curve(&bonding_curve, &mint),
+            user_ata,
+            user,
+            system_program::id(),
+            spl_token::id(),
+            sysvar::rent::id(),
+            derive_event_authority(&program_id),
+            program_id,
+        ];
+        let actual: Vec<Pubkey> = ix.accounts.iter().map(|a| a.pubkey).collect();
+        assert_eq!(actual, expected);
+
+        // Only the user signs; only curve, fee recipient and token accounts are writable
+        let signers: Vec<Pubkey> = ix.accounts.iter().filter(|a| a.is_signer).map(|a| a.pubkey).collect();
+        assert_eq!(signers, vec![user]);
+        assert!(ix.accounts[1].is_writable && ix.accounts[3].is_writable && ix.accounts[5].is_writable);
+        assert!(!ix.accounts[0].is_writable);
+
+        assert_eq!(ix.data.len(), 24);
+        assert_eq!(&ix.data[..8], &BUY_DISCRIMINATOR);
+        assert_eq!(u64::from_le_bytes(ix.data[8..16].try_into().unwrap()), 1_000_000);
+        assert_eq!(u64::from_le_bytes(ix.data[16..24].try_into().unwrap()), 105_000_000);
+    }
+
+    #[test]
+    fn test_max_sol_cost_applies_slippage() {
+        assert_eq!(max_sol_cost_with_slippage(1_000_000_000, 500), 1_050_000_000);
+        assert_eq!(max_sol_cost_with_slippage(1_000_000_000, 0), 1_000_000_000);
+    }
+}
@@ -0,0 +1,233 @@
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+
+// ============================================================================
+// Raydium AMM v4 Pool Layout
+// ============================================================================
+
+/// Size of a Raydium AMM v4 `LiquidityStateV4` account
+pub const AMM_V4_ACCOUNT_SIZE: u64 = 752;
+
+const NONCE_OFFSET: usize = 8;
+const BASE_VAULT_OFFSET: usize = 336;
+const QUOTE_VAULT_OFFSET: usize = 368;
+const BASE_MINT_OFFSET: usize = 400;
+const QUOTE_MINT_OFFSET: usize = 432;
+const OPEN_ORDERS_OFFSET: usize = 496;
+const MARKET_ID_OFFSET: usize = 528;
+const MARKET_PROGRAM_ID_OFFSET: usize = 560;
+const TARGET_ORDERS_OFFSET: usize = 592;
+
+/// Seed of the PDA that owns every AMM v4 pool vault
+const AMM_AUTHORITY_SEED: &[u8] = b"amm authority";
+
+/// `swap_base_in` instruction tag
+const SWAP_BASE_IN_TAG: u8 = 9;
+
+/// AMM v4 trade fee, 0.25%
+const SWAP_FEE_NUMERATOR: u128 = 25;
+const SWAP_FEE_DENOMINATOR: u128 = 10_000;
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    let mut buf = [0u8; 32];
+    buf.copy_from_slice(&data[offset..offset + 32]);
+    Pubkey::new_from_array(buf)
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&data[offset..offset + 8]);
+    u64::from_le_bytes(buf)
+}
+
+/// Accounts of an AMM v4 pool
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolKeys {
+    /// Bump of the AMM authority PDA
+    pub nonce: u8,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub open_orders: Pubkey,
+    pub target_orders: Pubkey,
+    /// OpenBook market the pool is paired with, still required by swaps
+    pub market_id: Pubkey,
+    pub market_program_id: Pubkey,
+}
+
+impl PoolKeys {
+    pub fn from_account_data(data: &[u8]) -> Option<Self> {
+        if data.len() < AMM_V4_ACCOUNT_SIZE as usize {
+            return None;
+        }
+
+        Some(Self {
+            nonce: read_u64(data, NONCE_OFFSET) as u8,
+            base_vault: read_pubkey(data, BASE_VAULT_OFFSET),
+            quote_vault: read_pubkey(data, QUOTE_VAULT_OFFSET),
+            base_mint: read_pubkey(data, BASE_MINT_OFFSET),
+            quote_mint: read_pubkey(data, QUOTE_MINT_OFFSET),
+            open_orders: read_pubkey(data, OPEN_ORDERS_OFFSET),
+            target_orders: read_pubkey(data, TARGET_ORDERS_OFFSET),
+            market_id: read_pubkey(data, MARKET_ID_OFFSET),
+            market_program_id: read_pubkey(data, MARKET_PROGRAM_ID_OFFSET),
+        })
+    }
+
+    /// The AMM authority PDA, or `None` if the stored nonce doesn't derive one
+    pub fn authority(&self, amm_program: &Pubkey) -> Option<Pubkey> {
+        Pubkey::create_program_address(&[AMM_AUTHORITY_SEED, &[self.nonce]], amm_program).ok()
+    }
+}
+
+/// `getProgramAccounts` filters selecting the token/WSOL pool for `mint`
+pub fn pool_filters(mint: &Pubkey) -> Vec<RpcFilterType> {
+    vec![
+        RpcFilterType::DataSize(AMM_V4_ACCOUNT_SIZE),
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(BASE_MINT_OFFSET, &mint.to_bytes())),
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            QUOTE_MINT_OFFSET,
+            &spl_token::native_mint::id().to_bytes(),
+        )),
+    ]
+}
+
+// ============================================================================
+// OpenBook Market Layout
+// ============================================================================
+
+/// Shortest OpenBook market account holding every field read here
+const MARKET_MIN_SIZE: usize = 349;
+
+const MARKET_VAULT_SIGNER_NONCE_OFFSET: usize = 45;
+const MARKET_BASE_VAULT_OFFSET: usize = 117;
+const MARKET_QUOTE_VAULT_OFFSET: usize = 165;
+const MARKET_EVENT_QUEUE_OFFSET: usize = 253;
+const MARKET_BIDS_OFFSET: usize = 285;
+const MARKET_ASKS_OFFSET: usize = 317;
+
+/// Market accounts an AMM v4 swap passes through
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarketKeys {
+    pub bids: Pubkey,
+    pub asks: Pubkey,
+    pub event_queue: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    pub vault_signer: Pubkey,
+}
+
+impl MarketKeys {
+    pub fn from_account_data(market: &Pubkey, market_program: &Pubkey, data: &[u8]) -> Option<Self> {
+        if data.len() < MARKET_MIN_SIZE {
+            return None;
+        }
+
+        let nonce = read_u64(data, MARKET_VAULT_SIGNER_NONCE_OFFSET);
+        let vault_signer = Pubkey::create_program_address(
+            &[market.as_ref(), &nonce.to_le_bytes()],
+            market_program,
+        ).ok()?;
+
+        Some(Self {
+            bids: read_pubkey(data, MARKET_BIDS_OFFSET),
+            asks: read_pubkey(data, MARKET_ASKS_OFFSET),
+            event_queue: read_pubkey(data, MARKET_EVENT_QUEUE_OFFSET),
+            base_vault: read_pubkey(data, MARKET_BASE_VAULT_OFFSET),
+            quote_vault: read_pubkey(data, MARKET_QUOTE_VAULT_OFFSET),
+            vault_signer,
+        })
+    }
+}
+
+// ============================================================================
+// Swap Math & Instruction Builders
+// ============================================================================
+
+/// Constant-product output for `amount_in` after the pool's trade fee
+pub fn swap_amount_out(reserve_in: u64, reserve_out: u64, amount_in: u64) -> u64 {
+    let amount_in = amount_in as u128 * (SWAP_FEE_DENOMINATOR - SWAP_FEE_NUMERATOR) / SWAP_FEE_DENOMINATOR;
+    let denominator = reserve_in as u128 + amount_in;
+    if denominator == 0 {
+        return 0;
+    }
+    (reserve_out as u128 * amount_in / denominator) as u64
+}
+
+/// Minimum output the swap may return, reduced by the allowed slippage
+pub fn min_amount_out_with_slippage(amount_out: u64, max_slippage_bps: u16) -> u64 {
+    let bps = max_slippage_bps.min(10_000) as u128;
+    (amount_out as u128 * (10_000 - bps) / 10_000) as u64
+}
+
+/// Build the AMM v4 `swap_base_in` instruction, spending `amount_in` from
+/// `user_source` for at least `minimum_amount_out` into `user_destination`
+///
+/// Data layout: tag (1) | amount_in: u64 LE | minimum_amount_out: u64 LE
+#[allow(clippy::too_many_arguments)]
+pub fn build_swap_base_in_instruction(
+    amm_program: &Pubkey,
+    amm_id: &Pubkey,
+    amm_authority: &Pubkey,
+    pool: &PoolKeys,
+    market: &MarketKeys,
+    user_source: &Pubkey,
+    user_destination: &Pubkey,
+    owner: &Pubkey,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(*amm_id, false),
+        AccountMeta::new_readonly(*amm_authority, false),
+        AccountMeta::new(pool.open_orders, false),
+        AccountMeta::new(pool.target_orders, false),
+        AccountMeta::new(pool.base_vault, false),
+        AccountMeta::new(pool.quote_vault, false),
+        AccountMeta::new_readonly(pool.market_program_id, false),
+        AccountMeta::new(pool.market_id, false),
+        AccountMeta::new(market.bids, false),
+        AccountMeta::new(market.asks, false),
+        AccountMeta::new(market.event_queue, false),
+        AccountMeta::new(market.base_vault, false),
+        AccountMeta::new(market.quote_vault, false),
+        AccountMeta::new_readonly(market.vault_signer, false),
+        AccountMeta::new(*user_source, false),
+        AccountMeta::new(*user_destination, false),
+        AccountMeta::new_readonly(*owner, true),
+    ];
+
+    let mut data = Vec::with_capacity(17);
+    data.push(SWAP_BASE_IN_TAG);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+
+    Instruction {
+        program_id: *amm_program,
+        accounts,
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swap_amount_out_charges_fee() {
+        // 1 SOL into 100 SOL / 1e15 tokens: ~0.99% of the token reserve, less 0.25%
+        let out = swap_amount_out(100_000_000_000, 1_000_000_000_000_000, 1_000_000_000);
+        let fee_free = 1_000_000_000_000_000u128 * 1_000_000_000 / 101_000_000_000;
+        assert!(out < fee_free as u64);
+        assert!(out as f64 > fee_free as f64 * 0.997);
+    }
+
+    #[test]
+    fn test_min_amount_out_applies_slippage() {
+        assert_eq!(min_amount_out_with_slippage(1_000_000, 500), 950_000);
+        assert_eq!(min_amount_out_with_slippage(1_000_000, 0), 1_000_000);
+    }
+}
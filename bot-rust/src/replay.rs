@@ -0,0 +1,677 @@
+use crate::api::{ApiState, DelegationInfo, PositionInfo};
+use crate::error::{BotError, Result};
+use crate::rpc::FailoverRpc;
+use crate::types::StrategyType;
+use crate::vault::from_scaled_price;
+use base64::Engine;
+use futures::StreamExt;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::{RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+use solana_sdk::{commitment_config::CommitmentConfig, hash::hash, pubkey::Pubkey, signature::Signature};
+use std::collections::HashSet;
+use std::str::FromStr;
+use tracing::{debug, info, warn};
+
+/// Prefix of the log line Anchor's `emit!` writes an event to
+const EVENT_LOG_PREFIX: &str = "Program data: ";
+
+/// Signatures fetched per `getSignaturesForAddress` page
+const SIGNATURE_PAGE_SIZE: usize = 1000;
+
+// ============================================================================
+// Vault Events
+// ============================================================================
+
+/// Anchor event discriminator: first 8 bytes of sha256("event:<name>")
+fn event_discriminator(name: &str) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash(format!("event:{}", name).as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+/// Borsh fields read in order from an event payload
+struct EventReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> EventReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.offset..self.offset + len)?;
+        self.offset += len;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn bool(&mut self) -> Option<bool> {
+        Some(self.u8()? != 0)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_le_bytes(self.take(2)?.try_into().ok()?))
+    }
+
+    fn i32(&mut self) -> Option<i32> {
+        Some(i32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn i64(&mut self) -> Option<i64> {
+        Some(i64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn pubkey(&mut self) -> Option<Pubkey> {
+        Pubkey::try_from(self.take(32)?).ok()
+    }
+}
+
+/// The vault program events that shape API state; fields follow the `#[event]`
+/// structs in `lib_mainnet.rs`, skipping those the API doesn't show
+#[derive(Debug, Clone, PartialEq)]
+pub enum VaultEvent {
+    DelegationCreated {
+        user: Pubkey,
+        vault_index: u8,
+        strategy: u8,
+        max_position_size_sol: u64,
+        max_concurrent_trades: u8,
+        timestamp: i64,
+    },
+    DelegationUpdated {
+        user: Pubkey,
+        vault_index: u8,
+        strategy: u8,
+        max_position_size_sol: u64,
+        max_concurrent_trades: u8,
+        is_active: bool,
+    },
+    DelegationRevoked {
+        user: Pubkey,
+        vault_index: u8,
+    },
+    DelegationClosed {
+        user: Pubkey,
+        vault_index: u8,
+    },
+    EmergencyWithdrawn {
+        user: Pubkey,
+        vault_index: u8,
+        active_trades_remaining: u8,
+    },
+    PositionOpened {
+        user: Pubkey,
+        vault_index: u8,
+        position_id: u64,
+        token_mint: Pubkey,
+        amount_sol: u64,
+        entry_price: u64,
+        take_profit_price: u64,
+        stop_loss_price: u64,
+        strategy: u8,
+        entry_confidence_bps: u16,
+        timestamp: i64,
+    },
+    PositionClosed {
+        user: Pubkey,
+        vault_index: u8,
+        position_id: u64,
+        exit_price: u64,
+        pnl: i64,
+        pnl_bps: i32,
+        timestamp: i64,
+    },
+    PositionLiquidated {
+        user: Pubkey,
+        vault_index: u8,
+        position_id: u64,
+        timestamp: i64,
+    },
+}
+
+impl VaultEvent {
+    /// Decode one `Program data:` payload; `None` for events the API ignores
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let discriminator: [u8; 8] = data.get(..8)?.try_into().ok()?;
+        let mut r = EventReader { data, offset: 8 };
+
+        if discriminator == event_discriminator("DelegationCreated") {
+            let user = r.pubkey()?;
+            r.take(32)?; // bot_authority
+            Some(Self::DelegationCreated {
+                user,
+                vault_index: r.u8()?,
+                strategy: r.u8()?,
+                max_position_size_sol: r.u64()?,
+                max_concurrent_trades: r.u8()?,
+                timestamp: r.i64()?,
+            })
+        } else if discriminator == event_discriminator("DelegationUpdated") {
+            Some(Self::DelegationUpdated {
+                user: r.pubkey()?,
+                vault_index: r.u8()?,
+                strategy: r.u8()?,
+                max_position_size_sol: r.u64()?,
+                max_concurrent_trades: r.u8()?,
+                is_active: r.bool()?,
+            })
+        } else if discriminator == event_discriminator("DelegationRevoked") {
+            Some(Self::DelegationRevoked { user: r.pubkey()?, vault_index: r.u8()? })
+        } else if discriminator == event_discriminator("DelegationClosed") {
+            Some(Self::DelegationClosed { user: r.pubkey()?, vault_index: r.u8()? })
+        } else if discriminator == event_discriminator("EmergencyWithdrawn") {
+            let (user, vault_index) = (r.pubkey()?, r.u8()?);
+            r.take(1)?; // positions_liquidated
+            Some(Self::EmergencyWithdrawn { user, vault_index, active_trades_remaining: r.u8()? })
+        } else if discriminator == event_discriminator("PositionOpened") {
+            let (user, vault_index, position_id) = (r.pubkey()?, r.u8()?, r.u64()?);
+            r.take(8)?; // client_order_id
+            Some(Self::PositionOpened {
+                user,
+                vault_index,
+                position_id,
+                token_mint: r.pubkey()?,
+                amount_sol: r.u64()?,
+                entry_price: r.u64()?,
+                take_profit_price: r.u64()?,
+                stop_loss_price: r.u64()?,
+                strategy: r.u8()?,
+                entry_confidence_bps: r.u16()?,
+                timestamp: r.i64()?,
+            })
+        } else if discriminator == event_discriminator("PositionClosed") {
+            let (user, vault_index, position_id) = (r.pubkey()?, r.u8()?, r.u64()?);
+            r.take(32 + 8)?; // token_mint, entry_price
+            Some(Self::PositionClosed {
+                user,
+                vault_index,
+                position_id,
+                exit_price: r.u64()?,
+                pnl: r.i64()?,
+                pnl_bps: r.i32()?,
+                timestamp: r.i64()?,
+            })
+        } else if discriminator == event_discriminator("PositionLiquidated") {
+            let (user, vault_index, position_id) = (r.pubkey()?, r.u8()?, r.u64()?);
+            r.take(32)?; // token_mint
+            Some(Self::PositionLiquidated { user, vault_index, position_id, timestamp: r.i64()? })
+        } else {
+            None
+        }
+    }
+
+    /// Every decodable event in a transaction's logs, in emission order
+    pub fn from_logs(logs: &[String]) -> Vec<Self> {
+        logs.iter()
+            .filter_map(|line| line.strip_prefix(EVENT_LOG_PREFIX))
+            .filter_map(|payload| base64::engine::general_purpose::STANDARD.decode(payload).ok())
+            .filter_map(|data| Self::decode(&data))
+            .collect()
+    }
+}
+
+/// API id of a vault position; on-chain ids are only unique per delegation,
+/// and a user may hold several delegations
+fn api_position_id(user: &Pubkey, vault_index: u8, position_id: u64) -> String {
+    format!("{}:{}:{}", user, vault_index, position_id)
+}
+
+// ============================================================================
+// State Rebuilder
+// ============================================================================
+
+/// Rebuilds `ApiState` delegations, positions and trade stats from the vault
+/// program's event history, then keeps them current from its live logs
+///
+/// Each transaction is applied once, so history and the live subscription may
+/// overlap. Closes emitted before the rebuilder started are folded into the
+/// trade stats; later ones are the trader's to report.
+pub struct StateRebuilder {
+    state: ApiState,
+    program_id: Pubkey,
+    commitment: CommitmentConfig,
+    started_at: i64,
+    seen: HashSet<String>,
+}
+
+impl StateRebuilder {
+    pub fn new(state: ApiState, program_id: Pubkey, commitment: CommitmentConfig) -> Self {
+        Self {
+            state,
+            program_id,
+            commitment,
+            started_at: chrono::Utc::now().timestamp(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Transaction history is only served at confirmed or finalized
+    fn history_commitment(&self) -> CommitmentConfig {
+        if self.commitment.is_at_least_confirmed() {
+            self.commitment
+        } else {
+            CommitmentConfig::confirmed()
+        }
+    }
+
+    /// Apply the events in the logs of transaction `signature`; false if it was
+    /// already applied
+    pub async fn apply(&mut self, signature: &str, logs: &[String]) -> bool {
+        if !self.seen.insert(signature.to_string()) {
+            return false;
+        }
+
+        for event in VaultEvent::from_logs(logs) {
+            debug!("Replaying {:?} from {}", event, signature);
+            self.apply_event(event).await;
+        }
+        true
+    }
+
+    /// Run `update` on the delegation of `user` at `vault_index`, if it is known
+    async fn update_delegation(&self, user: &Pubkey, vault_index: u8, update: impl FnOnce(&mut DelegationInfo)) {
+        let user = user.to_string();
+        let mut delegations = self.state.delegations.write().await;
+        if let Some(delegation) = delegations.iter_mut().find(|d| d.user == user && d.vault_index == vault_index) {
+            update(delegation);
+        }
+    }
+
+    async fn apply_event(&self, event: VaultEvent) {
+        match event {
+            VaultEvent::DelegationCreated { user, vault_index, strategy, max_position_size_sol, max_concurrent_trades, timestamp } => {
+                self.state.add_delegation(DelegationInfo {
+                    user: user.to_string(),
+                    vault_index,
+                    strategy: StrategyType::from_on_chain_id(strategy).unwrap_or_default(),
+                    max_position_size_sol: max_position_size_sol as f64 / 1e9,
+                    max_concurrent_trades,
+                    is_active: true,
+                    active_trades: 0,
+                    total_trades: 0,
+                    profitable_trades: 0,
+                    total_pnl: 0,
+                    created_at: timestamp,
+                }).await;
+            }
+            VaultEvent::DelegationUpdated { user, vault_index, strategy, max_position_size_sol, max_concurrent_trades, is_active } => {
+                self.update_delegation(&user, vault_index, |delegation| {
+                    delegation.strategy = StrategyType::from_on_chain_id(strategy).unwrap_or_default();
+                    delegation.max_position_size_sol = max_position_size_sol as f64 / 1e9;
+                    delegation.max_concurrent_trades = max_concurrent_trades;
+                    delegation.is_active = is_active;
+                }).await;
+            }
+            VaultEvent::DelegationRevoked { user, vault_index } => {
+                self.update_delegation(&user, vault_index, |delegation| delegation.is_active = false).await;
+            }
+            VaultEvent::DelegationClosed { user, vault_index } => {
+                let user = user.to_string();
+                self.state.delegations.write().await
+                    .retain(|d| d.user != user || d.vault_index != vault_index);
+            }
+            VaultEvent::EmergencyWithdrawn { user, vault_index, active_trades_remaining } => {
+                // Its PositionLiquidated events, emitted just before, closed the positions
+                self.update_delegation(&user, vault_index, |delegation| {
+                    delegation.is_active = false;
+                    delegation.active_trades = active_trades_remaining;
+                }).await;
+            }
+            VaultEvent::PositionOpened {
+                user,
+                vault_index,
+                position_id,
+                token_mint,
+                amount_sol,
+                entry_price,
+                take_profit_price,
+                stop_loss_price,
+                strategy,
+                entry_confidence_bps,
+                timestamp,
+            } => {
+                self.update_delegation(&user, vault_index, |delegation| {
+                    delegation.active_trades = delegation.active_trades.saturating_add(1);
+                    delegation.total_trades += 1;
+                }).await;
+
+                debug!(
+                    "Position {} of {} vault {} opened in {} at {:.12} SOL",
+                    position_id, user, vault_index, token_mint, from_scaled_price(entry_price)
+                );
+                self.state.add_position(PositionInfo {
+                    position_id: api_position_id(&user, vault_index, position_id),
+                    user: user.to_string(),
+                    token_mint: token_mint.to_string(),
+                    token_symbol: String::new(),
+                    amount_sol: amount_sol as f64 / 1e9,
+                    entry_price,
+                    current_price: entry_price,
+                    take_profit_price,
+                    stop_loss_price,
+                    status: "open".to_string(),
+                    pnl: 0,
+                    pnl_percentage: 0.0,
+                    opened_at: timestamp,
+                    closed_at: None,
+                    strategy,
+                    entry_confidence_bps,
+                    stale: false,
+                }).await;
+            }
+            VaultEvent::PositionClosed { user, vault_index, position_id, exit_price, pnl, pnl_bps, timestamp } => {
+                self.update_delegation(&user, vault_index, |delegation| {
+                    delegation.active_trades = delegation.active_trades.saturating_sub(1);
+                    if pnl > 0 {
+                        delegation.profitable_trades += 1;
+                    }
+                    delegation.total_pnl = delegation.total_pnl.saturating_add(pnl);
+                }).await;
+
+                self.state
+                    .close_position(&api_position_id(&user, vault_index, position_id), exit_price, pnl, pnl_bps, timestamp)
+                    .await;
+                if timestamp < self.started_at {
+                    self.state.record_close(pnl).await;
+                }
+            }
+            VaultEvent::PositionLiquidated { user, vault_index, position_id, timestamp } => {
+                self.state
+                    .liquidate_position(&api_position_id(&user, vault_index, position_id), timestamp)
+                    .await;
+            }
+        }
+    }
+
+    /// Replay every successful program transaction, oldest first
+    pub async fn backfill(&mut self, rpc: &FailoverRpc) -> Result<()> {
+        let commitment = self.history_commitment();
+        let mut signatures = Vec::new();
+        let mut before = None;
+        loop {
+            let page = rpc
+                .call_async(|client| {
+                    client.get_signatures_for_address_with_config(
+                        &self.program_id,
+                        GetConfirmedSignaturesForAddress2Config {
+                            before,
+                            until: None,
+                            limit: Some(SIGNATURE_PAGE_SIZE),
+                            commitment: Some(commitment),
+                        },
+                    )
+                })
+                .await?;
+            let last_page = page.len() < SIGNATURE_PAGE_SIZE;
+            before = match page.last() {
+                Some(status) => Some(Signature::from_str(&status.signature)
+                    .map_err(|e| BotError::Unknown(format!("Bad signature {}: {}", status.signature, e)))?),
+                None => None,
+            };
+            signatures.extend(page.into_iter().filter(|status| status.err.is_none()).map(|status| status.signature));
+            if last_page {
+                break;
+            }
+        }
+
+        info!("Replaying {} vault transactions", signatures.len());
+
+        for signature in signatures.into_iter().rev() {
+            if self.seen.contains(&signature) {
+                continue;
+            }
+            let parsed = Signature::from_str(&signature)
+                .map_err(|e| BotError::Unknown(format!("Bad signature {}: {}", signature, e)))?;
+            let transaction = rpc
+                .call_async(|client| {
+                    client.get_transaction_with_config(
+                        &parsed,
+                        RpcTransactionConfig {
+                            encoding: None,
+                            commitment: Some(commitment),
+                            max_supported_transaction_version: Some(0),
+                        },
+                    )
+                })
+                .await?;
+            let logs: Option<Vec<String>> = transaction.transaction.meta
+                .and_then(|meta| meta.log_messages.into());
+            self.apply(&signature, &logs.unwrap_or_default()).await;
+        }
+
+        Ok(())
+    }
+
+    /// Subscribe to the program's logs, replay its history, then apply live
+    /// transactions until the subscription drops
+    pub async fn run(mut self, rpc: &FailoverRpc, ws_url: &str) -> Result<()> {
+        let pubsub = PubsubClient::new(ws_url)
+            .await
+            .map_err(|e| BotError::WebSocket(e.to_string()))?;
+        // Subscribe first so nothing lands between the backfill and the stream
+        let (mut logs, _unsubscribe) = pubsub
+            .logs_subscribe(
+                RpcTransactionLogsFilter::Mentions(vec![self.program_id.to_string()]),
+                RpcTransactionLogsConfig { commitment: Some(self.commitment) },
+            )
+            .await
+            .map_err(|e| BotError::WebSocket(e.to_string()))?;
+
+        self.backfill(rpc).await?;
+        info!("🔁 Rebuilt API state from vault history; following live events");
+
+        while let Some(response) = logs.next().await {
+            let transaction = response.value;
+            if transaction.err.is_some() {
+                continue;
+            }
+            self.apply(&transaction.signature, &transaction.logs).await;
+        }
+
+        warn!("Vault log subscription closed");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::testing::{mock_version_async, rpc_result};
+    use mockito::Matcher;
+
+    fn event_log(name: &str, fields: &[&[u8]]) -> String {
+        let mut data = event_discriminator(name).to_vec();
+        for field in fields {
+            data.extend_from_slice(field);
+        }
+        format!("{}{}", EVENT_LOG_PREFIX, base64::engine::general_purpose::STANDARD.encode(data))
+    }
+
+    fn delegation_created(user: &Pubkey, vault_index: u8) -> String {
+        event_log("DelegationCreated", &[
+            user.as_ref(),
+            Pubkey::new_unique().as_ref(),
+            &[vault_index, 3], // strategy
+            &2_000_000_000u64.to_le_bytes(),
+            &[4],
+            &1_700_000_000i64.to_le_bytes(),
+        ])
+    }
+
+    fn position_opened(user: &Pubkey, vault_index: u8, position_id: u64) -> String {
+        event_log("PositionOpened", &[
+            user.as_ref(),
+            &[vault_index],
+            &position_id.to_le_bytes(),
+            &(position_id + 100).to_le_bytes(),
+            Pubkey::new_unique().as_ref(),
+            &500_000_000u64.to_le_bytes(),
+            &1_000u64.to_le_bytes(),
+            &2_000u64.to_le_bytes(),
+            &500u64.to_le_bytes(),
+            &[3],
+            &8_000u16.to_le_bytes(),
+            &1_700_000_100i64.to_le_bytes(),
+        ])
+    }
+
+    fn position_closed(user: &Pubkey, vault_index: u8, position_id: u64, exit_price: u64, pnl: i64, pnl_bps: i32) -> String {
+        event_log("PositionClosed", &[
+            user.as_ref(),
+            &[vault_index],
+            &position_id.to_le_bytes(),
+            Pubkey::new_unique().as_ref(),
+            &1_000u64.to_le_bytes(),
+            &exit_price.to_le_bytes(),
+            &pnl.to_le_bytes(),
+            &pnl_bps.to_le_bytes(),
+            &1_700_000_200i64.to_le_bytes(),
+        ])
+    }
+
+    fn position_liquidated(user: &Pubkey, vault_index: u8, position_id: u64) -> String {
+        event_log("PositionLiquidated", &[
+            user.as_ref(),
+            &[vault_index],
+            &position_id.to_le_bytes(),
+            Pubkey::new_unique().as_ref(),
+            &1_700_000_300i64.to_le_bytes(),
+        ])
+    }
+
+    fn emergency_withdrawn(user: &Pubkey, vault_index: u8, liquidated: u8, remaining: u8) -> String {
+        event_log("EmergencyWithdrawn", &[
+            user.as_ref(),
+            &[vault_index, liquidated, remaining],
+            &1_700_000_300i64.to_le_bytes(),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_rebuilds_state_from_event_stream() {
+        let state = ApiState::new();
+        let mut rebuilder = StateRebuilder::new(state.clone(), Pubkey::new_unique(), CommitmentConfig::confirmed());
+        let user = Pubkey::new_unique();
+
+        let stream = vec![
+            ("sig-1", vec!["Program log: Instruction: CreateDelegation".to_string(), delegation_created(&user, 0)]),
+            ("sig-2", vec![position_opened(&user, 0, 0)]),
+            ("sig-3", vec![position_opened(&user, 0, 1)]),
+            ("sig-4", vec![position_closed(&user, 0, 0, 1_500, 250_000_000, 5_000)]),
+            // Overlap between history and the live subscription
+            ("sig-4", vec![position_closed(&user, 0, 0, 1_500, 250_000_000, 5_000)]),
+            ("sig-5", vec![position_closed(&user, 0, 1, 800, -100_000_000, -2_000)]),
+            ("sig-6", vec![position_opened(&user, 0, 2)]),
+        ];
+        for (signature, logs) in &stream {
+            rebuilder.apply(signature, logs).await;
+        }
+
+        let delegations = state.delegations.read().await;
+        assert_eq!(delegations.len(), 1);
+        let delegation = &delegations[0];
+        assert_eq!(delegation.user, user.to_string());
+        assert_eq!(delegation.strategy, StrategyType::GraduationAnticipator);
+        assert_eq!(delegation.max_position_size_sol, 2.0);
+        assert_eq!(delegation.active_trades, 1);
+        assert_eq!(delegation.total_trades, 3);
+        assert_eq!(delegation.profitable_trades, 1);
+        assert_eq!(delegation.total_pnl, 150_000_000);
+
+        let positions = state.positions.read().await;
+        assert_eq!(positions.len(), 3);
+        let closed: Vec<_> = positions.iter().filter(|p| p.closed_at.is_some()).collect();
+        assert_eq!(closed.len(), 2);
+        assert_eq!(closed[0].position_id, api_position_id(&user, 0, 0));
+        assert_eq!(closed[0].current_price, 1_500);
+        assert_eq!(closed[0].pnl_percentage, 50.0);
+        assert_eq!(positions[2].status, "open");
+        assert_eq!(positions[2].amount_sol, 0.5);
+
+        let stats = state.stats.read().await;
+        assert_eq!(stats.closed_trades, 2);
+        assert_eq!(stats.winning_trades, 1);
+        assert_eq!(stats.realized_pnl_lamports, 150_000_000);
+        assert_eq!(stats.win_rate, 0.5);
+    }
+
+    #[tokio::test]
+    async fn test_keeps_each_vault_of_a_user_apart() {
+        let state = ApiState::new();
+        let mut rebuilder = StateRebuilder::new(state.clone(), Pubkey::new_unique(), CommitmentConfig::confirmed());
+        let user = Pubkey::new_unique();
+
+        let stream = vec![
+            ("sig-1", vec![delegation_created(&user, 0)]),
+            ("sig-2", vec![delegation_created(&user, 1)]),
+            // Both vaults number their positions from zero
+            ("sig-3", vec![position_opened(&user, 0, 0)]),
+            ("sig-4", vec![position_opened(&user, 1, 0)]),
+            ("sig-5", vec![position_opened(&user, 1, 1)]),
+            ("sig-6", vec![position_closed(&user, 1, 1, 1_500, 250_000_000, 5_000)]),
+            // The user pulls vault 1's remaining position
+            ("sig-7", vec![position_liquidated(&user, 1, 0), emergency_withdrawn(&user, 1, 1, 0)]),
+        ];
+        for (signature, logs) in &stream {
+            rebuilder.apply(signature, logs).await;
+        }
+
+        let delegations = state.delegations.read().await;
+        assert_eq!(delegations.len(), 2);
+        let vault = |index: u8| delegations.iter().find(|d| d.vault_index == index).unwrap();
+        assert!(vault(0).is_active);
+        assert_eq!((vault(0).active_trades, vault(0).total_trades, vault(0).total_pnl), (1, 1, 0));
+        assert!(!vault(1).is_active);
+        assert_eq!((vault(1).active_trades, vault(1).total_trades, vault(1).total_pnl), (0, 2, 250_000_000));
+
+        let positions = state.positions.read().await;
+        let status = |vault_index: u8, position_id: u64| {
+            let id = api_position_id(&user, vault_index, position_id);
+            positions.iter().find(|p| p.position_id == id).unwrap().status.clone()
+        };
+        assert_eq!(status(0, 0), "open");
+        assert_eq!(status(1, 0), "liquidated");
+        assert_eq!(status(1, 1), "closed");
+        assert_eq!(positions.iter().filter(|p| p.closed_at.is_none()).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_fails_over_at_history_commitment() {
+        let mut server = mockito::Server::new_async().await;
+        let _version = mock_version_async(&mut server).await;
+        // Processed isn't served for history, so the backfill asks at confirmed
+        let signatures = server.mock("POST", "/")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::PartialJsonString(r#"{"method":"getSignaturesForAddress"}"#.to_string()),
+                Matcher::Regex("confirmed".to_string()),
+            ]))
+            .with_body(rpc_result(serde_json::json!([])))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let rpc = FailoverRpc::new(&["http://127.0.0.1:1".to_string(), server.url()], CommitmentConfig::processed());
+        let mut rebuilder = StateRebuilder::new(ApiState::new(), Pubkey::new_unique(), CommitmentConfig::processed());
+        rebuilder.backfill(&rpc).await.unwrap();
+
+        signatures.assert_async().await;
+    }
+
+    #[test]
+    fn test_unknown_events_are_skipped() {
+        let logs = vec![
+            event_log("EmergencyPaused", &[Pubkey::new_unique().as_ref(), &0i64.to_le_bytes()]),
+            "Program data: not base64!".to_string(),
+            // Truncated payload
+            event_log("PositionClosed", &[Pubkey::new_unique().as_ref()]),
+        ];
+
+        assert!(VaultEvent::from_logs(&logs).is_empty());
+    }
+}
@@ -0,0 +1,115 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Halts new positions once realized losses draw equity down too far from its peak
+///
+/// The halt flag can be shared (see `with_halt_flag`) so an operator can clear it
+/// from the API; the peak is then re-based to the equity at that point.
+#[derive(Debug)]
+pub struct RiskManager {
+    max_drawdown_pct: f64,
+    peak_equity: f64,
+    equity: f64,
+    halted: Arc<AtomicBool>,
+    /// Whether this manager raised `halted`, so a cleared flag reads as a manual reset
+    tripped: bool,
+}
+
+impl RiskManager {
+    pub fn new(starting_equity: f64, max_drawdown_pct: f64) -> Self {
+        Self {
+            max_drawdown_pct,
+            peak_equity: starting_equity,
+            equity: starting_equity,
+            halted: Arc::new(AtomicBool::new(false)),
+            tripped: false,
+        }
+    }
+
+    /// Raise and read `flag` instead of a private one
+    pub fn with_halt_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        flag.store(self.tripped, Ordering::SeqCst);
+        self.halted = flag;
+        self
+    }
+
+    /// Apply a closed position's realized PnL, halting once drawdown passes the limit
+    pub fn record_close(&mut self, pnl: f64) {
+        self.sync_reset();
+
+        self.equity += pnl;
+        self.peak_equity = self.peak_equity.max(self.equity);
+
+        let drawdown = self.drawdown();
+        if !self.tripped && drawdown > self.max_drawdown_pct {
+            warn!(
+                "🚨 Drawdown {:.1}% exceeds max {:.1}% - halting new positions",
+                drawdown * 100.0,
+                self.max_drawdown_pct * 100.0
+            );
+            self.halted.store(true, Ordering::SeqCst);
+            self.tripped = true;
+        }
+    }
+
+    /// Fraction of peak equity lost since the peak
+    pub fn drawdown(&self) -> f64 {
+        if self.peak_equity <= 0.0 {
+            return 0.0;
+        }
+        ((self.peak_equity - self.equity) / self.peak_equity).max(0.0)
+    }
+
+    pub fn equity(&self) -> f64 {
+        self.equity
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted.load(Ordering::SeqCst)
+    }
+
+    /// Re-base the peak after the halt flag was cleared from outside
+    fn sync_reset(&mut self) {
+        if self.tripped && !self.is_halted() {
+            info!("▶️ Trading resumed at {:.4} SOL equity", self.equity);
+            self.peak_equity = self.equity;
+            self.tripped = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_halts_past_max_drawdown() {
+        let mut risk = RiskManager::new(10.0, 0.30);
+
+        risk.record_close(2.0);
+        risk.record_close(-3.0);
+        assert!(!risk.is_halted());
+        assert!((risk.drawdown() - 0.25).abs() < 1e-9);
+
+        risk.record_close(-1.0);
+        assert!(risk.is_halted());
+        assert!((risk.equity() - 8.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cleared_flag_rebases_peak() {
+        let flag = Arc::new(AtomicBool::new(false));
+        let mut risk = RiskManager::new(10.0, 0.30).with_halt_flag(flag.clone());
+
+        risk.record_close(-4.0);
+        assert!(flag.load(Ordering::SeqCst));
+
+        flag.store(false, Ordering::SeqCst);
+        risk.record_close(-1.0);
+
+        // 1 SOL off the re-based 6 SOL peak is under the limit
+        assert!(!risk.is_halted());
+        assert!((risk.drawdown() - 1.0 / 6.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,244 @@
+use solana_client::client_error::{ClientError, ClientErrorKind, Result as ClientResult};
+use solana_client::nonblocking::rpc_client::RpcClient as AsyncRpcClient;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_request::RpcError;
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Consecutive failures after which an endpoint is sidelined
+const SIDELINE_AFTER_FAILURES: u32 = 3;
+
+/// How long a sidelined endpoint is skipped while others are available
+const SIDELINE_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Sync and async clients for one RPC URL
+struct Endpoint {
+    url: String,
+    client: RpcClient,
+    async_client: AsyncRpcClient,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct EndpointHealth {
+    /// Every transport failure seen on the endpoint
+    failures: u64,
+    consecutive_failures: u32,
+    sidelined_until: Option<Instant>,
+}
+
+#[derive(Debug)]
+struct FailoverState {
+    /// Endpoint tried first: the last one that answered
+    current: usize,
+    health: Vec<EndpointHealth>,
+}
+
+/// RPC clients for an ordered list of endpoints
+///
+/// Calls go to the endpoint that last answered and move on to the next one when
+/// the request fails to reach it. An endpoint that keeps failing is sidelined for
+/// `SIDELINE_COOLDOWN`, and only retried early when every other endpoint is down.
+/// Errors the RPC itself returns, such as a failed simulation, are not failovers.
+pub struct FailoverRpc {
+    endpoints: Vec<Endpoint>,
+    state: Mutex<FailoverState>,
+}
+
+/// Whether `err` means the endpoint couldn't be reached, rather than that it answered with an error
+///
+/// Calls that take a commitment first query the node's version, and the client reports a
+/// failure there as an RPC error wrapping the transport one.
+fn is_endpoint_failure(err: &ClientError) -> bool {
+    match err.kind() {
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => true,
+        ClientErrorKind::RpcError(RpcError::RpcRequestError(message)) => {
+            message.starts_with("cluster version query failed")
+        }
+        _ => false,
+    }
+}
+
+impl FailoverRpc {
+    /// Clients for `urls`, tried in the given order; `urls` must not be empty
+    pub fn new(urls: &[String], commitment: CommitmentConfig) -> Self {
+        assert!(!urls.is_empty(), "FailoverRpc needs at least one RPC URL");
+
+        let endpoints = urls.iter()
+            .map(|url| Endpoint {
+                url: url.clone(),
+                client: RpcClient::new_with_commitment(url.clone(), commitment),
+                async_client: AsyncRpcClient::new_with_commitment(url.clone(), commitment),
+            })
+            .collect();
+
+        Self {
+            endpoints,
+            state: Mutex::new(FailoverState {
+                current: 0,
+                health: vec![EndpointHealth::default(); urls.len()],
+            }),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn commitment(&self) -> CommitmentConfig {
+        self.endpoints[0].client.commitment()
+    }
+
+    /// Run `request` against each endpoint in turn until one answers
+    pub fn call<T>(&self, request: impl Fn(&RpcClient) -> ClientResult<T>) -> ClientResult<T> {
+        let mut last_err = None;
+        for index in self.attempt_order() {
+            match request(&self.endpoints[index].client) {
+                Err(e) if is_endpoint_failure(&e) => {
+                    self.record_failure(index, &e);
+                    last_err = Some(e);
+                }
+                result => {
+                    self.record_success(index);
+                    return result;
+                }
+            }
+        }
+
+        Err(last_err.expect("attempt order covers at least one endpoint"))
+    }
+
+    /// `call` for the nonblocking client
+    pub async fn call_async<'a, T, F, Fut>(&'a self, request: F) -> ClientResult<T>
+    where
+        F: Fn(&'a AsyncRpcClient) -> Fut,
+        Fut: Future<Output = ClientResult<T>>,
+    {
+        let mut last_err = None;
+        for index in self.attempt_order() {
+            match request(&self.endpoints[index].async_client).await {
+                Err(e) if is_endpoint_failure(&e) => {
+                    self.record_failure(index, &e);
+                    last_err = Some(e);
+                }
+                result => {
+                    self.record_success(index);
+                    return result;
+                }
+            }
+        }
+
+        Err(last_err.expect("attempt order covers at least one endpoint"))
+    }
+
+    /// Endpoints from `current` onwards, wrapping around, with sidelined ones moved to the end
+    fn attempt_order(&self) -> Vec<usize> {
+        let state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let count = self.endpoints.len();
+
+        let (mut healthy, sidelined): (Vec<usize>, Vec<usize>) = (0..count)
+            .map(|offset| (state.current + offset) % count)
+            .partition(|&index| {
+                !matches!(state.health[index].sidelined_until, Some(until) if until > now)
+            });
+        healthy.extend(sidelined);
+        healthy
+    }
+
+    fn record_success(&self, index: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.current = index;
+        let health = &mut state.health[index];
+        health.consecutive_failures = 0;
+        health.sidelined_until = None;
+    }
+
+    fn record_failure(&self, index: usize, err: &ClientError) {
+        let mut state = self.state.lock().unwrap();
+        let health = &mut state.health[index];
+        health.failures += 1;
+        health.consecutive_failures += 1;
+
+        let url = &self.endpoints[index].url;
+        if health.consecutive_failures >= SIDELINE_AFTER_FAILURES {
+            health.sidelined_until = Some(Instant::now() + SIDELINE_COOLDOWN);
+            warn!(
+                "🔌 RPC {} failed {} times in a row, sidelining for {}s: {}",
+                url, health.consecutive_failures, SIDELINE_COOLDOWN.as_secs(), err
+            );
+        } else {
+            warn!("🔌 RPC {} failed, trying the next endpoint: {}", url, err);
+        }
+    }
+}
+
+/// Mocks shared by tests that point an RPC client at a mockito server
+#[cfg(test)]
+pub mod testing {
+    use mockito::{Matcher, Mock, Server};
+
+    /// A JSON-RPC response body carrying `result`
+    pub fn rpc_result(result: serde_json::Value) -> String {
+        serde_json::json!({ "jsonrpc": "2.0", "result": result, "id": 1 }).to_string()
+    }
+
+    fn version_result() -> String {
+        rpc_result(serde_json::json!({ "solana-core": "1.16.14", "feature-set": 0 }))
+    }
+
+    /// Answer `getVersion`, which the client queries before any call that takes a commitment
+    pub fn mock_version(server: &mut Server) -> Mock {
+        server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getVersion"}"#.to_string()))
+            .with_body(version_result())
+            .create()
+    }
+
+    /// `mock_version` for async tests
+    pub async fn mock_version_async(server: &mut Server) -> Mock {
+        server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getVersion"}"#.to_string()))
+            .with_body(version_result())
+            .create_async()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::{mock_version, rpc_result};
+    use super::*;
+    use mockito::Matcher;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn test_calls_fail_over_to_healthy_endpoint() {
+        let mut down = mockito::Server::new();
+        let mut healthy = mockito::Server::new();
+
+        let failing = down.mock("POST", "/")
+            .with_status(503)
+            .expect(1)
+            .create();
+        let _version = mock_version(&mut healthy);
+        let balance = healthy.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getBalance"}"#.to_string()))
+            .with_body(rpc_result(serde_json::json!({ "context": { "slot": 1 }, "value": 42 })))
+            .expect(2)
+            .create();
+
+        let rpc = FailoverRpc::new(&[down.url(), healthy.url()], CommitmentConfig::confirmed());
+        let owner = Pubkey::new_unique();
+
+        assert_eq!(rpc.call(|client| client.get_balance(&owner)).unwrap(), 42);
+        // The healthy endpoint is now tried first
+        assert_eq!(rpc.call(|client| client.get_balance(&owner)).unwrap(), 42);
+
+        failing.assert();
+        balance.assert();
+        let state = rpc.state.lock().unwrap();
+        assert_eq!(state.current, 1);
+        assert_eq!(state.health[0].failures, 1);
+        assert_eq!(state.health[1].failures, 0);
+    }
+}
@@ -0,0 +1,193 @@
+use crate::error::{BotError, Result};
+use crate::pump_fun;
+use crate::types::{BotConfig, RugFlag, RugRisk};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{program_pack::Pack, pubkey::Pubkey};
+use spl_token::state::Mint;
+use tracing::debug;
+
+/// Score contribution of each flag; the total is capped at 1.0
+const MINT_AUTHORITY_RISK: f64 = 0.5;
+const FREEZE_AUTHORITY_RISK: f64 = 0.4;
+const WHALE_HOLDER_RISK: f64 = 0.3;
+
+/// On-chain rug-vector checks: mint/freeze authority and single-wallet concentration
+pub struct RugCheck {
+    rpc_client: RpcClient,
+    pump_fun_program: Pubkey,
+    /// Largest share of supply (0-1) one non-curve account may hold
+    max_holder_share: f64,
+}
+
+impl RugCheck {
+    pub fn new(config: &BotConfig) -> Self {
+        Self {
+            rpc_client: RpcClient::new_with_commitment(config.rpc_url.clone(), config.commitment.config()),
+            pump_fun_program: config.pump_fun_program,
+            max_holder_share: config.max_single_holder_pct,
+        }
+    }
+
+    pub fn assess(&self, mint: &Pubkey) -> Result<RugRisk> {
+        let account = self.rpc_client
+            .get_account_with_commitment(mint, self.rpc_client.commitment())?
+            .value
+            .ok_or_else(|| BotError::TokenNotFound(mint.to_string()))?;
+
+        let mint_state = Mint::unpack(&account.data)
+            .map_err(|e| BotError::Analysis(format!("Invalid mint account {}: {}", mint, e)))?;
+
+        let mut flags = Vec::new();
+        if mint_state.mint_authority.is_some() {
+            flags.push(RugFlag::MintAuthority);
+        }
+        if mint_state.freeze_authority.is_some() {
+            flags.push(RugFlag::FreezeAuthority);
+        }
+        if let Some(whale) = self.find_whale(mint, mint_state.supply)? {
+            flags.push(whale);
+        }
+
+        let score = flags.iter()
+            .map(|flag| match flag {
+                RugFlag::MintAuthority => MINT_AUTHORITY_RISK,
+                RugFlag::FreezeAuthority => FREEZE_AUTHORITY_RISK,
+                RugFlag::WhaleHolder { .. } => WHALE_HOLDER_RISK,
+            })
+            .sum::<f64>()
+            .min(1.0);
+
+        debug!("Rug check for {}: score={:.2} flags={:?}", mint, score, flags);
+        Ok(RugRisk { score, flags })
+    }
+
+    /// Largest token account other than the bonding curve's, if it exceeds the allowed share
+    fn find_whale(&self, mint: &Pubkey, supply: u64) -> Result<Option<RugFlag>> {
+        if supply == 0 {
+            return Ok(None);
+        }
+
+        let bonding_curve = pump_fun::derive_bonding_curve(&self.pump_fun_program, mint);
+        let curve_account = pump_fun::derive_associated_bonding_curve(&bonding_curve, mint).to_string();
+
+        let largest = self.rpc_client.get_token_largest_accounts(mint)?;
+        let whale = largest
+            .into_iter()
+            .filter(|account| account.address != curve_account)
+            .filter_map(|account| {
+                let amount: u64 = account.amount.amount.parse().ok()?;
+                Some((account.address, amount as f64 / supply as f64))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        Ok(whale
+            .filter(|(_, share)| *share > self.max_holder_share)
+            .map(|(account, share)| RugFlag::WhaleHolder { account, share }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::testing::{mock_version, rpc_result};
+    use base64::Engine;
+    use mockito::Matcher;
+    use solana_sdk::program_option::COption;
+
+    const SUPPLY: u64 = 1_000_000_000_000_000;
+
+    fn mint_account(mint_authority: Option<Pubkey>, freeze_authority: Option<Pubkey>) -> serde_json::Value {
+        let mut data = vec![0u8; Mint::LEN];
+        Mint {
+            mint_authority: mint_authority.map_or(COption::None, COption::Some),
+            supply: SUPPLY,
+            decimals: pump_fun::PUMP_FUN_TOKEN_DECIMALS,
+            is_initialized: true,
+            freeze_authority: freeze_authority.map_or(COption::None, COption::Some),
+        }
+        .pack_into_slice(&mut data);
+
+        serde_json::json!({
+            "context": { "slot": 1 },
+            "value": {
+                "data": [base64::engine::general_purpose::STANDARD.encode(&data), "base64"],
+                "executable": false,
+                "lamports": 1_461_600,
+                "owner": spl_token::id().to_string(),
+                "rentEpoch": 0
+            }
+        })
+    }
+
+    fn largest_accounts(accounts: &[(String, u64)]) -> serde_json::Value {
+        let value: Vec<serde_json::Value> = accounts
+            .iter()
+            .map(|(address, amount)| serde_json::json!({
+                "address": address,
+                "amount": amount.to_string(),
+                "decimals": 6,
+                "uiAmount": *amount as f64 / 1e6,
+                "uiAmountString": (*amount as f64 / 1e6).to_string()
+            }))
+            .collect();
+        serde_json::json!({ "context": { "slot": 1 }, "value": value })
+    }
+
+    fn mock_rpc(server: &mut mockito::Server, mint: serde_json::Value, holders: serde_json::Value) -> Vec<mockito::Mock> {
+        vec![
+            mock_version(server),
+            server.mock("POST", "/")
+                .match_body(Matcher::PartialJsonString(r#"{"method":"getAccountInfo"}"#.to_string()))
+                .with_body(rpc_result(mint))
+                .create(),
+            server.mock("POST", "/")
+                .match_body(Matcher::PartialJsonString(r#"{"method":"getTokenLargestAccounts"}"#.to_string()))
+                .with_body(rpc_result(holders))
+                .create(),
+        ]
+    }
+
+    #[test]
+    fn test_renounced_mint_passes() {
+        let mut server = mockito::Server::new();
+        let config = BotConfig::for_tests(&server.url());
+        let mint = Pubkey::new_unique();
+
+        // The curve's own account holds most of the supply; that is expected
+        let curve = pump_fun::derive_bonding_curve(&config.pump_fun_program, &mint);
+        let curve_account = pump_fun::derive_associated_bonding_curve(&curve, &mint).to_string();
+        let _mocks = mock_rpc(
+            &mut server,
+            mint_account(None, None),
+            largest_accounts(&[(curve_account, SUPPLY * 8 / 10), (Pubkey::new_unique().to_string(), SUPPLY / 20)]),
+        );
+
+        let risk = RugCheck::new(&config).assess(&mint).unwrap();
+
+        assert!(risk.flags.is_empty());
+        assert_eq!(risk.score, 0.0);
+        assert!(!risk.has_critical());
+    }
+
+    #[test]
+    fn test_retained_authorities_are_critical() {
+        let mut server = mockito::Server::new();
+        let config = BotConfig::for_tests(&server.url());
+        let whale = Pubkey::new_unique().to_string();
+        let _mocks = mock_rpc(
+            &mut server,
+            mint_account(Some(Pubkey::new_unique()), Some(Pubkey::new_unique())),
+            largest_accounts(&[(whale.clone(), SUPPLY / 2)]),
+        );
+
+        let risk = RugCheck::new(&config).assess(&Pubkey::new_unique()).unwrap();
+
+        assert!(risk.has_critical());
+        assert_eq!(risk.flags, vec![
+            RugFlag::MintAuthority,
+            RugFlag::FreezeAuthority,
+            RugFlag::WhaleHolder { account: whale, share: 0.5 },
+        ]);
+        assert_eq!(risk.score, 1.0);
+    }
+}
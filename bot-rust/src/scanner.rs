@@ -1,9 +1,33 @@
-use crate::types::{TokenMetrics, BotConfig};
+use crate::types::{TokenMetrics, BotConfig, MetricsSourceType, ScanMode};
 use crate::error::{Result, BotError};
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use crate::birdeye::BirdeyeSource;
+use crate::ratelimit::RateLimiter;
+use crate::dexscreener::DexScreenerSource;
+use async_trait::async_trait;
+use futures::channel::mpsc::{self, UnboundedSender};
+use futures::{SinkExt, Stream, StreamExt};
+use reqwest::{Client, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use tracing::{info, warn, debug, error};
-use std::time::Duration;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Pluggable provider of per-token metrics
+#[async_trait]
+pub trait MetricsSource: Send + Sync {
+    async fn get_metrics(&self, mint: &str) -> Result<TokenMetrics>;
+}
+
+/// Factory function to create the configured metrics source
+pub fn create_metrics_source(config: &BotConfig) -> Box<dyn MetricsSource> {
+    match config.metrics_source {
+        MetricsSourceType::PumpFun => Box::new(PumpFunScanner::new(config)),
+        MetricsSourceType::DexScreener => Box::new(DexScreenerSource::new(config)),
+        MetricsSourceType::Birdeye => Box::new(BirdeyeSource::new(config)),
+    }
+}
 
 #[derive(Debug, Deserialize)]
 struct PumpFunToken {
@@ -21,6 +45,25 @@ struct PumpFunToken {
     associated_bonding_curve: Option<String>,
     #[serde(default)]
     creator: Option<String>,
+    /// Creation time in unix milliseconds
+    #[serde(default)]
+    created_timestamp: Option<i64>,
+    /// Bonding curve has completed and liquidity migrated
+    #[serde(default)]
+    complete: bool,
+    #[serde(default)]
+    raydium_pool: Option<String>,
+    /// Bonding curve virtual reserves in lamports and raw token units
+    #[serde(default)]
+    virtual_sol_reserves: u64,
+    #[serde(default)]
+    virtual_token_reserves: u64,
+}
+
+/// Token creation event pushed by the new-token websocket
+#[derive(Debug, Deserialize)]
+struct NewTokenEvent {
+    mint: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -29,10 +72,88 @@ struct PumpFunResponse {
     tokens: Vec<PumpFunToken>,
 }
 
+/// Base delay for the first retry; doubled on each subsequent attempt
+const RETRY_BASE_DELAY_MS: u64 = 200;
+
+/// Upper bound on the websocket reconnect delay
+const MAX_RECONNECT_DELAY_MS: u64 = 10_000;
+
+/// Per-trade return standard deviation treated as maximally volatile (score 1.0)
+const MAX_VOLATILITY_STDDEV: f64 = 0.10;
+
+/// Addresses whose share of 5m volume the wash-trade score weighs
+const WASH_TRADE_TOP_ADDRESSES: usize = 3;
+
+/// Holders requested per page from the holders endpoint
+const HOLDER_PAGE_SIZE: usize = 100;
+
+/// Maximum number of tokens kept in the metrics cache
+const METRICS_CACHE_CAPACITY: usize = 256;
+
+struct CacheEntry {
+    fetched_at: Instant,
+    last_used: Instant,
+    metrics: TokenMetrics,
+}
+
+/// TTL cache for token metrics with least-recently-used eviction
+struct MetricsCache {
+    entries: HashMap<String, CacheEntry>,
+    ttl: Duration,
+    capacity: usize,
+}
+
+impl MetricsCache {
+    fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            ttl,
+            capacity,
+        }
+    }
+
+    /// Return a copy of the cached metrics if still fresh
+    fn get(&mut self, mint: &str) -> Option<TokenMetrics> {
+        let now = Instant::now();
+        let entry = self.entries.get_mut(mint)?;
+
+        if now.duration_since(entry.fetched_at) > self.ttl {
+            self.entries.remove(mint);
+            return None;
+        }
+
+        entry.last_used = now;
+        Some(entry.metrics.clone())
+    }
+
+    fn insert(&mut self, mint: String, metrics: TokenMetrics) {
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&mint) {
+            let oldest = self.entries.iter()
+                .min_by_key(|(_, e)| e.last_used)
+                .map(|(k, _)| k.clone());
+            if let Some(key) = oldest {
+                self.entries.remove(&key);
+            }
+        }
+
+        let now = Instant::now();
+        self.entries.insert(mint, CacheEntry {
+            fetched_at: now,
+            last_used: now,
+            metrics,
+        });
+    }
+}
+
 pub struct PumpFunScanner {
     client: Client,
     api_url: String,
+    ws_url: String,
     dry_run: bool,
+    max_retries: u32,
+    holder_max_pages: usize,
+    metrics_cache: Mutex<MetricsCache>,
+    rate_limiter: RateLimiter,
 }
 
 impl PumpFunScanner {
@@ -45,7 +166,57 @@ impl PumpFunScanner {
         Self {
             client,
             api_url: config.pump_fun_api_url.clone(),
+            ws_url: config.pump_fun_ws_url.clone(),
             dry_run: config.dry_run,
+            max_retries: config.http_max_retries,
+            holder_max_pages: config.holder_max_pages,
+            metrics_cache: Mutex::new(MetricsCache::new(
+                Duration::from_millis(config.metrics_cache_ttl_ms),
+                METRICS_CACHE_CAPACITY,
+            )),
+            rate_limiter: RateLimiter::new(config.api_requests_per_second, config.api_burst),
+        }
+    }
+
+    /// GET `url` and decode JSON, retrying transient failures with exponential backoff
+    ///
+    /// 429, 5xx, timeouts and connection errors are retried; any other 4xx is terminal.
+    /// Every attempt, retries included, waits for a rate limiter permit.
+    async fn fetch_json_with_retry<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        let mut attempt = 0;
+
+        loop {
+            self.rate_limiter.acquire().await;
+            let error = match self.client.get(url).send().await {
+                Ok(response) => {
+                    let status = response.status();
+                    if status.is_success() {
+                        let body = response.text().await?;
+                        return Ok(serde_json::from_str(&body)?);
+                    }
+
+                    let retryable = status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+                    let error = response.error_for_status().unwrap_err();
+                    if !retryable {
+                        return Err(error.into());
+                    }
+                    error
+                }
+                Err(e) if e.is_timeout() || e.is_connect() => e,
+                Err(e) => return Err(e.into()),
+            };
+
+            if attempt >= self.max_retries {
+                error!("Giving up on {} after {} retries: {}", url, attempt, error);
+                return Err(error.into());
+            }
+
+            let backoff = RETRY_BASE_DELAY_MS * 2u64.pow(attempt);
+            let jitter = rand::random::<u64>() % (RETRY_BASE_DELAY_MS / 2);
+            warn!("Request to {} failed ({}), retrying in {}ms", url, error, backoff + jitter);
+
+            tokio::time::sleep(Duration::from_millis(backoff + jitter)).await;
+            attempt += 1;
         }
     }
 
@@ -67,6 +238,7 @@ impl PumpFunScanner {
             mint: mint.to_string(),
             name: format!("Mock Token {}", &mint[..8]),
             symbol: format!("MOCK{}", &mint[..4]),
+            creator: None,
             volume_5m: rng.gen_range(1.0..50.0),
             volume_1h: rng.gen_range(10.0..200.0),
             volume_24h: rng.gen_range(100.0..1000.0),
@@ -75,6 +247,8 @@ impl PumpFunScanner {
             price_change_1h: rng.gen_range(-20.0..50.0),
             liquidity_sol: rng.gen_range(5.0..50.0),
             liquidity_usd: rng.gen_range(500.0..5000.0),
+            virtual_sol_reserves: rng.gen_range(30.0..85.0),
+            virtual_token_reserves: rng.gen_range(280_000_000.0..1_073_000_000.0),
             holder_count: rng.gen_range(20..200),
             holder_concentration: rng.gen_range(0.1..0.5),
             unique_buyers_5m: rng.gen_range(5..50),
@@ -87,7 +261,11 @@ impl PumpFunScanner {
             time_since_creation: rng.gen_range(60..3600),
             buy_pressure: rng.gen_range(0.5..2.0),
             sell_pressure: rng.gen_range(0.3..1.5),
+            buy_count_5m: rng.gen_range(5..100),
+            sell_count_5m: rng.gen_range(2..50),
             volatility_score: rng.gen_range(0.1..0.8),
+            wash_trade_score: 0.0,
+            rug_risk: None,
         }
     }
 
@@ -104,12 +282,7 @@ impl PumpFunScanner {
 
         debug!("Scanning pump.fun for new tokens...");
 
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?
-            .json::<PumpFunResponse>()
-            .await?;
+        let response: PumpFunResponse = self.fetch_json_with_retry(&url).await?;
 
         let mints: Vec<String> = response.tokens.iter().map(|t| t.mint.clone()).collect();
 
@@ -117,6 +290,48 @@ impl PumpFunScanner {
         Ok(mints)
     }
 
+    /// Stream mint addresses as tokens are created, reconnecting whenever the socket drops
+    ///
+    /// The background task exits once the returned stream is dropped.
+    pub fn subscribe_new_tokens(&self) -> impl Stream<Item = String> {
+        let (tx, rx) = mpsc::unbounded();
+
+        if self.dry_run {
+            debug!("[DRY RUN] Streaming mock tokens");
+            for mint in self.generate_mock_tokens() {
+                let _ = tx.unbounded_send(mint);
+            }
+            return rx;
+        }
+
+        let ws_url = self.ws_url.clone();
+        tokio::spawn(async move {
+            let mut attempt = 0u32;
+
+            while !tx.is_closed() {
+                match stream_new_tokens(&ws_url, &tx).await {
+                    // Only back off further while connections keep failing to deliver
+                    Ok(delivered) if delivered > 0 => attempt = 0,
+                    Ok(_) => debug!("New token stream closed without events"),
+                    Err(e) => warn!("New token stream error: {}", e),
+                }
+
+                if tx.is_closed() {
+                    break;
+                }
+
+                let delay = (RETRY_BASE_DELAY_MS * 2u64.pow(attempt.min(6))).min(MAX_RECONNECT_DELAY_MS);
+                info!("🔌 Reconnecting to new token stream in {}ms", delay);
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                attempt += 1;
+            }
+
+            debug!("New token stream dropped, stopping subscription");
+        });
+
+        rx
+    }
+
     /// Scan for trending/popular tokens
     pub async fn scan_trending_tokens(&self, limit: usize) -> Result<Vec<String>> {
         if self.dry_run {
@@ -130,12 +345,7 @@ impl PumpFunScanner {
 
         debug!("Scanning trending tokens on pump.fun...");
 
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?
-            .json::<PumpFunResponse>()
-            .await?;
+        let response: PumpFunResponse = self.fetch_json_with_retry(&url).await?;
 
         let mints: Vec<String> = response.tokens.iter().map(|t| t.mint.clone()).collect();
 
@@ -143,6 +353,27 @@ impl PumpFunScanner {
         Ok(mints)
     }
 
+    /// Scan the listings selected by `mode`; `both` merges new launches and up to
+    /// `trending_limit` trending tokens, new first, without duplicates
+    pub async fn scan_tokens(&self, mode: ScanMode, trending_limit: usize) -> Result<Vec<String>> {
+        match mode {
+            ScanMode::New => self.scan_new_tokens().await,
+            ScanMode::Trending => self.scan_trending_tokens(trending_limit).await,
+            ScanMode::Both => {
+                let (new, trending) = futures::try_join!(
+                    self.scan_new_tokens(),
+                    self.scan_trending_tokens(trending_limit),
+                )?;
+
+                let mut seen = std::collections::HashSet::new();
+                Ok(new.into_iter()
+                    .chain(trending)
+                    .filter(|mint| seen.insert(mint.clone()))
+                    .collect())
+            }
+        }
+    }
+
     /// Get detailed metrics for a specific token
     pub async fn get_token_metrics(&self, mint: &str) -> Result<TokenMetrics> {
         if self.dry_run {
@@ -150,17 +381,17 @@ impl PumpFunScanner {
             return Ok(self.generate_mock_metrics(mint));
         }
 
+        if let Some(metrics) = self.metrics_cache.lock().unwrap().get(mint) {
+            debug!("Using cached metrics for {}", mint);
+            return Ok(metrics);
+        }
+
         let url = format!("{}/tokens/{}", self.api_url, mint);
 
         debug!("Fetching metrics for token {}", mint);
 
         // Fetch basic token data
-        let token_data = self.client
-            .get(&url)
-            .send()
-            .await?
-            .json::<PumpFunToken>()
-            .await?;
+        let token_data: PumpFunToken = self.fetch_json_with_retry(&url).await?;
 
         // Fetch additional metrics (trades, holders, etc.)
         let trades_data = self.fetch_trade_data(mint).await?;
@@ -174,45 +405,74 @@ impl PumpFunScanner {
             metrics.volume_5m
         );
 
+        self.metrics_cache.lock().unwrap().insert(mint.to_string(), metrics.clone());
+
         Ok(metrics)
     }
 
     /// Fetch recent trade data
     async fn fetch_trade_data(&self, mint: &str) -> Result<TradeData> {
         let url = format!("{}/trades/{}?limit=100", self.api_url, mint);
-        
-        let trades: Vec<Trade> = self.client
-            .get(&url)
-            .send()
-            .await?
-            .json()
-            .await
-            .unwrap_or_default();
+
+        // A malformed payload just means no usable trades
+        let trades: Vec<Trade> = match self.fetch_json_with_retry(&url).await {
+            Ok(trades) => trades,
+            Err(BotError::Serialization(_)) => Vec::new(),
+            Err(e) => return Err(e),
+        };
 
         Ok(self.aggregate_trade_data(trades))
     }
 
-    /// Fetch holder distribution data
+    /// Fetch holder distribution data, paging until the holder list is exhausted
+    /// or `holder_max_pages` pages have been read
     async fn fetch_holder_data(&self, mint: &str) -> Result<HolderData> {
-        let url = format!("{}/holders/{}?limit=100", self.api_url, mint);
-        
-        let holders: Vec<Holder> = self.client
-            .get(&url)
-            .send()
-            .await?
-            .json()
-            .await
-            .unwrap_or_default();
+        let mut holders: Vec<Holder> = Vec::new();
+
+        for page in 0..self.holder_max_pages {
+            let url = format!(
+                "{}/holders/{}?limit={}&offset={}",
+                self.api_url,
+                mint,
+                HOLDER_PAGE_SIZE,
+                page * HOLDER_PAGE_SIZE
+            );
+
+            let batch: Vec<Holder> = match self.fetch_json_with_retry(&url).await {
+                Ok(batch) => batch,
+                Err(BotError::Serialization(_)) => Vec::new(),
+                Err(e) => return Err(e),
+            };
+
+            let exhausted = batch.len() < HOLDER_PAGE_SIZE;
+            holders.extend(batch);
+            if exhausted {
+                break;
+            }
+
+            if page + 1 == self.holder_max_pages {
+                debug!("Stopped paging holders for {} after {} pages", mint, self.holder_max_pages);
+            }
+        }
 
         Ok(self.aggregate_holder_data(holders))
     }
 
     /// Aggregate trade data into metrics
-    fn aggregate_trade_data(&self, trades: Vec<Trade>) -> TradeData {
+    fn aggregate_trade_data(&self, mut trades: Vec<Trade>) -> TradeData {
         let now = chrono::Utc::now().timestamp();
         let five_min_ago = now - 300;
         let one_hour_ago = now - 3600;
 
+        // Oldest first, so window boundaries and the latest price are easy to find
+        trades.sort_by_key(|t| t.timestamp);
+        let first_trade_at = trades.first().map(|t| t.timestamp);
+        let last_trade_at = trades.last().map(|t| t.timestamp);
+        let price_change_5m = price_change_since(&trades, five_min_ago);
+        let price_change_1h = price_change_since(&trades, one_hour_ago);
+        let volatility_score = volatility_score(&trades);
+        let wash_trade_score = wash_trade_score(&trades, five_min_ago);
+
         let mut volume_5m = 0.0;
         let mut volume_1h = 0.0;
         let mut volume_24h = 0.0;
@@ -220,6 +480,8 @@ impl PumpFunScanner {
         let mut unique_sellers_5m = std::collections::HashSet::new();
         let mut buy_volume = 0.0;
         let mut sell_volume = 0.0;
+        let mut buy_count_5m = 0;
+        let mut sell_count_5m = 0;
 
         for trade in trades {
             volume_24h += trade.amount_sol;
@@ -234,9 +496,11 @@ impl PumpFunScanner {
                 if trade.is_buy {
                     unique_buyers_5m.insert(trade.user.clone());
                     buy_volume += trade.amount_sol;
+                    buy_count_5m += 1;
                 } else {
                     unique_sellers_5m.insert(trade.user.clone());
                     sell_volume += trade.amount_sol;
+                    sell_count_5m += 1;
                 }
             }
         }
@@ -261,13 +525,23 @@ impl PumpFunScanner {
             unique_sellers_5m: unique_sellers_5m.len() as u32,
             buy_pressure,
             sell_pressure,
+            buy_count_5m,
+            sell_count_5m,
+            price_change_5m,
+            price_change_1h,
+            first_trade_at,
+            last_trade_at,
+            volatility_score,
+            wash_trade_score,
         }
     }
 
-    /// Aggregate holder data
-    fn aggregate_holder_data(&self, holders: Vec<Holder>) -> HolderData {
+    /// Aggregate holder data; pages aren't guaranteed to be ordered across each other,
+    /// so the top 10 are picked after sorting the full list
+    fn aggregate_holder_data(&self, mut holders: Vec<Holder>) -> HolderData {
         let holder_count = holders.len() as u32;
-        
+
+        holders.sort_by(|a, b| b.amount.cmp(&a.amount));
         let total_supply: u64 = holders.iter().map(|h| h.amount).sum();
         let top_10_amount: u64 = holders.iter().take(10).map(|h| h.amount).sum();
 
@@ -291,15 +565,31 @@ impl PumpFunScanner {
         holders: HolderData,
     ) -> Result<TokenMetrics> {
         // Fetch current price and liquidity from bonding curve
-        let (current_price, liquidity_sol, bonding_progress) = (0.001, 10.0, 50.0); // TODO: actual calc
+        let (current_price, liquidity_sol, mut bonding_progress) = (0.001, 10.0, 50.0); // TODO: actual calc
 
-        let price_change_5m = 0.0; // TODO: calculate from trade history
-        let price_change_1h = 0.0;
+        // Same signal the trader reads on-chain: a complete curve or an existing Raydium pool
+        let is_graduated = token.complete || token.raydium_pool.is_some();
+        if is_graduated {
+            bonding_progress = 100.0;
+        }
+
+        let price_change_5m = trades.price_change_5m;
+        let price_change_1h = trades.price_change_1h;
+
+        // Fall back to the first trade we saw; with neither, treat the token as old
+        // rather than brand new, so age-gated strategies fail closed
+        let now = chrono::Utc::now().timestamp();
+        let created_at = token.created_timestamp
+            .map(|ms| ms / 1000)
+            .or(trades.first_trade_at)
+            .unwrap_or(0);
+        let time_since_creation = (now - created_at).max(0) as u64;
 
         Ok(TokenMetrics {
             mint: token.mint,
             name: token.name,
             symbol: token.symbol,
+            creator: token.creator,
             volume_5m: trades.volume_5m,
             volume_1h: trades.volume_1h,
             volume_24h: trades.volume_24h,
@@ -308,6 +598,9 @@ impl PumpFunScanner {
             price_change_1h,
             liquidity_sol,
             liquidity_usd: liquidity_sol * 100.0, // Assuming SOL price
+            virtual_sol_reserves: token.virtual_sol_reserves as f64 / 1e9,
+            virtual_token_reserves: token.virtual_token_reserves as f64
+                / 10f64.powi(crate::pump_fun::PUMP_FUN_TOKEN_DECIMALS as i32),
             holder_count: holders.holder_count,
             holder_concentration: holders.holder_concentration,
             unique_buyers_5m: trades.unique_buyers_5m,
@@ -315,16 +608,118 @@ impl PumpFunScanner {
             market_cap: token.usd_market_cap,
             fully_diluted_valuation: token.usd_market_cap,
             bonding_curve_progress: bonding_progress,
-            is_graduated: false,
-            created_at: chrono::Utc::now().timestamp(),
-            time_since_creation: 0,
+            is_graduated,
+            created_at,
+            time_since_creation,
             buy_pressure: trades.buy_pressure,
             sell_pressure: trades.sell_pressure,
-            volatility_score: 0.0,
+            buy_count_5m: trades.buy_count_5m,
+            sell_count_5m: trades.sell_count_5m,
+            volatility_score: trades.volatility_score,
+            wash_trade_score: trades.wash_trade_score,
+            rug_risk: None,
         })
     }
 }
 
+/// Run one websocket session, forwarding new mints until the socket closes.
+/// Returns how many mints were delivered.
+async fn stream_new_tokens(url: &str, tx: &UnboundedSender<String>) -> Result<usize> {
+    let (mut ws, _) = tokio_tungstenite::connect_async(url)
+        .await
+        .map_err(|e| BotError::WebSocket(e.to_string()))?;
+
+    ws.send(Message::Text(r#"{"method":"subscribeNewToken"}"#.to_string()))
+        .await
+        .map_err(|e| BotError::WebSocket(e.to_string()))?;
+
+    info!("📡 Subscribed to new token stream");
+
+    let mut delivered = 0;
+    while let Some(message) = ws.next().await {
+        match message.map_err(|e| BotError::WebSocket(e.to_string()))? {
+            Message::Text(text) => {
+                // Subscription acks and other notices don't carry a mint
+                let Ok(event) = serde_json::from_str::<NewTokenEvent>(&text) else {
+                    continue;
+                };
+
+                if tx.unbounded_send(event.mint).is_err() {
+                    break;
+                }
+                delivered += 1;
+            }
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+
+    Ok(delivered)
+}
+
+#[async_trait]
+impl MetricsSource for PumpFunScanner {
+    async fn get_metrics(&self, mint: &str) -> Result<TokenMetrics> {
+        self.get_token_metrics(mint).await
+    }
+}
+
+/// Fractional change from the earliest priced trade after `since` to the latest priced trade.
+/// Expects `trades` sorted oldest first.
+fn price_change_since(trades: &[Trade], since: i64) -> f64 {
+    let latest = trades.iter().rev().find(|t| t.price > 0.0);
+    let earliest = trades.iter().find(|t| t.timestamp > since && t.price > 0.0);
+
+    match (earliest, latest) {
+        (Some(first), Some(last)) => (last.price - first.price) / first.price,
+        _ => 0.0,
+    }
+}
+
+/// Standard deviation of trade-to-trade returns, scaled to 0-1 where
+/// `MAX_VOLATILITY_STDDEV` or more is 1.0. Expects `trades` sorted oldest first.
+/// With fewer than two priced trades volatility is unknown, which counts as risky.
+fn volatility_score(trades: &[Trade]) -> f64 {
+    let prices: Vec<f64> = trades.iter().map(|t| t.price).filter(|&p| p > 0.0).collect();
+    if prices.len() < 2 {
+        return 1.0;
+    }
+
+    let returns: Vec<f64> = prices.windows(2).map(|w| w[1] / w[0] - 1.0).collect();
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64;
+
+    (variance.sqrt() / MAX_VOLATILITY_STDDEV).min(1.0)
+}
+
+/// How much of the volume after `since` looks like wash trading, 0-1: the share
+/// that round-trips (the same address buying and selling) times the share the
+/// top `WASH_TRADE_TOP_ADDRESSES` addresses account for. Organic flow from many
+/// one-way traders scores near zero; two wallets ping-ponging score 1.0.
+fn wash_trade_score(trades: &[Trade], since: i64) -> f64 {
+    let mut by_address: HashMap<&str, (f64, f64)> = HashMap::new();
+    for trade in trades.iter().filter(|t| t.timestamp > since) {
+        let (bought, sold) = by_address.entry(trade.user.as_str()).or_default();
+        if trade.is_buy {
+            *bought += trade.amount_sol;
+        } else {
+            *sold += trade.amount_sol;
+        }
+    }
+
+    let mut volumes: Vec<f64> = by_address.values().map(|(bought, sold)| bought + sold).collect();
+    let total: f64 = volumes.iter().sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+
+    let round_trip: f64 = by_address.values().map(|(bought, sold)| 2.0 * bought.min(*sold)).sum();
+    volumes.sort_by(|a, b| b.total_cmp(a));
+    let top: f64 = volumes.iter().take(WASH_TRADE_TOP_ADDRESSES).sum();
+
+    (round_trip / total) * (top / total)
+}
+
 #[derive(Debug, Deserialize)]
 struct Trade {
     #[serde(default)]
@@ -335,6 +730,9 @@ struct Trade {
     is_buy: bool,
     #[serde(default)]
     timestamp: i64,
+    /// Execution price in SOL per token
+    #[serde(default)]
+    price: f64,
 }
 
 struct TradeData {
@@ -345,6 +743,16 @@ struct TradeData {
     unique_sellers_5m: u32,
     buy_pressure: f64,
     sell_pressure: f64,
+    buy_count_5m: u32,
+    sell_count_5m: u32,
+    price_change_5m: f64,
+    price_change_1h: f64,
+    first_trade_at: Option<i64>,
+    last_trade_at: Option<i64>,
+    /// 0-1, higher = choppier price action
+    volatility_score: f64,
+    /// 0-1, higher = more of the 5m volume looks like wash trading
+    wash_trade_score: f64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -359,3 +767,376 @@ struct HolderData {
     holder_count: u32,
     holder_concentration: f64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_trades() -> TradeData {
+        TradeData {
+            volume_5m: 0.0,
+            volume_1h: 0.0,
+            volume_24h: 0.0,
+            unique_buyers_5m: 0,
+            unique_sellers_5m: 0,
+            buy_pressure: 0.0,
+            sell_pressure: 0.0,
+            buy_count_5m: 0,
+            sell_count_5m: 0,
+            price_change_5m: 0.0,
+            price_change_1h: 0.0,
+            first_trade_at: None,
+            last_trade_at: None,
+            volatility_score: 1.0,
+            wash_trade_score: 0.0,
+        }
+    }
+
+    fn empty_holders() -> HolderData {
+        HolderData {
+            holder_count: 0,
+            holder_concentration: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_completed_token_is_graduated() {
+        let scanner = PumpFunScanner::new(&BotConfig::for_tests("http://localhost"));
+        let token: PumpFunToken = serde_json::from_value(serde_json::json!({
+            "mint": "So11111111111111111111111111111111111111112",
+            "name": "Graduated",
+            "symbol": "GRAD",
+            "uri": "",
+            "complete": true,
+            "raydium_pool": "58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2"
+        })).unwrap();
+
+        let metrics = scanner.calculate_metrics(token, empty_trades(), empty_holders()).unwrap();
+        assert!(metrics.is_graduated);
+        assert_eq!(metrics.bonding_curve_progress, 100.0);
+    }
+
+    #[test]
+    fn test_token_age_from_creation_timestamp() {
+        let scanner = PumpFunScanner::new(&BotConfig::for_tests("http://localhost"));
+        let created_ms = (chrono::Utc::now().timestamp() - 600) * 1000;
+        let token: PumpFunToken = serde_json::from_value(serde_json::json!({
+            "mint": "So11111111111111111111111111111111111111112",
+            "name": "Ten Minutes",
+            "symbol": "TEN",
+            "uri": "",
+            "created_timestamp": created_ms
+        })).unwrap();
+
+        let metrics = scanner.calculate_metrics(token, empty_trades(), empty_holders()).unwrap();
+
+        // Allow for the clock ticking over between the two `now`s
+        assert!((600..=601).contains(&metrics.time_since_creation), "{}", metrics.time_since_creation);
+        assert_eq!(metrics.age_minutes(), 10);
+
+        let signal = crate::analyzer::create_strategy(crate::types::StrategyType::UltraEarlySniper, None, &crate::types::HolderThresholds::default())
+            .analyze(&metrics)
+            .unwrap();
+        assert_eq!(signal.confidence, 0.0);
+        assert!(signal.reasoning[0].contains("Too old"));
+    }
+
+    #[test]
+    fn test_token_age_falls_back_to_first_trade() {
+        let scanner = PumpFunScanner::new(&BotConfig::for_tests("http://localhost"));
+        let token: PumpFunToken = serde_json::from_value(serde_json::json!({
+            "mint": "So11111111111111111111111111111111111111112",
+            "name": "No Timestamp",
+            "symbol": "NTS",
+            "uri": ""
+        })).unwrap();
+        let mut trades = empty_trades();
+        trades.first_trade_at = Some(chrono::Utc::now().timestamp() - 120);
+
+        let metrics = scanner.calculate_metrics(token, trades, empty_holders()).unwrap();
+
+        assert_eq!(metrics.age_minutes(), 2);
+    }
+
+    fn trade(user: &str, seconds_ago: i64, price: f64, is_buy: bool) -> Trade {
+        Trade {
+            user: user.to_string(),
+            amount_sol: 1.0,
+            is_buy,
+            timestamp: chrono::Utc::now().timestamp() - seconds_ago,
+            price,
+        }
+    }
+
+    #[test]
+    fn test_price_changes_from_trade_history() {
+        let scanner = PumpFunScanner::new(&BotConfig::for_tests("http://localhost"));
+        // Deliberately unordered, as the API may return newest first
+        let trades = vec![
+            trade("a", 10, 0.0030, true),
+            trade("b", 3000, 0.0010, true),
+            trade("c", 240, 0.0020, false),
+            trade("d", 1800, 0.0015, true),
+        ];
+
+        let data = scanner.aggregate_trade_data(trades);
+
+        // 5m window starts at 0.0020, 1h window at 0.0010, latest is 0.0030
+        assert!((data.price_change_5m - 0.5).abs() < 1e-9);
+        assert!((data.price_change_1h - 2.0).abs() < 1e-9);
+        assert!(data.first_trade_at.unwrap() < data.last_trade_at.unwrap());
+    }
+
+    #[test]
+    fn test_volatility_score_from_trade_prices() {
+        let scanner = PumpFunScanner::new(&BotConfig::for_tests("http://localhost"));
+        let series = |prices: &[f64]| {
+            prices.iter()
+                .enumerate()
+                .map(|(i, &price)| trade("a", 100 - i as i64, price, true))
+                .collect::<Vec<_>>()
+        };
+
+        let flat = scanner.aggregate_trade_data(series(&[0.0010, 0.00101, 0.00102, 0.00103, 0.00104]));
+        let jumpy = scanner.aggregate_trade_data(series(&[0.0010, 0.0013, 0.0009, 0.0014, 0.0008]));
+
+        assert!(flat.volatility_score < 0.05, "flat: {}", flat.volatility_score);
+        assert!(jumpy.volatility_score > flat.volatility_score);
+        assert_eq!(jumpy.volatility_score, 1.0);
+
+        // A single trade says nothing about volatility
+        let single = scanner.aggregate_trade_data(series(&[0.0010]));
+        assert_eq!(single.volatility_score, 1.0);
+    }
+
+    #[test]
+    fn test_ping_pong_trades_score_as_wash_trading() {
+        let scanner = PumpFunScanner::new(&BotConfig::for_tests("http://localhost"));
+        let ping_pong: Vec<Trade> = (0..20)
+            .map(|i| trade(if i % 2 == 0 { "a" } else { "b" }, 200 - i, 0.001, i % 4 < 2))
+            .collect();
+        let organic: Vec<Trade> = (0..20)
+            .map(|i| trade(&format!("buyer{}", i), 200 - i, 0.001, i % 5 != 0))
+            .collect();
+
+        let washed = scanner.aggregate_trade_data(ping_pong);
+        let real = scanner.aggregate_trade_data(organic);
+        assert!(washed.wash_trade_score > 0.9, "washed: {}", washed.wash_trade_score);
+        assert_eq!(real.wash_trade_score, 0.0);
+
+        // Same headline volume, but the washed token's volume factor is discounted
+        let token = |symbol: &str| -> PumpFunToken {
+            serde_json::from_value(serde_json::json!({
+                "mint": "So11111111111111111111111111111111111111112",
+                "name": symbol,
+                "symbol": symbol,
+                "uri": ""
+            })).unwrap()
+        };
+        let washed = scanner.calculate_metrics(token("WASH"), washed, empty_holders()).unwrap();
+        let real = scanner.calculate_metrics(token("REAL"), real, empty_holders()).unwrap();
+        assert_eq!(washed.volume_5m, real.volume_5m);
+
+        let analyzer = crate::analyzer::TokenAnalyzer::new(5.0, 5.0, 50, 0.3);
+        let washed_volume = analyzer.score_factors(&washed).volume;
+        let unpenalized = analyzer.score_factors(&TokenMetrics { wash_trade_score: 0.0, ..washed.clone() }).volume;
+        assert!(washed_volume < unpenalized * 0.1, "{} vs {}", washed_volume, unpenalized);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_respect_rate_limit() {
+        let mut server = mockito::Server::new_async().await;
+        let requests = server.mock("GET", "/tokens/latest")
+            .with_body(r#"{"tokens":[]}"#)
+            .expect(25)
+            .create_async()
+            .await;
+
+        let mut config = BotConfig::for_tests(&server.url());
+        config.api_requests_per_second = 20.0;
+        config.api_burst = 5;
+        let scanner = PumpFunScanner::new(&config);
+
+        let started = Instant::now();
+        let scans = futures::future::join_all((0..25).map(|_| scanner.scan_new_tokens())).await;
+        let elapsed = started.elapsed().as_secs_f64();
+
+        assert!(scans.iter().all(|scan| scan.is_ok()));
+        requests.assert_async().await;
+        // Past the initial burst, the 20 remaining requests can't beat 20/s
+        let observed_rate = 20.0 / elapsed;
+        assert!(observed_rate <= 20.0 * 1.05, "{:.1} requests/s over {:.2}s", observed_rate, elapsed);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_retries_transient_errors() {
+        let mut server = mockito::Server::new_async().await;
+        let unavailable = server.mock("GET", "/tokens/latest")
+            .with_status(503)
+            .expect(2)
+            .create_async()
+            .await;
+        let ok = server.mock("GET", "/tokens/latest")
+            .with_status(200)
+            .with_body(r#"{"tokens":[{"mint":"Mint111","name":"A","symbol":"A","uri":""}]}"#)
+            .create_async()
+            .await;
+
+        let scanner = PumpFunScanner::new(&BotConfig::for_tests(&server.url()));
+        let mints = scanner.scan_new_tokens().await.unwrap();
+
+        assert_eq!(mints, vec!["Mint111".to_string()]);
+        unavailable.assert_async().await;
+        ok.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_scan_both_merges_new_and_trending() {
+        let mut server = mockito::Server::new_async().await;
+        let _new = server.mock("GET", "/tokens/latest")
+            .with_body(r#"{"tokens":[{"mint":"MintA","name":"A","symbol":"A","uri":""},{"mint":"MintB","name":"B","symbol":"B","uri":""}]}"#)
+            .create_async()
+            .await;
+        let _trending = server.mock("GET", "/tokens/trending")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"tokens":[{"mint":"MintB","name":"B","symbol":"B","uri":""},{"mint":"MintC","name":"C","symbol":"C","uri":""}]}"#)
+            .create_async()
+            .await;
+
+        let scanner = PumpFunScanner::new(&BotConfig::for_tests(&server.url()));
+
+        let both = scanner.scan_tokens(ScanMode::Both, 20).await.unwrap();
+        assert_eq!(both, vec!["MintA".to_string(), "MintB".to_string(), "MintC".to_string()]);
+
+        let trending = scanner.scan_tokens(ScanMode::Trending, 20).await.unwrap();
+        assert_eq!(trending, vec!["MintB".to_string(), "MintC".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_does_not_retry_client_errors() {
+        let mut server = mockito::Server::new_async().await;
+        let not_found = server.mock("GET", "/tokens/latest")
+            .with_status(404)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let scanner = PumpFunScanner::new(&BotConfig::for_tests(&server.url()));
+        assert!(scanner.scan_new_tokens().await.is_err());
+        not_found.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_metrics_cached_within_ttl() {
+        let mut server = mockito::Server::new_async().await;
+        let token = server.mock("GET", "/tokens/Mint111")
+            .with_body(r#"{"mint":"Mint111","name":"A","symbol":"A","uri":""}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let _trades = server.mock("GET", mockito::Matcher::Regex("^/trades/".to_string()))
+            .with_body("[]")
+            .create_async()
+            .await;
+        let _holders = server.mock("GET", mockito::Matcher::Regex("^/holders/".to_string()))
+            .with_body("[]")
+            .create_async()
+            .await;
+
+        let scanner = PumpFunScanner::new(&BotConfig::for_tests(&server.url()));
+        let first = scanner.get_token_metrics("Mint111").await.unwrap();
+        let second = scanner.get_token_metrics("Mint111").await.unwrap();
+
+        assert_eq!(first.mint, second.mint);
+        token.assert_async().await;
+    }
+
+    fn holders_page(amounts: impl IntoIterator<Item = u64>) -> String {
+        let holders: Vec<_> = amounts.into_iter()
+            .enumerate()
+            .map(|(i, amount)| serde_json::json!({ "address": format!("Holder{}", i), "amount": amount }))
+            .collect();
+        serde_json::Value::Array(holders).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_holder_data_pages_through_all_holders() {
+        let mut server = mockito::Server::new_async().await;
+        let small = |count| std::iter::repeat(10).take(count);
+        let mut pages = Vec::new();
+        // The 10 whales only show up on the last, partial page
+        for (offset, body) in [
+            ("0", holders_page(small(100))),
+            ("100", holders_page(small(100))),
+            ("200", holders_page(small(40).chain(std::iter::repeat(1_000).take(10)))),
+        ] {
+            pages.push(
+                server.mock("GET", "/holders/Mint111")
+                    .match_query(mockito::Matcher::UrlEncoded("offset".to_string(), offset.to_string()))
+                    .with_body(body)
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+
+        let scanner = PumpFunScanner::new(&BotConfig::for_tests(&server.url()));
+        let data = scanner.fetch_holder_data("Mint111").await.unwrap();
+
+        assert_eq!(data.holder_count, 250);
+        // 10 x 1000 of 240 x 10 + 10 x 1000
+        assert!((data.holder_concentration - 10_000.0 / 12_400.0).abs() < 1e-9);
+        for page in pages {
+            page.assert_async().await;
+        }
+    }
+
+    #[test]
+    fn test_metrics_cache_evicts_least_recently_used() {
+        let scanner = PumpFunScanner::new(&BotConfig::for_tests("http://localhost"));
+        let mut cache = MetricsCache::new(Duration::from_secs(60), 2);
+
+        cache.insert("a".to_string(), scanner.generate_mock_metrics("aaaaaaaaaa"));
+        cache.insert("b".to_string(), scanner.generate_mock_metrics("bbbbbbbbbb"));
+        assert!(cache.get("a").is_some()); // "b" is now least recently used
+        cache.insert("c".to_string(), scanner.generate_mock_metrics("cccccccccc"));
+
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("c").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_new_token_stream_reconnects() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // Each connection pushes one token then drops, forcing a reconnect
+        tokio::spawn(async move {
+            for mint in ["MintA", "MintB"] {
+                let (socket, _) = listener.accept().await.unwrap();
+                let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+
+                let subscribe = ws.next().await.unwrap().unwrap();
+                assert!(subscribe.to_text().unwrap().contains("subscribeNewToken"));
+
+                ws.send(Message::Text(r#"{"message":"Successfully subscribed"}"#.to_string())).await.unwrap();
+                ws.send(Message::Text(format!(r#"{{"mint":"{}","txType":"create"}}"#, mint))).await.unwrap();
+                ws.close(None).await.unwrap();
+            }
+        });
+
+        let mut config = BotConfig::for_tests("http://localhost");
+        config.pump_fun_ws_url = format!("ws://{}", addr);
+        let scanner = PumpFunScanner::new(&config);
+
+        let mints: Vec<String> = tokio::time::timeout(
+            Duration::from_secs(5),
+            scanner.subscribe_new_tokens().take(2).collect(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(mints, vec!["MintA".to_string(), "MintB".to_string()]);
+    }
+}
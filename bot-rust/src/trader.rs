@@ -1,67 +1,193 @@
-use crate::types::{BotConfig, Position, PositionStatus};
+use crate::amount::Amount;
+use crate::types::{BotConfig, Position, PositionStatus, SignalType, StrategyExitParams};
 use crate::error::{Result, BotError};
-use solana_client::rpc_client::RpcClient;
+use crate::analyzer::{create_strategy, TradingStrategy};
+use crate::export::{self, TradeRecord};
+use crate::api::{ApiState, PositionInfo};
+use crate::risk::RiskManager;
+use crate::scanner::MetricsSource;
+use crate::{pump_fun, raydium};
+use crate::jupiter::JupiterSwap;
+use crate::rpc::FailoverRpc;
+use crate::vault::{self, VaultClient, VaultPositionStatus};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSendTransactionConfig};
 use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    instruction::Instruction,
+    native_token::{lamports_to_sol, sol_to_lamports},
     pubkey::Pubkey,
     signature::Signer,
-    transaction::Transaction,
+    transaction::{Transaction, VersionedTransaction},
     system_instruction,
     commitment_config::CommitmentConfig,
 };
-use tracing::{info, warn};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+/// Compute units requested per trade; pump.fun buys use well under this
+const COMPUTE_UNIT_LIMIT: u32 = 200_000;
+
+/// Delay between signature status polls while waiting for confirmation
+const CONFIRM_POLL_INTERVAL_MS: u64 = 400;
+
+/// Held positions `Trader::reconcile_with_chain` corrected, by mint
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReconcileReport {
+    /// Held positions that were checked against a vault record
+    pub checked: usize,
+    /// Marked closed because the vault had already closed them
+    pub closed: Vec<Pubkey>,
+    /// Dropped because their vault position doesn't exist
+    pub dropped: Vec<Pubkey>,
+}
+
+impl ReconcileReport {
+    /// Whether local state already matched the chain
+    pub fn is_clean(&self) -> bool {
+        self.closed.is_empty() && self.dropped.is_empty()
+    }
+}
 
 pub struct Trader {
-    rpc_client: RpcClient,
+    /// Its async clients send and confirm, so a slow transaction never blocks the runtime
+    rpc: FailoverRpc,
+    /// Mirrors opened/closed positions into the vault program when configured
+    vault: Option<VaultClient>,
+    jupiter: JupiterSwap,
+    /// Exit rules of the configured strategy; only the trailing stop and loss cooldown are read here
+    exit_params: StrategyExitParams,
+    /// Receives position status changes for the frontend
+    api_state: Option<ApiState>,
     config: BotConfig,
     positions: Vec<Position>,
+    /// Simulated prices that dry-run fills and exits are taken at, keyed by mint
+    paper_prices: HashMap<Pubkey, f64>,
+    /// PnL of every closed position, in SOL
+    realized_pnl_sol: f64,
+    /// Halts new positions past `max_drawdown_pct`
+    risk: RiskManager,
+    /// When each mint last closed at a loss, for `loss_cooldown_seconds`
+    loss_cooldowns: HashMap<Pubkey, i64>,
 }
 
 impl Trader {
-    pub fn new(config: &BotConfig) -> Self {
-        let rpc_client = RpcClient::new_with_commitment(
-            config.rpc_url.clone(),
-            CommitmentConfig::confirmed(),
+    pub fn new(config: BotConfig) -> Self {
+        // Drawdown is measured against the capital the bot can have deployed at once
+        let risk = RiskManager::new(
+            config.max_position_size_sol * config.max_concurrent_positions as f64,
+            config.max_drawdown_pct,
         );
 
         Self {
-            rpc_client,
-            config: BotConfig {
-                rpc_url: config.rpc_url.clone(),
-                rpc_ws_url: config.rpc_ws_url.clone(),
-                wallet_keypair: solana_sdk::signature::Keypair::from_bytes(&config.wallet_keypair.to_bytes()).unwrap(),
-                min_liquidity_sol: config.min_liquidity_sol,
-                max_position_size_sol: config.max_position_size_sol,
-                take_profit_multiplier: config.take_profit_multiplier,
-                stop_loss_percentage: config.stop_loss_percentage,
-                pump_fun_api_url: config.pump_fun_api_url.clone(),
-                raydium_amm_program: config.raydium_amm_program,
-                max_slippage_bps: config.max_slippage_bps,
-                max_concurrent_positions: config.max_concurrent_positions,
-                position_timeout_seconds: config.position_timeout_seconds,
-                scan_interval_ms: config.scan_interval_ms,
-                volume_threshold_sol: config.volume_threshold_sol,
-                holder_count_min: config.holder_count_min,
-                strategy_type: config.strategy_type,
-                dry_run: config.dry_run,
-            },
+            rpc: FailoverRpc::new(&config.rpc_urls(), config.commitment.config()),
+            vault: VaultClient::new(&config),
+            jupiter: JupiterSwap::new(&config),
+            exit_params: create_strategy(config.strategy_type, None, &config.holder_thresholds)
+                .get_exit_params()
+                .with_take_profit_ladder(&config.take_profit_ladder),
+            api_state: None,
+            config,
             positions: Vec::new(),
+            paper_prices: HashMap::new(),
+            realized_pnl_sol: 0.0,
+            risk,
+            loss_cooldowns: HashMap::new(),
         }
     }
 
-    /// Buy token on pump.fun bonding curve
+    pub fn with_api_state(mut self, api_state: ApiState) -> Self {
+        self.risk = self.risk.with_halt_flag(api_state.trading_halted.clone());
+        self.api_state = Some(api_state);
+        self
+    }
+
+    /// Set the simulated price of a mint for dry runs; ignored when trading live
+    pub fn set_paper_price(&mut self, token_mint: Pubkey, price: f64) {
+        self.paper_prices.insert(token_mint, price);
+    }
+
+    /// Realized PnL of all closed positions, in SOL
+    pub fn realized_pnl(&self) -> f64 {
+        self.realized_pnl_sol
+    }
+
+    /// Whether the drawdown limit has stopped new positions
+    pub fn trading_halted(&self) -> bool {
+        self.risk.is_halted()
+    }
+
+    /// Buy token on pump.fun bonding curve, or through its Raydium pool once graduated;
+    /// `entry_confidence` is recorded with the vault position, and `liquidity_sol` is
+    /// checked against the delegation's liquidity floor
+    ///
+    /// Buying a mint that is already held scales into that position instead of opening
+    /// another one, at most `max_scale_ins` times; the vault keeps tracking only the
+    /// original entry.
     pub async fn buy_token(
         &mut self,
         token_mint: &Pubkey,
         sol_amount: f64,
+        entry_confidence: f64,
+        liquidity_sol: f64,
     ) -> Result<Position> {
         info!("🚀 Attempting to buy {} SOL of token {}", sol_amount, token_mint);
 
-        // Check position limit
-        if self.positions.len() >= self.config.max_concurrent_positions {
-            return Err(BotError::PositionLimitReached(
-                self.positions.len(),
-                self.config.max_concurrent_positions,
-            ));
+        let held = self.active_position_index(token_mint);
+
+        // Check position limit; scaling in doesn't take a new slot but has its own cap
+        match held {
+            None if self.positions.len() >= self.config.max_concurrent_positions => {
+                return Err(BotError::PositionLimitReached(
+                    self.positions.len(),
+                    self.config.max_concurrent_positions,
+                ));
+            }
+            Some(i) if self.positions[i].scale_ins >= self.config.max_scale_ins => {
+                return Err(BotError::ScaleInLimitReached {
+                    mint: token_mint.to_string(),
+                    max: self.config.max_scale_ins,
+                });
+            }
+            _ => {}
+        }
+
+        if self.risk.is_halted() {
+            return Err(BotError::TradingHalted);
+        }
+
+        // Don't chase a token that just lost money
+        if let Some(closed_at) = self.loss_cooldowns.get(token_mint) {
+            let elapsed = chrono::Utc::now().timestamp() - closed_at;
+            let cooldown = self.exit_params.loss_cooldown_seconds as i64;
+            if elapsed <= cooldown {
+                return Err(BotError::CoolingDown {
+                    mint: token_mint.to_string(),
+                    remaining_secs: cooldown - elapsed,
+                });
+            }
+        }
+
+        if self.config.dry_run {
+            return self.paper_buy(token_mint, sol_amount).await;
+        }
+
+        // Thin curves fill far from the spot price; Raydium swaps carry their own minimum out
+        let is_graduated = self.check_if_graduated(token_mint).await?;
+        if !is_graduated {
+            let (_, price_impact) = self.estimate_buy_output(token_mint, sol_amount)?;
+            let max_impact = self.config.max_slippage_bps as f64 / 10_000.0;
+            if price_impact > max_impact {
+                warn!(
+                    "Price impact {:.2}% exceeds max slippage {:.2}% for {}",
+                    price_impact * 100.0,
+                    max_impact * 100.0,
+                    token_mint
+                );
+                return Err(BotError::HighSlippage(price_impact * 100.0));
+            }
         }
 
         // Check wallet balance
@@ -73,15 +199,16 @@ impl Trader {
             });
         }
 
-        // Get or create associated token account
-        let token_account = self.get_or_create_token_account(token_mint).await?;
+        // Get or create associated token account; creation rides in the buy transaction
+        let (token_account, create_token_account) = self.get_or_create_token_account(token_mint).await?;
 
         // Build buy transaction
-        let transaction = self.build_buy_transaction(
-            token_mint,
-            &token_account,
-            sol_amount,
-        ).await?;
+        let transaction = if is_graduated {
+            info!("Token graduated - buying through Raydium");
+            self.build_raydium_buy_transaction(token_mint, &token_account, create_token_account, sol_amount).await?
+        } else {
+            self.build_buy_transaction(token_mint, &token_account, create_token_account, sol_amount).await?
+        };
 
         // Send and confirm transaction
         let signature = self.send_and_confirm_transaction(transaction).await?;
@@ -90,7 +217,87 @@ impl Trader {
 
         // Get entry price and create position
         let entry_price = self.get_token_price(token_mint).await?;
-        let amount = self.get_token_balance(&token_account)?;
+        // The token account also holds what an existing position already bought
+        let held_amount = held.map_or(0, |i| self.positions[i].amount);
+        let balance = self.get_token_amount(&token_account)?;
+        let amount = Amount::new(balance.raw.saturating_sub(held_amount), balance.decimals);
+
+        let vault_position = match held {
+            Some(_) => None,
+            None => self.record_open_position(
+                token_mint,
+                sol_amount,
+                entry_price,
+                entry_price * self.config.take_profit_multiplier,
+                entry_price * (1.0 - self.config.stop_loss_percentage),
+                entry_confidence,
+                liquidity_sol,
+            ).await,
+        };
+
+        Ok(self.track_position(token_mint, sol_amount, entry_price, amount, vault_position))
+    }
+
+    /// Dry-run buy: fill `sol_amount` at the simulated price without sending anything
+    async fn paper_buy(&mut self, token_mint: &Pubkey, sol_amount: f64) -> Result<Position> {
+        let entry_price = self.get_token_price(token_mint).await?;
+        if entry_price <= 0.0 {
+            return Err(BotError::TokenNotFound(token_mint.to_string()));
+        }
+        let amount = Amount::for_sol(sol_amount, entry_price, pump_fun::PUMP_FUN_TOKEN_DECIMALS);
+
+        info!(
+            "🧪 [DRY RUN] Would buy {} SOL of {} at ${:.9} for {} tokens",
+            sol_amount, token_mint, entry_price, amount.ui()
+        );
+
+        Ok(self.track_position(token_mint, sol_amount, entry_price, amount, None))
+    }
+
+    /// Start tracking a filled buy, with TP/SL set from the entry price
+    ///
+    /// A fill into a held mint is averaged into that position: the entry becomes the
+    /// token-weighted average and TP/SL are recomputed from it.
+    fn track_position(
+        &mut self,
+        token_mint: &Pubkey,
+        sol_amount: f64,
+        entry_price: f64,
+        amount: Amount,
+        vault_position: Option<Pubkey>,
+    ) -> Position {
+        let (amount, decimals) = (amount.raw, amount.decimals);
+        if let Some(i) = self.active_position_index(token_mint) {
+            let take_profit_multiplier = self.config.take_profit_multiplier;
+            let stop_loss_percentage = self.config.stop_loss_percentage;
+            let position = &mut self.positions[i];
+
+            let total_amount = position.amount + amount;
+            if total_amount > 0 {
+                position.entry_price = (position.entry_price * position.amount as f64
+                    + entry_price * amount as f64)
+                    / total_amount as f64;
+            }
+            position.initial_amount = position.initial_amount.max(position.amount) + amount;
+            position.amount = total_amount;
+            position.decimals = decimals;
+            position.sol_invested += sol_amount;
+            position.scale_ins += 1;
+            position.take_profit_price = position.entry_price * take_profit_multiplier;
+            position.stop_loss_price = position.entry_price * (1.0 - stop_loss_percentage);
+
+            info!(
+                mint = %token_mint,
+                entry_price = position.entry_price,
+                sol_invested = position.sol_invested,
+                "📊 Scaled into position: avg entry=${:.6}, TP=${:.6}, SL=${:.6}",
+                position.entry_price,
+                position.take_profit_price,
+                position.stop_loss_price
+            );
+
+            return position.clone();
+        }
 
         let position = Position {
             token_mint: *token_mint,
@@ -101,18 +308,30 @@ impl Trader {
             take_profit_price: entry_price * self.config.take_profit_multiplier,
             stop_loss_price: entry_price * (1.0 - self.config.stop_loss_percentage),
             status: PositionStatus::Open,
+            vault_position,
+            initial_amount: amount,
+            sol_received: 0.0,
+            tp_rungs_hit: 0,
+            scale_ins: 0,
+            vault_amount: amount,
+            decimals,
+            exit_price: None,
+            closed_at: None,
         };
 
         self.positions.push(position.clone());
 
         info!(
+            mint = %token_mint,
+            entry_price,
+            sol_invested = sol_amount,
             "📊 Position opened: entry=${:.6}, TP=${:.6}, SL=${:.6}",
             entry_price,
             position.take_profit_price,
             position.stop_loss_price
         );
 
-        Ok(position)
+        position
     }
 
     /// Sell token (either on bonding curve or DEX after graduation)
@@ -124,64 +343,154 @@ impl Trader {
         info!("💰 Attempting to sell token {}", token_mint);
 
         // Find position index first to avoid borrow checker issues
-        let pos_index = self.positions.iter().position(|p| &p.token_mint == token_mint && p.status == PositionStatus::Open)
+        let pos_index = self.active_position_index(token_mint)
             .ok_or_else(|| BotError::TokenNotFound(token_mint.to_string()))?;
 
         // Get sell_amount before mut borrow
-        let sell_amount = {
+        let (sell_amount, decimals, vault_position, prior_received, vault_share) = {
             let position = &self.positions[pos_index];
-            amount.unwrap_or(position.amount)
+            let sell_amount = amount.unwrap_or(position.amount).min(position.amount);
+            // The vault records one close, so only the sale that empties the position reports it
+            let closes = sell_amount == position.amount;
+            (
+                sell_amount,
+                position.decimals,
+                position.vault_position.filter(|_| closes),
+                position.sol_received,
+                position.vault_share(),
+            )
         };
 
+        if self.config.dry_run {
+            let exit_price = self.get_token_price(token_mint).await?;
+            info!(
+                "🧪 [DRY RUN] Would sell {} tokens of {} at ${:.9}",
+                sell_amount, token_mint, exit_price
+            );
+            return Ok(self.settle_sell(pos_index, sell_amount, exit_price).await);
+        }
+
         // Get token account and graduation status before mut borrow
         let token_account = self.get_token_account(token_mint)?;
         let is_graduated = self.check_if_graduated(token_mint).await?;
 
         let transaction = if is_graduated {
-            info!("Token graduated - selling on Raydium");
-            self.build_raydium_sell_transaction(token_mint, &token_account, sell_amount).await?
+            info!("Token graduated - selling through Jupiter");
+            self.build_jupiter_sell_transaction(token_mint, sell_amount).await?
         } else {
             info!("Selling on pump.fun bonding curve");
-            self.build_sell_transaction(token_mint, &token_account, sell_amount).await?
+            self.build_sell_transaction(token_mint, &token_account, sell_amount).await?.into()
         };
 
         let signature = self.send_and_confirm_transaction(transaction).await?;
+        info!("✅ Sell transaction confirmed: {}", signature);
+
         let exit_price = self.get_token_price(token_mint).await?;
-        let sol_received = (sell_amount as f64 * exit_price) / 1e9;
+        if let Some(vault_position) = vault_position {
+            // Scale-ins never reached the vault, so it only hears about the opening buy's share
+            let sol_received = (prior_received + Amount::new(sell_amount, decimals).sol_value(exit_price))
+                * vault_share;
+            self.record_close_position(&vault_position, exit_price, sol_received).await;
+        }
+
+        Ok(self.settle_sell(pos_index, sell_amount, exit_price).await)
+    }
 
-        // Now update position
+    /// Settle selling `sell_amount` of the position at `pos_index` at `exit_price`,
+    /// returning the PnL of the tokens sold, in SOL
+    ///
+    /// The position closes once nothing is left; a partial sell only shrinks it.
+    async fn settle_sell(&mut self, pos_index: usize, sell_amount: u64, exit_price: f64) -> f64 {
         let position = &mut self.positions[pos_index];
-        let pnl = sol_received - position.sol_invested;
-        let pnl_percentage = (pnl / position.sol_invested) * 100.0;
-        position.status = PositionStatus::Closed;
+        let token_mint = position.token_mint;
+        let sell_amount = sell_amount.min(position.amount);
+        let sol_received = Amount::new(sell_amount, position.decimals).sol_value(exit_price);
+
+        let bought = position.initial_amount.max(position.amount).max(1);
+        let cost = position.sol_invested * sell_amount as f64 / bought as f64;
+        let pnl = sol_received - cost;
+        position.amount -= sell_amount;
+        position.sol_received += sol_received;
+        let remaining = position.amount;
+        let total_pnl = position.sol_received - position.sol_invested;
+        if remaining == 0 {
+            position.status = PositionStatus::Closed;
+            position.exit_price = Some(exit_price);
+            position.closed_at = Some(chrono::Utc::now().timestamp());
+        }
+
+        self.realized_pnl_sol += pnl;
+        self.risk.record_close(pnl);
+
+        if remaining > 0 {
+            info!(
+                mint = %token_mint,
+                sol_received,
+                pnl,
+                remaining,
+                "💵 Partial sell: {:.4} SOL received, PnL {:.4} SOL, {} tokens still held",
+                sol_received, pnl, remaining
+            );
+            return pnl;
+        }
+
+        // A scaled-out position's outcome is its total across every sell
+        let pnl_percentage = (total_pnl / self.positions[pos_index].sol_invested) * 100.0;
+        if total_pnl < 0.0 {
+            self.loss_cooldowns.insert(token_mint, chrono::Utc::now().timestamp());
+        }
+        if let Some(api_state) = &self.api_state {
+            api_state.record_close((total_pnl * 1e9) as i64).await;
+        }
 
         info!(
-            "✅ Sell transaction confirmed: {}\n\
-             💵 SOL received: {:.4}\n\
+            mint = %token_mint,
+            sol_received,
+            pnl,
+            pnl_percentage,
+            "💵 SOL received: {:.4}\n\
              📈 PnL: {:.4} SOL ({:+.2}%)",
-            signature, sol_received, pnl, pnl_percentage
+            sol_received, total_pnl, pnl_percentage
         );
 
-        Ok(pnl)
+        pnl
     }
 
     /// Monitor open positions and execute exit strategies
-    pub async fn monitor_positions(&mut self) -> Result<()> {
-        // Collect open positions' indices to avoid borrow checker issues
+    ///
+    /// Besides TP/SL/timeout, each held token is re-analyzed with `strategy` and
+    /// exited early when it now reads as a confident Sell/StrongSell.
+    pub async fn monitor_positions(
+        &mut self,
+        metrics_source: &dyn MetricsSource,
+        strategy: &dyn TradingStrategy,
+    ) -> Result<()> {
+        // Collect held positions' indices to avoid borrow checker issues
         let open_indices: Vec<_> = self.positions.iter().enumerate()
-            .filter(|(_, p)| p.status == PositionStatus::Open)
+            .filter(|(_, p)| p.status.is_active())
             .map(|(i, _)| i)
             .collect();
 
         for i in open_indices {
-            let (token_mint, take_profit_price, stop_loss_price, entry_time) = {
+            let (token_mint, entry_price, take_profit_price, stop_loss_price, entry_time) = {
                 let p = &self.positions[i];
-                (p.token_mint, p.take_profit_price, p.stop_loss_price, p.entry_time)
+                (p.token_mint, p.entry_price, p.take_profit_price, p.stop_loss_price, p.entry_time)
             };
+            if self.config.dry_run {
+                // Paper positions follow the metrics feed, keeping the last price on a miss
+                if let Ok(metrics) = metrics_source.get_metrics(&token_mint.to_string()).await {
+                    self.set_paper_price(token_mint, metrics.current_price);
+                }
+            }
             let current_price = self.get_token_price(&token_mint).await?;
             let time_elapsed = chrono::Utc::now().timestamp() - entry_time;
 
-            if current_price >= take_profit_price {
+            if !self.exit_params.take_profit_ladder.is_empty() {
+                self.sell_take_profit_rungs(i, current_price).await?;
+                if !self.positions[i].status.is_active() {
+                    continue;
+                }
+            } else if current_price >= take_profit_price {
                 info!("🎯 Take profit triggered for {}: ${:.6} >= ${:.6}", token_mint, current_price, take_profit_price);
                 self.sell_token(&token_mint, None).await?;
                 continue;
@@ -196,6 +505,33 @@ impl Trader {
                 self.sell_token(&token_mint, None).await?;
                 continue;
             }
+            if time_elapsed > self.config.absolute_max_hold_seconds as i64 {
+                warn!("⏰ Max hold time reached for {}: {} seconds elapsed", token_mint, time_elapsed);
+                self.sell_token(&token_mint, None).await?;
+                continue;
+            }
+            if let Some(conviction) = self.sell_signal_conviction(&token_mint, metrics_source, strategy).await {
+                warn!("📉 Sell signal for {}: {:.0}% conviction - exiting early", token_mint, conviction * 100.0);
+                self.sell_token(&token_mint, None).await?;
+                continue;
+            }
+            if self.exit_params.use_trailing_stop
+                && self.positions[i].status == PositionStatus::Open
+                && current_price >= entry_price * (1.0 + self.exit_params.trailing_activation_pct)
+            {
+                info!("📉 Trailing stop active for {}: ${:.6} is +{:.0}% over entry",
+                    token_mint, current_price, (current_price / entry_price - 1.0) * 100.0);
+                self.positions[i].status = PositionStatus::Monitoring;
+                if let Some(api_state) = &self.api_state {
+                    api_state.mark_trailing_active(&token_mint.to_string(), current_price).await;
+                }
+                if let Some(vault_position) = self.positions[i].vault_position {
+                    self.record_trailing_update(&vault_position, current_price).await;
+                }
+            }
+            if self.config.dry_run {
+                continue;
+            }
             let is_graduated = self.check_if_graduated(&token_mint).await?;
             if is_graduated {
                 info!("🎓 Token {} graduated to DEX - considering exit", token_mint);
@@ -205,37 +541,289 @@ impl Trader {
         Ok(())
     }
 
-    /// Build buy transaction for pump.fun
+    /// Sell into every take-profit rung `current_price` has reached that the position
+    /// at `pos_index` hasn't sold into yet, lowest first
+    async fn sell_take_profit_rungs(&mut self, pos_index: usize, current_price: f64) -> Result<()> {
+        loop {
+            let position = &self.positions[pos_index];
+            let Some(rung) = self.exit_params.take_profit_ladder.get(position.tp_rungs_hit).copied() else {
+                return Ok(());
+            };
+            if !position.status.is_active() || current_price < position.entry_price * rung.price_multiple {
+                return Ok(());
+            }
+
+            let token_mint = position.token_mint;
+            let bought = position.initial_amount.max(position.amount);
+            let sell_amount = ((bought as f64 * rung.fraction) as u64).min(position.amount);
+            if sell_amount == 0 {
+                debug!(
+                    "Take profit rung {} for {} rounds to nothing, skipping",
+                    position.tp_rungs_hit + 1, token_mint
+                );
+                self.positions[pos_index].tp_rungs_hit += 1;
+                continue;
+            }
+            info!(
+                "🎯 Take profit rung {} hit for {}: ${:.6} >= {}x entry, selling {:.0}%",
+                position.tp_rungs_hit + 1, token_mint, current_price, rung.price_multiple, rung.fraction * 100.0
+            );
+
+            self.sell_token(&token_mint, Some(sell_amount)).await?;
+            self.positions[pos_index].tp_rungs_hit += 1;
+        }
+    }
+
+    /// Market-sell every held position in `token_mint`, returning how many closed
+    pub async fn close_mint(&mut self, token_mint: &Pubkey) -> usize {
+        let mut closed = 0;
+        while self.active_position_index(token_mint).is_some() {
+            if let Err(e) = self.sell_token(token_mint, None).await {
+                error!("Failed to force-close {}: {}", token_mint, e);
+                break;
+            }
+            closed += 1;
+        }
+        closed
+    }
+
+    /// Stop trading: sell every held position when `close_positions`, then write
+    /// whatever is still held to `positions_file`
+    ///
+    /// Returns how many positions were persisted. A failed sale leaves the position
+    /// held, so it is persisted rather than lost.
+    pub async fn shutdown(&mut self, close_positions: bool) -> Result<usize> {
+        if close_positions {
+            let held: Vec<Pubkey> = self.get_active_positions().iter().map(|p| p.token_mint).collect();
+            for token_mint in held {
+                if let Err(e) = self.sell_token(&token_mint, None).await {
+                    error!("Failed to close {} on shutdown: {}", token_mint, e);
+                }
+            }
+        }
+
+        let path = self.config.positions_file.clone();
+        self.persist_positions(Path::new(&path))
+    }
+
+    /// Write held positions to `path` as JSON
+    pub fn persist_positions(&self, path: &Path) -> Result<usize> {
+        let held = self.get_active_positions();
+        let json = serde_json::to_string_pretty(&held)?;
+        std::fs::write(path, json)
+            .map_err(|e| BotError::Unknown(format!("Failed to write {}: {}", path.display(), e)))?;
+
+        Ok(held.len())
+    }
+
+    /// Correct held positions that diverged from their vault records, e.g. after a
+    /// crash: ones the vault already closed are marked closed at its exit price and
+    /// ones with no vault position are dropped, so they are never sold again.
+    /// Positions without a vault record are left alone.
+    ///
+    /// Every record is fetched before anything changes, so an RPC failure leaves
+    /// local state as it was.
+    pub fn reconcile_with_chain(&mut self, client: &VaultClient) -> Result<ReconcileReport> {
+        let mut on_chain = Vec::new();
+        for (i, position) in self.positions.iter().enumerate() {
+            if let (true, Some(vault_position)) = (position.status.is_active(), position.vault_position) {
+                on_chain.push((i, client.fetch_position(&vault_position)?));
+            }
+        }
+
+        let mut report = ReconcileReport { checked: on_chain.len(), ..Default::default() };
+        let mut missing = Vec::new();
+        for (i, state) in on_chain {
+            let position = &mut self.positions[i];
+            match state {
+                None => {
+                    warn!("Dropping {}: its vault position doesn't exist", position.token_mint);
+                    report.dropped.push(position.token_mint);
+                    missing.push(i);
+                }
+                Some(state) if state.status != VaultPositionStatus::Open => {
+                    warn!("Marking {} closed: the vault already closed it", position.token_mint);
+                    position.status = PositionStatus::Closed;
+                    position.amount = 0;
+                    position.exit_price = Some(state.current_price);
+                    position.closed_at = Some(state.closed_at);
+                    report.closed.push(position.token_mint);
+                }
+                Some(_) => {}
+            }
+        }
+
+        // Back to front, so the remaining indices stay valid
+        for i in missing.into_iter().rev() {
+            self.positions.remove(i);
+        }
+
+        Ok(report)
+    }
+
+    /// Write every closed position to `path` as CSV; returns how many were written
+    pub fn export_positions_csv(&self, path: &Path) -> Result<usize> {
+        let records: Vec<TradeRecord> = self.positions.iter()
+            .filter_map(|p| self.trade_record(p))
+            .collect();
+        let exported = records.len();
+
+        let file = std::fs::File::create(path)
+            .map_err(|e| BotError::Unknown(format!("Failed to create {}: {}", path.display(), e)))?;
+        export::write_trades(std::io::BufWriter::new(file), records)
+            .map_err(|e| BotError::Unknown(format!("Failed to write {}: {}", path.display(), e)))?;
+
+        Ok(exported)
+    }
+
+    /// Export row for a closed position; the trader doesn't know token symbols
+    fn trade_record(&self, position: &Position) -> Option<TradeRecord> {
+        if position.status != PositionStatus::Closed {
+            return None;
+        }
+        let (exit_price, closed_at) = (position.exit_price?, position.closed_at?);
+        let pnl = position.sol_received - position.sol_invested;
+
+        Some(TradeRecord {
+            mint: position.token_mint.to_string(),
+            symbol: String::new(),
+            entry_price: position.entry_price,
+            exit_price,
+            sol_invested: position.sol_invested,
+            sol_received: position.sol_received,
+            pnl,
+            pnl_pct: if position.sol_invested > 0.0 { pnl / position.sol_invested * 100.0 } else { 0.0 },
+            opened_at: position.entry_time,
+            closed_at,
+            hold_seconds: closed_at - position.entry_time,
+            strategy: Some(self.config.strategy_type),
+        })
+    }
+
+    /// Conviction of a fresh Sell/StrongSell read on a held token, when it clears
+    /// `sell_signal_min_confidence`
+    ///
+    /// Signal confidence is a buy score, so a sell's conviction is its complement.
+    /// Metrics or analysis failures never force an exit.
+    async fn sell_signal_conviction(
+        &self,
+        token_mint: &Pubkey,
+        metrics_source: &dyn MetricsSource,
+        strategy: &dyn TradingStrategy,
+    ) -> Option<f64> {
+        let signal = match metrics_source.get_metrics(&token_mint.to_string()).await {
+            Ok(metrics) => strategy.analyze(&metrics),
+            Err(e) => Err(e),
+        };
+        let signal = match signal {
+            Ok(signal) => signal,
+            Err(e) => {
+                debug!("Skipping re-analysis of held token {}: {}", token_mint, e);
+                return None;
+            }
+        };
+
+        let conviction = 1.0 - signal.confidence;
+        let is_sell = matches!(signal.signal_type, SignalType::Sell | SignalType::StrongSell);
+        (is_sell && conviction >= self.config.sell_signal_min_confidence).then_some(conviction)
+    }
+
+    /// Expected tokens out and fractional price impact for buying `sol_amount` SOL
+    /// on the bonding curve
+    pub fn estimate_buy_output(&self, token_mint: &Pubkey, sol_amount: f64) -> Result<(u64, f64)> {
+        let lamports = sol_to_lamports(sol_amount);
+        let curve = self.read_bonding_curve(token_mint)?;
+
+        Ok((curve.buy_token_amount(lamports), curve.buy_price_impact(lamports)))
+    }
+
+    /// Build buy transaction for pump.fun, creating the token account first when needed
     async fn build_buy_transaction(
         &self,
-        _token_mint: &Pubkey,
+        token_mint: &Pubkey,
         token_account: &Pubkey,
+        create_token_account: Option<Instruction>,
         sol_amount: f64,
     ) -> Result<Transaction> {
-        // TODO: Implement actual pump.fun buy instruction
-        // This is a placeholder - actual implementation would need:
-        // 1. Get bonding curve PDA
-        // 2. Calculate expected token amount
-        // 3. Build swap instruction with slippage protection
-        
-        let lamports = (sol_amount * 1e9) as u64;
-        
-        let instruction = system_instruction::transfer(
+        let lamports = sol_to_lamports(sol_amount);
+
+        // Expected output comes from the current bonding curve reserves
+        let curve = self.read_bonding_curve(token_mint)?;
+
+        let token_amount = curve.buy_token_amount(lamports);
+        let max_sol_cost = pump_fun::max_sol_cost_with_slippage(lamports, self.config.max_slippage_bps);
+
+        let instruction = pump_fun::build_buy_instruction(
+            &self.config.pump_fun_program,
             &self.config.wallet_keypair.pubkey(),
+            token_mint,
             token_account,
-            lamports,
+            token_amount,
+            max_sol_cost,
         );
 
-        let recent_blockhash = self.rpc_client.get_latest_blockhash()?;
-        
-        let transaction = Transaction::new_signed_with_payer(
-            &[instruction],
-            Some(&self.config.wallet_keypair.pubkey()),
-            &[&self.config.wallet_keypair],
-            recent_blockhash,
+        let instructions = create_token_account.into_iter().chain([instruction]).collect();
+        self.sign_transaction(instructions)
+    }
+
+    /// Build a SOL -> token swap through the mint's Raydium AMM v4 pool
+    ///
+    /// The SOL is wrapped into a temporary WSOL account that is closed again after the
+    /// swap; the minimum out is the pool's constant-product quote less `max_slippage_bps`.
+    async fn build_raydium_buy_transaction(
+        &self,
+        token_mint: &Pubkey,
+        token_account: &Pubkey,
+        create_token_account: Option<Instruction>,
+        sol_amount: f64,
+    ) -> Result<Transaction> {
+        let lamports = sol_to_lamports(sol_amount);
+        let amm_program = self.config.raydium_amm_program;
+        let (amm_id, pool) = self.find_raydium_pool(token_mint)?;
+        let amm_authority = pool.authority(&amm_program)
+            .ok_or_else(|| BotError::Unknown(format!("Invalid Raydium authority nonce for {}", amm_id)))?;
+        let market = self.read_market(&pool)?;
+
+        // Expected output from the current pool reserves
+        let token_reserve = self.get_token_balance(&pool.base_vault)?;
+        let sol_reserve = self.get_token_balance(&pool.quote_vault)?;
+        let amount_out = raydium::swap_amount_out(sol_reserve, token_reserve, lamports);
+        let min_amount_out = raydium::min_amount_out_with_slippage(amount_out, self.config.max_slippage_bps);
+
+        let owner = self.config.wallet_keypair.pubkey();
+        let native_mint = spl_token::native_mint::id();
+        let wsol_account = spl_associated_token_account::get_associated_token_address(&owner, &native_mint);
+        let wrap_sol = [
+            spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                &owner,
+                &owner,
+                &native_mint,
+                &spl_token::id(),
+            ),
+            system_instruction::transfer(&owner, &wsol_account, lamports),
+            spl_token::instruction::sync_native(&spl_token::id(), &wsol_account)
+                .map_err(|e| BotError::Unknown(format!("Failed to build sync_native: {}", e)))?,
+        ];
+        let swap = raydium::build_swap_base_in_instruction(
+            &amm_program,
+            &amm_id,
+            &amm_authority,
+            &pool,
+            &market,
+            &wsol_account,
+            token_account,
+            &owner,
+            lamports,
+            min_amount_out,
         );
+        let unwrap_sol = spl_token::instruction::close_account(&spl_token::id(), &wsol_account, &owner, &owner, &[])
+            .map_err(|e| BotError::Unknown(format!("Failed to build close_account: {}", e)))?;
 
-        Ok(transaction)
+        let instructions = wrap_sol.into_iter()
+            .chain(create_token_account)
+            .chain([swap, unwrap_sol])
+            .collect();
+        self.sign_transaction(instructions)
     }
 
     /// Build sell transaction for pump.fun
@@ -254,61 +842,295 @@ impl Trader {
             amount,
         );
 
-        let recent_blockhash = self.rpc_client.get_latest_blockhash()?;
-        
-        let transaction = Transaction::new_signed_with_payer(
-            &[instruction],
-            Some(&self.config.wallet_keypair.pubkey()),
-            &[&self.config.wallet_keypair],
-            recent_blockhash,
+        self.sign_transaction(vec![instruction])
+    }
+
+    /// Build a token -> SOL swap through Jupiter for graduated tokens
+    async fn build_jupiter_sell_transaction(
+        &self,
+        token_mint: &Pubkey,
+        amount: u64,
+    ) -> Result<VersionedTransaction> {
+        let quote = self.jupiter.quote(token_mint, &spl_token::native_mint::id(), amount).await?;
+        info!(
+            "Jupiter quote: {} tokens -> {} lamports (min {}, impact {}%)",
+            amount, quote.out_amount, quote.other_amount_threshold, quote.price_impact_pct
         );
 
-        Ok(transaction)
+        self.jupiter.swap_transaction(&quote, &self.config.wallet_keypair).await
     }
 
-    /// Build sell transaction for Raydium DEX
-    async fn build_raydium_sell_transaction(
+    /// Record a new position in the vault program; failures are logged, not fatal,
+    /// since the swap itself has already landed
+    #[allow(clippy::too_many_arguments)]
+    async fn record_open_position(
         &self,
         token_mint: &Pubkey,
-        token_account: &Pubkey,
-        amount: u64,
-    ) -> Result<Transaction> {
-        // TODO: Implement Raydium swap instruction
-        // Would use Raydium SDK to build swap through liquidity pool
+        sol_amount: f64,
+        entry_price: f64,
+        take_profit_price: f64,
+        stop_loss_price: f64,
+        entry_confidence: f64,
+        liquidity_sol: f64,
+    ) -> Option<Pubkey> {
+        let vault = self.vault.as_ref()?;
+        let bot_authority = self.config.wallet_keypair.pubkey();
+
+        // Fixed before the first send, so a rebroadcast lands on the same position PDA
+        let client_order_id = chrono::Utc::now().timestamp_millis() as u64;
+
+        let result = async {
+            let (position, instruction) = vault.open_position_instruction(
+                &bot_authority,
+                client_order_id,
+                token_mint,
+                sol_amount,
+                entry_price,
+                take_profit_price,
+                stop_loss_price,
+                self.config.strategy_type.on_chain_id(),
+                entry_confidence,
+                self.exit_params.use_trailing_stop.then_some((
+                    self.exit_params.trailing_activation_pct,
+                    self.exit_params.trailing_distance_pct,
+                )),
+                liquidity_sol,
+            )?;
+            let transaction = self.sign_transaction(vec![instruction])?;
+            let signature = self.send_and_confirm_transaction(transaction).await?;
+            Ok::<_, BotError>((position, signature))
+        }.await;
 
-        warn!("Raydium sell not yet implemented - using placeholder");
-        self.build_sell_transaction(token_mint, token_account, amount).await
+        match result {
+            Ok((position, signature)) => {
+                info!("🏦 Vault position {} opened: {}", position, signature);
+                Some(position)
+            }
+            Err(e) => {
+                warn!("Failed to record position for {} in vault: {}", token_mint, e);
+                None
+            }
+        }
     }
 
-    /// Send and confirm transaction with retries
-    async fn send_and_confirm_transaction(&self, transaction: Transaction) -> Result<String> {
-        let signature = self.rpc_client.send_and_confirm_transaction(&transaction)?;
-        Ok(signature.to_string())
+    async fn record_close_position(&self, vault_position: &Pubkey, exit_price: f64, sol_received: f64) {
+        let Some(vault) = self.vault.as_ref() else {
+            return;
+        };
+
+        let instruction = vault.close_position_instruction(
+            &self.config.wallet_keypair.pubkey(),
+            vault_position,
+            exit_price,
+            sol_received,
+            // The bot arms trailing stops but doesn't exit on them yet
+            false,
+        );
+        let result = match self.sign_transaction(vec![instruction]) {
+            Ok(transaction) => self.send_and_confirm_transaction(transaction).await,
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(signature) => info!("🏦 Vault position {} closed: {}", vault_position, signature),
+            Err(e) => warn!("Failed to close vault position {}: {}", vault_position, e),
+        }
+    }
+
+    /// Report a price to the vault position so the program arms its trailing stop
+    async fn record_trailing_update(&self, vault_position: &Pubkey, current_price: f64) {
+        let Some(vault) = self.vault.as_ref() else {
+            return;
+        };
+
+        let instruction = vault.update_trailing_instruction(
+            &self.config.wallet_keypair.pubkey(),
+            vault_position,
+            current_price,
+        );
+        let result = match self.sign_transaction(vec![instruction]) {
+            Ok(transaction) => self.send_and_confirm_transaction(transaction).await,
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(signature) => info!("🏦 Vault position {} trailing updated: {}", vault_position, signature),
+            Err(e) => warn!("Failed to update trailing stop of vault position {}: {}", vault_position, e),
+        }
+    }
+
+    /// Sign `instructions` behind compute-budget instructions carrying the priority fee
+    fn sign_transaction(&self, instructions: Vec<Instruction>) -> Result<Transaction> {
+        let priority_fee = self.priority_fee(&instructions);
+        let instructions: Vec<Instruction> = [
+            ComputeBudgetInstruction::set_compute_unit_limit(COMPUTE_UNIT_LIMIT),
+            ComputeBudgetInstruction::set_compute_unit_price(priority_fee),
+        ]
+        .into_iter()
+        .chain(instructions)
+        .collect();
+
+        let recent_blockhash = self.rpc.call(|client| client.get_latest_blockhash())?;
+
+        Ok(Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&self.config.wallet_keypair.pubkey()),
+            &[&self.config.wallet_keypair],
+            recent_blockhash,
+        ))
+    }
+
+    /// Configured compute-unit price, raised to the recent median for the
+    /// instructions' writable accounts when dynamic fees are enabled
+    fn priority_fee(&self, instructions: &[Instruction]) -> u64 {
+        let floor = self.config.priority_fee_micro_lamports;
+        if !self.config.dynamic_priority_fee {
+            return floor;
+        }
+
+        let writable: Vec<Pubkey> = instructions.iter()
+            .flat_map(|ix| &ix.accounts)
+            .filter(|a| a.is_writable)
+            .map(|a| a.pubkey)
+            .collect();
+
+        match self.rpc.call(|client| client.get_recent_prioritization_fees(&writable)) {
+            Ok(recent) => {
+                let mut fees: Vec<u64> = recent.iter()
+                    .map(|f| f.prioritization_fee)
+                    .filter(|fee| *fee > 0)
+                    .collect();
+                if fees.is_empty() {
+                    return floor;
+                }
+                fees.sort_unstable();
+                fees[fees.len() / 2].max(floor)
+            }
+            Err(e) => {
+                warn!("Failed to fetch recent prioritization fees, using {}: {}", floor, e);
+                floor
+            }
+        }
+    }
+
+    /// Send a transaction and poll until it confirms
+    ///
+    /// Each attempt polls for `confirm_timeout_ms`, then rebroadcasts. The transaction is
+    /// only re-signed with a fresh blockhash once the old one has expired, so at most one
+    /// copy can ever land.
+    async fn send_and_confirm_transaction(&self, transaction: impl Into<VersionedTransaction>) -> Result<String> {
+        let mut transaction = transaction.into();
+        if self.config.preflight_simulation {
+            self.preflight(&transaction).await?;
+        }
+        let max_attempts = self.config.send_max_attempts;
+        let timeout = Duration::from_millis(self.config.confirm_timeout_ms);
+        let poll_interval = Duration::from_millis(CONFIRM_POLL_INTERVAL_MS).min(timeout);
+
+        for attempt in 1..=max_attempts {
+            let send_config = RpcSendTransactionConfig {
+                // A rebroadcast of the same signature would fail simulation as already processed,
+                // and a first send that passed our own preflight needn't be simulated twice
+                skip_preflight: attempt > 1 || self.config.preflight_simulation,
+                ..Default::default()
+            };
+            let signature = self.rpc
+                .call_async(|client| client.send_transaction_with_config(&transaction, send_config))
+                .await?;
+
+            let deadline = tokio::time::Instant::now() + timeout;
+            while tokio::time::Instant::now() < deadline {
+                tokio::time::sleep(poll_interval).await;
+
+                let status = self.rpc
+                    .call_async(|client| client.get_signature_status(&signature))
+                    .await?;
+                if let Some(status) = status {
+                    status.map_err(|e| BotError::TransactionFailed(format!("{}: {}", signature, e)))?;
+                    return Ok(signature.to_string());
+                }
+            }
+
+            warn!(
+                "⏳ Transaction {} not confirmed after {}ms (attempt {}/{})",
+                signature, self.config.confirm_timeout_ms, attempt, max_attempts
+            );
+
+            if attempt < max_attempts {
+                let recent_blockhash = *transaction.message.recent_blockhash();
+                let blockhash_valid = self.rpc
+                    .call_async(|client| client.is_blockhash_valid(&recent_blockhash, CommitmentConfig::processed()))
+                    .await?;
+                if !blockhash_valid {
+                    let mut message = transaction.message;
+                    message.set_recent_blockhash(
+                        self.rpc.call_async(|client| client.get_latest_blockhash()).await?,
+                    );
+                    transaction = VersionedTransaction::try_new(message, &[&self.config.wallet_keypair])
+                        .map_err(|e| BotError::Unknown(format!("Failed to re-sign transaction: {}", e)))?;
+                }
+            }
+        }
+
+        Err(BotError::TradeTimeout)
+    }
+
+    /// Simulate `transaction`, failing with its error and program log if it wouldn't succeed
+    async fn preflight(&self, transaction: &VersionedTransaction) -> Result<()> {
+        let simulation = self.rpc
+            .call_async(|client| client.simulate_transaction(transaction))
+            .await?
+            .value;
+
+        match simulation.err {
+            Some(err) => {
+                let logs = simulation.logs.unwrap_or_default();
+                warn!("🧪 Preflight simulation failed: {}", err);
+                for line in &logs {
+                    debug!("    {}", line);
+                }
+                Err(BotError::SimulationFailed { err: err.to_string(), logs })
+            }
+            None => Ok(()),
+        }
     }
 
     /// Get wallet SOL balance
     fn get_wallet_balance(&self) -> Result<f64> {
-        let balance = self.rpc_client.get_balance(&self.config.wallet_keypair.pubkey())?;
-        Ok(balance as f64 / 1e9)
+        let owner = self.config.wallet_keypair.pubkey();
+        let balance = self.rpc.call(|client| client.get_balance(&owner))?;
+        Ok(lamports_to_sol(balance))
     }
 
-    /// Get or create associated token account
-    async fn get_or_create_token_account(&self, token_mint: &Pubkey) -> Result<Pubkey> {
+    /// Get the associated token account, plus an idempotent create instruction when
+    /// it doesn't exist yet
+    ///
+    /// The caller folds the instruction into its buy so the account only ever exists
+    /// alongside a filled trade.
+    async fn get_or_create_token_account(&self, token_mint: &Pubkey) -> Result<(Pubkey, Option<Instruction>)> {
+        let owner = self.config.wallet_keypair.pubkey();
         let associated_token_address = spl_associated_token_account::get_associated_token_address(
-            &self.config.wallet_keypair.pubkey(),
+            &owner,
             token_mint,
         );
 
         // Check if account exists
-        if self.rpc_client.get_account(&associated_token_address).is_ok() {
-            return Ok(associated_token_address);
+        let existing = self.rpc
+            .call(|client| client.get_account_with_commitment(&associated_token_address, client.commitment()))?
+            .value;
+        if existing.is_some() {
+            return Ok((associated_token_address, None));
         }
 
-        // Create account
         info!("Creating associated token account for {}", token_mint);
-        // TODO: Implement account creation
+        let create = spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            &owner,
+            &owner,
+            token_mint,
+            &spl_token::id(),
+        );
 
-        Ok(associated_token_address)
+        Ok((associated_token_address, Some(create)))
     }
 
     /// Get existing token account
@@ -319,35 +1141,1304 @@ impl Trader {
         ))
     }
 
-    /// Get token balance
-    fn get_token_balance(&self, _token_account: &Pubkey) -> Result<u64> {
-        // TODO: Implement actual token balance check
-        Ok(0)
+    /// Get raw token balance of a token account
+    fn get_token_balance(&self, token_account: &Pubkey) -> Result<u64> {
+        Ok(self.get_token_amount(token_account)?.raw)
     }
 
-    /// Get current token price
-    async fn get_token_price(&self, _token_mint: &Pubkey) -> Result<f64> {
-        // TODO: Implement actual price fetch from bonding curve or DEX
-        Ok(0.001)
+    /// Raw balance of a token account, with the decimals the RPC reads from its mint
+    fn get_token_amount(&self, token_account: &Pubkey) -> Result<Amount> {
+        let balance = self.rpc.call(|client| client.get_token_account_balance(token_account))
+            .map_err(|e| {
+                if e.to_string().contains("could not find account") {
+                    BotError::TokenNotFound(token_account.to_string())
+                } else {
+                    BotError::SolanaClient(e)
+                }
+            })?;
+
+        let raw = balance.amount.parse::<u64>()
+            .map_err(|e| BotError::Unknown(format!("Invalid token amount '{}': {}", balance.amount, e)))?;
+        Ok(Amount::new(raw, balance.decimals))
+    }
+
+    /// Get current token price in SOL, from the bonding curve or the DEX pool once graduated
+    ///
+    /// Dry runs only ever see the simulated price set for the mint.
+    async fn get_token_price(&self, token_mint: &Pubkey) -> Result<f64> {
+        if self.config.dry_run {
+            return self.paper_prices.get(token_mint).copied()
+                .ok_or_else(|| BotError::TokenNotFound(token_mint.to_string()));
+        }
+
+        match self.read_bonding_curve(token_mint) {
+            Ok(curve) if !curve.complete => Ok(curve.spot_price()),
+            // Complete or closed curve - liquidity has migrated to Raydium
+            Ok(_) | Err(BotError::TokenNotFound(_)) => self.get_dex_price(token_mint),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Fetch and decode the pump.fun bonding curve account for a mint
+    fn read_bonding_curve(&self, token_mint: &Pubkey) -> Result<pump_fun::BondingCurveState> {
+        let address = pump_fun::derive_bonding_curve(&self.config.pump_fun_program, token_mint);
+        let account = self.rpc
+            .call(|client| client.get_account_with_commitment(&address, client.commitment()))?
+            .value
+            .ok_or_else(|| BotError::TokenNotFound(token_mint.to_string()))?;
+
+        pump_fun::BondingCurveState::from_account_data(&account.data)
+            .ok_or_else(|| BotError::Unknown(format!("Malformed bonding curve for {}", token_mint)))
+    }
+
+    /// Price from the token/WSOL Raydium pool vault balances
+    fn get_dex_price(&self, token_mint: &Pubkey) -> Result<f64> {
+        let (_, pool_keys) = self.find_raydium_pool(token_mint)?;
+
+        let base = self.rpc.call(|client| client.get_token_account_balance(&pool_keys.base_vault))?;
+        let quote = self.rpc.call(|client| client.get_token_account_balance(&pool_keys.quote_vault))?;
+
+        let base_amount = base.ui_amount.unwrap_or(0.0);
+        if base_amount <= 0.0 {
+            return Err(BotError::TokenNotFound(token_mint.to_string()));
+        }
+
+        Ok(quote.ui_amount.unwrap_or(0.0) / base_amount)
+    }
+
+    /// Locate the Raydium AMM v4 pool pairing the mint with WSOL, returning its address and keys
+    fn find_raydium_pool(&self, token_mint: &Pubkey) -> Result<(Pubkey, raydium::PoolKeys)> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(raydium::pool_filters(token_mint)),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let pools = self.rpc
+            .call(|client| client.get_program_accounts_with_config(&self.config.raydium_amm_program, config.clone()))?;
+        let (pool_address, pool_account) = pools.first()
+            .ok_or_else(|| BotError::TokenNotFound(token_mint.to_string()))?;
+
+        let pool_keys = raydium::PoolKeys::from_account_data(&pool_account.data)
+            .ok_or_else(|| BotError::Unknown(format!("Malformed Raydium pool for {}", token_mint)))?;
+        Ok((*pool_address, pool_keys))
+    }
+
+    /// Fetch and decode the OpenBook market a Raydium pool is paired with
+    fn read_market(&self, pool: &raydium::PoolKeys) -> Result<raydium::MarketKeys> {
+        let account = self.rpc
+            .call(|client| client.get_account_with_commitment(&pool.market_id, client.commitment()))?
+            .value
+            .ok_or_else(|| BotError::TokenNotFound(pool.market_id.to_string()))?;
+
+        raydium::MarketKeys::from_account_data(&pool.market_id, &pool.market_program_id, &account.data)
+            .ok_or_else(|| BotError::Unknown(format!("Malformed market {}", pool.market_id)))
     }
 
     /// Check if token graduated to DEX
-    async fn check_if_graduated(&self, _token_mint: &Pubkey) -> Result<bool> {
-        // TODO: Check if bonding curve is complete and token moved to Raydium
-        Ok(false)
+    ///
+    /// A token has graduated once its bonding curve is marked `complete`, or when the
+    /// curve account has been closed and a Raydium pool exists for the mint.
+    async fn check_if_graduated(&self, token_mint: &Pubkey) -> Result<bool> {
+        match self.read_bonding_curve(token_mint) {
+            Ok(curve) => Ok(curve.complete),
+            Err(BotError::TokenNotFound(_)) => match self.find_raydium_pool(token_mint) {
+                Ok(_) => Ok(true),
+                Err(BotError::TokenNotFound(_)) => Ok(false),
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Index of the held position in `token_mint`, if any
+    fn active_position_index(&self, token_mint: &Pubkey) -> Option<usize> {
+        self.positions.iter().position(|p| &p.token_mint == token_mint && p.status.is_active())
     }
 
     /// Get active positions
     pub fn get_active_positions(&self) -> Vec<&Position> {
         self.positions.iter()
-            .filter(|p| p.status == PositionStatus::Open)
+            .filter(|p| p.status.is_active())
             .collect()
     }
 
     /// Get position count
     pub fn position_count(&self) -> usize {
         self.positions.iter()
-            .filter(|p| p.status == PositionStatus::Open)
+            .filter(|p| p.status.is_active())
             .count()
     }
+
+    /// Push live prices and PnL of held positions to the API
+    pub async fn publish_live_positions(&self) {
+        if let Some(api_state) = &self.api_state {
+            api_state.refresh_live_positions(self.snapshot_positions().await).await;
+        }
+    }
+
+    /// Active positions at live prices for the API
+    ///
+    /// A position whose price can't be fetched is reported at its entry price with
+    /// zero PnL and `stale` set, rather than failing the whole snapshot.
+    pub async fn snapshot_positions(&self) -> Vec<PositionInfo> {
+        let user = self.config.vault_delegation_user
+            .unwrap_or_else(|| self.config.wallet_keypair.pubkey())
+            .to_string();
+        let mut snapshot = Vec::new();
+
+        for position in self.get_active_positions() {
+            let (current_price, pnl, stale) = match self.get_token_price(&position.token_mint).await {
+                Ok(price) => (price, position.sol_value(price) - position.remaining_cost_sol(), false),
+                Err(e) => {
+                    warn!("No live price for {}, reporting it stale: {}", position.token_mint, e);
+                    (position.entry_price, 0.0, true)
+                }
+            };
+            let pnl_percentage = if position.remaining_cost_sol() > 0.0 {
+                (pnl / position.remaining_cost_sol()) * 100.0
+            } else {
+                0.0
+            };
+
+            snapshot.push(PositionInfo {
+                position_id: position.vault_position
+                    .unwrap_or(position.token_mint)
+                    .to_string(),
+                user: user.clone(),
+                token_mint: position.token_mint.to_string(),
+                token_symbol: String::new(),
+                amount_sol: position.sol_invested,
+                entry_price: vault::to_scaled_price(position.entry_price),
+                current_price: vault::to_scaled_price(current_price),
+                take_profit_price: vault::to_scaled_price(position.take_profit_price),
+                stop_loss_price: vault::to_scaled_price(position.stop_loss_price),
+                status: match position.status {
+                    PositionStatus::Monitoring => "monitoring",
+                    _ => "open",
+                }.to_string(),
+                pnl: (pnl * 1e9) as i64,
+                pnl_percentage,
+                opened_at: position.entry_time,
+                closed_at: None,
+                strategy: self.config.strategy_type.on_chain_id(),
+                entry_confidence_bps: 0,
+                stale,
+            });
+        }
+
+        snapshot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::testing::{mock_version, mock_version_async, rpc_result};
+    use base64::Engine;
+    use mockito::Matcher;
+
+    fn bonding_curve_data(virtual_sol: u64, virtual_tokens: u64, complete: bool) -> Vec<u8> {
+        let mut data = vec![0u8; 8];
+        for value in [virtual_tokens, virtual_sol, virtual_tokens, virtual_sol, 1_000_000_000_000_000] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.push(complete as u8);
+        data
+    }
+
+    /// Metrics source that always returns the same metrics, or fails when unset
+    struct FixedMetrics(Option<crate::types::TokenMetrics>);
+
+    #[async_trait::async_trait]
+    impl MetricsSource for FixedMetrics {
+        async fn get_metrics(&self, mint: &str) -> Result<crate::types::TokenMetrics> {
+            self.0.clone().ok_or_else(|| BotError::TokenNotFound(mint.to_string()))
+        }
+    }
+
+    fn account_info(data: &[u8]) -> serde_json::Value {
+        serde_json::json!({
+            "context": { "slot": 1 },
+            "value": {
+                "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+                "executable": false,
+                "lamports": 1_000_000,
+                "owner": crate::pump_fun::PUMP_FUN_PROGRAM_ID,
+                "rentEpoch": 0
+            }
+        })
+    }
+
+    #[test]
+    fn test_rpc_clients_use_configured_commitment() {
+        for (name, expected) in [
+            ("processed", CommitmentConfig::processed()),
+            ("confirmed", CommitmentConfig::confirmed()),
+            ("Finalized", CommitmentConfig::finalized()),
+        ] {
+            let mut config = BotConfig::for_tests("http://127.0.0.1:1");
+            config.commitment = name.parse().unwrap();
+
+            let trader = Trader::new(config);
+            assert_eq!(trader.rpc.commitment(), expected);
+        }
+
+        let err = "recent".parse::<crate::types::RpcCommitment>().unwrap_err();
+        assert!(err.to_string().contains("Unknown commitment level: recent"), "{}", err);
+    }
+
+    #[test]
+    fn test_get_token_balance_reads_raw_amount() {
+        let mut server = mockito::Server::new();
+        let _version = mock_version(&mut server);
+        let _mock = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getTokenAccountBalance"}"#.to_string()))
+            .with_body(rpc_result(serde_json::json!({
+                "context": { "slot": 1 },
+                "value": { "amount": "1500000", "decimals": 6, "uiAmount": 1.5, "uiAmountString": "1.5" }
+            })))
+            .create();
+
+        let trader = Trader::new(BotConfig::for_tests(&server.url()));
+        assert_eq!(trader.get_token_balance(&Pubkey::new_unique()).unwrap(), 1_500_000);
+    }
+
+    #[test]
+    fn test_get_token_price_from_bonding_curve() {
+        let mut server = mockito::Server::new();
+        let _version = mock_version(&mut server);
+        // 30 SOL against 1,073,000,000 tokens (6 decimals)
+        let data = bonding_curve_data(30_000_000_000, 1_073_000_000_000_000, false);
+        let _mock = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getAccountInfo"}"#.to_string()))
+            .with_body(rpc_result(account_info(&data)))
+            .create();
+
+        let trader = Trader::new(BotConfig::for_tests(&server.url()));
+        let price = futures::executor::block_on(trader.get_token_price(&Pubkey::new_unique())).unwrap();
+
+        let expected = 30.0 / 1_073_000_000.0;
+        assert!((price - expected).abs() / expected < 1e-9);
+    }
+
+    #[test]
+    fn test_check_if_graduated_complete_curve() {
+        let mut server = mockito::Server::new();
+        let _version = mock_version(&mut server);
+        let data = bonding_curve_data(85_000_000_000, 280_000_000_000_000, true);
+        let _mock = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getAccountInfo"}"#.to_string()))
+            .with_body(rpc_result(account_info(&data)))
+            .create();
+
+        let trader = Trader::new(BotConfig::for_tests(&server.url()));
+        assert!(futures::executor::block_on(trader.check_if_graduated(&Pubkey::new_unique())).unwrap());
+    }
+
+    #[test]
+    fn test_check_if_graduated_closed_curve_without_pool() {
+        let mut server = mockito::Server::new();
+        let _version = mock_version(&mut server);
+        let _curve = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getAccountInfo"}"#.to_string()))
+            .with_body(rpc_result(serde_json::json!({ "context": { "slot": 1 }, "value": null })))
+            .create();
+        let _pools = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getProgramAccounts"}"#.to_string()))
+            .with_body(rpc_result(serde_json::json!([])))
+            .create();
+
+        let trader = Trader::new(BotConfig::for_tests(&server.url()));
+        assert!(!futures::executor::block_on(trader.check_if_graduated(&Pubkey::new_unique())).unwrap());
+    }
+
+    #[test]
+    fn test_get_token_price_missing_curve_is_not_found() {
+        let mut server = mockito::Server::new();
+        let _version = mock_version(&mut server);
+        let _mock = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getAccountInfo"}"#.to_string()))
+            .with_body(rpc_result(serde_json::json!({ "context": { "slot": 1 }, "value": null })))
+            .create();
+        let _pools = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getProgramAccounts"}"#.to_string()))
+            .with_body(rpc_result(serde_json::json!([])))
+            .create();
+
+        let trader = Trader::new(BotConfig::for_tests(&server.url()));
+        let result = futures::executor::block_on(trader.get_token_price(&Pubkey::new_unique()));
+        assert!(matches!(result, Err(BotError::TokenNotFound(_))));
+    }
+
+    #[test]
+    fn test_buy_rejected_on_thin_curve() {
+        let mut server = mockito::Server::new();
+        let _version = mock_version(&mut server);
+        // 5 SOL of virtual reserves: a 1 SOL buy moves the price ~20%
+        let data = bonding_curve_data(5_000_000_000, 1_000_000_000_000_000, false);
+        let _mock = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getAccountInfo"}"#.to_string()))
+            .with_body(rpc_result(account_info(&data)))
+            .create();
+
+        let mut trader = Trader::new(BotConfig::for_tests(&server.url()));
+        let result = futures::executor::block_on(trader.buy_token(&Pubkey::new_unique(), 1.0, 0.8, 50.0));
+
+        match result {
+            Err(BotError::HighSlippage(pct)) => assert!((pct - 20.0).abs() < 0.1),
+            other => panic!("expected HighSlippage, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_buy_passes_slippage_check_on_deep_curve() {
+        let mut server = mockito::Server::new();
+        let _version = mock_version(&mut server);
+        // 100 SOL of virtual reserves: a 1 SOL buy moves the price ~1%
+        let data = bonding_curve_data(100_000_000_000, 1_000_000_000_000_000, false);
+        let _curve = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getAccountInfo"}"#.to_string()))
+            .with_body(rpc_result(account_info(&data)))
+            .create();
+        let _balance = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getBalance"}"#.to_string()))
+            .with_body(rpc_result(serde_json::json!({ "context": { "slot": 1 }, "value": 0 })))
+            .create();
+
+        let mut trader = Trader::new(BotConfig::for_tests(&server.url()));
+        let mint = Pubkey::new_unique();
+
+        let (tokens_out, impact) = trader.estimate_buy_output(&mint, 1.0).unwrap();
+        assert!(tokens_out > 0);
+        assert!((impact - 0.01).abs() < 1e-4);
+
+        // Gets past the slippage check and fails on the next one
+        let result = futures::executor::block_on(trader.buy_token(&mint, 1.0, 0.8, 50.0));
+        assert!(matches!(result, Err(BotError::InsufficientFunds { .. })));
+    }
+
+    #[test]
+    fn test_buy_transaction_starts_with_compute_budget() {
+        let mut server = mockito::Server::new();
+        let _version = mock_version(&mut server);
+        let data = bonding_curve_data(30_000_000_000, 1_073_000_000_000_000, false);
+        let _curve = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getAccountInfo"}"#.to_string()))
+            .with_body(rpc_result(account_info(&data)))
+            .create();
+        let _blockhash = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getLatestBlockhash"}"#.to_string()))
+            .with_body(rpc_result(serde_json::json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "blockhash": solana_sdk::hash::Hash::new_unique().to_string(),
+                    "lastValidBlockHeight": 100
+                }
+            })))
+            .create();
+
+        let trader = Trader::new(BotConfig::for_tests(&server.url()));
+        let mint = Pubkey::new_unique();
+        let token_account = trader.get_token_account(&mint).unwrap();
+        let transaction = futures::executor::block_on(
+            trader.build_buy_transaction(&mint, &token_account, None, 0.1)
+        ).unwrap();
+
+        let message = &transaction.message;
+        let program_ids: Vec<&Pubkey> = message.instructions.iter()
+            .map(|ix| &message.account_keys[ix.program_id_index as usize])
+            .collect();
+
+        assert_eq!(program_ids.len(), 3);
+        assert_eq!(program_ids[0], &solana_sdk::compute_budget::id());
+        assert_eq!(program_ids[1], &solana_sdk::compute_budget::id());
+        assert_eq!(program_ids[2], &trader.config.pump_fun_program);
+        assert_eq!(
+            message.instructions[1].data,
+            ComputeBudgetInstruction::set_compute_unit_price(100_000).data
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_confirms_on_second_poll() {
+        let mut server = mockito::Server::new_async().await;
+        let trader = Trader::new(BotConfig::for_tests(&server.url()));
+
+        let payer = trader.config.wallet_keypair.pubkey();
+        let transaction = Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(&payer, &Pubkey::new_unique(), 1)],
+            Some(&payer),
+            &[&trader.config.wallet_keypair],
+            solana_sdk::hash::Hash::new_unique(),
+        );
+        let signature = transaction.signatures[0].to_string();
+
+        let _version = mock_version_async(&mut server).await;
+        let send = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"sendTransaction"}"#.to_string()))
+            .with_body(rpc_result(serde_json::json!(signature)))
+            .expect(1)
+            .create_async()
+            .await;
+        let pending = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getSignatureStatuses"}"#.to_string()))
+            .with_body(rpc_result(serde_json::json!({ "context": { "slot": 1 }, "value": [null] })))
+            .expect(1)
+            .create_async()
+            .await;
+        let confirmed = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getSignatureStatuses"}"#.to_string()))
+            .with_body(rpc_result(serde_json::json!({
+                "context": { "slot": 2 },
+                "value": [{
+                    "slot": 2,
+                    "confirmations": 0,
+                    "err": null,
+                    "status": { "Ok": null },
+                    "confirmationStatus": "confirmed"
+                }]
+            })))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let result = trader.send_and_confirm_transaction(transaction).await.unwrap();
+
+        assert_eq!(result, signature);
+        send.assert_async().await;
+        pending.assert_async().await;
+        confirmed.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_failed_preflight_aborts_send() {
+        let mut server = mockito::Server::new_async().await;
+        let mut config = BotConfig::for_tests(&server.url());
+        config.preflight_simulation = true;
+        let trader = Trader::new(config);
+
+        let payer = trader.config.wallet_keypair.pubkey();
+        let transaction = Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(&payer, &Pubkey::new_unique(), 1)],
+            Some(&payer),
+            &[&trader.config.wallet_keypair],
+            solana_sdk::hash::Hash::new_unique(),
+        );
+
+        let _version = mock_version_async(&mut server).await;
+        let _simulate = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"simulateTransaction"}"#.to_string()))
+            .with_body(rpc_result(serde_json::json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "err": { "InstructionError": [0, { "Custom": 6002 }] },
+                    "logs": [
+                        "Program 11111111111111111111111111111111 invoke [1]",
+                        "Program log: Error: slippage exceeded"
+                    ],
+                    "accounts": null,
+                    "unitsConsumed": 1200,
+                    "returnData": null
+                }
+            })))
+            .create_async()
+            .await;
+        let send = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"sendTransaction"}"#.to_string()))
+            .expect(0)
+            .create_async()
+            .await;
+
+        let err = trader.send_and_confirm_transaction(transaction).await.unwrap_err();
+
+        match err {
+            BotError::SimulationFailed { logs, .. } => {
+                assert!(logs.iter().any(|line| line.contains("slippage exceeded")));
+            }
+            other => panic!("expected a simulation failure, got {:?}", other),
+        }
+        send.assert_async().await;
+    }
+
+    #[test]
+    fn test_missing_token_account_is_created_in_buy() {
+        let mut server = mockito::Server::new();
+        let _version = mock_version(&mut server);
+        let config = BotConfig::for_tests(&server.url());
+        let mint = Pubkey::new_unique();
+        let ata = spl_associated_token_account::get_associated_token_address(&config.wallet_keypair.pubkey(), &mint);
+
+        let _missing_ata = server.mock("POST", "/")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::PartialJsonString(r#"{"method":"getAccountInfo"}"#.to_string()),
+                Matcher::Regex(ata.to_string()),
+            ]))
+            .with_body(rpc_result(serde_json::json!({ "context": { "slot": 1 }, "value": null })))
+            .create();
+        let data = bonding_curve_data(30_000_000_000, 1_073_000_000_000_000, false);
+        let _curve = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getAccountInfo"}"#.to_string()))
+            .with_body(rpc_result(account_info(&data)))
+            .create();
+        let _blockhash = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getLatestBlockhash"}"#.to_string()))
+            .with_body(rpc_result(serde_json::json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "blockhash": solana_sdk::hash::Hash::new_unique().to_string(),
+                    "lastValidBlockHeight": 100
+                }
+            })))
+            .create();
+
+        let trader = Trader::new(config);
+        let (token_account, create) = futures::executor::block_on(trader.get_or_create_token_account(&mint)).unwrap();
+        assert_eq!(token_account, ata);
+        assert!(create.is_some());
+
+        let transaction = futures::executor::block_on(
+            trader.build_buy_transaction(&mint, &token_account, create, 0.1)
+        ).unwrap();
+
+        let message = &transaction.message;
+        let program_ids: Vec<&Pubkey> = message.instructions.iter()
+            .map(|ix| &message.account_keys[ix.program_id_index as usize])
+            .collect();
+        assert_eq!(program_ids[2], &spl_associated_token_account::id());
+        assert_eq!(program_ids[3], &trader.config.pump_fun_program);
+    }
+
+    #[test]
+    fn test_trader_keeps_full_config() {
+        let mut config = BotConfig::for_tests("http://localhost");
+        config.strategy_type = crate::types::StrategyType::MomentumScalper;
+        config.dry_run = true;
+
+        let trader = Trader::new(config.clone());
+
+        assert_eq!(trader.config.strategy_type, config.strategy_type);
+        assert!(trader.config.dry_run);
+        assert_eq!(trader.config.rpc_url, config.rpc_url);
+        assert_eq!(trader.config.wallet_keypair.pubkey(), config.wallet_keypair.pubkey());
+        assert_eq!(trader.config.vault_program, config.vault_program);
+    }
+
+    #[test]
+    fn test_trailing_activation_moves_position_to_monitoring() {
+        let mut server = mockito::Server::new();
+        let _version = mock_version(&mut server);
+        let data = bonding_curve_data(30_000_000_000, 1_073_000_000_000_000, false);
+        let _mock = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getAccountInfo"}"#.to_string()))
+            .with_body(rpc_result(account_info(&data)))
+            .create();
+
+        // Momentum scalper arms its trailing stop at +20%
+        let mut config = BotConfig::for_tests(&server.url());
+        config.strategy_type = crate::types::StrategyType::MomentumScalper;
+        let api_state = ApiState::new();
+        let mut events = api_state.events.subscribe();
+        let mut trader = Trader::new(config).with_api_state(api_state);
+
+        let current_price = 30.0 / 1_073_000_000.0;
+        let entry_price = current_price / 1.25;
+        let mint = Pubkey::new_unique();
+        trader.positions.push(Position {
+            token_mint: mint,
+            entry_price,
+            amount: 1_000_000,
+            sol_invested: 0.1,
+            entry_time: chrono::Utc::now().timestamp(),
+            take_profit_price: entry_price * 2.0,
+            stop_loss_price: entry_price * 0.5,
+            status: PositionStatus::Open,
+            vault_position: None,
+            initial_amount: 1_000_000,
+            sol_received: 0.0,
+            tp_rungs_hit: 0,
+            scale_ins: 0,
+            vault_amount: 0,
+            decimals: 6,
+            exit_price: None,
+            closed_at: None,
+        });
+
+        let strategy = create_strategy(crate::types::StrategyType::MomentumScalper, None, &crate::types::HolderThresholds::default());
+        futures::executor::block_on(trader.monitor_positions(&FixedMetrics(None), strategy.as_ref())).unwrap();
+
+        assert_eq!(trader.positions[0].status, PositionStatus::Monitoring);
+        assert_eq!(trader.position_count(), 1);
+        assert_eq!(trader.get_active_positions().len(), 1);
+        match events.try_recv().unwrap() {
+            crate::api::StreamEvent::TrailingActivated { token_mint, .. } => assert_eq!(token_mint, mint.to_string()),
+            other => panic!("expected TrailingActivated, got {:?}", other),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_strong_sell_signal_closes_position() {
+        let mut server = mockito::Server::new_async().await;
+        let data = bonding_curve_data(30_000_000_000, 1_073_000_000_000_000, false);
+        let _curve = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getAccountInfo"}"#.to_string()))
+            .with_body(rpc_result(account_info(&data)))
+            .create_async()
+            .await;
+        let _blockhash = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getLatestBlockhash"}"#.to_string()))
+            .with_body(rpc_result(serde_json::json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "blockhash": solana_sdk::hash::Hash::new_unique().to_string(),
+                    "lastValidBlockHeight": 100
+                }
+            })))
+            .create_async()
+            .await;
+        let _version = mock_version_async(&mut server).await;
+        let send = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"sendTransaction"}"#.to_string()))
+            .with_body_from_request(|request| {
+                // The client checks the node echoes the signature of the transaction it sent
+                let body: serde_json::Value = serde_json::from_slice(request.body().unwrap()).unwrap();
+                let wire = base64::engine::general_purpose::STANDARD
+                    .decode(body["params"][0].as_str().unwrap())
+                    .unwrap();
+                let transaction: VersionedTransaction = bincode::deserialize(&wire).unwrap();
+                rpc_result(serde_json::json!(transaction.signatures[0].to_string())).into_bytes()
+            })
+            .expect(1)
+            .create_async()
+            .await;
+        let _status = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getSignatureStatuses"}"#.to_string()))
+            .with_body(rpc_result(serde_json::json!({
+                "context": { "slot": 2 },
+                "value": [{
+                    "slot": 2,
+                    "confirmations": 0,
+                    "err": null,
+                    "status": { "Ok": null },
+                    "confirmationStatus": "confirmed"
+                }]
+            })))
+            .create_async()
+            .await;
+
+        let mut trader = Trader::new(BotConfig::for_tests(&server.url()));
+        let price = 30.0 / 1_073_000_000.0;
+        let mint = Pubkey::new_unique();
+        trader.positions.push(Position {
+            token_mint: mint,
+            entry_price: price,
+            amount: 1_000_000,
+            sol_invested: 0.1,
+            entry_time: chrono::Utc::now().timestamp(),
+            take_profit_price: price * 2.0,
+            stop_loss_price: price * 0.5,
+            status: PositionStatus::Open,
+            vault_position: None,
+            initial_amount: 1_000_000,
+            sol_received: 0.0,
+            tp_rungs_hit: 0,
+            scale_ins: 0,
+            vault_amount: 0,
+            decimals: 6,
+            exit_price: None,
+            closed_at: None,
+        });
+
+        // Liquid enough to be analyzed, but volume, holders, momentum and pressure have dried up
+        let metrics = crate::types::TokenMetrics {
+            mint: mint.to_string(),
+            name: "Fading Token".to_string(),
+            symbol: "FADE".to_string(),
+            creator: None,
+            volume_5m: 1.0,
+            volume_1h: 0.0,
+            volume_24h: 20.0,
+            current_price: price,
+            price_change_5m: -0.10,
+            price_change_1h: -0.30,
+            liquidity_sol: 6.0,
+            liquidity_usd: 600.0,
+            virtual_sol_reserves: 0.0,
+            virtual_token_reserves: 0.0,
+            holder_count: 10,
+            holder_concentration: 0.40,
+            unique_buyers_5m: 5,
+            unique_sellers_5m: 20,
+            market_cap: 10000.0,
+            fully_diluted_valuation: 10000.0,
+            bonding_curve_progress: 50.0,
+            is_graduated: false,
+            created_at: 0,
+            time_since_creation: 3600,
+            buy_pressure: 0.5,
+            sell_pressure: 1.0,
+            buy_count_5m: 0,
+            sell_count_5m: 0,
+            volatility_score: 0.3,
+            wash_trade_score: 0.0,
+            rug_risk: None,
+        };
+        let strategy = create_strategy(crate::types::StrategyType::Conservative, None, &crate::types::HolderThresholds::default());
+        let signal = strategy.analyze(&metrics).unwrap();
+        assert_eq!(signal.signal_type, SignalType::StrongSell);
+
+        trader.monitor_positions(&FixedMetrics(Some(metrics)), strategy.as_ref()).await.unwrap();
+
+        assert_eq!(trader.positions[0].status, PositionStatus::Closed);
+        assert_eq!(trader.position_count(), 0);
+        send.assert_async().await;
+    }
+
+    fn vault_position_data(mint: &Pubkey, status: u8, exit_price: f64, closed_at: i64) -> Vec<u8> {
+        let mut data = vec![0u8; 8 + 32 * 2];
+        data.extend_from_slice(mint.as_ref());
+        for value in [100_000_000, 0, vault::to_scaled_price(exit_price), 0, 0] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.push(status);
+        data.extend_from_slice(&0i64.to_le_bytes());
+        data.extend_from_slice(&closed_at.to_le_bytes());
+        data.extend_from_slice(&[0u8; 64]);
+        data
+    }
+
+    #[test]
+    fn test_reconcile_corrects_positions_the_vault_disagrees_with() {
+        let mut server = mockito::Server::new();
+        let _version = mock_version(&mut server);
+        let mut config = BotConfig::for_tests(&server.url());
+        config.vault_delegation_user = Some(Pubkey::new_unique());
+        let client = VaultClient::new(&config).unwrap();
+        let mut trader = Trader::new(config);
+
+        let (still_open, closed, missing, local_only) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let records = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let accounts = [
+            Some(vault_position_data(&still_open, 0, 0.0, 0)),
+            Some(vault_position_data(&closed, 1, 0.000_003, 1_700_000_000)),
+            None,
+        ];
+        let mut _mocks = Vec::new();
+        for (record, data) in records.iter().zip(accounts) {
+            let value = data.map_or(serde_json::Value::Null, |data| account_info(&data)["value"].clone());
+            _mocks.push(server.mock("POST", "/")
+                .match_body(Matcher::AllOf(vec![
+                    Matcher::PartialJsonString(r#"{"method":"getAccountInfo"}"#.to_string()),
+                    Matcher::Regex(record.to_string()),
+                ]))
+                .with_body(rpc_result(serde_json::json!({ "context": { "slot": 1 }, "value": value })))
+                .create());
+        }
+
+        let vault_positions = [Some(records[0]), Some(records[1]), Some(records[2]), None];
+        for (token_mint, vault_position) in [still_open, closed, missing, local_only].into_iter().zip(vault_positions) {
+            trader.positions.push(Position {
+                token_mint,
+                entry_price: 0.000_001,
+                amount: 1_000_000,
+                sol_invested: 0.1,
+                entry_time: 0,
+                take_profit_price: 0.000_002,
+                stop_loss_price: 0.000_000_5,
+                status: PositionStatus::Open,
+                vault_position,
+                initial_amount: 1_000_000,
+                sol_received: 0.0,
+                tp_rungs_hit: 0,
+                scale_ins: 0,
+                vault_amount: 0,
+                decimals: 6,
+                exit_price: None,
+                closed_at: None,
+            });
+        }
+
+        let report = trader.reconcile_with_chain(&client).unwrap();
+
+        assert_eq!(report.checked, 3);
+        assert_eq!(report.closed, vec![closed]);
+        assert_eq!(report.dropped, vec![missing]);
+        let mints: Vec<Pubkey> = trader.positions.iter().map(|p| p.token_mint).collect();
+        assert_eq!(mints, vec![still_open, closed, local_only]);
+        let reconciled = &trader.positions[1];
+        assert_eq!(reconciled.status, PositionStatus::Closed);
+        assert_eq!(reconciled.amount, 0);
+        assert_eq!(reconciled.closed_at, Some(1_700_000_000));
+        assert!((reconciled.exit_price.unwrap() - 0.000_003).abs() < 1e-12);
+        assert_eq!(trader.position_count(), 2);
+
+        // Nothing left to correct the second time round
+        assert!(trader.reconcile_with_chain(&client).unwrap().is_clean());
+    }
+
+    #[test]
+    fn test_shutdown_persists_held_positions() {
+        let path = std::env::temp_dir().join(format!("curverider-positions-{}.json", std::process::id()));
+        let mut config = BotConfig::for_tests("http://localhost");
+        config.positions_file = path.to_string_lossy().into_owned();
+        let mut trader = Trader::new(config);
+
+        let held = Pubkey::new_unique();
+        for (token_mint, status) in [(held, PositionStatus::Monitoring), (Pubkey::new_unique(), PositionStatus::Closed)] {
+            trader.positions.push(Position {
+                token_mint,
+                entry_price: 0.000_001,
+                amount: 1_000_000,
+                sol_invested: 0.1,
+                entry_time: 0,
+                take_profit_price: 0.000_002,
+                stop_loss_price: 0.000_000_5,
+                status,
+                vault_position: None,
+                initial_amount: 1_000_000,
+                sol_received: 0.0,
+                tp_rungs_hit: 0,
+                scale_ins: 0,
+                vault_amount: 0,
+                decimals: 6,
+                exit_price: None,
+                closed_at: None,
+            });
+        }
+
+        let persisted = futures::executor::block_on(trader.shutdown(false)).unwrap();
+
+        let saved: Vec<Position> = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(persisted, 1);
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].token_mint, held);
+        assert_eq!(saved[0].status, PositionStatus::Monitoring);
+    }
+
+    #[test]
+    fn test_dry_run_paper_trade_takes_profit() {
+        // Nothing listens here, so any RPC call would fail the trade
+        let mut config = BotConfig::for_tests("http://127.0.0.1:1");
+        config.dry_run = true;
+        let mut trader = Trader::new(config);
+
+        let mint = Pubkey::new_unique();
+        let entry_price = 30.0 / 1_073_000_000.0;
+        trader.set_paper_price(mint, entry_price);
+
+        let position = futures::executor::block_on(trader.buy_token(&mint, 0.5, 0.8, 50.0)).unwrap();
+        assert_eq!(position.entry_price, entry_price);
+        assert_eq!(position.vault_position, None);
+        assert_eq!(trader.position_count(), 1);
+
+        // Price runs 2.5x, past the 2x take profit
+        trader.set_paper_price(mint, entry_price * 2.5);
+        let strategy = create_strategy(crate::types::StrategyType::Conservative, None, &crate::types::HolderThresholds::default());
+        futures::executor::block_on(trader.monitor_positions(&FixedMetrics(None), strategy.as_ref())).unwrap();
+
+        assert_eq!(trader.positions[0].status, PositionStatus::Closed);
+        assert_eq!(trader.position_count(), 0);
+        assert!((trader.realized_pnl() - 0.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_export_positions_csv_writes_closed_trades() {
+        let path = std::env::temp_dir().join(format!("curverider-trades-{}.csv", std::process::id()));
+        let mut config = BotConfig::for_tests("http://127.0.0.1:1");
+        config.dry_run = true;
+        let mut trader = Trader::new(config);
+
+        let (sold, held) = (Pubkey::new_unique(), Pubkey::new_unique());
+        for mint in [sold, held] {
+            trader.set_paper_price(mint, 0.5);
+            futures::executor::block_on(trader.buy_token(&mint, 0.5, 0.8, 50.0)).unwrap();
+        }
+        trader.set_paper_price(sold, 0.75);
+        futures::executor::block_on(trader.sell_token(&sold, None)).unwrap();
+
+        assert_eq!(trader.export_positions_csv(&path).unwrap(), 1);
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], export::TRADE_CSV_HEADER.join(","));
+        let row: Vec<&str> = lines[1].split(',').collect();
+        assert_eq!(row[0], sold.to_string());
+        assert_eq!(&row[2..8], &["0.5", "0.75", "0.5", "0.75", "0.25", "50.0"]);
+        assert_eq!(row[11], "conservative");
+    }
+
+    #[test]
+    fn test_absolute_max_hold_overrides_long_timeout() {
+        let mut config = BotConfig::for_tests("http://127.0.0.1:1");
+        config.dry_run = true;
+        config.position_timeout_seconds = 7200;
+        config.absolute_max_hold_seconds = 60;
+        let mut trader = Trader::new(config);
+        let strategy = create_strategy(crate::types::StrategyType::Conservative, None, &crate::types::HolderThresholds::default());
+
+        let mint = Pubkey::new_unique();
+        trader.set_paper_price(mint, 30.0 / 1_073_000_000.0);
+        futures::executor::block_on(trader.buy_token(&mint, 0.5, 0.8, 50.0)).unwrap();
+
+        // Well inside both limits
+        trader.positions[0].entry_time -= 30;
+        futures::executor::block_on(trader.monitor_positions(&FixedMetrics(None), strategy.as_ref())).unwrap();
+        assert!(trader.positions[0].status.is_active());
+
+        // Past the cap, though the timeout is nearly two hours off
+        trader.positions[0].entry_time -= 60;
+        futures::executor::block_on(trader.monitor_positions(&FixedMetrics(None), strategy.as_ref())).unwrap();
+        assert_eq!(trader.positions[0].status, PositionStatus::Closed);
+    }
+
+    #[test]
+    fn test_take_profit_ladder_scales_out_in_order() {
+        let mut config = BotConfig::for_tests("http://127.0.0.1:1");
+        config.dry_run = true;
+        let mut trader = Trader::new(config);
+        trader.exit_params.take_profit_ladder = vec![
+            crate::types::TakeProfitRung { price_multiple: 2.0, fraction: 0.5 },
+            crate::types::TakeProfitRung { price_multiple: 3.0, fraction: 0.25 },
+        ];
+
+        let mint = Pubkey::new_unique();
+        let entry_price = 30.0 / 1_073_000_000.0;
+        trader.set_paper_price(mint, entry_price);
+        let bought = futures::executor::block_on(trader.buy_token(&mint, 0.5, 0.8, 50.0)).unwrap().amount;
+        let strategy = create_strategy(crate::types::StrategyType::Conservative, None, &crate::types::HolderThresholds::default());
+
+        // (price multiple, rungs sold into, share of the buy still held, realized PnL)
+        let expected = [
+            (1.5, 0, 1.0, 0.0),
+            (2.2, 1, 0.5, 0.25 * 2.2 - 0.25),
+            (2.5, 1, 0.5, 0.25 * 2.2 - 0.25),
+            (3.5, 2, 0.25, 0.25 * 2.2 - 0.25 + 0.125 * 3.5 - 0.125),
+        ];
+        for (multiple, rungs_hit, held, realized) in expected {
+            trader.set_paper_price(mint, entry_price * multiple);
+            futures::executor::block_on(trader.monitor_positions(&FixedMetrics(None), strategy.as_ref())).unwrap();
+
+            let position = &trader.positions[0];
+            assert_eq!(position.tp_rungs_hit, rungs_hit, "at {}x", multiple);
+            assert!(position.status.is_active(), "at {}x", multiple);
+            assert!((position.amount as f64 / bought as f64 - held).abs() < 1e-6, "at {}x", multiple);
+            assert!((trader.realized_pnl() - realized).abs() < 1e-6, "at {}x", multiple);
+        }
+
+        // The last quarter rides; selling it closes the position with its total PnL
+        futures::executor::block_on(trader.sell_token(&mint, None)).unwrap();
+        assert_eq!(trader.positions[0].status, PositionStatus::Closed);
+        assert!((trader.realized_pnl() - (0.25 * 2.2 + 0.125 * 3.5 + 0.125 * 3.5 - 0.5)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_configured_ladder_skips_rungs_too_small_to_sell() {
+        let mut config = BotConfig::for_tests("http://127.0.0.1:1");
+        config.dry_run = true;
+        config.take_profit_ladder = vec![
+            crate::types::TakeProfitRung { price_multiple: 2.0, fraction: 0.5 },
+            crate::types::TakeProfitRung { price_multiple: 3.0, fraction: 0.25 },
+        ];
+        let mut trader = Trader::new(config);
+        assert_eq!(trader.exit_params.take_profit_ladder.len(), 2);
+
+        let mint = Pubkey::new_unique();
+        let entry_price = 30.0 / 1_073_000_000.0;
+        trader.set_paper_price(mint, entry_price);
+        futures::executor::block_on(trader.buy_token(&mint, 0.5, 0.8, 50.0)).unwrap();
+        // A dust position: half of one token rounds down to zero
+        trader.positions[0].initial_amount = 1;
+        trader.positions[0].amount = 1;
+        let strategy = create_strategy(crate::types::StrategyType::Conservative, None, &crate::types::HolderThresholds::default());
+
+        trader.set_paper_price(mint, entry_price * 2.5);
+        futures::executor::block_on(trader.monitor_positions(&FixedMetrics(None), strategy.as_ref())).unwrap();
+
+        let position = &trader.positions[0];
+        assert_eq!(position.tp_rungs_hit, 1);
+        assert!(position.status.is_active());
+        assert_eq!(position.amount, 1);
+        assert_eq!(trader.realized_pnl(), 0.0);
+    }
+
+    #[test]
+    fn test_snapshot_positions_at_live_price() {
+        let mut config = BotConfig::for_tests("http://127.0.0.1:1");
+        config.dry_run = true;
+        let mut trader = Trader::new(config);
+
+        let mint = Pubkey::new_unique();
+        let entry_price = 30.0 / 1_073_000_000.0;
+        trader.set_paper_price(mint, entry_price);
+        futures::executor::block_on(trader.buy_token(&mint, 0.5, 0.8, 50.0)).unwrap();
+
+        // Up 40%: 0.5 SOL is now worth 0.7
+        trader.set_paper_price(mint, entry_price * 1.4);
+        let snapshot = futures::executor::block_on(trader.snapshot_positions());
+        assert_eq!(snapshot.len(), 1);
+        assert!(!snapshot[0].stale);
+        assert!((snapshot[0].pnl as f64 / 1e9 - 0.2).abs() < 1e-6);
+        assert!((snapshot[0].pnl_percentage - 40.0).abs() < 1e-3);
+
+        // No price to read: reported stale at entry instead of failing
+        trader.paper_prices.remove(&mint);
+        let snapshot = futures::executor::block_on(trader.snapshot_positions());
+        assert!(snapshot[0].stale);
+        assert_eq!(snapshot[0].pnl, 0);
+        assert_eq!(snapshot[0].current_price, snapshot[0].entry_price);
+    }
+
+    #[tokio::test]
+    async fn test_live_positions_reach_the_api() {
+        let mut config = BotConfig::for_tests("http://127.0.0.1:1");
+        config.dry_run = true;
+        let wallet = config.wallet_keypair.pubkey();
+        let api_state = ApiState::new();
+        let mut trader = Trader::new(config).with_api_state(api_state.clone());
+
+        let mint = Pubkey::new_unique();
+        let entry_price = 30.0 / 1_073_000_000.0;
+        trader.set_paper_price(mint, entry_price);
+        trader.buy_token(&mint, 0.5, 0.8, 50.0).await.unwrap();
+
+        // As replayed from the vault's PositionOpened event
+        let mut opened = trader.snapshot_positions().await.remove(0);
+        opened.position_id = format!("{}:0:0", wallet);
+        api_state.add_position(opened).await;
+
+        trader.set_paper_price(mint, entry_price * 1.4);
+        trader.publish_live_positions().await;
+
+        let positions = api_state.positions.read().await;
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].current_price, vault::to_scaled_price(entry_price * 1.4));
+        assert!((positions[0].pnl as f64 / 1e9 - 0.2).abs() < 1e-6);
+        assert!((positions[0].pnl_percentage - 40.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_drawdown_halts_buys_but_not_exits() {
+        let mut config = BotConfig::for_tests("http://127.0.0.1:1");
+        config.dry_run = true;
+        // 5 SOL of equity (5 x 1 SOL positions); halts past 1.5 SOL of losses
+        config.max_drawdown_pct = 0.30;
+        let mut trader = Trader::new(config);
+        let price = 0.000_001;
+
+        let held = Pubkey::new_unique();
+        trader.set_paper_price(held, price);
+        futures::executor::block_on(trader.buy_token(&held, 1.0, 0.8, 50.0)).unwrap();
+
+        for _ in 0..2 {
+            let loser = Pubkey::new_unique();
+            trader.set_paper_price(loser, price);
+            futures::executor::block_on(trader.buy_token(&loser, 1.0, 0.8, 50.0)).unwrap();
+            trader.set_paper_price(loser, price * 0.1);
+            futures::executor::block_on(trader.sell_token(&loser, None)).unwrap();
+        }
+        assert!(trader.trading_halted());
+
+        let fresh = Pubkey::new_unique();
+        trader.set_paper_price(fresh, price);
+        let result = futures::executor::block_on(trader.buy_token(&fresh, 1.0, 0.8, 50.0));
+        assert!(matches!(result, Err(BotError::TradingHalted)));
+
+        // The held position still takes profit
+        trader.set_paper_price(held, price * 3.0);
+        let strategy = create_strategy(crate::types::StrategyType::Conservative, None, &crate::types::HolderThresholds::default());
+        futures::executor::block_on(trader.monitor_positions(&FixedMetrics(None), strategy.as_ref())).unwrap();
+        assert_eq!(trader.positions[0].status, PositionStatus::Closed);
+        assert_eq!(trader.position_count(), 0);
+    }
+
+    #[test]
+    fn test_losing_close_cools_down_mint() {
+        let mut config = BotConfig::for_tests("http://127.0.0.1:1");
+        config.dry_run = true;
+        let mut trader = Trader::new(config);
+        let price = 0.000_001;
+
+        let loser = Pubkey::new_unique();
+        trader.set_paper_price(loser, price);
+        futures::executor::block_on(trader.buy_token(&loser, 0.5, 0.8, 50.0)).unwrap();
+        trader.set_paper_price(loser, price * 0.8);
+        let pnl = futures::executor::block_on(trader.sell_token(&loser, None)).unwrap();
+        assert!(pnl < 0.0);
+
+        match futures::executor::block_on(trader.buy_token(&loser, 0.5, 0.8, 50.0)) {
+            Err(BotError::CoolingDown { mint, remaining_secs }) => {
+                assert_eq!(mint, loser.to_string());
+                assert!(remaining_secs > 0);
+            }
+            other => panic!("expected CoolingDown, got {:?}", other),
+        }
+
+        let other_mint = Pubkey::new_unique();
+        trader.set_paper_price(other_mint, price);
+        assert!(futures::executor::block_on(trader.buy_token(&other_mint, 0.5, 0.8, 50.0)).is_ok());
+    }
+
+    #[test]
+    fn test_scale_in_blends_entry_and_targets() {
+        let mut config = BotConfig::for_tests("http://127.0.0.1:1");
+        config.dry_run = true;
+        let mut trader = Trader::new(config);
+        let mint = Pubkey::new_unique();
+        let price = 0.000_001;
+
+        trader.set_paper_price(mint, price);
+        futures::executor::block_on(trader.buy_token(&mint, 1.0, 0.8, 50.0)).unwrap();
+        trader.set_paper_price(mint, price * 2.0);
+        let position = futures::executor::block_on(trader.buy_token(&mint, 1.0, 0.8, 50.0)).unwrap();
+
+        // 1 SOL at p plus 1 SOL at 2p buys 1.5 SOL/p of tokens: average entry 4p/3
+        let avg_entry = price * 4.0 / 3.0;
+        assert_eq!(trader.positions.len(), 1);
+        assert!((position.entry_price - avg_entry).abs() / avg_entry < 1e-9);
+        assert!((position.sol_invested - 2.0).abs() < 1e-12);
+        assert_eq!(position.amount, trader.positions[0].amount);
+        assert!((position.take_profit_price - avg_entry * 2.0).abs() / avg_entry < 1e-9);
+        assert!((position.stop_loss_price - avg_entry * 0.5).abs() / avg_entry < 1e-9);
+        // The vault only recorded the first buy: two thirds of the tokens
+        assert_eq!(position.scale_ins, 1);
+        assert!((position.vault_share() - 2.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_scale_ins_are_capped_per_mint() {
+        let mut config = BotConfig::for_tests("http://127.0.0.1:1");
+        config.dry_run = true;
+        config.max_scale_ins = 2;
+        let mut trader = Trader::new(config);
+        let mint = Pubkey::new_unique();
+        trader.set_paper_price(mint, 0.000_001);
+
+        for _ in 0..3 {
+            futures::executor::block_on(trader.buy_token(&mint, 1.0, 0.8, 50.0)).unwrap();
+        }
+        match futures::executor::block_on(trader.buy_token(&mint, 1.0, 0.8, 50.0)) {
+            Err(BotError::ScaleInLimitReached { mint: capped, max }) => {
+                assert_eq!(capped, mint.to_string());
+                assert_eq!(max, 2);
+            }
+            other => panic!("expected ScaleInLimitReached, got {:?}", other),
+        }
+        assert_eq!(trader.positions.len(), 1);
+        assert!((trader.positions[0].sol_invested - 3.0).abs() < 1e-12);
+
+        // Once closed, the mint can be bought afresh
+        futures::executor::block_on(trader.sell_token(&mint, None)).unwrap();
+        let reopened = futures::executor::block_on(trader.buy_token(&mint, 1.0, 0.8, 50.0)).unwrap();
+        assert_eq!(reopened.scale_ins, 0);
+    }
+
+    #[test]
+    fn test_raydium_buy_references_pool_accounts() {
+        let mut server = mockito::Server::new();
+        let _version = mock_version(&mut server);
+        let config = BotConfig::for_tests(&server.url());
+        let amm_program = config.raydium_amm_program;
+        let mint = Pubkey::new_unique();
+
+        let amm_id = Pubkey::new_unique();
+        let (amm_authority, amm_nonce) = Pubkey::find_program_address(&[b"amm authority"], &amm_program);
+        let [base_vault, quote_vault, open_orders, target_orders, market_id, market_program] =
+            [(); 6].map(|_| Pubkey::new_unique());
+        let mut pool = vec![0u8; raydium::AMM_V4_ACCOUNT_SIZE as usize];
+        pool[8..16].copy_from_slice(&(amm_nonce as u64).to_le_bytes());
+        for (offset, key) in [
+            (336, base_vault), (368, quote_vault), (400, mint), (432, spl_token::native_mint::id()),
+            (496, open_orders), (528, market_id), (560, market_program), (592, target_orders),
+        ] {
+            pool[offset..offset + 32].copy_from_slice(key.as_ref());
+        }
+
+        let signer_nonce = (0u64..)
+            .find(|n| Pubkey::create_program_address(&[market_id.as_ref(), &n.to_le_bytes()], &market_program).is_ok())
+            .unwrap();
+        let vault_signer = Pubkey::create_program_address(&[market_id.as_ref(), &signer_nonce.to_le_bytes()], &market_program).unwrap();
+        let bids = Pubkey::new_unique();
+        let mut market = vec![0u8; 388];
+        market[45..53].copy_from_slice(&signer_nonce.to_le_bytes());
+        market[285..317].copy_from_slice(bids.as_ref());
+
+        let _pools = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getProgramAccounts"}"#.to_string()))
+            .with_body(rpc_result(serde_json::json!([{
+                "pubkey": amm_id.to_string(),
+                "account": account_info(&pool)["value"],
+            }])))
+            .create();
+        let _market = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getAccountInfo"}"#.to_string()))
+            .with_body(rpc_result(account_info(&market)))
+            .create();
+        let _reserves: Vec<_> = [(base_vault, "1000000000000000"), (quote_vault, "100000000000")]
+            .into_iter()
+            .map(|(vault, amount)| {
+                server.mock("POST", "/")
+                    .match_body(Matcher::AllOf(vec![
+                        Matcher::PartialJsonString(r#"{"method":"getTokenAccountBalance"}"#.to_string()),
+                        Matcher::Regex(vault.to_string()),
+                    ]))
+                    .with_body(rpc_result(serde_json::json!({
+                        "context": { "slot": 1 },
+                        "value": { "amount": amount, "decimals": 6, "uiAmount": null, "uiAmountString": amount }
+                    })))
+                    .create()
+            })
+            .collect();
+        let _blockhash = server.mock("POST", "/")
+            .match_body(Matcher::PartialJsonString(r#"{"method":"getLatestBlockhash"}"#.to_string()))
+            .with_body(rpc_result(serde_json::json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "blockhash": solana_sdk::hash::Hash::new_unique().to_string(),
+                    "lastValidBlockHeight": 100
+                }
+            })))
+            .create();
+
+        let trader = Trader::new(config);
+        let token_account = trader.get_token_account(&mint).unwrap();
+        let transaction = futures::executor::block_on(
+            trader.build_raydium_buy_transaction(&mint, &token_account, None, 1.0)
+        ).unwrap();
+
+        let message = &transaction.message;
+        let swap = message.instructions.iter()
+            .find(|ix| message.account_keys[ix.program_id_index as usize] == amm_program)
+            .unwrap();
+        let account = |i: usize| message.account_keys[swap.accounts[i] as usize];
+        let wsol_account = spl_associated_token_account::get_associated_token_address(
+            &trader.config.wallet_keypair.pubkey(),
+            &spl_token::native_mint::id(),
+        );
+
+        assert_eq!(account(1), amm_id);
+        assert_eq!(account(2), amm_authority);
+        assert_eq!(account(3), open_orders);
+        assert_eq!(account(4), target_orders);
+        assert_eq!(account(5), base_vault);
+        assert_eq!(account(6), quote_vault);
+        assert_eq!(account(7), market_program);
+        assert_eq!(account(8), market_id);
+        assert_eq!(account(9), bids);
+        assert_eq!(account(14), vault_signer);
+        assert_eq!(account(15), wsol_account);
+        assert_eq!(account(16), token_account);
+
+        // 1 SOL in, at least the quote less 5% slippage out
+        let expected_out = raydium::swap_amount_out(100_000_000_000, 1_000_000_000_000_000, 1_000_000_000);
+        assert_eq!(swap.data[0], 9);
+        assert_eq!(swap.data[1..9], 1_000_000_000u64.to_le_bytes());
+        assert_eq!(swap.data[9..17], raydium::min_amount_out_with_slippage(expected_out, 500).to_le_bytes());
+    }
 }
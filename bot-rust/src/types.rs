@@ -1,13 +1,22 @@
 use serde::{Deserialize, Serialize};
+use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Keypair;
+use std::path::Path;
 use std::str::FromStr;
 
+use crate::amount::Amount;
+use crate::error::BotError;
+
 #[derive(Debug)]
 pub struct BotConfig {
     // Solana
     pub rpc_url: String,
+    /// Tried in order after `rpc_url` when it can't be reached
+    pub rpc_fallback_urls: Vec<String>,
     pub rpc_ws_url: String,
+    /// Commitment the RPC clients read and confirm at
+    pub commitment: RpcCommitment,
     pub wallet_keypair: solana_sdk::signature::Keypair,
 
     // Trading Parameters
@@ -15,34 +24,156 @@ pub struct BotConfig {
     pub max_position_size_sol: f64,
     pub take_profit_multiplier: f64,
     pub stop_loss_percentage: f64,
+    /// Scale-out targets replacing the strategy's own ladder; empty keeps it
+    pub take_profit_ladder: Vec<TakeProfitRung>,
 
     // API Endpoints
     pub pump_fun_api_url: String,
+    pub pump_fun_program: Pubkey,
+    pub pump_fun_ws_url: String,
     pub raydium_amm_program: Pubkey,
+    pub http_max_retries: u32,
+    pub metrics_cache_ttl_ms: u64,
+    pub metrics_source: MetricsSourceType,
+    pub dexscreener_api_url: String,
+    pub birdeye_api_url: String,
+    /// Sent as `X-API-KEY`; the Birdeye source refuses to run without it
+    pub birdeye_api_key: Option<String>,
+    pub jupiter_api_url: String,
 
     // Risk Management
     pub max_slippage_bps: u16,
     pub max_concurrent_positions: usize,
+    /// Buys allowed into an already held mint on top of the one that opened it
+    pub max_scale_ins: u32,
     pub position_timeout_seconds: u64,
+    /// Hard cap on holding any position, whatever the timeout in effect
+    pub absolute_max_hold_seconds: u64,
+    /// Largest share of supply (0-1) a single non-curve holder may own
+    pub max_single_holder_pct: f64,
+    /// Sell conviction (1 - signal confidence) a Sell/StrongSell re-analysis needs to exit early
+    pub sell_signal_min_confidence: f64,
+    /// Realized drawdown from peak equity (0-1) that halts new positions
+    pub max_drawdown_pct: f64,
+    /// Confidence (0-1) a StrongBuy signal needs to open a position
+    pub strong_buy_confidence: f64,
+    /// Confidence (0-1) a Buy signal needs to be reported
+    pub buy_confidence: f64,
+    /// Consecutive qualifying StrongBuy analyses of a mint before it is bought
+    pub signal_confirmations: u32,
+
+    // Transaction Landing
+    pub priority_fee_micro_lamports: u64,
+    /// Raise the priority fee to the recent median paid for the same accounts
+    pub dynamic_priority_fee: bool,
+    /// How long to poll for confirmation before rebroadcasting
+    pub confirm_timeout_ms: u64,
+    pub send_max_attempts: u32,
+    /// Simulate each trade before sending it, aborting on a simulated failure
+    pub preflight_simulation: bool,
 
     // Monitoring
     pub scan_interval_ms: u64,
+    /// Tokens analyzed at once per scan
+    pub analysis_concurrency: usize,
+    /// Metric fetches/rug checks in flight at once, to stay under API rate limits
+    pub max_concurrent_requests: usize,
+    /// Sustained pump.fun API request rate, across all concurrent scans
+    pub api_requests_per_second: f64,
+    /// Requests allowed back-to-back after a quiet period
+    pub api_burst: u32,
+    /// Which pump.fun listings the polling loop scans
+    pub scan_mode: ScanMode,
+    pub stream_new_tokens: bool,
+    pub blacklist_path: Option<String>,
     pub volume_threshold_sol: f64,
     pub holder_count_min: u32,
+    /// Pages of 100 holders fetched per token before concentration is computed
+    pub holder_max_pages: usize,
+
+    // Shutdown
+    /// Market-sell every open position on Ctrl-C/SIGTERM instead of only persisting them
+    pub close_on_shutdown: bool,
+    /// Where positions still held at shutdown are written
+    pub positions_file: String,
+    /// Closed trades are written here as CSV on shutdown; `None` skips the export
+    pub trades_csv_file: Option<String>,
 
     // Strategy Selection
     pub strategy_type: StrategyType,
+    /// Per-strategy overrides of the built-in holder thresholds
+    pub holder_thresholds: HolderThresholds,
+
+    // On-chain Vault
+    pub vault_program: Pubkey,
+    /// Delegating user whose on-chain stats record the bot's trades; unset disables recording
+    pub vault_delegation_user: Option<Pubkey>,
+    pub vault_index: u8,
 
     // Dry run mode - no real trades, mock API responses
     pub dry_run: bool,
+
+    // HTTP API
+    pub api_port: u16,
+    pub api_auth_token: Option<String>,
+}
+
+/// Substrings that mark an environment variable's value as secret
+const SECRET_KEY_MARKERS: [&str; 4] = ["KEY", "SECRET", "TOKEN", "PRIVATE"];
+
+/// Lines to log for an environment dump, with secret values redacted.
+/// Empty unless the environment opts in with `DEBUG_DUMP_ENV=true`.
+fn env_dump_lines(vars: impl IntoIterator<Item = (String, String)>) -> Vec<String> {
+    let mut vars: Vec<(String, String)> = vars.into_iter().collect();
+    let enabled = vars.iter().any(|(key, value)| key == "DEBUG_DUMP_ENV" && (value == "true" || value == "1"));
+    if !enabled {
+        return Vec::new();
+    }
+
+    vars.sort();
+    vars.into_iter()
+        .map(|(key, value)| {
+            let upper = key.to_uppercase();
+            if SECRET_KEY_MARKERS.iter().any(|marker| upper.contains(marker)) {
+                format!("{}=<redacted>", key)
+            } else {
+                format!("{}={}", key, value)
+            }
+        })
+        .collect()
 }
 
 impl BotConfig {
     pub fn from_env() -> anyhow::Result<Self> {
         dotenv::from_filename("bot-rust/.env").ok();
 
+        for line in env_dump_lines(std::env::vars()) {
+            tracing::info!("{}", line);
+        }
+
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// Load from a TOML file whose keys are the lowercase env var names.
+    /// Environment variables take precedence over file values.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let file = config::Config::builder()
+            .add_source(config::File::from(path).format(config::FileFormat::Toml))
+            .build()
+            .map_err(|e| BotError::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+
+        Self::from_lookup(|key| {
+            std::env::var(key)
+                .ok()
+                .or_else(|| file.get_string(&key.to_lowercase()).ok())
+        })
+    }
+
+    /// Build the config from a key lookup; missing keys fall back to defaults
+    fn from_lookup(var: impl Fn(&str) -> Option<String>) -> anyhow::Result<Self> {
+
         // Load wallet keypair - supports both file path and direct private key
-        let wallet_keypair = if let Ok(private_key) = std::env::var("WALLET_PRIVATE_KEY") {
+        let wallet_keypair = if let Some(private_key) = var("WALLET_PRIVATE_KEY") {
             // Try JSON array format first (e.g., from solana-keygen output)
             if let Ok(bytes) = serde_json::from_str::<Vec<u8>>(&private_key) {
                 Keypair::from_bytes(&bytes)
@@ -55,7 +186,7 @@ impl BotConfig {
                 Keypair::from_bytes(&decoded)
                     .map_err(|e| anyhow::anyhow!("Invalid keypair from base58: {}", e))?
             }
-        } else if let Ok(keypair_path) = std::env::var("WALLET_KEYPAIR") {
+        } else if let Some(keypair_path) = var("WALLET_KEYPAIR") {
             // Fall back to file path
             solana_sdk::signature::read_keypair_file(&keypair_path)
                 .map_err(|e| anyhow::anyhow!("Failed to read keypair file: {}", e))?
@@ -66,68 +197,465 @@ impl BotConfig {
         };
 
         // Raydium AMM Program - defaults to mainnet address (not fully implemented yet)
-        let raydium_program_str = std::env::var("RAYDIUM_AMM_PROGRAM")
-            .unwrap_or_else(|_| "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8".to_string());
+        let raydium_program_str = var("RAYDIUM_AMM_PROGRAM")
+            .unwrap_or_else(|| "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8".to_string());
         let raydium_amm_program = Pubkey::from_str(&raydium_program_str)?;
 
-        Ok(Self {
-            rpc_url: std::env::var("RPC_URL")
-                .unwrap_or_else(|_| "https://api.devnet.solana.com".to_string()),
-            rpc_ws_url: std::env::var("RPC_WS_URL")
-                .unwrap_or_else(|_| "wss://api.devnet.solana.com".to_string()),
+        // pump.fun bonding curve program - defaults to mainnet address
+        let pump_fun_program_str = var("PUMP_FUN_PROGRAM")
+            .unwrap_or_else(|| crate::pump_fun::PUMP_FUN_PROGRAM_ID.to_string());
+        let pump_fun_program = Pubkey::from_str(&pump_fun_program_str)?;
+
+        let vault_program_str = var("VAULT_PROGRAM_ID")
+            .unwrap_or_else(|| crate::vault::VAULT_PROGRAM_ID.to_string());
+        let vault_program = Pubkey::from_str(&vault_program_str)?;
+        let vault_delegation_user = var("VAULT_DELEGATION_USER")
+            .filter(|u| !u.is_empty())
+            .map(|u| Pubkey::from_str(&u))
+            .transpose()?;
+
+        let config = Self {
+            rpc_url: var("RPC_URL")
+                .unwrap_or_else(|| "https://api.devnet.solana.com".to_string()),
+            rpc_fallback_urls: var("RPC_FALLBACK_URLS")
+                .map(|urls| {
+                    urls.split(',')
+                        .map(str::trim)
+                        .filter(|url| !url.is_empty())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            rpc_ws_url: var("RPC_WS_URL")
+                .unwrap_or_else(|| "wss://api.devnet.solana.com".to_string()),
+            commitment: var("COMMITMENT")
+                .unwrap_or_else(|| "confirmed".to_string())
+                .parse()?,
             wallet_keypair,
 
-            min_liquidity_sol: std::env::var("MIN_LIQUIDITY_SOL")
-                .unwrap_or_else(|_| "5.0".to_string())
+            min_liquidity_sol: var("MIN_LIQUIDITY_SOL")
+                .unwrap_or_else(|| "5.0".to_string())
                 .parse()?,
-            max_position_size_sol: std::env::var("MAX_POSITION_SIZE_SOL")
-                .unwrap_or_else(|_| "1.0".to_string())
+            max_position_size_sol: var("MAX_POSITION_SIZE_SOL")
+                .unwrap_or_else(|| "1.0".to_string())
                 .parse()?,
-            take_profit_multiplier: std::env::var("TAKE_PROFIT_MULTIPLIER")
-                .unwrap_or_else(|_| "2.0".to_string())
+            take_profit_multiplier: var("TAKE_PROFIT_MULTIPLIER")
+                .unwrap_or_else(|| "2.0".to_string())
                 .parse()?,
-            stop_loss_percentage: std::env::var("STOP_LOSS_PERCENTAGE")
-                .unwrap_or_else(|_| "0.5".to_string())
+            stop_loss_percentage: var("STOP_LOSS_PERCENTAGE")
+                .unwrap_or_else(|| "0.5".to_string())
                 .parse()?,
+            take_profit_ladder: TakeProfitRung::parse_ladder(
+                &var("TAKE_PROFIT_LADDER").unwrap_or_default(),
+            )?,
 
-            pump_fun_api_url: std::env::var("PUMP_FUN_API_URL")
-                .unwrap_or_else(|_| "https://frontend-api.pump.fun".to_string()),
+            pump_fun_api_url: var("PUMP_FUN_API_URL")
+                .unwrap_or_else(|| "https://frontend-api.pump.fun".to_string()),
+            pump_fun_program,
+            pump_fun_ws_url: var("PUMP_FUN_WS_URL")
+                .unwrap_or_else(|| "wss://pumpportal.fun/api/data".to_string()),
             raydium_amm_program,
+            http_max_retries: var("HTTP_MAX_RETRIES")
+                .unwrap_or_else(|| "3".to_string())
+                .parse()?,
+            metrics_cache_ttl_ms: var("METRICS_CACHE_TTL_MS")
+                .unwrap_or_else(|| "5000".to_string())
+                .parse()?,
+            metrics_source: var("METRICS_SOURCE")
+                .unwrap_or_else(|| "pumpfun".to_string())
+                .parse()?,
+            dexscreener_api_url: var("DEXSCREENER_API_URL")
+                .unwrap_or_else(|| "https://api.dexscreener.com".to_string()),
+            birdeye_api_url: var("BIRDEYE_API_URL")
+                .unwrap_or_else(|| "https://public-api.birdeye.so".to_string()),
+            birdeye_api_key: var("BIRDEYE_API_KEY").filter(|k| !k.is_empty()),
+            jupiter_api_url: var("JUPITER_API_URL")
+                .unwrap_or_else(|| "https://quote-api.jup.ag/v6".to_string()),
+
+            max_slippage_bps: var("MAX_SLIPPAGE_BPS")
+                .unwrap_or_else(|| "500".to_string())
+                .parse()?,
+            max_concurrent_positions: var("MAX_CONCURRENT_POSITIONS")
+                .unwrap_or_else(|| "5".to_string())
+                .parse()?,
+            max_scale_ins: var("MAX_SCALE_INS")
+                .unwrap_or_else(|| "1".to_string())
+                .parse()?,
+            position_timeout_seconds: var("POSITION_TIMEOUT_SECONDS")
+                .unwrap_or_else(|| "3600".to_string())
+                .parse()?,
+            absolute_max_hold_seconds: var("ABSOLUTE_MAX_HOLD_SECONDS")
+                .unwrap_or_else(|| "86400".to_string())
+                .parse()?,
+            max_single_holder_pct: var("MAX_SINGLE_HOLDER_PCT")
+                .unwrap_or_else(|| "0.20".to_string())
+                .parse()?,
+            sell_signal_min_confidence: var("SELL_SIGNAL_MIN_CONFIDENCE")
+                .unwrap_or_else(|| "0.70".to_string())
+                .parse()?,
+            max_drawdown_pct: var("MAX_DRAWDOWN_PCT")
+                .unwrap_or_else(|| "0.30".to_string())
+                .parse()?,
+            strong_buy_confidence: var("STRONG_BUY_CONFIDENCE")
+                .unwrap_or_else(|| "0.75".to_string())
+                .parse()?,
+            buy_confidence: var("BUY_CONFIDENCE")
+                .unwrap_or_else(|| "0.65".to_string())
+                .parse()?,
+            signal_confirmations: var("SIGNAL_CONFIRMATIONS")
+                .unwrap_or_else(|| "1".to_string())
+                .parse()?,
 
-            max_slippage_bps: std::env::var("MAX_SLIPPAGE_BPS")
-                .unwrap_or_else(|_| "500".to_string())
+            priority_fee_micro_lamports: var("PRIORITY_FEE_MICRO_LAMPORTS")
+                .unwrap_or_else(|| "100000".to_string())
                 .parse()?,
-            max_concurrent_positions: std::env::var("MAX_CONCURRENT_POSITIONS")
-                .unwrap_or_else(|_| "5".to_string())
+            dynamic_priority_fee: var("DYNAMIC_PRIORITY_FEE")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            confirm_timeout_ms: var("CONFIRM_TIMEOUT_MS")
+                .unwrap_or_else(|| "30000".to_string())
                 .parse()?,
-            position_timeout_seconds: std::env::var("POSITION_TIMEOUT_SECONDS")
-                .unwrap_or_else(|_| "3600".to_string())
+            send_max_attempts: var("SEND_MAX_ATTEMPTS")
+                .unwrap_or_else(|| "3".to_string())
                 .parse()?,
+            preflight_simulation: var("PREFLIGHT_SIMULATION")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(true),
 
-            scan_interval_ms: std::env::var("SCAN_INTERVAL_MS")
-                .unwrap_or_else(|_| "1000".to_string())
+            scan_interval_ms: var("SCAN_INTERVAL_MS")
+                .unwrap_or_else(|| "1000".to_string())
+                .parse()?,
+            analysis_concurrency: var("ANALYSIS_CONCURRENCY")
+                .unwrap_or_else(|| "8".to_string())
+                .parse()?,
+            max_concurrent_requests: var("MAX_CONCURRENT_REQUESTS")
+                .unwrap_or_else(|| "4".to_string())
                 .parse()?,
-            volume_threshold_sol: std::env::var("VOLUME_THRESHOLD_SOL")
-                .unwrap_or_else(|_| "10.0".to_string())
+            api_requests_per_second: var("API_REQUESTS_PER_SECOND")
+                .unwrap_or_else(|| "10.0".to_string())
                 .parse()?,
-            holder_count_min: std::env::var("HOLDER_COUNT_MIN")
-                .unwrap_or_else(|_| "50".to_string())
+            api_burst: var("API_BURST")
+                .unwrap_or_else(|| "10".to_string())
+                .parse()?,
+            scan_mode: var("SCAN_MODE")
+                .unwrap_or_else(|| "both".to_string())
+                .parse()?,
+            stream_new_tokens: var("STREAM_NEW_TOKENS")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            blacklist_path: var("BLACKLIST_FILE").filter(|p| !p.is_empty()),
+            volume_threshold_sol: var("VOLUME_THRESHOLD_SOL")
+                .unwrap_or_else(|| "10.0".to_string())
+                .parse()?,
+            holder_max_pages: var("HOLDER_MAX_PAGES")
+                .unwrap_or_else(|| "20".to_string())
+                .parse()?,
+            holder_count_min: var("HOLDER_COUNT_MIN")
+                .unwrap_or_else(|| "50".to_string())
+                .parse()?,
+
+            close_on_shutdown: var("CLOSE_ON_SHUTDOWN")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            positions_file: var("POSITIONS_FILE")
+                .unwrap_or_else(|| "./positions.json".to_string()),
+            trades_csv_file: var("TRADES_CSV_FILE").filter(|f| !f.is_empty()),
+
+            strategy_type: var("STRATEGY_TYPE")
+                .unwrap_or_else(|| "conservative".to_string())
                 .parse()?,
+            holder_thresholds: HolderThresholds::from_lookup(&var)?,
 
-            strategy_type: std::env::var("STRATEGY_TYPE")
-                .unwrap_or_else(|_| "conservative".to_string())
+            vault_program,
+            vault_delegation_user,
+            vault_index: var("VAULT_INDEX")
+                .unwrap_or_else(|| "0".to_string())
                 .parse()?,
 
             // Enable dry run mode on devnet by default
-            dry_run: std::env::var("DRY_RUN")
+            dry_run: var("DRY_RUN")
                 .map(|v| v == "true" || v == "1")
-                .unwrap_or_else(|_| {
+                .unwrap_or_else(|| {
                     // Auto-enable dry run if using devnet
-                    std::env::var("RPC_URL")
+                    var("RPC_URL")
                         .map(|url| url.contains("devnet"))
                         .unwrap_or(true)
                 }),
-        })
+
+            api_port: var("API_PORT")
+                .unwrap_or_else(|| "8080".to_string())
+                .parse()?,
+            api_auth_token: var("API_AUTH_TOKEN").filter(|t| !t.is_empty()),
+        };
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// `rpc_url` followed by the fallbacks, in failover order
+    pub fn rpc_urls(&self) -> Vec<String> {
+        let mut urls = vec![self.rpc_url.clone()];
+        for url in &self.rpc_fallback_urls {
+            if !urls.contains(url) {
+                urls.push(url.clone());
+            }
+        }
+        urls
+    }
+
+    /// Reject values that parse but make no sense to trade with
+    pub fn validate(&self) -> crate::error::Result<()> {
+        if let Some(url) = self.rpc_fallback_urls.iter()
+            .find(|url| !url.starts_with("http://") && !url.starts_with("https://"))
+        {
+            return Err(BotError::Config(format!(
+                "rpc_fallback_urls entries must be http(s) URLs, got {}",
+                url
+            )));
+        }
+        if self.take_profit_multiplier.is_nan() || self.take_profit_multiplier <= 1.0 {
+            return Err(BotError::Config(format!(
+                "take_profit_multiplier must be greater than 1.0, got {}",
+                self.take_profit_multiplier
+            )));
+        }
+        if self.stop_loss_percentage.is_nan()
+            || self.stop_loss_percentage <= 0.0
+            || self.stop_loss_percentage >= 1.0
+        {
+            return Err(BotError::Config(format!(
+                "stop_loss_percentage must be between 0 and 1 (exclusive), got {}",
+                self.stop_loss_percentage
+            )));
+        }
+        if self.max_slippage_bps > 10_000 {
+            return Err(BotError::Config(format!(
+                "max_slippage_bps must be at most 10000, got {}",
+                self.max_slippage_bps
+            )));
+        }
+        if self.max_position_size_sol.is_nan() || self.max_position_size_sol <= 0.0 {
+            return Err(BotError::Config(format!(
+                "max_position_size_sol must be positive, got {}",
+                self.max_position_size_sol
+            )));
+        }
+        if self.min_liquidity_sol.is_nan() || self.min_liquidity_sol <= 0.0 {
+            return Err(BotError::Config(format!(
+                "min_liquidity_sol must be positive, got {}",
+                self.min_liquidity_sol
+            )));
+        }
+        if self.max_concurrent_positions < 1 {
+            return Err(BotError::Config(
+                "max_concurrent_positions must be at least 1, got 0".to_string(),
+            ));
+        }
+        if self.absolute_max_hold_seconds < 1 {
+            return Err(BotError::Config(
+                "absolute_max_hold_seconds must be at least 1, got 0".to_string(),
+            ));
+        }
+        if self.sell_signal_min_confidence.is_nan()
+            || self.sell_signal_min_confidence < 0.0
+            || self.sell_signal_min_confidence > 1.0
+        {
+            return Err(BotError::Config(format!(
+                "sell_signal_min_confidence must be between 0 and 1, got {}",
+                self.sell_signal_min_confidence
+            )));
+        }
+        if self.max_drawdown_pct.is_nan()
+            || self.max_drawdown_pct <= 0.0
+            || self.max_drawdown_pct > 1.0
+        {
+            return Err(BotError::Config(format!(
+                "max_drawdown_pct must be in (0, 1], got {}",
+                self.max_drawdown_pct
+            )));
+        }
+        for (name, value) in [
+            ("strong_buy_confidence", self.strong_buy_confidence),
+            ("buy_confidence", self.buy_confidence),
+        ] {
+            if value.is_nan() || !(0.0..=1.0).contains(&value) {
+                return Err(BotError::Config(format!(
+                    "{} must be between 0 and 1, got {}",
+                    name, value
+                )));
+            }
+        }
+        if self.strong_buy_confidence < self.buy_confidence {
+            return Err(BotError::Config(format!(
+                "strong_buy_confidence ({}) must be at least buy_confidence ({})",
+                self.strong_buy_confidence, self.buy_confidence
+            )));
+        }
+        if self.signal_confirmations < 1 {
+            return Err(BotError::Config(
+                "signal_confirmations must be at least 1, got 0".to_string(),
+            ));
+        }
+        if self.analysis_concurrency < 1 || self.max_concurrent_requests < 1 {
+            return Err(BotError::Config(
+                "analysis_concurrency and max_concurrent_requests must be at least 1".to_string(),
+            ));
+        }
+        if self.api_requests_per_second.is_nan() || self.api_requests_per_second <= 0.0 || self.api_burst < 1 {
+            return Err(BotError::Config(format!(
+                "api_requests_per_second must be positive and api_burst at least 1, got {} and {}",
+                self.api_requests_per_second, self.api_burst
+            )));
+        }
+        if self.holder_max_pages < 1 {
+            return Err(BotError::Config(
+                "holder_max_pages must be at least 1, got 0".to_string(),
+            ));
+        }
+        if self.send_max_attempts < 1 {
+            return Err(BotError::Config(
+                "send_max_attempts must be at least 1, got 0".to_string(),
+            ));
+        }
+        self.holder_thresholds.validate()?;
+
+        Ok(())
+    }
+}
+
+/// `Keypair` is not `Clone`, so the wallet is copied through its bytes
+impl Clone for BotConfig {
+    fn clone(&self) -> Self {
+        Self {
+            rpc_url: self.rpc_url.clone(),
+            rpc_fallback_urls: self.rpc_fallback_urls.clone(),
+            rpc_ws_url: self.rpc_ws_url.clone(),
+            commitment: self.commitment,
+            wallet_keypair: Keypair::from_bytes(&self.wallet_keypair.to_bytes())
+                .expect("keypair round-trips through its bytes"),
+            min_liquidity_sol: self.min_liquidity_sol,
+            max_position_size_sol: self.max_position_size_sol,
+            take_profit_multiplier: self.take_profit_multiplier,
+            stop_loss_percentage: self.stop_loss_percentage,
+            take_profit_ladder: self.take_profit_ladder.clone(),
+            pump_fun_api_url: self.pump_fun_api_url.clone(),
+            pump_fun_program: self.pump_fun_program,
+            pump_fun_ws_url: self.pump_fun_ws_url.clone(),
+            raydium_amm_program: self.raydium_amm_program,
+            http_max_retries: self.http_max_retries,
+            metrics_cache_ttl_ms: self.metrics_cache_ttl_ms,
+            metrics_source: self.metrics_source,
+            dexscreener_api_url: self.dexscreener_api_url.clone(),
+            birdeye_api_url: self.birdeye_api_url.clone(),
+            birdeye_api_key: self.birdeye_api_key.clone(),
+            jupiter_api_url: self.jupiter_api_url.clone(),
+            max_slippage_bps: self.max_slippage_bps,
+            max_concurrent_positions: self.max_concurrent_positions,
+            max_scale_ins: self.max_scale_ins,
+            position_timeout_seconds: self.position_timeout_seconds,
+            absolute_max_hold_seconds: self.absolute_max_hold_seconds,
+            max_single_holder_pct: self.max_single_holder_pct,
+            sell_signal_min_confidence: self.sell_signal_min_confidence,
+            max_drawdown_pct: self.max_drawdown_pct,
+            strong_buy_confidence: self.strong_buy_confidence,
+            buy_confidence: self.buy_confidence,
+            signal_confirmations: self.signal_confirmations,
+            priority_fee_micro_lamports: self.priority_fee_micro_lamports,
+            dynamic_priority_fee: self.dynamic_priority_fee,
+            confirm_timeout_ms: self.confirm_timeout_ms,
+            send_max_attempts: self.send_max_attempts,
+            preflight_simulation: self.preflight_simulation,
+            scan_interval_ms: self.scan_interval_ms,
+            analysis_concurrency: self.analysis_concurrency,
+            max_concurrent_requests: self.max_concurrent_requests,
+            api_requests_per_second: self.api_requests_per_second,
+            api_burst: self.api_burst,
+            scan_mode: self.scan_mode,
+            stream_new_tokens: self.stream_new_tokens,
+            blacklist_path: self.blacklist_path.clone(),
+            volume_threshold_sol: self.volume_threshold_sol,
+            holder_count_min: self.holder_count_min,
+            holder_max_pages: self.holder_max_pages,
+            close_on_shutdown: self.close_on_shutdown,
+            positions_file: self.positions_file.clone(),
+            trades_csv_file: self.trades_csv_file.clone(),
+            strategy_type: self.strategy_type,
+            holder_thresholds: self.holder_thresholds.clone(),
+            vault_program: self.vault_program,
+            vault_delegation_user: self.vault_delegation_user,
+            vault_index: self.vault_index,
+            dry_run: self.dry_run,
+            api_port: self.api_port,
+            api_auth_token: self.api_auth_token.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl BotConfig {
+    /// Minimal config pointed at `rpc_url`, for unit tests
+    pub fn for_tests(rpc_url: &str) -> Self {
+        Self {
+            rpc_url: rpc_url.to_string(),
+            rpc_fallback_urls: Vec::new(),
+            rpc_ws_url: rpc_url.replace("http", "ws"),
+            commitment: RpcCommitment::Confirmed,
+            wallet_keypair: Keypair::new(),
+            min_liquidity_sol: 5.0,
+            max_position_size_sol: 1.0,
+            take_profit_multiplier: 2.0,
+            stop_loss_percentage: 0.5,
+            take_profit_ladder: Vec::new(),
+            pump_fun_api_url: rpc_url.to_string(),
+            pump_fun_program: Pubkey::from_str(crate::pump_fun::PUMP_FUN_PROGRAM_ID).unwrap(),
+            pump_fun_ws_url: rpc_url.replace("http", "ws"),
+            raydium_amm_program: Pubkey::from_str("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8").unwrap(),
+            http_max_retries: 3,
+            metrics_cache_ttl_ms: 5000,
+            metrics_source: MetricsSourceType::PumpFun,
+            dexscreener_api_url: rpc_url.to_string(),
+            birdeye_api_url: rpc_url.to_string(),
+            birdeye_api_key: None,
+            jupiter_api_url: rpc_url.to_string(),
+            max_slippage_bps: 500,
+            max_concurrent_positions: 5,
+            max_scale_ins: 1,
+            position_timeout_seconds: 3600,
+            absolute_max_hold_seconds: 86_400,
+            max_single_holder_pct: 0.20,
+            sell_signal_min_confidence: 0.70,
+            max_drawdown_pct: 0.30,
+            strong_buy_confidence: 0.75,
+            buy_confidence: 0.65,
+            signal_confirmations: 1,
+            priority_fee_micro_lamports: 100_000,
+            dynamic_priority_fee: false,
+            confirm_timeout_ms: 30_000,
+            send_max_attempts: 3,
+            preflight_simulation: false,
+            scan_interval_ms: 1000,
+            analysis_concurrency: 8,
+            max_concurrent_requests: 4,
+            api_requests_per_second: 10.0,
+            api_burst: 10,
+            scan_mode: ScanMode::Both,
+            stream_new_tokens: false,
+            blacklist_path: None,
+            volume_threshold_sol: 10.0,
+            holder_count_min: 50,
+            holder_max_pages: 20,
+            close_on_shutdown: false,
+            positions_file: "./positions.json".to_string(),
+            trades_csv_file: None,
+            strategy_type: StrategyType::Conservative,
+            holder_thresholds: HolderThresholds::default(),
+            vault_program: Pubkey::from_str(crate::vault::VAULT_PROGRAM_ID).unwrap(),
+            vault_delegation_user: None,
+            vault_index: 0,
+            dry_run: false,
+            api_port: 0,
+            api_auth_token: None,
+        }
     }
 }
 
@@ -136,6 +664,8 @@ pub struct TokenMetrics {
     pub mint: String,
     pub name: String,
     pub symbol: String,
+    #[serde(default)]
+    pub creator: Option<String>,
     
     // Volume Metrics
     pub volume_5m: f64,
@@ -150,6 +680,11 @@ pub struct TokenMetrics {
     // Liquidity
     pub liquidity_sol: f64,
     pub liquidity_usd: f64,
+    /// Bonding curve virtual reserves, in SOL and whole tokens; 0 when unknown
+    #[serde(default)]
+    pub virtual_sol_reserves: f64,
+    #[serde(default)]
+    pub virtual_token_reserves: f64,
     
     // Social Metrics
     pub holder_count: u32,
@@ -170,9 +705,94 @@ pub struct TokenMetrics {
     pub time_since_creation: u64, // seconds
     
     // Risk Factors
+    /// Buy/sell volume ratios over the last 5 minutes
     pub buy_pressure: f64,
     pub sell_pressure: f64,
+    /// Buy and sell trades over the last 5 minutes, whatever their size
+    #[serde(default)]
+    pub buy_count_5m: u32,
+    #[serde(default)]
+    pub sell_count_5m: u32,
     pub volatility_score: f64,
+    /// 0-1, higher = more of the 5m volume looks like a few addresses trading
+    /// back and forth
+    #[serde(default)]
+    pub wash_trade_score: f64,
+
+    /// Filled in by `RugCheck` when on-chain checks ran
+    #[serde(default)]
+    pub rug_risk: Option<RugRisk>,
+}
+
+/// The ratio the analyzers score for a buy pressure against a sell pressure
+fn pressure_ratio_of(buy_pressure: f64, sell_pressure: f64) -> f64 {
+    if sell_pressure > 0.0 {
+        buy_pressure / sell_pressure
+    } else {
+        buy_pressure
+    }
+}
+
+impl TokenMetrics {
+    /// Buy-to-sell pressure ratio, blending SOL value with trade counts when counts are known
+    ///
+    /// The blend leans toward the more bearish of the two, so a flurry of tiny buys
+    /// can't paper over a few large sells (or a few whale buys over steady selling).
+    pub fn pressure_ratio(&self) -> f64 {
+        let value_ratio = pressure_ratio_of(self.buy_pressure, self.sell_pressure);
+        if self.buy_count_5m == 0 && self.sell_count_5m == 0 {
+            return value_ratio;
+        }
+
+        // Counts go through the same buy/sell pressure derivation as volumes
+        let (buys, sells) = (self.buy_count_5m as f64, self.sell_count_5m as f64);
+        let count_ratio = pressure_ratio_of(
+            if sells > 0.0 { buys / sells } else { buys },
+            if buys > 0.0 { sells / buys } else { 1.0 },
+        );
+
+        let (low, high) = if value_ratio < count_ratio {
+            (value_ratio, count_ratio)
+        } else {
+            (count_ratio, value_ratio)
+        };
+        low.powf(0.75) * high.powf(0.25)
+    }
+
+    /// Whole minutes since the token was created
+    pub fn age_minutes(&self) -> u64 {
+        self.time_since_creation / 60
+    }
+}
+
+/// On-chain rug vectors found for a token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RugFlag {
+    /// Mint authority not renounced - supply can be inflated
+    MintAuthority,
+    /// Freeze authority present - holders can be blocked from selling
+    FreezeAuthority,
+    /// One wallet outside the bonding curve holds more than the allowed share
+    WhaleHolder { account: String, share: f64 },
+}
+
+impl RugFlag {
+    pub fn is_critical(&self) -> bool {
+        matches!(self, RugFlag::MintAuthority | RugFlag::FreezeAuthority)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RugRisk {
+    /// 0-1, higher = riskier
+    pub score: f64,
+    pub flags: Vec<RugFlag>,
+}
+
+impl RugRisk {
+    pub fn has_critical(&self) -> bool {
+        self.flags.iter().any(RugFlag::is_critical)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -181,10 +801,66 @@ pub struct TradingSignal {
     pub signal_type: SignalType,
     pub confidence: f64, // 0-1
     pub reasoning: Vec<String>,
+    /// `reason_codes[i]` is the category of `reasoning[i]`
+    pub reason_codes: Vec<ReasonCode>,
     pub timestamp: i64,
+    /// A deal-breaker forced `Hold`; the reason leads `reasoning`
+    pub rejected: bool,
+}
+
+impl TradingSignal {
+    /// Force `Hold` for a deal-breaker, leading the reasoning with it
+    pub fn reject(&mut self, code: ReasonCode, reason: &str) {
+        self.signal_type = SignalType::Hold;
+        self.reasoning.insert(0, format!("REJECTED: {}", reason));
+        self.reason_codes.insert(0, code);
+        self.rejected = true;
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// What a `TradingSignal` reason is about, so reasons can be counted and
+/// rendered without parsing their text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ReasonCode {
+    ExceptionalVolume,
+    GoodVolume,
+    LowVolume,
+    VolumeAccelerating,
+    StableVolume,
+    StrongBuyerInterest,
+    BuyerInterest,
+    WashTrading,
+    HighLiquidity,
+    AdequateLiquidity,
+    LowLiquidity,
+    StrongHolderBase,
+    GoodHolderBase,
+    LowHolderCount,
+    WellDistributed,
+    AcceptableDistribution,
+    HighConcentration,
+    StrongMomentum,
+    PositiveMomentum,
+    WeakMomentum,
+    StrongBuyPressure,
+    BuyPressure,
+    SellPressure,
+    Graduated,
+    NearGraduation,
+    CurveSweetSpot,
+    EarlyCurve,
+    LowVolatility,
+    ModerateVolatility,
+    HighVolatility,
+    /// Token is outside the age or curve range the strategy trades
+    OutsideEntryWindow,
+    BlacklistedCreator,
+    RugRisk,
+    /// Which strategy the ensemble acted on
+    EnsembleWinner,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SignalType {
     StrongBuy,
     Buy,
@@ -193,7 +869,7 @@ pub enum SignalType {
     StrongSell,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Position {
     pub token_mint: Pubkey,
     pub entry_price: f64,
@@ -203,22 +879,182 @@ pub struct Position {
     pub take_profit_price: f64,
     pub stop_loss_price: f64,
     pub status: PositionStatus,
+    /// Position PDA in the vault program, when the trade was recorded on-chain
+    pub vault_position: Option<Pubkey>,
+    /// Tokens bought in total; `amount` is what partial sells have left
+    #[serde(default)]
+    pub initial_amount: u64,
+    /// SOL received from partial sells so far
+    #[serde(default)]
+    pub sol_received: f64,
+    /// Take-profit ladder rungs already sold into
+    #[serde(default)]
+    pub tp_rungs_hit: usize,
+    /// Buys averaged into the position after the one that opened it
+    #[serde(default)]
+    pub scale_ins: u32,
+    /// Tokens the vault position covers: the opening buy, not later scale-ins
+    #[serde(default)]
+    pub vault_amount: u64,
+    /// Decimals of the token's mint; `amount` is in its base units
+    #[serde(default = "default_token_decimals")]
+    pub decimals: u8,
+    /// Price of the sale that closed the position
+    #[serde(default)]
+    pub exit_price: Option<f64>,
+    #[serde(default)]
+    pub closed_at: Option<i64>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+fn default_token_decimals() -> u8 {
+    crate::pump_fun::PUMP_FUN_TOKEN_DECIMALS
+}
+
+impl Position {
+    /// SOL value of the tokens still held, at `price` SOL per whole token
+    pub fn sol_value(&self, price: f64) -> f64 {
+        Amount::new(self.amount, self.decimals).sol_value(price)
+    }
+
+    /// Share of `sol_invested` paid for the tokens still held
+    pub fn remaining_cost_sol(&self) -> f64 {
+        let bought = self.initial_amount.max(self.amount);
+        if bought == 0 {
+            return 0.0;
+        }
+        self.sol_invested * self.amount as f64 / bought as f64
+    }
+
+    /// Share (0-1) of what the position sells for that its vault record covers;
+    /// positions saved before scale-ins were tracked count as wholly covered
+    pub fn vault_share(&self) -> f64 {
+        let bought = self.initial_amount.max(self.amount);
+        if self.vault_amount == 0 || bought == 0 {
+            return 1.0;
+        }
+        (self.vault_amount as f64 / bought as f64).min(1.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PositionStatus {
     Open,
     Closed,
+    /// Still held, with the trailing stop armed
     Monitoring,
 }
 
-/// Strategy configuration for multi-strategy support
+impl PositionStatus {
+    /// Held positions, whether or not the trailing stop is armed
+    pub fn is_active(&self) -> bool {
+        matches!(self, PositionStatus::Open | PositionStatus::Monitoring)
+    }
+}
+
+/// Where token metrics are fetched from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MetricsSourceType {
+    PumpFun,     // pump.fun frontend API (default)
+    DexScreener, // DexScreener pairs API, also covers graduated tokens
+    Birdeye,     // Birdeye token overview, for graduated tokens; needs an API key
+}
+
+impl std::str::FromStr for MetricsSourceType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pumpfun" | "pump_fun" | "pump.fun" => Ok(MetricsSourceType::PumpFun),
+            "dexscreener" => Ok(MetricsSourceType::DexScreener),
+            "birdeye" => Ok(MetricsSourceType::Birdeye),
+            _ => Err(anyhow::anyhow!("Unknown metrics source: {}", s)),
+        }
+    }
+}
+
+/// Commitment level RPC reads and confirmations use
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RpcCommitment {
+    Processed, // Fastest, but the block may still be skipped
+    Confirmed, // Voted on by a supermajority (default)
+    Finalized, // Rooted; slowest, for when a rollback is unacceptable
+}
+
+impl RpcCommitment {
+    pub fn config(self) -> CommitmentConfig {
+        match self {
+            RpcCommitment::Processed => CommitmentConfig::processed(),
+            RpcCommitment::Confirmed => CommitmentConfig::confirmed(),
+            RpcCommitment::Finalized => CommitmentConfig::finalized(),
+        }
+    }
+}
+
+impl std::str::FromStr for RpcCommitment {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "processed" => Ok(RpcCommitment::Processed),
+            "confirmed" => Ok(RpcCommitment::Confirmed),
+            "finalized" => Ok(RpcCommitment::Finalized),
+            _ => Err(anyhow::anyhow!(
+                "Unknown commitment level: {} (expected processed, confirmed or finalized)",
+                s
+            )),
+        }
+    }
+}
+
+/// Which token listings a polling scan pulls from
 #[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScanMode {
+    New,      // Latest launches only
+    Trending, // Trending tokens only
+    Both,     // Union of the two (default)
+}
+
+impl std::str::FromStr for ScanMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "new" => Ok(ScanMode::New),
+            "trending" => Ok(ScanMode::Trending),
+            "both" => Ok(ScanMode::Both),
+            _ => Err(anyhow::anyhow!("Unknown scan mode: {}", s)),
+        }
+    }
+}
+
+/// Output format of the bot's logs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    Pretty, // Human-readable lines (default)
+    Json,   // One JSON object per event, fields included
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pretty" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(anyhow::anyhow!("Unknown log format: {}", s)),
+        }
+    }
+}
+
+/// Strategy configuration for multi-strategy support
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum StrategyType {
     Conservative,      // Original multi-factor strategy (default)
     UltraEarlySniper, // High risk, first 5 minutes, 10-100x targets
     MomentumScalper,  // Quick flips on explosive momentum
     GraduationAnticipator, // Pre-DEX positioning, lower risk
+    Ensemble,         // Best fit of all of the above, per token
 }
 
 impl Default for StrategyType {
@@ -227,6 +1063,31 @@ impl Default for StrategyType {
     }
 }
 
+impl StrategyType {
+    /// Strategy tag stored on vault positions; 0-3 match the delegation's strategy ids
+    pub fn on_chain_id(&self) -> u8 {
+        match self {
+            StrategyType::Conservative => 0,
+            StrategyType::UltraEarlySniper => 1,
+            StrategyType::MomentumScalper => 2,
+            StrategyType::GraduationAnticipator => 3,
+            StrategyType::Ensemble => 4,
+        }
+    }
+
+    /// Inverse of `on_chain_id`; `None` for tags no strategy uses
+    pub fn from_on_chain_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(StrategyType::Conservative),
+            1 => Some(StrategyType::UltraEarlySniper),
+            2 => Some(StrategyType::MomentumScalper),
+            3 => Some(StrategyType::GraduationAnticipator),
+            4 => Some(StrategyType::Ensemble),
+            _ => None,
+        }
+    }
+}
+
 impl std::str::FromStr for StrategyType {
     type Err = anyhow::Error;
 
@@ -236,11 +1097,83 @@ impl std::str::FromStr for StrategyType {
             "ultra_early_sniper" | "ultra-early-sniper" | "early" => Ok(StrategyType::UltraEarlySniper),
             "momentum_scalper" | "momentum-scalper" | "momentum" => Ok(StrategyType::MomentumScalper),
             "graduation_anticipator" | "graduation-anticipator" | "graduation" => Ok(StrategyType::GraduationAnticipator),
+            "ensemble" => Ok(StrategyType::Ensemble),
             _ => Err(anyhow::anyhow!("Unknown strategy type: {}", s)),
         }
     }
 }
 
+/// Holder thresholds overriding a strategy's built-in defaults; `None` keeps the default
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HolderThresholds {
+    /// Named conservative analyzer preset the overrides below apply on top of
+    /// (see `analyzer::profile`); `None` is `balanced`
+    pub analyzer_profile: Option<String>,
+    pub conservative_min_holders: Option<u32>,
+    pub conservative_max_concentration: Option<f64>,
+    pub grad_min_holders: Option<u32>,
+    pub grad_max_concentration: Option<f64>,
+    /// 5m volume (SOL) below which the conservative analyzer rejects a token outright
+    pub conservative_volume_floor_5m: Option<f64>,
+    /// Price impact (0-1) of a position at which the conservative analyzer stops scoring liquidity
+    pub conservative_max_price_impact: Option<f64>,
+    /// Position size (SOL) the conservative analyzer estimates price impact for
+    pub position_size_sol: Option<f64>,
+}
+
+impl HolderThresholds {
+    /// Read `ANALYZER_PROFILE`, `CONSERVATIVE_MIN_HOLDERS`, `CONSERVATIVE_MAX_CONCENTRATION`,
+    /// `GRAD_MIN_HOLDERS`, `GRAD_MAX_CONCENTRATION`, `CONSERVATIVE_VOLUME_FLOOR_5M`,
+    /// `CONSERVATIVE_MAX_PRICE_IMPACT` and the trade size from `MAX_POSITION_SIZE_SOL`;
+    /// empty values count as unset
+    pub fn from_lookup(var: impl Fn(&str) -> Option<String>) -> anyhow::Result<Self> {
+        let optional = |key: &str| var(key).filter(|v| !v.is_empty());
+
+        Ok(Self {
+            analyzer_profile: optional("ANALYZER_PROFILE"),
+            conservative_min_holders: optional("CONSERVATIVE_MIN_HOLDERS").map(|v| v.parse()).transpose()?,
+            conservative_max_concentration: optional("CONSERVATIVE_MAX_CONCENTRATION").map(|v| v.parse()).transpose()?,
+            grad_min_holders: optional("GRAD_MIN_HOLDERS").map(|v| v.parse()).transpose()?,
+            grad_max_concentration: optional("GRAD_MAX_CONCENTRATION").map(|v| v.parse()).transpose()?,
+            conservative_volume_floor_5m: optional("CONSERVATIVE_VOLUME_FLOOR_5M").map(|v| v.parse()).transpose()?,
+            conservative_max_price_impact: optional("CONSERVATIVE_MAX_PRICE_IMPACT").map(|v| v.parse()).transpose()?,
+            position_size_sol: optional("MAX_POSITION_SIZE_SOL").map(|v| v.parse()).transpose()?,
+        })
+    }
+
+    pub fn validate(&self) -> crate::error::Result<()> {
+        if let Some(name) = &self.analyzer_profile {
+            crate::analyzer::profile(name)?;
+        }
+
+        for (name, value) in [
+            ("conservative_max_concentration", self.conservative_max_concentration),
+            ("grad_max_concentration", self.grad_max_concentration),
+            ("conservative_max_price_impact", self.conservative_max_price_impact),
+        ] {
+            if let Some(value) = value {
+                if value.is_nan() || value <= 0.0 || value > 1.0 {
+                    return Err(BotError::Config(format!(
+                        "{} must be in (0, 1], got {}",
+                        name, value
+                    )));
+                }
+            }
+        }
+
+        if let Some(floor) = self.conservative_volume_floor_5m {
+            if floor.is_nan() || floor < 0.0 {
+                return Err(BotError::Config(format!(
+                    "conservative_volume_floor_5m must be >= 0, got {}",
+                    floor
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// Strategy-specific exit parameters
 #[derive(Debug, Clone)]
 pub struct StrategyExitParams {
@@ -250,4 +1183,279 @@ pub struct StrategyExitParams {
     pub use_trailing_stop: bool,
     pub trailing_activation_pct: f64,
     pub trailing_distance_pct: f64,
+    /// How long a mint is off-limits after a position in it closed at a loss
+    pub loss_cooldown_seconds: u64,
+    /// Scale-out targets, lowest first; when set they replace the single take
+    /// profit and whatever they leave unsold rides on the stop loss/trailing stop
+    pub take_profit_ladder: Vec<TakeProfitRung>,
+}
+
+impl StrategyExitParams {
+    /// Replace the strategy's ladder with a configured one, unless that is empty
+    pub fn with_take_profit_ladder(mut self, ladder: &[TakeProfitRung]) -> Self {
+        if !ladder.is_empty() {
+            self.take_profit_ladder = ladder.to_vec();
+        }
+        self
+    }
+
+    pub fn validate(&self) -> crate::error::Result<()> {
+        let mut previous_multiple = 1.0;
+        let mut total_fraction = 0.0;
+        for rung in &self.take_profit_ladder {
+            if rung.price_multiple.is_nan() || rung.price_multiple <= previous_multiple {
+                return Err(BotError::Config(format!(
+                    "take_profit_ladder multiples must rise above 1.0, got {}",
+                    rung.price_multiple
+                )));
+            }
+            if rung.fraction.is_nan() || rung.fraction <= 0.0 {
+                return Err(BotError::Config(format!(
+                    "take_profit_ladder fractions must be positive, got {}",
+                    rung.fraction
+                )));
+            }
+            previous_multiple = rung.price_multiple;
+            total_fraction += rung.fraction;
+        }
+        if total_fraction > 1.0 + 1e-9 {
+            return Err(BotError::Config(format!(
+                "take_profit_ladder fractions must sum to at most 1.0, got {}",
+                total_fraction
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Sell `fraction` of the tokens bought once the price reaches `price_multiple` x entry
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TakeProfitRung {
+    pub price_multiple: f64,
+    pub fraction: f64,
+}
+
+impl TakeProfitRung {
+    /// Parse `multiple:fraction` pairs separated by commas, e.g. `2.0:0.5,3.0:0.25`
+    /// (half at 2x, a quarter at 3x); an empty string is no ladder
+    pub fn parse_ladder(s: &str) -> anyhow::Result<Vec<Self>> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|rung| !rung.is_empty())
+            .map(|rung| {
+                let (price_multiple, fraction) = rung
+                    .split_once(':')
+                    .ok_or_else(|| anyhow::anyhow!("Take profit rung must be multiple:fraction, got {}", rung))?;
+                Ok(Self {
+                    price_multiple: price_multiple.trim().parse()?,
+                    fraction: fraction.trim().parse()?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_dump_is_opt_in_and_redacted() {
+        let wallet_path = "/home/trader/.config/solana/id.json";
+        let vars = |dump: Option<&str>| {
+            let mut vars = vec![
+                ("WALLET_KEYPAIR".to_string(), wallet_path.to_string()),
+                ("BIRDEYE_API_KEY".to_string(), "birdeye-secret".to_string()),
+                ("RPC_URL".to_string(), "https://api.devnet.solana.com".to_string()),
+            ];
+            if let Some(dump) = dump {
+                vars.push(("DEBUG_DUMP_ENV".to_string(), dump.to_string()));
+            }
+            vars
+        };
+
+        assert!(env_dump_lines(vars(None)).is_empty());
+        assert!(env_dump_lines(vars(Some("false"))).is_empty());
+
+        let lines = env_dump_lines(vars(Some("true")));
+        assert!(lines.contains(&"RPC_URL=https://api.devnet.solana.com".to_string()));
+        assert!(lines.contains(&"WALLET_KEYPAIR=<redacted>".to_string()));
+        assert!(lines.iter().all(|line| !line.contains(wallet_path) && !line.contains("birdeye-secret")));
+    }
+
+    fn write_config(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}.toml", name, std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_from_file_with_env_override() {
+        let wallet = bs58::encode(Keypair::new().to_bytes()).into_string();
+        let path = write_config("curverider-config", &format!(r#"
+wallet_private_key = "{wallet}"
+rpc_url = "https://rpc.example.com"
+max_position_size_sol = 0.25
+max_slippage_bps = 300
+strategy_type = "momentum_scalper"
+dry_run = true
+position_timeout_seconds = 900
+"#));
+
+        std::env::set_var("POSITION_TIMEOUT_SECONDS", "120");
+        let config = BotConfig::from_file(&path);
+        std::env::remove_var("POSITION_TIMEOUT_SECONDS");
+        std::fs::remove_file(&path).ok();
+        let config = config.unwrap();
+
+        assert_eq!(config.rpc_url, "https://rpc.example.com");
+        assert_eq!(config.max_position_size_sol, 0.25);
+        assert_eq!(config.max_slippage_bps, 300);
+        assert_eq!(config.strategy_type, StrategyType::MomentumScalper);
+        assert!(config.dry_run);
+        // Env wins over the file
+        assert_eq!(config.position_timeout_seconds, 120);
+        // Unset keys keep their defaults
+        assert_eq!(config.scan_interval_ms, 1000);
+    }
+
+    #[test]
+    fn test_from_file_rejects_out_of_range_values() {
+        let wallet = bs58::encode(Keypair::new().to_bytes()).into_string();
+        let path = write_config("curverider-bad-config", &format!(r#"
+wallet_private_key = "{wallet}"
+max_slippage_bps = 60000
+"#));
+
+        let result = BotConfig::from_file(&path);
+        std::fs::remove_file(&path).ok();
+
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("max_slippage_bps"), "{}", err);
+    }
+
+    fn assert_invalid(config: BotConfig, field: &str) {
+        match config.validate() {
+            Err(BotError::Config(msg)) => assert!(msg.contains(field), "{}", msg),
+            other => panic!("expected config error for {}, got {:?}", field, other),
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_defaults() {
+        assert!(BotConfig::for_tests("http://localhost").validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_take_profit_multiplier() {
+        let mut config = BotConfig::for_tests("http://localhost");
+        config.take_profit_multiplier = 0.8;
+        assert_invalid(config, "take_profit_multiplier");
+    }
+
+    #[test]
+    fn test_validate_stop_loss_percentage() {
+        for bad in [0.0, 1.0, 5.0, -0.1] {
+            let mut config = BotConfig::for_tests("http://localhost");
+            config.stop_loss_percentage = bad;
+            assert_invalid(config, "stop_loss_percentage");
+        }
+    }
+
+    #[test]
+    fn test_validate_max_slippage_bps() {
+        let mut config = BotConfig::for_tests("http://localhost");
+        config.max_slippage_bps = 60_000;
+        assert_invalid(config, "max_slippage_bps");
+    }
+
+    #[test]
+    fn test_validate_position_and_liquidity_sizes() {
+        let mut config = BotConfig::for_tests("http://localhost");
+        config.max_position_size_sol = 0.0;
+        assert_invalid(config, "max_position_size_sol");
+
+        let mut config = BotConfig::for_tests("http://localhost");
+        config.min_liquidity_sol = -1.0;
+        assert_invalid(config, "min_liquidity_sol");
+    }
+
+    #[test]
+    fn test_validate_max_concurrent_positions() {
+        let mut config = BotConfig::for_tests("http://localhost");
+        config.max_concurrent_positions = 0;
+        assert_invalid(config, "max_concurrent_positions");
+    }
+
+    #[test]
+    fn test_validate_signal_confidence_thresholds() {
+        let mut config = BotConfig::for_tests("http://localhost");
+        config.buy_confidence = 1.2;
+        assert_invalid(config, "buy_confidence");
+
+        let mut config = BotConfig::for_tests("http://localhost");
+        config.strong_buy_confidence = 0.60;
+        assert_invalid(config, "strong_buy_confidence");
+    }
+
+    fn ladder(rungs: &[(f64, f64)]) -> StrategyExitParams {
+        StrategyExitParams {
+            take_profit_multiplier: 2.0,
+            stop_loss_percentage: 0.5,
+            position_timeout_seconds: 3600,
+            use_trailing_stop: true,
+            trailing_activation_pct: 0.2,
+            trailing_distance_pct: 0.1,
+            loss_cooldown_seconds: 0,
+            take_profit_ladder: rungs
+                .iter()
+                .map(|&(price_multiple, fraction)| TakeProfitRung { price_multiple, fraction })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_take_profit_ladder_validation() {
+        assert!(ladder(&[(2.0, 0.5), (3.0, 0.25)]).validate().is_ok());
+        assert!(ladder(&[(2.0, 0.5), (3.0, 0.5)]).validate().is_ok());
+        assert!(ladder(&[(2.0, 0.75), (3.0, 0.5)]).validate().is_err());
+        assert!(ladder(&[(3.0, 0.25), (2.0, 0.25)]).validate().is_err());
+        assert!(ladder(&[(0.9, 0.25)]).validate().is_err());
+        assert!(ladder(&[(2.0, 0.0)]).validate().is_err());
+    }
+
+    #[test]
+    fn test_take_profit_ladder_from_config() {
+        let wallet = Keypair::new().to_base58_string();
+        let config = BotConfig::from_lookup(|key| match key {
+            "WALLET_PRIVATE_KEY" => Some(wallet.clone()),
+            "TAKE_PROFIT_LADDER" => Some("2.0:0.5, 3.0:0.25".to_string()),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(
+            config.take_profit_ladder,
+            vec![
+                TakeProfitRung { price_multiple: 2.0, fraction: 0.5 },
+                TakeProfitRung { price_multiple: 3.0, fraction: 0.25 },
+            ]
+        );
+
+        let exit = ladder(&[]).with_take_profit_ladder(&config.take_profit_ladder);
+        assert_eq!(exit.take_profit_ladder, config.take_profit_ladder);
+        let exit = ladder(&[(4.0, 0.5)]).with_take_profit_ladder(&[]);
+        assert_eq!(exit.take_profit_ladder, vec![TakeProfitRung { price_multiple: 4.0, fraction: 0.5 }]);
+
+        assert!(TakeProfitRung::parse_ladder("").unwrap().is_empty());
+        assert!(TakeProfitRung::parse_ladder("2.0").is_err());
+        assert!(TakeProfitRung::parse_ladder("2.0:half").is_err());
+    }
+
+    #[test]
+    fn test_validate_holder_concentration_overrides() {
+        let mut config = BotConfig::for_tests("http://localhost");
+        config.holder_thresholds.grad_max_concentration = Some(1.5);
+        assert_invalid(config, "grad_max_concentration");
+    }
 }
@@ -0,0 +1,679 @@
+use crate::error::{BotError, Result};
+use crate::rpc::FailoverRpc;
+use crate::types::BotConfig;
+use solana_sdk::{
+    hash::hash,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+/// Non-custodial vault program (programs/curverider-vault, mainnet layout)
+pub const VAULT_PROGRAM_ID: &str = "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS";
+
+const CONFIG_SEED: &[u8] = b"config";
+const DELEGATION_SEED: &[u8] = b"delegation";
+const POSITION_SEED: &[u8] = b"position";
+const MINT_WHITELIST_SEED: &[u8] = b"mint_whitelist";
+
+/// On-chain prices are lamports per whole token, scaled by `PRICE_SCALE`
+pub const PRICE_SCALE: u64 = 1_000_000;
+
+const LAMPORTS_PER_SOL: f64 = 1e9;
+
+// ============================================================================
+// PDA Derivation
+// ============================================================================
+
+pub fn derive_config(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[CONFIG_SEED], program_id).0
+}
+
+pub fn derive_delegation(program_id: &Pubkey, user: &Pubkey, vault_index: u8) -> Pubkey {
+    Pubkey::find_program_address(&[DELEGATION_SEED, user.as_ref(), &[vault_index]], program_id).0
+}
+
+pub fn derive_position(program_id: &Pubkey, delegation: &Pubkey, client_order_id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[POSITION_SEED, delegation.as_ref(), &client_order_id.to_le_bytes()],
+        program_id,
+    )
+    .0
+}
+
+pub fn derive_mint_whitelist(program_id: &Pubkey, delegation: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[MINT_WHITELIST_SEED, delegation.as_ref()], program_id).0
+}
+
+/// Anchor instruction discriminator: first 8 bytes of sha256("global:<name>")
+fn instruction_discriminator(name: &str) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash(format!("global:{}", name).as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+/// On-chain representation of a price in SOL per whole token
+pub fn to_scaled_price(price_sol: f64) -> u64 {
+    (price_sol * LAMPORTS_PER_SOL * PRICE_SCALE as f64).round() as u64
+}
+
+/// Price in SOL per whole token of an on-chain scaled price
+pub fn from_scaled_price(scaled: u64) -> f64 {
+    scaled as f64 / PRICE_SCALE as f64 / LAMPORTS_PER_SOL
+}
+
+// ============================================================================
+// Delegation State
+// ============================================================================
+
+/// The parts of an on-chain `DelegationAccount` the bot needs
+#[derive(Debug, Clone, PartialEq)]
+pub struct DelegationState {
+    pub user: Pubkey,
+    pub bot_authority: Pubkey,
+    pub is_active: bool,
+    pub active_trades: u8,
+    pub position_counter: u64,
+}
+
+impl DelegationState {
+    /// Borsh layout after the 8-byte discriminator:
+    /// user (32) | bot_authority (32) | vault_index (1) | strategy (1) | max_position_size_sol (8)
+    /// | max_concurrent_trades (1) | is_active (1) | active_trades (1) | total_trades (8)
+    /// | profitable_trades (8) | total_pnl (8) | total_volume (8) | created_at (8)
+    /// | last_trade_at (8) | bump (1) | position_counter (8)
+    const LEN: usize = 8 + 32 + 32 + 1 + 1 + 8 + 1 + 1 + 1 + 8 * 6 + 1 + 8;
+
+    pub fn from_account_data(data: &[u8]) -> Option<Self> {
+        if data.len() < Self::LEN {
+            return None;
+        }
+
+        let read_pubkey = |offset: usize| Pubkey::try_from(&data[offset..offset + 32]).ok();
+
+        let mut counter = [0u8; 8];
+        counter.copy_from_slice(&data[Self::LEN - 8..Self::LEN]);
+
+        Some(Self {
+            user: read_pubkey(8)?,
+            bot_authority: read_pubkey(40)?,
+            is_active: data[83] != 0,
+            active_trades: data[84],
+            position_counter: u64::from_le_bytes(counter),
+        })
+    }
+}
+
+/// Status of an on-chain `Position`; `Closed` and `Liquidated` are both final
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VaultPositionStatus {
+    Open,
+    Closed,
+    Liquidated,
+}
+
+/// The parts of an on-chain `Position` reconciliation needs
+#[derive(Debug, Clone, PartialEq)]
+pub struct VaultPositionState {
+    pub token_mint: Pubkey,
+    pub status: VaultPositionStatus,
+    /// Exit price once closed, in SOL per whole token
+    pub current_price: f64,
+    pub closed_at: i64,
+}
+
+impl VaultPositionState {
+    /// Borsh layout after the 8-byte discriminator:
+    /// delegation (32) | user (32) | token_mint (32) | amount_sol | entry_price | current_price
+    /// | take_profit_price | stop_loss_price (8 each) | status (1) | opened_at (8) | closed_at (8) | ...
+    const STATUS_OFFSET: usize = 8 + 32 * 3 + 8 * 5;
+    const LEN: usize = Self::STATUS_OFFSET + 1 + 8 * 2;
+
+    pub fn from_account_data(data: &[u8]) -> Option<Self> {
+        if data.len() < Self::LEN {
+            return None;
+        }
+
+        let read_u64 = |offset: usize| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&data[offset..offset + 8]);
+            u64::from_le_bytes(bytes)
+        };
+        let status = match data[Self::STATUS_OFFSET] {
+            0 => VaultPositionStatus::Open,
+            1 => VaultPositionStatus::Closed,
+            2 => VaultPositionStatus::Liquidated,
+            _ => return None,
+        };
+
+        Some(Self {
+            token_mint: Pubkey::try_from(&data[72..104]).ok()?,
+            status,
+            current_price: from_scaled_price(read_u64(120)),
+            closed_at: read_u64(Self::STATUS_OFFSET + 9) as i64,
+        })
+    }
+}
+
+// ============================================================================
+// Instruction Builders
+// ============================================================================
+
+/// Build the vault `open_position` instruction
+///
+/// Data layout: discriminator (8) | client_order_id (u64 LE) | token_mint (32) | amount_sol | entry_price
+/// | take_profit_price | stop_loss_price (u64 LE each) | strategy (1)
+/// | entry_confidence_bps | trailing_activation_bps | trailing_distance_bps (u16 LE each)
+/// | liquidity_sol (u64 LE)
+#[allow(clippy::too_many_arguments)]
+pub fn build_open_position_instruction(
+    program_id: &Pubkey,
+    bot_authority: &Pubkey,
+    user: &Pubkey,
+    delegation: &Pubkey,
+    position: &Pubkey,
+    client_order_id: u64,
+    token_mint: &Pubkey,
+    amount_sol: u64,
+    entry_price: u64,
+    take_profit_price: u64,
+    stop_loss_price: u64,
+    strategy: u8,
+    entry_confidence_bps: u16,
+    trailing_activation_bps: u16,
+    trailing_distance_bps: u16,
+    liquidity_sol: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(derive_config(program_id), false),
+        AccountMeta::new(*delegation, false),
+        AccountMeta::new(*position, false),
+        AccountMeta::new_readonly(derive_mint_whitelist(program_id, delegation), false),
+        AccountMeta::new_readonly(*user, false),
+        AccountMeta::new(*bot_authority, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    let mut data = Vec::with_capacity(8 + 8 + 32 + 4 * 8 + 1 + 3 * 2 + 8);
+    data.extend_from_slice(&instruction_discriminator("open_position"));
+    data.extend_from_slice(&client_order_id.to_le_bytes());
+    data.extend_from_slice(token_mint.as_ref());
+    for value in [amount_sol, entry_price, take_profit_price, stop_loss_price] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.push(strategy);
+    for value in [entry_confidence_bps, trailing_activation_bps, trailing_distance_bps] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(&liquidity_sol.to_le_bytes());
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Build the vault `update_trailing` instruction
+///
+/// Data layout: discriminator (8) | current_price: u64 LE
+pub fn build_update_trailing_instruction(
+    program_id: &Pubkey,
+    bot_authority: &Pubkey,
+    delegation: &Pubkey,
+    position: &Pubkey,
+    current_price: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*delegation, false),
+        AccountMeta::new(*position, false),
+        AccountMeta::new_readonly(*bot_authority, true),
+    ];
+
+    let mut data = Vec::with_capacity(8 + 8);
+    data.extend_from_slice(&instruction_discriminator("update_trailing"));
+    data.extend_from_slice(&current_price.to_le_bytes());
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Build the vault `close_position` instruction
+///
+/// Data layout: discriminator (8) | exit_price: u64 LE | amount_received: u64 LE | trailing_stop (1)
+pub fn build_close_position_instruction(
+    program_id: &Pubkey,
+    bot_authority: &Pubkey,
+    delegation: &Pubkey,
+    position: &Pubkey,
+    exit_price: u64,
+    amount_received: u64,
+    trailing_stop: bool,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*delegation, false),
+        AccountMeta::new(*position, false),
+        AccountMeta::new_readonly(*bot_authority, true),
+    ];
+
+    let mut data = Vec::with_capacity(8 + 2 * 8 + 1);
+    data.extend_from_slice(&instruction_discriminator("close_position"));
+    data.extend_from_slice(&exit_price.to_le_bytes());
+    data.extend_from_slice(&amount_received.to_le_bytes());
+    data.push(u8::from(trailing_stop));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+// ============================================================================
+// Vault Client
+// ============================================================================
+
+/// Records the bot's trades against a user's delegation in the vault program
+pub struct VaultClient {
+    rpc: FailoverRpc,
+    program_id: Pubkey,
+    user: Pubkey,
+    delegation: Pubkey,
+}
+
+impl VaultClient {
+    /// `None` unless a delegating user is configured
+    pub fn new(config: &BotConfig) -> Option<Self> {
+        let user = config.vault_delegation_user?;
+
+        Some(Self {
+            rpc: FailoverRpc::new(&config.rpc_urls(), config.commitment.config()),
+            program_id: config.vault_program,
+            user,
+            delegation: derive_delegation(&config.vault_program, &user, config.vault_index),
+        })
+    }
+
+    pub fn fetch_delegation(&self) -> Result<DelegationState> {
+        let account = self.rpc
+            .call(|client| client.get_account_with_commitment(&self.delegation, client.commitment()))?
+            .value
+            .ok_or_else(|| BotError::Config(format!("No delegation account at {}", self.delegation)))?;
+
+        DelegationState::from_account_data(&account.data)
+            .ok_or_else(|| BotError::Unknown(format!("Malformed delegation account {}", self.delegation)))
+    }
+
+    /// The vault's record of `position`, or `None` when no such account exists
+    pub fn fetch_position(&self, position: &Pubkey) -> Result<Option<VaultPositionState>> {
+        let account = match self.rpc
+            .call(|client| client.get_account_with_commitment(position, client.commitment()))?
+            .value
+        {
+            Some(account) => account,
+            None => return Ok(None),
+        };
+
+        VaultPositionState::from_account_data(&account.data)
+            .map(Some)
+            .ok_or_else(|| BotError::Unknown(format!("Malformed position account {}", position)))
+    }
+
+    /// `open_position` for the position PDA of `client_order_id`, returned alongside
+    /// the instruction; resending the same order id can't open a second position.
+    /// `trailing_stop` is the (activation, distance) fractions the program enforces, and
+    /// `liquidity_sol` the token's current liquidity for the delegation's floor.
+    #[allow(clippy::too_many_arguments)]
+    pub fn open_position_instruction(
+        &self,
+        bot_authority: &Pubkey,
+        client_order_id: u64,
+        token_mint: &Pubkey,
+        sol_amount: f64,
+        entry_price: f64,
+        take_profit_price: f64,
+        stop_loss_price: f64,
+        strategy: u8,
+        entry_confidence: f64,
+        trailing_stop: Option<(f64, f64)>,
+        liquidity_sol: f64,
+    ) -> Result<(Pubkey, Instruction)> {
+        let delegation = self.fetch_delegation()?;
+        if !delegation.is_active {
+            return Err(BotError::Config(format!("Delegation {} is not active", self.delegation)));
+        }
+        if delegation.bot_authority != *bot_authority {
+            return Err(BotError::Config(format!(
+                "Delegation {} is held by bot {}, not {}",
+                self.delegation, delegation.bot_authority, bot_authority
+            )));
+        }
+
+        let position = derive_position(&self.program_id, &self.delegation, client_order_id);
+        let (trailing_activation, trailing_distance) = trailing_stop.unwrap_or((0.0, 0.0));
+        let instruction = build_open_position_instruction(
+            &self.program_id,
+            bot_authority,
+            &self.user,
+            &self.delegation,
+            &position,
+            client_order_id,
+            token_mint,
+            (sol_amount * 1e9) as u64,
+            to_scaled_price(entry_price),
+            to_scaled_price(take_profit_price),
+            to_scaled_price(stop_loss_price),
+            strategy,
+            (entry_confidence.clamp(0.0, 1.0) * 10_000.0).round() as u16,
+            (trailing_activation.max(0.0) * 10_000.0).round() as u16,
+            (trailing_distance.clamp(0.0, 0.9999) * 10_000.0).round() as u16,
+            (liquidity_sol * 1e9) as u64,
+        );
+
+        Ok((position, instruction))
+    }
+
+    pub fn update_trailing_instruction(
+        &self,
+        bot_authority: &Pubkey,
+        position: &Pubkey,
+        current_price: f64,
+    ) -> Instruction {
+        build_update_trailing_instruction(
+            &self.program_id,
+            bot_authority,
+            &self.delegation,
+            position,
+            to_scaled_price(current_price),
+        )
+    }
+
+    pub fn close_position_instruction(
+        &self,
+        bot_authority: &Pubkey,
+        position: &Pubkey,
+        exit_price: f64,
+        sol_received: f64,
+        trailing_stop: bool,
+    ) -> Instruction {
+        build_close_position_instruction(
+            &self.program_id,
+            bot_authority,
+            &self.delegation,
+            position,
+            to_scaled_price(exit_price),
+            (sol_received * 1e9) as u64,
+            trailing_stop,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::testing::mock_version;
+    use base64::Engine;
+    use mockito::Matcher;
+    use std::str::FromStr;
+
+    fn delegation_data(user: &Pubkey, bot_authority: &Pubkey, position_counter: u64) -> Vec<u8> {
+        let mut data = instruction_discriminator("unused").to_vec();
+        data.extend_from_slice(user.as_ref());
+        data.extend_from_slice(bot_authority.as_ref());
+        data.extend_from_slice(&[0, 0]); // vault_index, strategy
+        data.extend_from_slice(&1_000_000_000u64.to_le_bytes());
+        data.extend_from_slice(&[5, 1, 2]); // max_concurrent_trades, is_active, active_trades
+        data.extend_from_slice(&[0u8; 8 * 6]);
+        data.push(255); // bump
+        data.extend_from_slice(&position_counter.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_open_position_instruction_layout() {
+        let program_id = Pubkey::from_str(VAULT_PROGRAM_ID).unwrap();
+        let bot = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let delegation = derive_delegation(&program_id, &user, 0);
+        let position = derive_position(&program_id, &delegation, 7);
+
+        let ix = build_open_position_instruction(
+            &program_id, &bot, &user, &delegation, &position, 7, &mint, 500_000_000, 30, 60, 15, 2, 8_250, 2_000, 1_000, 40_000_000_000,
+        );
+
+        let accounts: Vec<Pubkey> = ix.accounts.iter().map(|a| a.pubkey).collect();
+        assert_eq!(accounts, vec![
+            derive_config(&program_id),
+            delegation,
+            position,
+            derive_mint_whitelist(&program_id, &delegation),
+            user,
+            bot,
+            system_program::id(),
+        ]);
+        assert!(ix.accounts[5].is_signer);
+
+        assert_eq!(ix.data.len(), 8 + 8 + 32 + 32 + 1 + 3 * 2 + 8);
+        assert_eq!(&ix.data[..8], &instruction_discriminator("open_position"));
+        assert_eq!(&ix.data[8..16], &7u64.to_le_bytes());
+        assert_eq!(&ix.data[16..48], mint.as_ref());
+        assert_eq!(&ix.data[48..56], &500_000_000u64.to_le_bytes());
+        assert_eq!(&ix.data[72..80], &15u64.to_le_bytes());
+        assert_eq!(ix.data[80], 2);
+        assert_eq!(&ix.data[81..83], &8_250u16.to_le_bytes());
+        assert_eq!(&ix.data[83..85], &2_000u16.to_le_bytes());
+        assert_eq!(&ix.data[85..87], &1_000u16.to_le_bytes());
+        assert_eq!(&ix.data[87..95], &40_000_000_000u64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_scaled_prices_round_trip() {
+        for price in [0.000000028, 0.0000015, 0.0003, 1.5, 42.0] {
+            let scaled = to_scaled_price(price);
+            let back = from_scaled_price(scaled);
+            assert!(((back - price) / price).abs() < 1e-9, "{} -> {} -> {}", price, scaled, back);
+        }
+
+        // 30 lamports per token
+        assert_eq!(to_scaled_price(0.00000003), 30 * PRICE_SCALE);
+        assert_eq!(from_scaled_price(30 * PRICE_SCALE), 0.00000003);
+    }
+
+    #[test]
+    fn test_open_position_targets_client_order_pda() {
+        let mut server = mockito::Server::new();
+        let _version = mock_version(&mut server);
+        // The primary RPC is down; reads fail over at the configured commitment
+        let mut config = BotConfig::for_tests("http://127.0.0.1:1");
+        config.rpc_fallback_urls = vec![server.url()];
+        config.commitment = crate::types::RpcCommitment::Finalized;
+        let user = Pubkey::new_unique();
+        let bot = Pubkey::new_unique();
+        config.vault_delegation_user = Some(user);
+
+        let data = delegation_data(&user, &bot, 3);
+        let _mock = server.mock("POST", "/")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::PartialJsonString(r#"{"method":"getAccountInfo"}"#.to_string()),
+                Matcher::Regex("finalized".to_string()),
+            ]))
+            .with_body(serde_json::json!({
+                "jsonrpc": "2.0",
+                "result": {
+                    "context": { "slot": 1 },
+                    "value": {
+                        "data": [base64::engine::general_purpose::STANDARD.encode(&data), "base64"],
+                        "executable": false,
+                        "lamports": 1_000_000,
+                        "owner": VAULT_PROGRAM_ID,
+                        "rentEpoch": 0
+                    }
+                },
+                "id": 1
+            }).to_string())
+            .create();
+
+        let client = VaultClient::new(&config).unwrap();
+        let state = client.fetch_delegation().unwrap();
+        assert_eq!(state.user, user);
+        assert_eq!(state.active_trades, 2);
+
+        let (position, ix) = client
+            .open_position_instruction(&bot, 1_700_000_000_123, &Pubkey::new_unique(), 0.5, 0.00000003, 0.00000006, 0.000000015, 1, 0.91, Some((0.2, 0.1)), 12.5)
+            .unwrap();
+
+        let delegation = derive_delegation(&config.vault_program, &user, 0);
+        assert_eq!(position, derive_position(&config.vault_program, &delegation, 1_700_000_000_123));
+        assert_eq!(ix.accounts[2].pubkey, position);
+        // 30 lamports per token, scaled by 1e6
+        assert_eq!(&ix.data[56..64], &30_000_000u64.to_le_bytes());
+        assert_eq!(&ix.data[81..83], &9_100u16.to_le_bytes());
+        assert_eq!(&ix.data[83..87], &[0xd0, 0x07, 0xe8, 0x03]); // 2000, 1000 bps
+        assert_eq!(&ix.data[87..95], &12_500_000_000u64.to_le_bytes());
+
+        // Some other bot can't record against this delegation
+        assert!(client.open_position_instruction(&Pubkey::new_unique(), 1, &Pubkey::new_unique(), 0.5, 1.0, 2.0, 0.5, 0, 0.8, None, 12.5).is_err());
+    }
+
+    // ------------------------------------------------------------------------
+    // Layouts against the compiled vault program
+    // ------------------------------------------------------------------------
+
+    use anchor_lang::{AccountSerialize, AnchorDeserialize, Discriminator, ToAccountMetas};
+    use curverider_vault_mainnet as program;
+
+    /// `ix`'s arguments, decoded the way the program's dispatcher decodes `T`
+    fn program_args<T: Discriminator + AnchorDeserialize>(ix: &Instruction) -> T {
+        assert_eq!(ix.program_id, program::ID);
+        assert_eq!(&ix.data[..8], &T::DISCRIMINATOR[..]);
+        T::try_from_slice(&ix.data[8..]).unwrap()
+    }
+
+    fn serialized(account: &impl AccountSerialize) -> Vec<u8> {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_vault_program_id_is_the_non_custodial_program() {
+        assert_eq!(Pubkey::from_str(VAULT_PROGRAM_ID).unwrap(), program::ID);
+    }
+
+    #[test]
+    fn test_open_position_matches_program_instruction() {
+        let [bot, user, mint] = [(); 3].map(|_| Pubkey::new_unique());
+        let delegation = derive_delegation(&program::ID, &user, 2);
+        let position = derive_position(&program::ID, &delegation, 7);
+
+        let ix = build_open_position_instruction(
+            &program::ID, &bot, &user, &delegation, &position, 7, &mint, 500_000_000, 30, 60, 15, 2, 8_250, 2_000, 1_000, 40_000_000_000,
+        );
+
+        let args: program::instruction::OpenPosition = program_args(&ix);
+        assert_eq!((args.client_order_id, args.token_mint, args.amount_sol), (7, mint, 500_000_000));
+        assert_eq!((args.entry_price, args.take_profit_price, args.stop_loss_price), (30, 60, 15));
+        assert_eq!((args.strategy, args.entry_confidence_bps), (2, 8_250));
+        assert_eq!((args.trailing_activation_bps, args.trailing_distance_bps), (2_000, 1_000));
+        assert_eq!(args.liquidity_sol, 40_000_000_000);
+
+        let accounts = program::accounts::OpenPosition {
+            config: derive_config(&program::ID),
+            delegation,
+            position,
+            mint_whitelist: derive_mint_whitelist(&program::ID, &delegation),
+            user,
+            bot_authority: bot,
+            system_program: system_program::id(),
+        };
+        assert_eq!(ix.accounts, accounts.to_account_metas(None));
+    }
+
+    #[test]
+    fn test_close_and_trailing_match_program_instructions() {
+        let [bot, delegation, position] = [(); 3].map(|_| Pubkey::new_unique());
+        let accounts = program::accounts::ClosePosition { delegation, position, bot_authority: bot };
+
+        let ix = build_close_position_instruction(&program::ID, &bot, &delegation, &position, 45, 750_000_000, true);
+        let args: program::instruction::ClosePosition = program_args(&ix);
+        assert_eq!((args.exit_price, args.amount_received, args.trailing_stop), (45, 750_000_000, true));
+        assert_eq!(ix.accounts, accounts.to_account_metas(None));
+
+        let ix = build_update_trailing_instruction(&program::ID, &bot, &delegation, &position, 52);
+        let args: program::instruction::UpdateTrailing = program_args(&ix);
+        assert_eq!(args.current_price, 52);
+        let accounts = program::accounts::UpdateTrailing { delegation, position, bot_authority: bot };
+        assert_eq!(ix.accounts, accounts.to_account_metas(None));
+    }
+
+    #[test]
+    fn test_account_states_read_program_accounts() {
+        let [user, bot, mint] = [(); 3].map(|_| Pubkey::new_unique());
+        let delegation = program::DelegationAccount {
+            user,
+            bot_authority: bot,
+            vault_index: 1,
+            strategy: 2,
+            max_position_size_sol: 1_000_000_000,
+            max_concurrent_trades: 5,
+            is_active: true,
+            active_trades: 3,
+            total_trades: 9,
+            profitable_trades: 6,
+            total_pnl: -12,
+            total_volume: 4_000_000_000,
+            created_at: 1_700_000_000,
+            last_trade_at: 1_700_000_500,
+            bump: 254,
+            position_counter: 11,
+            min_liquidity_sol: 10_000_000_000,
+            performance_fee_bps: 2_000,
+            fees_owed: 7,
+        };
+        let state = DelegationState::from_account_data(&serialized(&delegation)).unwrap();
+        assert_eq!(state, DelegationState {
+            user,
+            bot_authority: bot,
+            is_active: true,
+            active_trades: 3,
+            position_counter: 11,
+        });
+
+        let position = program::Position {
+            delegation: Pubkey::new_unique(),
+            user,
+            token_mint: mint,
+            amount_sol: 500_000_000,
+            entry_price: 30_000_000,
+            current_price: 45_000_000,
+            take_profit_price: 60_000_000,
+            stop_loss_price: 15_000_000,
+            status: program::PositionStatus::Liquidated as u8,
+            opened_at: 1_700_000_100,
+            closed_at: 1_700_000_900,
+            pnl: 0,
+            position_id: 4,
+            client_order_id: 99,
+            bump: 253,
+            strategy: 1,
+            entry_confidence_bps: 8_000,
+            pnl_bps: 0,
+            trailing_activation_bps: 0,
+            trailing_distance_bps: 0,
+            highest_price_seen: 30_000_000,
+            trailing_active: false,
+            closed_by_trailing_stop: false,
+        };
+        let state = VaultPositionState::from_account_data(&serialized(&position)).unwrap();
+        assert_eq!(state, VaultPositionState {
+            token_mint: mint,
+            status: VaultPositionStatus::Liquidated,
+            current_price: from_scaled_price(45_000_000),
+            closed_at: 1_700_000_900,
+        });
+    }
+}
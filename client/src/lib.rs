@@ -0,0 +1,625 @@
+//! Typed instruction builders and account decoders for the Curverider vault program
+//!
+//! Builders derive the program's PDAs and assemble complete `Instruction`s, so
+//! callers never hand-roll account metas or instruction data.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, system_program};
+use anchor_lang::InstructionData;
+use anchor_spl::associated_token::{self, get_associated_token_address};
+use anchor_spl::token;
+use curverider_vault::{accounts, instruction};
+
+pub use curverider_vault::{MintExposure, Position, ReferralAccount, UserAccount, Vault, ID as PROGRAM_ID};
+
+const VAULT_SEED: &[u8] = b"vault";
+const USER_SEED: &[u8] = b"user";
+const REFERRAL_SEED: &[u8] = b"referral";
+const POSITION_SEED: &[u8] = b"position";
+const MINT_EXPOSURE_SEED: &[u8] = b"mint_exposure";
+
+// ============================================================================
+// PDA Derivation
+// ============================================================================
+
+pub fn vault_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED], &PROGRAM_ID)
+}
+
+pub fn user_account_pda(user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[USER_SEED, user.as_ref()], &PROGRAM_ID).0
+}
+
+pub fn referral_pda(referrer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[REFERRAL_SEED, referrer.as_ref()], &PROGRAM_ID).0
+}
+
+/// Address of the position opened when the vault's `position_counter` was `position_id`
+pub fn position_pda(position_id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[POSITION_SEED, vault_pda().0.as_ref(), &position_id.to_le_bytes()],
+        &PROGRAM_ID,
+    )
+    .0
+}
+
+/// Address tracking the vault's open exposure to `token_mint`
+pub fn mint_exposure_pda(token_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[MINT_EXPOSURE_SEED, vault_pda().0.as_ref(), token_mint.as_ref()],
+        &PROGRAM_ID,
+    )
+    .0
+}
+
+// ============================================================================
+// Instruction Builders
+// ============================================================================
+
+/// Limits and fees a vault is created with
+#[derive(Debug, Clone, Copy)]
+pub struct VaultParams {
+    pub min_deposit: u64,
+    pub max_deposit: u64,
+    pub management_fee_bps: u16,
+    pub performance_fee_bps: u16,
+    pub vault_capacity: u64,
+    pub per_user_cap: u64,
+    /// SPL mint the vault takes deposits in; `None` for native SOL
+    pub deposit_mint: Option<Pubkey>,
+}
+
+/// Create the vault, paying claimed fees to `fee_recipient`
+pub fn initialize_vault_ix(authority: &Pubkey, fee_recipient: &Pubkey, params: &VaultParams) -> Instruction {
+    let (vault, vault_bump) = vault_pda();
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::InitializeVault {
+            vault,
+            authority: *authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::InitializeVault {
+            vault_bump,
+            min_deposit: params.min_deposit,
+            max_deposit: params.max_deposit,
+            management_fee_bps: params.management_fee_bps,
+            performance_fee_bps: params.performance_fee_bps,
+            vault_capacity: params.vault_capacity,
+            per_user_cap: params.per_user_cap,
+            deposit_mint: params.deposit_mint,
+            fee_recipient: *fee_recipient,
+        }
+        .data(),
+    }
+}
+
+/// `referrer` is the user's recorded referrer, or the one to record on a first deposit;
+/// its referral account is passed along when set
+pub fn deposit_ix(user: &Pubkey, amount: u64, referrer: Option<Pubkey>) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::Deposit {
+            vault: vault_pda().0,
+            user_account: user_account_pda(user),
+            referral_account: referrer.as_ref().map(referral_pda),
+            user: *user,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Deposit { amount, referrer }.data(),
+    }
+}
+
+/// `referrer` is the user's recorded referrer, if any
+pub fn withdraw_ix(
+    user: &Pubkey,
+    shares_to_burn: u64,
+    min_sol_out: u64,
+    referrer: Option<Pubkey>,
+) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::Withdraw {
+            vault: vault_pda().0,
+            user_account: user_account_pda(user),
+            referral_account: referrer.as_ref().map(referral_pda),
+            user: *user,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Withdraw {
+            shares_to_burn,
+            min_sol_out,
+        }
+        .data(),
+    }
+}
+
+/// Deposit `amount` base units of `mint` from the user's ATA; `referrer` as for `deposit_ix`
+pub fn deposit_spl_ix(user: &Pubkey, mint: &Pubkey, amount: u64, referrer: Option<Pubkey>) -> Instruction {
+    let vault = vault_pda().0;
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::DepositSpl {
+            vault,
+            user_account: user_account_pda(user),
+            referral_account: referrer.as_ref().map(referral_pda),
+            deposit_mint: *mint,
+            user_token_account: get_associated_token_address(user, mint),
+            vault_token_account: get_associated_token_address(&vault, mint),
+            user: *user,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::DepositSpl { amount, referrer }.data(),
+    }
+}
+
+/// Withdraw into the user's ATA for `mint`; `referrer` as for `withdraw_ix`
+pub fn withdraw_spl_ix(
+    user: &Pubkey,
+    mint: &Pubkey,
+    shares_to_burn: u64,
+    min_amount_out: u64,
+    referrer: Option<Pubkey>,
+) -> Instruction {
+    let vault = vault_pda().0;
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::WithdrawSpl {
+            vault,
+            user_account: user_account_pda(user),
+            referral_account: referrer.as_ref().map(referral_pda),
+            deposit_mint: *mint,
+            user_token_account: get_associated_token_address(user, mint),
+            vault_token_account: get_associated_token_address(&vault, mint),
+            user: *user,
+            token_program: token::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::WithdrawSpl {
+            shares_to_burn,
+            min_amount_out,
+        }
+        .data(),
+    }
+}
+
+/// `position_id` must be the vault's current `position_counter`; the position
+/// is created at `position_pda(position_id)`
+pub fn open_position_ix(
+    authority: &Pubkey,
+    position_id: u64,
+    token_mint: Pubkey,
+    amount_sol: u64,
+    entry_price: u64,
+    take_profit_price: u64,
+    stop_loss_price: u64,
+) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::OpenPosition {
+            vault: vault_pda().0,
+            position: position_pda(position_id),
+            mint_exposure: mint_exposure_pda(&token_mint),
+            authority: *authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::OpenPosition {
+            token_mint,
+            amount_sol,
+            entry_price,
+            take_profit_price,
+            stop_loss_price,
+        }
+        .data(),
+    }
+}
+
+/// Close `position`, which holds `token_mint`
+pub fn close_position_ix(
+    authority: &Pubkey,
+    position: &Pubkey,
+    token_mint: &Pubkey,
+    exit_price: u64,
+    amount_received: u64,
+) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::ClosePosition {
+            vault: vault_pda().0,
+            position: *position,
+            mint_exposure: mint_exposure_pda(token_mint),
+            authority: *authority,
+        }
+        .to_account_metas(None),
+        data: instruction::ClosePosition {
+            exit_price,
+            amount_received,
+        }
+        .data(),
+    }
+}
+
+/// Settings `update_vault_config` changes; `None` leaves a setting as it is
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VaultConfigUpdate {
+    pub min_deposit: Option<u64>,
+    pub max_deposit: Option<u64>,
+    pub management_fee_bps: Option<u16>,
+    pub performance_fee_bps: Option<u16>,
+    pub is_active: Option<bool>,
+    pub vault_capacity: Option<u64>,
+    pub per_user_cap: Option<u64>,
+    pub referral_fee_bps: Option<u16>,
+    pub fee_recipient: Option<Pubkey>,
+    pub compound_profits: Option<bool>,
+    pub profit_skim_bps: Option<u16>,
+    pub max_mint_allocation_bps: Option<u16>,
+}
+
+pub fn update_vault_config_ix(authority: &Pubkey, update: &VaultConfigUpdate) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::UpdateVaultConfig {
+            vault: vault_pda().0,
+            authority: *authority,
+        }
+        .to_account_metas(None),
+        data: instruction::UpdateVaultConfig {
+            min_deposit: update.min_deposit,
+            max_deposit: update.max_deposit,
+            management_fee_bps: update.management_fee_bps,
+            performance_fee_bps: update.performance_fee_bps,
+            is_active: update.is_active,
+            vault_capacity: update.vault_capacity,
+            per_user_cap: update.per_user_cap,
+            referral_fee_bps: update.referral_fee_bps,
+            fee_recipient: update.fee_recipient,
+            compound_profits: update.compound_profits,
+            profit_skim_bps: update.profit_skim_bps,
+            max_mint_allocation_bps: update.max_mint_allocation_bps,
+        }
+        .data(),
+    }
+}
+
+/// Claim `amount` of fees; `fee_recipient` must be the vault's current recipient
+pub fn claim_fees_ix(authority: &Pubkey, fee_recipient: &Pubkey, amount: u64) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::ClaimFees {
+            vault: vault_pda().0,
+            authority: *authority,
+            fee_recipient: *fee_recipient,
+        }
+        .to_account_metas(None),
+        data: instruction::ClaimFees { amount }.data(),
+    }
+}
+
+/// Compound `amount` of the profit reserve into deposits for current shareholders
+pub fn release_profit_reserve_ix(authority: &Pubkey, amount: u64) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::UpdateVaultConfig {
+            vault: vault_pda().0,
+            authority: *authority,
+        }
+        .to_account_metas(None),
+        data: instruction::ReleaseProfitReserve { amount }.data(),
+    }
+}
+
+/// Emit the vault's NAV and share price as a `VaultStats` event; read-only
+pub fn get_vault_stats_ix() -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::GetVaultStats {
+            vault: vault_pda().0,
+        }
+        .to_account_metas(None),
+        data: instruction::GetVaultStats {}.data(),
+    }
+}
+
+/// Create `referrer`'s referral account, paid for by `referrer`
+pub fn register_referrer_ix(referrer: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::RegisterReferrer {
+            vault: vault_pda().0,
+            referral_account: referral_pda(referrer),
+            referrer: *referrer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RegisterReferrer {}.data(),
+    }
+}
+
+/// Pay `referrer` the referral fees accrued to them
+pub fn claim_referral_fees_ix(referrer: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::ClaimReferralFees {
+            vault: vault_pda().0,
+            referral_account: referral_pda(referrer),
+            referrer: *referrer,
+        }
+        .to_account_metas(None),
+        data: instruction::ClaimReferralFees {}.data(),
+    }
+}
+
+/// Close the emptied vault, returning its lamports to `authority`; an SPL vault
+/// passes its `deposit_mint` so its ATA is closed as well
+pub fn close_vault_ix(authority: &Pubkey, deposit_mint: Option<&Pubkey>) -> Instruction {
+    let vault = vault_pda().0;
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts::CloseVault {
+            vault,
+            vault_token_account: deposit_mint.map(|mint| get_associated_token_address(&vault, mint)),
+            authority: *authority,
+            token_program: deposit_mint.map(|_| token::ID),
+        }
+        .to_account_metas(None),
+        data: instruction::CloseVault {}.data(),
+    }
+}
+
+// ============================================================================
+// Account Decoding
+// ============================================================================
+
+/// Decode fetched account data, checking the account discriminator
+pub fn decode_account<T: AccountDeserialize>(data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut &data[..])
+}
+
+pub fn decode_vault(data: &[u8]) -> Result<Vault> {
+    decode_account(data)
+}
+
+pub fn decode_user_account(data: &[u8]) -> Result<UserAccount> {
+    decode_account(data)
+}
+
+pub fn decode_position(data: &[u8]) -> Result<Position> {
+    decode_account(data)
+}
+
+pub fn decode_referral_account(data: &[u8]) -> Result<ReferralAccount> {
+    decode_account(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    /// Split an instruction's data into its discriminator and decoded arguments
+    fn decode_args<T: AnchorDeserialize + Discriminator>(ix: &Instruction) -> T {
+        assert_eq!(&ix.data[..8], &T::DISCRIMINATOR[..]);
+        T::try_from_slice(&ix.data[8..]).unwrap()
+    }
+
+    #[test]
+    fn test_initialize_vault_round_trips() {
+        let authority = Pubkey::new_unique();
+        let params = VaultParams {
+            min_deposit: 1_000_000,
+            max_deposit: 10_000_000,
+            management_fee_bps: 100,
+            performance_fee_bps: 2000,
+            vault_capacity: u64::MAX,
+            per_user_cap: 5_000_000,
+            deposit_mint: None,
+        };
+
+        let treasury = Pubkey::new_unique();
+        let ix = initialize_vault_ix(&authority, &treasury, &params);
+        let args: instruction::InitializeVault = decode_args(&ix);
+
+        assert_eq!(ix.accounts[0].pubkey, vault_pda().0);
+        assert!(ix.accounts[1].is_signer);
+        assert_eq!(args.vault_bump, vault_pda().1);
+        assert_eq!(args.max_deposit, 10_000_000);
+        assert_eq!(args.per_user_cap, 5_000_000);
+        assert_eq!(args.fee_recipient, treasury);
+    }
+
+    #[test]
+    fn test_fee_instructions_round_trip() {
+        let authority = Pubkey::new_unique();
+        let treasury = Pubkey::new_unique();
+
+        let update = VaultConfigUpdate {
+            fee_recipient: Some(treasury),
+            ..Default::default()
+        };
+        let ix = update_vault_config_ix(&authority, &update);
+        let args: instruction::UpdateVaultConfig = decode_args(&ix);
+        assert_eq!(args.fee_recipient, Some(treasury));
+        assert_eq!(args.min_deposit, None);
+
+        let ix = claim_fees_ix(&authority, &treasury, 100_000);
+        let args: instruction::ClaimFees = decode_args(&ix);
+        assert_eq!(args.amount, 100_000);
+        assert_eq!(ix.accounts[2].pubkey, treasury);
+        assert!(ix.accounts[2].is_writable && !ix.accounts[2].is_signer);
+
+        let ix = release_profit_reserve_ix(&authority, 50_000);
+        let args: instruction::ReleaseProfitReserve = decode_args(&ix);
+        assert_eq!(args.amount, 50_000);
+        assert_eq!(ix.accounts[1].pubkey, authority);
+        assert!(ix.accounts[1].is_signer);
+    }
+
+    #[test]
+    fn test_deposit_and_withdraw_round_trip() {
+        let user = Pubkey::new_unique();
+        let referrer = Pubkey::new_unique();
+
+        let ix = deposit_ix(&user, 2_000_000, Some(referrer));
+        let args: instruction::Deposit = decode_args(&ix);
+        assert_eq!(args.amount, 2_000_000);
+        assert_eq!(args.referrer, Some(referrer));
+        assert_eq!(ix.accounts[1].pubkey, user_account_pda(&user));
+        assert_eq!(ix.accounts[2].pubkey, referral_pda(&referrer));
+
+        // Without a referrer the optional account slot holds the program id
+        let ix = withdraw_ix(&user, 1_000_000, 900_000, None);
+        let args: instruction::Withdraw = decode_args(&ix);
+        assert_eq!(args.shares_to_burn, 1_000_000);
+        assert_eq!(args.min_sol_out, 900_000);
+        assert_eq!(ix.accounts[2].pubkey, PROGRAM_ID);
+    }
+
+    #[test]
+    fn test_spl_instructions_round_trip() {
+        let user = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+
+        let ix = deposit_spl_ix(&user, &mint, 100_000_000, None);
+        let args: instruction::DepositSpl = decode_args(&ix);
+        assert_eq!(args.amount, 100_000_000);
+        assert_eq!(ix.accounts[3].pubkey, mint);
+        assert_eq!(ix.accounts[4].pubkey, get_associated_token_address(&user, &mint));
+        assert_eq!(ix.accounts[5].pubkey, get_associated_token_address(&vault_pda().0, &mint));
+
+        let ix = withdraw_spl_ix(&user, &mint, 50_000_000_000, 49_000_000, None);
+        let args: instruction::WithdrawSpl = decode_args(&ix);
+        assert_eq!(args.shares_to_burn, 50_000_000_000);
+        assert_eq!(args.min_amount_out, 49_000_000);
+    }
+
+    #[test]
+    fn test_position_instructions_round_trip() {
+        let authority = Pubkey::new_unique();
+        let position = position_pda(3);
+        let mint = Pubkey::new_unique();
+
+        let ix = open_position_ix(&authority, 3, mint, 2_000_000, 100_000, 120_000, 90_000);
+        let args: instruction::OpenPosition = decode_args(&ix);
+        assert_eq!(args.token_mint, mint);
+        assert_eq!(args.stop_loss_price, 90_000);
+        assert_eq!(ix.accounts[1].pubkey, position);
+        assert!(!ix.accounts[1].is_signer);
+        assert_ne!(position, position_pda(4));
+        assert_eq!(ix.accounts[2].pubkey, mint_exposure_pda(&mint));
+        assert!(ix.accounts[2].is_writable);
+
+        let ix = close_position_ix(&authority, &position, &mint, 110_000, 2_100_000);
+        let args: instruction::ClosePosition = decode_args(&ix);
+        assert_eq!(ix.accounts[2].pubkey, mint_exposure_pda(&mint));
+        assert_eq!(args.exit_price, 110_000);
+        assert_eq!(args.amount_received, 2_100_000);
+    }
+
+    #[test]
+    fn test_referral_instructions_round_trip() {
+        let referrer = Pubkey::new_unique();
+
+        let ix = register_referrer_ix(&referrer);
+        let _: instruction::RegisterReferrer = decode_args(&ix);
+        assert_eq!(ix.data.len(), 8);
+        assert_eq!(ix.accounts[0].pubkey, vault_pda().0);
+        assert_eq!(ix.accounts[1].pubkey, referral_pda(&referrer));
+        assert!(ix.accounts[1].is_writable);
+        assert!(ix.accounts[2].is_signer && ix.accounts[2].is_writable);
+
+        let ix = claim_referral_fees_ix(&referrer);
+        let _: instruction::ClaimReferralFees = decode_args(&ix);
+        assert!(ix.accounts[0].is_writable);
+        assert_eq!(ix.accounts[1].pubkey, referral_pda(&referrer));
+        assert_eq!(ix.accounts[2].pubkey, referrer);
+        assert!(ix.accounts[2].is_signer);
+    }
+
+    #[test]
+    fn test_get_vault_stats_round_trips() {
+        let ix = get_vault_stats_ix();
+        let _: instruction::GetVaultStats = decode_args(&ix);
+
+        assert_eq!(ix.data.len(), 8);
+        assert_eq!(ix.accounts.len(), 1);
+        assert_eq!(ix.accounts[0].pubkey, vault_pda().0);
+        assert!(!ix.accounts[0].is_writable && !ix.accounts[0].is_signer);
+    }
+
+    #[test]
+    fn test_decode_referral_account_round_trips() {
+        let referral = ReferralAccount {
+            referrer: Pubkey::new_unique(),
+            referred_shares: 5_000_000,
+            fee_per_share_paid: 42,
+            accrued_fees: 1_000,
+            bump: 254,
+        };
+        let mut data = Vec::new();
+        referral.try_serialize(&mut data).unwrap();
+
+        let decoded = decode_referral_account(&data).unwrap();
+        assert_eq!(decoded.referrer, referral.referrer);
+        assert_eq!(decoded.referred_shares, 5_000_000);
+        assert_eq!(decoded.accrued_fees, 1_000);
+        assert!(decode_position(&data).is_err());
+    }
+
+    #[test]
+    fn test_close_vault_round_trips() {
+        let authority = Pubkey::new_unique();
+
+        let ix = close_vault_ix(&authority, None);
+        let _: instruction::CloseVault = decode_args(&ix);
+
+        assert_eq!(ix.data.len(), 8);
+        assert_eq!(ix.accounts[0].pubkey, vault_pda().0);
+        assert_eq!(ix.accounts[2].pubkey, authority);
+        assert!(ix.accounts[2].is_signer && ix.accounts[2].is_writable);
+        // A SOL vault leaves the optional token account and program slots as the program id
+        assert_eq!(ix.accounts[1].pubkey, PROGRAM_ID);
+        assert_eq!(ix.accounts[3].pubkey, PROGRAM_ID);
+
+        let mint = Pubkey::new_unique();
+        let ix = close_vault_ix(&authority, Some(&mint));
+        assert_eq!(ix.accounts[1].pubkey, get_associated_token_address(&vault_pda().0, &mint));
+        assert!(ix.accounts[1].is_writable);
+        assert_eq!(ix.accounts[3].pubkey, token::ID);
+    }
+
+    #[test]
+    fn test_decode_position_checks_discriminator() {
+        let position = Position {
+            vault: vault_pda().0,
+            token_mint: Pubkey::new_unique(),
+            amount_sol: 2_000_000,
+            entry_price: 100_000,
+            current_price: 100_000,
+            take_profit_price: 120_000,
+            stop_loss_price: 90_000,
+            status: 0,
+            opened_at: 1_700_000_000,
+            closed_at: 0,
+            pnl: 0,
+            position_id: 0,
+            bump: 255,
+        };
+        let mut data = Vec::new();
+        position.try_serialize(&mut data).unwrap();
+
+        assert_eq!(decode_position(&data).unwrap().amount_sol, 2_000_000);
+        assert!(decode_vault(&data).is_err());
+    }
+}
@@ -28,6 +28,9 @@ export interface Position {
   pnl_percentage: number;
   opened_at: number;
   closed_at?: number;
+  strategy: number;
+  entry_confidence_bps: number;
+  stale?: boolean;
 }
 
 export interface UserStats {
@@ -40,12 +43,23 @@ export interface UserStats {
   win_rate: number;
   total_pnl_sol: number;
   total_pnl_usd: number;
+  open_exposure_sol: number;
+  largest_open_position_sol: number;
+  realized_vs_unrealized_pnl: {
+    realized_sol: number;
+    unrealized_sol: number;
+  };
+  avg_hold_seconds: number;
 }
 
 export interface BotHealth {
   status: string;
   version: string;
   uptime_seconds: number;
+  dependencies?: {
+    rpc: boolean;
+    metrics_api: boolean;
+  };
 }
 
 export interface BotStats {
@@ -56,6 +70,11 @@ export interface BotStats {
   signals_generated: number;
   trades_executed: number;
   last_scan_at?: number;
+  closed_trades: number;
+  winning_trades: number;
+  realized_pnl_lamports: number;
+  win_rate: number;
+  trading_halted: boolean;
 }
 
 class BotApiClient {
@@ -93,6 +112,10 @@ class BotApiClient {
     return this.fetch<Position[]>('/api/positions');
   }
 
+  async getPosition(positionId: string): Promise<Position> {
+    return this.fetch<Position>(`/api/positions/${positionId}`);
+  }
+
   async getBotStats(): Promise<BotStats> {
     return this.fetch<BotStats>('/api/stats');
   }
@@ -104,8 +127,10 @@ class BotApiClient {
 
     ws.onmessage = (event) => {
       try {
-        const data = JSON.parse(event.data);
-        onMessage(data);
+        const message = JSON.parse(event.data);
+        if (message.kind === 'stats_update') {
+          onMessage(message.data);
+        }
       } catch (error) {
         console.error('Failed to parse WebSocket message:', error);
       }
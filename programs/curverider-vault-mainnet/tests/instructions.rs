@@ -0,0 +1,870 @@
+//! Instructions run through the program's entrypoint, against accounts laid out
+//! the way the runtime hands them over, so Anchor's constraints and the
+//! handlers execute exactly as they would on-chain.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Once;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    entrypoint::{deserialize, ProgramResult, BPF_ALIGN_OF_U128, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER, SUCCESS},
+    instruction::{AccountMeta, Instruction},
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    system_program,
+};
+use anchor_lang::{Discriminator, InstructionData, ToAccountMetas};
+use curverider_vault_mainnet::{
+    accounts, entry, instruction, DelegationAccount, EmergencyWithdrawn, GlobalConfig, Position,
+    PositionLiquidated, PositionStatus, VaultError, ID as PROGRAM_ID,
+};
+
+const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+const NOW: i64 = 1_700_000_000;
+
+// ============================================================================
+// Runtime
+// ============================================================================
+
+thread_local! {
+    /// `emit!` payloads of the last instruction sent on this thread
+    static EVENTS: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Sysvars and the system program instructions Anchor issues for `init`, `close` and transfers
+struct TestRuntime;
+
+impl SyscallStubs for TestRuntime {
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        EVENTS.with(|events| events.borrow_mut().push(fields.concat()));
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock { unix_timestamp: NOW, ..Clock::default() };
+        unsafe { std::ptr::write(var_addr as *mut Clock, clock) };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { std::ptr::write(var_addr as *mut Rent, Rent::default()) };
+        SUCCESS
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        assert_eq!(instruction.program_id, system_program::ID, "unexpected CPI");
+
+        let account = |index: usize| {
+            account_infos
+                .iter()
+                .find(|info| *info.key == instruction.accounts[index].pubkey)
+                .unwrap()
+        };
+        let data = &instruction.data;
+        let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+        let pubkey_at = |offset: usize| Pubkey::try_from(&data[offset..offset + 32]).unwrap();
+
+        match u32::from_le_bytes(data[..4].try_into().unwrap()) {
+            // CreateAccount { lamports, space, owner }
+            0 => {
+                move_lamports(account(0), account(1), u64_at(4))?;
+                account(1).realloc(u64_at(12) as usize, true)?;
+                account(1).assign(&pubkey_at(20));
+            }
+            // Assign { owner }
+            1 => account(0).assign(&pubkey_at(4)),
+            // Transfer { lamports }
+            2 => move_lamports(account(0), account(1), u64_at(4))?,
+            // Allocate { space }
+            8 => account(0).realloc(u64_at(4) as usize, true)?,
+            other => panic!("unsupported system instruction {other}"),
+        }
+
+        Ok(())
+    }
+}
+
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    let remaining = from.lamports().checked_sub(lamports).ok_or(ProgramError::InsufficientFunds)?;
+    **from.try_borrow_mut_lamports()? = remaining;
+    **to.try_borrow_mut_lamports()? += lamports;
+    Ok(())
+}
+
+#[derive(Clone, Default)]
+struct AccountState {
+    lamports: u64,
+    data: Vec<u8>,
+    owner: Pubkey,
+    executable: bool,
+}
+
+/// Accounts that persist between instructions
+struct Ledger {
+    accounts: HashMap<Pubkey, AccountState>,
+}
+
+impl Ledger {
+    fn new() -> Self {
+        static RUNTIME: Once = Once::new();
+        RUNTIME.call_once(|| {
+            set_syscall_stubs(Box::new(TestRuntime));
+        });
+
+        let mut accounts = HashMap::new();
+        accounts.insert(system_program::ID, AccountState { lamports: 1, executable: true, ..AccountState::default() });
+
+        Self { accounts }
+    }
+
+    fn fund(&mut self, address: &Pubkey, lamports: u64) {
+        self.accounts.entry(*address).or_default().lamports += lamports;
+    }
+
+    /// Process `ix` as a one-instruction transaction: account changes only land if it succeeds
+    fn send(&mut self, ix: Instruction) -> std::result::Result<(), ProgramError> {
+        let mut input = self.serialize(&ix);
+        let (program_id, account_infos, data) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+
+        EVENTS.with(|events| events.borrow_mut().clear());
+        entry(program_id, &account_infos, data)?;
+
+        for info in &account_infos {
+            if info.lamports() == 0 {
+                self.accounts.remove(info.key);
+                continue;
+            }
+            self.accounts.insert(
+                *info.key,
+                AccountState {
+                    lamports: info.lamports(),
+                    data: info.data.borrow().to_vec(),
+                    owner: *info.owner,
+                    executable: info.executable,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    /// The loader's input buffer for `ix`, in u64 words so its aligned reads hold
+    fn serialize(&self, ix: &Instruction) -> Vec<u64> {
+        let mut bytes = Vec::new();
+        bytes.extend((ix.accounts.len() as u64).to_le_bytes());
+
+        for (index, meta) in ix.accounts.iter().enumerate() {
+            if let Some(first) = ix.accounts[..index].iter().position(|m| m.pubkey == meta.pubkey) {
+                bytes.push(first as u8);
+                bytes.extend([0; 7]);
+                continue;
+            }
+
+            let account = self.accounts.get(&meta.pubkey).cloned().unwrap_or_default();
+            bytes.push(NON_DUP_MARKER);
+            bytes.push(meta.is_signer as u8);
+            bytes.push(meta.is_writable as u8);
+            bytes.push(account.executable as u8);
+            bytes.extend([0; 4]); // original data length, filled in by deserialize
+            bytes.extend(meta.pubkey.to_bytes());
+            bytes.extend(account.owner.to_bytes());
+            bytes.extend(account.lamports.to_le_bytes());
+            bytes.extend((account.data.len() as u64).to_le_bytes());
+            bytes.extend(&account.data);
+            bytes.resize(bytes.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+            bytes.resize(bytes.len().next_multiple_of(BPF_ALIGN_OF_U128), 0);
+            bytes.extend(0u64.to_le_bytes()); // rent epoch
+        }
+
+        bytes.extend((ix.data.len() as u64).to_le_bytes());
+        bytes.extend(&ix.data);
+        bytes.extend(ix.program_id.to_bytes());
+
+        let mut words = vec![0u64; bytes.len().div_ceil(8)];
+        unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), words.as_mut_ptr() as *mut u8, bytes.len()) };
+        words
+    }
+
+    fn lamports(&self, address: &Pubkey) -> u64 {
+        self.accounts.get(address).map_or(0, |account| account.lamports)
+    }
+
+    fn account<T: AccountDeserialize>(&self, address: &Pubkey) -> T {
+        T::try_deserialize(&mut &self.accounts[address].data[..]).unwrap()
+    }
+}
+
+/// Events of type `T` the last instruction sent emitted, in order
+fn emitted<T: Discriminator + AnchorDeserialize>() -> Vec<T> {
+    EVENTS.with(|events| {
+        events
+            .borrow()
+            .iter()
+            .filter_map(|data| data.strip_prefix(&T::DISCRIMINATOR[..]))
+            .map(|mut data| T::deserialize(&mut data).unwrap())
+            .collect()
+    })
+}
+
+fn vault_error(error: VaultError) -> ProgramError {
+    anchor_lang::error::Error::from(error).into()
+}
+
+// ============================================================================
+// Instruction Builders
+// ============================================================================
+
+fn config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], &PROGRAM_ID).0
+}
+
+fn delegation_pda(user: &Pubkey, vault_index: u8) -> Pubkey {
+    Pubkey::find_program_address(&[b"delegation", user.as_ref(), &[vault_index]], &PROGRAM_ID).0
+}
+
+fn position_pda(delegation: &Pubkey, client_order_id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"position", delegation.as_ref(), &client_order_id.to_le_bytes()],
+        &PROGRAM_ID,
+    )
+    .0
+}
+
+fn mint_whitelist_pda(delegation: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"mint_whitelist", delegation.as_ref()], &PROGRAM_ID).0
+}
+
+fn program_ix(accounts: Vec<AccountMeta>, data: impl InstructionData) -> Instruction {
+    Instruction { program_id: PROGRAM_ID, accounts, data: data.data() }
+}
+
+/// A user's delegation to a bot
+struct Delegation {
+    user: Pubkey,
+    bot: Pubkey,
+    vault_index: u8,
+    address: Pubkey,
+}
+
+/// Terms `create_delegation` is called with
+struct DelegationTerms {
+    min_liquidity_sol: u64,
+    performance_fee_bps: u16,
+}
+
+const DEFAULT_TERMS: DelegationTerms = DelegationTerms { min_liquidity_sol: 0, performance_fee_bps: 0 };
+
+/// A ledger with the global config set up and a funded user delegating vault 0 to a funded bot
+fn start(terms: DelegationTerms) -> (Ledger, Delegation) {
+    let mut ledger = Ledger::new();
+    let authority = Pubkey::new_unique();
+    ledger.fund(&authority, LAMPORTS_PER_SOL);
+    ledger
+        .send(program_ix(
+            accounts::InitializeConfig {
+                config: config_pda(),
+                authority,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            instruction::InitializeConfig { emergency_authority: authority },
+        ))
+        .unwrap();
+
+    let user = Pubkey::new_unique();
+    let bot = Pubkey::new_unique();
+    ledger.fund(&user, 10 * LAMPORTS_PER_SOL);
+    ledger.fund(&bot, 10 * LAMPORTS_PER_SOL);
+
+    let delegation = Delegation { user, bot, vault_index: 0, address: delegation_pda(&user, 0) };
+    ledger.send(create_delegation_ix(&delegation, terms)).unwrap();
+
+    (ledger, delegation)
+}
+
+fn create_delegation_ix(delegation: &Delegation, terms: DelegationTerms) -> Instruction {
+    program_ix(
+        accounts::CreateDelegation {
+            config: config_pda(),
+            delegation: delegation.address,
+            bot_authority: delegation.bot,
+            user: delegation.user,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        instruction::CreateDelegation {
+            vault_index: delegation.vault_index,
+            strategy: 0,
+            max_position_size_sol: 5 * LAMPORTS_PER_SOL,
+            max_concurrent_trades: 5,
+            min_liquidity_sol: terms.min_liquidity_sol,
+            performance_fee_bps: terms.performance_fee_bps,
+        },
+    )
+}
+
+fn set_min_liquidity_ix(delegation: &Delegation, min_liquidity_sol: u64) -> Instruction {
+    program_ix(
+        accounts::UpdateDelegation {
+            delegation: delegation.address,
+            user: delegation.user,
+        }
+        .to_account_metas(None),
+        instruction::UpdateDelegation {
+            vault_index: delegation.vault_index,
+            strategy: None,
+            max_position_size_sol: None,
+            max_concurrent_trades: None,
+            is_active: None,
+            min_liquidity_sol: Some(min_liquidity_sol),
+        },
+    )
+}
+
+/// An order for `amount_sol` entering at 1.0, targeting 2.0 and stopping out at 0.5
+fn order(client_order_id: u64, amount_sol: u64) -> instruction::OpenPosition {
+    instruction::OpenPosition {
+        client_order_id,
+        token_mint: Pubkey::new_unique(),
+        amount_sol,
+        entry_price: 1_000_000,
+        take_profit_price: 2_000_000,
+        stop_loss_price: 500_000,
+        strategy: 0,
+        entry_confidence_bps: 0,
+        trailing_activation_bps: 0,
+        trailing_distance_bps: 0,
+        liquidity_sol: 0,
+    }
+}
+
+fn open_position_ix(delegation: &Delegation, order: instruction::OpenPosition) -> Instruction {
+    program_ix(
+        accounts::OpenPosition {
+            config: config_pda(),
+            delegation: delegation.address,
+            position: position_pda(&delegation.address, order.client_order_id),
+            mint_whitelist: mint_whitelist_pda(&delegation.address),
+            user: delegation.user,
+            bot_authority: delegation.bot,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        order,
+    )
+}
+
+fn close_position_ix(
+    delegation: &Delegation,
+    client_order_id: u64,
+    exit_price: u64,
+    amount_received: u64,
+    trailing_stop: bool,
+) -> Instruction {
+    program_ix(
+        accounts::ClosePosition {
+            delegation: delegation.address,
+            position: position_pda(&delegation.address, client_order_id),
+            bot_authority: delegation.bot,
+        }
+        .to_account_metas(None),
+        instruction::ClosePosition { exit_price, amount_received, trailing_stop },
+    )
+}
+
+fn update_trailing_ix(delegation: &Delegation, client_order_id: u64, current_price: u64) -> Instruction {
+    program_ix(
+        accounts::UpdateTrailing {
+            delegation: delegation.address,
+            position: position_pda(&delegation.address, client_order_id),
+            bot_authority: delegation.bot,
+        }
+        .to_account_metas(None),
+        instruction::UpdateTrailing { current_price },
+    )
+}
+
+fn close_positions_batch_ix(
+    delegation: &Delegation,
+    positions: &[Pubkey],
+    exit_prices: Vec<u64>,
+    amounts_received: Vec<u64>,
+) -> Instruction {
+    let mut metas = accounts::ClosePositionsBatch {
+        delegation: delegation.address,
+        bot_authority: delegation.bot,
+    }
+    .to_account_metas(None);
+    metas.extend(positions.iter().map(|position| AccountMeta::new(*position, false)));
+
+    program_ix(metas, instruction::ClosePositionsBatch { exit_prices, amounts_received })
+}
+
+fn set_mint_whitelist_ix(delegation: &Delegation, mints: Vec<Pubkey>) -> Instruction {
+    program_ix(
+        accounts::SetMintWhitelist {
+            delegation: delegation.address,
+            mint_whitelist: mint_whitelist_pda(&delegation.address),
+            user: delegation.user,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        instruction::SetMintWhitelist { vault_index: delegation.vault_index, mints },
+    )
+}
+
+fn emergency_withdraw_ix(delegation: &Delegation, positions: &[Pubkey]) -> Instruction {
+    let mut metas = accounts::EmergencyWithdraw {
+        delegation: delegation.address,
+        user: delegation.user,
+    }
+    .to_account_metas(None);
+    metas.extend(positions.iter().map(|position| AccountMeta::new(*position, false)));
+
+    program_ix(metas, instruction::EmergencyWithdraw { vault_index: delegation.vault_index })
+}
+
+fn collect_delegation_fees_ix(ledger: &Ledger, delegation: &Delegation) -> Instruction {
+    program_ix(
+        accounts::CollectDelegationFees {
+            config: config_pda(),
+            delegation: delegation.address,
+            user: delegation.user,
+            operator: ledger.account::<GlobalConfig>(&config_pda()).authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        instruction::CollectDelegationFees { vault_index: delegation.vault_index },
+    )
+}
+
+fn close_delegation_ix(delegation: &Delegation) -> Instruction {
+    program_ix(
+        accounts::CloseDelegation {
+            config: config_pda(),
+            delegation: delegation.address,
+            user: delegation.user,
+        }
+        .to_account_metas(None),
+        instruction::CloseDelegation { vault_index: delegation.vault_index },
+    )
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[test]
+fn test_batch_close_three_positions() {
+    let (mut ledger, delegation) = start(DEFAULT_TERMS);
+    for client_order_id in 1..=3 {
+        ledger
+            .send(open_position_ix(&delegation, order(client_order_id, 100_000_000)))
+            .unwrap();
+    }
+    let positions: Vec<Pubkey> = (1..=3).map(|id| position_pda(&delegation.address, id)).collect();
+
+    ledger
+        .send(close_positions_batch_ix(
+            &delegation,
+            &positions,
+            vec![1_500_000, 800_000, 1_200_000],
+            vec![150_000_000, 80_000_000, 120_000_000],
+        ))
+        .unwrap();
+
+    let closed: Vec<Position> = positions.iter().map(|address| ledger.account(address)).collect();
+    assert!(closed.iter().all(|p| p.status == PositionStatus::Closed as u8 && p.closed_at == NOW));
+    assert_eq!(
+        closed.iter().map(|p| p.pnl).collect::<Vec<_>>(),
+        vec![50_000_000, -20_000_000, 20_000_000]
+    );
+
+    let stats: DelegationAccount = ledger.account(&delegation.address);
+    assert_eq!(stats.active_trades, 0);
+    assert_eq!(stats.total_trades, 3);
+    assert_eq!(stats.total_pnl, 50_000_000);
+    assert_eq!(stats.profitable_trades, 2);
+}
+
+#[test]
+fn test_batch_close_rejects_foreign_or_repeated_position() {
+    let (mut ledger, delegation) = start(DEFAULT_TERMS);
+    ledger.send(open_position_ix(&delegation, order(1, 100_000_000))).unwrap();
+    let position = position_pda(&delegation.address, 1);
+
+    // A second user's position can't be closed through this delegation
+    let other_user = Pubkey::new_unique();
+    ledger.fund(&other_user, 10 * LAMPORTS_PER_SOL);
+    let other = Delegation {
+        user: other_user,
+        bot: delegation.bot,
+        vault_index: 0,
+        address: delegation_pda(&other_user, 0),
+    };
+    ledger.send(create_delegation_ix(&other, DEFAULT_TERMS)).unwrap();
+    ledger.send(open_position_ix(&other, order(1, 100_000_000))).unwrap();
+    let foreign = position_pda(&other.address, 1);
+
+    let err = ledger
+        .send(close_positions_batch_ix(&delegation, &[position, foreign], vec![1_000_000; 2], vec![100_000_000; 2]))
+        .unwrap_err();
+    assert_eq!(err, vault_error(VaultError::InvalidPosition));
+
+    // Listing a position twice fails on the second copy, which is no longer open
+    let err = ledger
+        .send(close_positions_batch_ix(&delegation, &[position, position], vec![1_000_000; 2], vec![100_000_000; 2]))
+        .unwrap_err();
+    assert_eq!(err, vault_error(VaultError::PositionNotOpen));
+
+    // Neither failed batch changed anything
+    assert_eq!(ledger.account::<Position>(&position).status, PositionStatus::Open as u8);
+    assert_eq!(ledger.account::<DelegationAccount>(&delegation.address).active_trades, 1);
+}
+
+#[test]
+fn test_batch_close_rejects_mismatched_lengths() {
+    let (mut ledger, delegation) = start(DEFAULT_TERMS);
+    ledger.send(open_position_ix(&delegation, order(1, 100_000_000))).unwrap();
+
+    let err = ledger
+        .send(close_positions_batch_ix(
+            &delegation,
+            &[position_pda(&delegation.address, 1)],
+            vec![1_000_000, 1_000_000],
+            vec![100_000_000],
+        ))
+        .unwrap_err();
+    assert_eq!(err, vault_error(VaultError::BatchLengthMismatch));
+}
+
+#[test]
+fn test_open_position_enforces_mint_whitelist() {
+    let (mut ledger, delegation) = start(DEFAULT_TERMS);
+
+    // Without a whitelist any mint can be traded
+    ledger.send(open_position_ix(&delegation, order(1, 100_000_000))).unwrap();
+
+    let allowed = Pubkey::new_unique();
+    ledger.send(set_mint_whitelist_ix(&delegation, vec![allowed])).unwrap();
+
+    let err = ledger
+        .send(open_position_ix(&delegation, order(2, 100_000_000)))
+        .unwrap_err();
+    assert_eq!(err, vault_error(VaultError::MintNotWhitelisted));
+
+    ledger
+        .send(open_position_ix(&delegation, instruction::OpenPosition { token_mint: allowed, ..order(2, 100_000_000) }))
+        .unwrap();
+    let position: Position = ledger.account(&position_pda(&delegation.address, 2));
+    assert_eq!(position.token_mint, allowed);
+
+    // Clearing the list allows everything again
+    ledger.send(set_mint_whitelist_ix(&delegation, Vec::new())).unwrap();
+    ledger.send(open_position_ix(&delegation, order(3, 100_000_000))).unwrap();
+}
+
+#[test]
+fn test_open_position_rejects_spoofed_whitelist_account() {
+    let (mut ledger, delegation) = start(DEFAULT_TERMS);
+    ledger.send(set_mint_whitelist_ix(&delegation, vec![Pubkey::new_unique()])).unwrap();
+
+    // Swapping in an address other than the delegation's whitelist PDA doesn't skip the check
+    let mut ix = open_position_ix(&delegation, order(1, 100_000_000));
+    ix.accounts[3].pubkey = Pubkey::new_unique();
+    let err = ledger.send(ix).unwrap_err();
+    assert_eq!(err, anchor_lang::error::Error::from(anchor_lang::error::ErrorCode::ConstraintSeeds).into());
+}
+
+#[test]
+fn test_open_position_stores_strategy_tags() {
+    let (mut ledger, delegation) = start(DEFAULT_TERMS);
+    let entries = [(0u8, 7_500u16), (1, 9_100), (3, 6_800), (4, 8_250)];
+
+    for (client_order_id, &(strategy, entry_confidence_bps)) in (1..).zip(entries.iter()) {
+        ledger
+            .send(open_position_ix(
+                &delegation,
+                instruction::OpenPosition { strategy, entry_confidence_bps, ..order(client_order_id, 100_000_000) },
+            ))
+            .unwrap();
+    }
+
+    let stored: Vec<(u8, u16)> = (1..=entries.len() as u64)
+        .map(|id| ledger.account::<Position>(&position_pda(&delegation.address, id)))
+        .map(|p| (p.strategy, p.entry_confidence_bps))
+        .collect();
+    assert_eq!(stored, entries.to_vec());
+
+    let err = ledger
+        .send(open_position_ix(&delegation, instruction::OpenPosition { strategy: 5, ..order(9, 100_000_000) }))
+        .unwrap_err();
+    assert_eq!(err, vault_error(VaultError::InvalidStrategy));
+
+    let err = ledger
+        .send(open_position_ix(
+            &delegation,
+            instruction::OpenPosition { entry_confidence_bps: 10_001, ..order(9, 100_000_000) },
+        ))
+        .unwrap_err();
+    assert_eq!(err, vault_error(VaultError::InvalidConfidence));
+}
+
+#[test]
+fn test_close_position_records_pnl_bps() {
+    let (mut ledger, delegation) = start(DEFAULT_TERMS);
+    ledger.send(open_position_ix(&delegation, order(1, 200_000_000))).unwrap();
+    ledger.send(open_position_ix(&delegation, order(2, 200_000_000))).unwrap();
+
+    ledger.send(close_position_ix(&delegation, 1, 1_500_000, 300_000_000, false)).unwrap();
+    ledger.send(close_position_ix(&delegation, 2, 700_000, 140_000_000, false)).unwrap();
+
+    let winner: Position = ledger.account(&position_pda(&delegation.address, 1));
+    assert_eq!((winner.pnl, winner.pnl_bps), (100_000_000, 5_000));
+    let loser: Position = ledger.account(&position_pda(&delegation.address, 2));
+    assert_eq!((loser.pnl, loser.pnl_bps), (-60_000_000, -3_000));
+}
+
+#[test]
+fn test_emergency_withdraw_liquidates_open_position() {
+    let (mut ledger, delegation) = start(DEFAULT_TERMS);
+    ledger.send(open_position_ix(&delegation, order(1, 100_000_000))).unwrap();
+    ledger.send(open_position_ix(&delegation, order(2, 100_000_000))).unwrap();
+    ledger.send(close_position_ix(&delegation, 2, 1_200_000, 120_000_000, false)).unwrap();
+    let positions = [position_pda(&delegation.address, 1), position_pda(&delegation.address, 2)];
+
+    // The bot never closed the first position; the user pulls the plug anyway
+    ledger.send(emergency_withdraw_ix(&delegation, &positions)).unwrap();
+
+    let liquidated: Position = ledger.account(&positions[0]);
+    assert_eq!(liquidated.status, PositionStatus::Liquidated as u8);
+    assert_eq!((liquidated.pnl, liquidated.closed_at), (0, NOW));
+    let closed: Position = ledger.account(&positions[1]);
+    assert_eq!(closed.status, PositionStatus::Closed as u8);
+    assert_eq!(closed.pnl, 20_000_000);
+
+    let stats: DelegationAccount = ledger.account(&delegation.address);
+    assert!(!stats.is_active);
+    assert_eq!(stats.active_trades, 0);
+    assert_eq!(stats.total_pnl, 20_000_000);
+
+    // Only the position still open is reported liquidated
+    let events = emitted::<PositionLiquidated>();
+    assert_eq!(events.len(), 1);
+    assert_eq!((events[0].position_id, events[0].token_mint), (liquidated.position_id, liquidated.token_mint));
+    assert_eq!((events[0].vault_index, events[0].timestamp), (0, NOW));
+    assert_eq!(emitted::<EmergencyWithdrawn>()[0].positions_liquidated, 1);
+
+    let err = ledger.send(open_position_ix(&delegation, order(3, 100_000_000))).unwrap_err();
+    assert_eq!(err, vault_error(VaultError::DelegationNotActive));
+}
+
+#[test]
+fn test_emergency_withdraw_rejects_foreign_position() {
+    let (mut ledger, delegation) = start(DEFAULT_TERMS);
+    ledger.send(open_position_ix(&delegation, order(1, 100_000_000))).unwrap();
+
+    let other_user = Pubkey::new_unique();
+    ledger.fund(&other_user, 10 * LAMPORTS_PER_SOL);
+    let other = Delegation {
+        user: other_user,
+        bot: delegation.bot,
+        vault_index: 0,
+        address: delegation_pda(&other_user, 0),
+    };
+    ledger.send(create_delegation_ix(&other, DEFAULT_TERMS)).unwrap();
+
+    // Another user can't sweep this delegation's position in with their own
+    let err = ledger
+        .send(emergency_withdraw_ix(&other, &[position_pda(&delegation.address, 1)]))
+        .unwrap_err();
+    assert_eq!(err, vault_error(VaultError::InvalidPosition));
+    let position: Position = ledger.account(&position_pda(&delegation.address, 1));
+    assert_eq!(position.status, PositionStatus::Open as u8);
+}
+
+#[test]
+fn test_retried_client_order_id_opens_one_position() {
+    let (mut ledger, delegation) = start(DEFAULT_TERMS);
+    let address = position_pda(&delegation.address, 42);
+
+    ledger.send(open_position_ix(&delegation, order(42, 100_000_000))).unwrap();
+    let opened = ledger.accounts[&address].data.clone();
+
+    // The resubmitted order lands on the same PDA and leaves it as it was
+    ledger.send(open_position_ix(&delegation, order(42, 200_000_000))).unwrap();
+    assert_eq!(ledger.accounts[&address].data, opened);
+
+    let position: Position = ledger.account(&address);
+    assert_eq!((position.client_order_id, position.position_id), (42, 0));
+    assert_eq!(position.amount_sol, 100_000_000);
+    let stats: DelegationAccount = ledger.account(&delegation.address);
+    assert_eq!((stats.active_trades, stats.total_trades, stats.position_counter), (1, 1, 1));
+
+    ledger.send(open_position_ix(&delegation, order(43, 100_000_000))).unwrap();
+    let position: Position = ledger.account(&position_pda(&delegation.address, 43));
+    assert_eq!(position.position_id, 1);
+    assert_eq!(ledger.account::<DelegationAccount>(&delegation.address).active_trades, 2);
+}
+
+#[test]
+fn test_trailing_stop_arms_and_flags_the_close() {
+    let (mut ledger, delegation) = start(DEFAULT_TERMS);
+    let address = position_pda(&delegation.address, 1);
+    // Arm at +20%, trail by 10%
+    ledger
+        .send(open_position_ix(
+            &delegation,
+            instruction::OpenPosition {
+                trailing_activation_bps: 2_000,
+                trailing_distance_bps: 1_000,
+                ..order(1, 100_000_000)
+            },
+        ))
+        .unwrap();
+
+    ledger.send(update_trailing_ix(&delegation, 1, 1_100_000)).unwrap();
+    let position: Position = ledger.account(&address);
+    assert!(!position.trailing_active);
+    assert_eq!(position.trailing_stop_price(), None);
+    let err = ledger.send(close_position_ix(&delegation, 1, 1_100_000, 110_000_000, true)).unwrap_err();
+    assert_eq!(err, vault_error(VaultError::TrailingStopNotActive));
+
+    ledger.send(update_trailing_ix(&delegation, 1, 1_200_000)).unwrap();
+    ledger.send(update_trailing_ix(&delegation, 1, 1_500_000)).unwrap();
+    let position: Position = ledger.account(&address);
+    assert!(position.trailing_active);
+    assert_eq!(position.trailing_stop_price(), Some(1_350_000));
+
+    // The pullback leaves the high-water mark where it was and crosses the stop
+    ledger.send(update_trailing_ix(&delegation, 1, 1_300_000)).unwrap();
+    let position: Position = ledger.account(&address);
+    assert_eq!(position.highest_price_seen, 1_500_000);
+    assert!(position.current_price <= position.trailing_stop_price().unwrap());
+
+    ledger.send(close_position_ix(&delegation, 1, 1_300_000, 130_000_000, true)).unwrap();
+    let position: Position = ledger.account(&address);
+    assert!(position.closed_by_trailing_stop);
+    assert_eq!(position.pnl, 30_000_000);
+
+    let err = ledger.send(update_trailing_ix(&delegation, 1, 1_600_000)).unwrap_err();
+    assert_eq!(err, vault_error(VaultError::PositionNotOpen));
+}
+
+#[test]
+fn test_open_position_rejects_full_trailing_distance() {
+    let (mut ledger, delegation) = start(DEFAULT_TERMS);
+
+    let err = ledger
+        .send(open_position_ix(
+            &delegation,
+            instruction::OpenPosition { trailing_distance_bps: 10_000, ..order(1, 100_000_000) },
+        ))
+        .unwrap_err();
+    assert_eq!(err, vault_error(VaultError::InvalidTrailingParams));
+}
+
+#[test]
+fn test_open_below_liquidity_floor_is_rejected() {
+    let (mut ledger, delegation) = start(DelegationTerms { min_liquidity_sol: 20 * LAMPORTS_PER_SOL, ..DEFAULT_TERMS });
+
+    let err = ledger
+        .send(open_position_ix(
+            &delegation,
+            instruction::OpenPosition { liquidity_sol: 5 * LAMPORTS_PER_SOL, ..order(1, 100_000_000) },
+        ))
+        .unwrap_err();
+    assert_eq!(err, vault_error(VaultError::InsufficientLiquidity));
+    assert!(!ledger.accounts.contains_key(&position_pda(&delegation.address, 1)));
+}
+
+#[test]
+fn test_open_at_or_above_liquidity_floor_succeeds() {
+    let (mut ledger, delegation) = start(DelegationTerms { min_liquidity_sol: 20 * LAMPORTS_PER_SOL, ..DEFAULT_TERMS });
+
+    for (client_order_id, liquidity_sol) in [(1, 20 * LAMPORTS_PER_SOL), (2, 85 * LAMPORTS_PER_SOL)] {
+        ledger
+            .send(open_position_ix(&delegation, instruction::OpenPosition { liquidity_sol, ..order(client_order_id, 100_000_000) }))
+            .unwrap();
+    }
+
+    // Lifting the floor lets any liquidity through
+    ledger.send(set_min_liquidity_ix(&delegation, 0)).unwrap();
+    assert_eq!(ledger.account::<DelegationAccount>(&delegation.address).min_liquidity_sol, 0);
+    ledger.send(open_position_ix(&delegation, order(3, 100_000_000))).unwrap();
+    assert_eq!(ledger.account::<DelegationAccount>(&delegation.address).active_trades, 3);
+}
+
+#[test]
+fn test_fee_accrues_on_winning_close() {
+    let (mut ledger, delegation) = start(DelegationTerms { performance_fee_bps: 2_000, ..DEFAULT_TERMS });
+    ledger.send(open_position_ix(&delegation, order(1, LAMPORTS_PER_SOL))).unwrap();
+
+    // 1 SOL in, 1.5 SOL out: 0.5 SOL profit, 20% of it owed
+    ledger.send(close_position_ix(&delegation, 1, 1_500_000, 1_500_000_000, false)).unwrap();
+
+    assert_eq!(ledger.account::<DelegationAccount>(&delegation.address).fees_owed, 100_000_000);
+}
+
+#[test]
+fn test_no_fee_on_losing_close() {
+    let (mut ledger, delegation) = start(DelegationTerms { performance_fee_bps: 2_000, ..DEFAULT_TERMS });
+    ledger.send(open_position_ix(&delegation, order(1, LAMPORTS_PER_SOL))).unwrap();
+
+    ledger.send(close_position_ix(&delegation, 1, 600_000, 600_000_000, false)).unwrap();
+
+    assert_eq!(ledger.account::<DelegationAccount>(&delegation.address).fees_owed, 0);
+}
+
+#[test]
+fn test_fee_is_charged_on_at_most_the_position_size() {
+    let (mut ledger, delegation) = start(DelegationTerms { performance_fee_bps: 2_000, ..DEFAULT_TERMS });
+    for client_order_id in 1..=3 {
+        ledger.send(open_position_ix(&delegation, order(client_order_id, LAMPORTS_PER_SOL))).unwrap();
+    }
+
+    // A 10x report only owes the fee on a 100% gain
+    ledger.send(close_position_ix(&delegation, 1, 10_000_000, 10 * LAMPORTS_PER_SOL, false)).unwrap();
+    assert_eq!(ledger.account::<DelegationAccount>(&delegation.address).fees_owed, 200_000_000);
+
+    // The batch close caps each position the same way
+    let positions = [position_pda(&delegation.address, 2), position_pda(&delegation.address, 3)];
+    ledger
+        .send(close_positions_batch_ix(
+            &delegation,
+            &positions,
+            vec![50_000_000, 1_500_000],
+            vec![50 * LAMPORTS_PER_SOL, 1_500_000_000],
+        ))
+        .unwrap();
+    assert_eq!(ledger.account::<DelegationAccount>(&delegation.address).fees_owed, 500_000_000);
+}
+
+#[test]
+fn test_close_delegation_waits_for_fee_collection() {
+    let (mut ledger, delegation) = start(DelegationTerms { performance_fee_bps: 2_000, ..DEFAULT_TERMS });
+    ledger.send(open_position_ix(&delegation, order(1, LAMPORTS_PER_SOL))).unwrap();
+    ledger.send(close_position_ix(&delegation, 1, 1_500_000, 1_500_000_000, false)).unwrap();
+
+    let err = ledger.send(close_delegation_ix(&delegation)).unwrap_err();
+    assert_eq!(err, vault_error(VaultError::FeesOutstanding));
+
+    let operator = ledger.account::<GlobalConfig>(&config_pda()).authority;
+    let operator_before = ledger.lamports(&operator);
+    let user_before = ledger.lamports(&delegation.user);
+    ledger.send(collect_delegation_fees_ix(&ledger, &delegation)).unwrap();
+
+    assert_eq!(ledger.lamports(&operator), operator_before + 100_000_000);
+    assert_eq!(ledger.lamports(&delegation.user), user_before - 100_000_000);
+    assert_eq!(ledger.account::<DelegationAccount>(&delegation.address).fees_owed, 0);
+
+    let err = ledger.send(collect_delegation_fees_ix(&ledger, &delegation)).unwrap_err();
+    assert_eq!(err, vault_error(VaultError::NoFeesOwed));
+
+    ledger.send(close_delegation_ix(&delegation)).unwrap();
+    assert!(!ledger.accounts.contains_key(&delegation.address));
+}
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
-// use anchor_spl::token::{self, Token, TokenAccount, Transfer, Mint};
-// use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, CloseAccount, Token, TokenAccount, Transfer, Mint};
+use anchor_spl::associated_token::AssociatedToken;
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
@@ -11,6 +11,7 @@ pub mod curverider_vault {
     use super::*;
 
     /// Initialize the vault with configuration parameters
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize_vault(
         ctx: Context<InitializeVault>,
         vault_bump: u8,
@@ -18,6 +19,10 @@ pub mod curverider_vault {
         max_deposit: u64,
         management_fee_bps: u16,
         performance_fee_bps: u16,
+        vault_capacity: u64,
+        per_user_cap: u64,
+        deposit_mint: Option<Pubkey>,
+        fee_recipient: Pubkey,
     ) -> Result<()> {
         let vault = &mut ctx.accounts.vault;
         
@@ -29,40 +34,59 @@ pub mod curverider_vault {
         vault.max_deposit = max_deposit;
         vault.management_fee_bps = management_fee_bps;
         vault.performance_fee_bps = performance_fee_bps;
+        vault.vault_capacity = vault_capacity;
+        vault.per_user_cap = per_user_cap;
+        vault.referral_fee_bps = 0;
+        vault.referred_shares = 0;
+        vault.referral_fee_per_share = 0;
+        vault.referral_fees_owed = 0;
+        vault.open_exposure = 0;
         vault.is_active = true;
         vault.total_trades = 0;
         vault.profitable_trades = 0;
         vault.total_pnl = 0;
         vault.created_at = Clock::get()?.unix_timestamp;
+        vault.deposit_mint = deposit_mint.unwrap_or_default();
+        vault.fee_recipient = fee_recipient;
+        vault.sequence = 0;
+        vault.position_counter = 0;
+        vault.compound_profits = true;
+        vault.profit_skim_bps = 0;
+        vault.profit_reserve = 0;
+        vault.max_mint_allocation_bps = 10_000;
         
         msg!("✅ Vault initialized!");
         msg!("Authority: {}", vault.authority);
+        if let Some(mint) = deposit_mint {
+            msg!("Deposit mint: {} (amounts in its base units)", mint);
+        }
         msg!("Min deposit: {} lamports", min_deposit);
         msg!("Max deposit: {} lamports", max_deposit);
+        msg!("Capacity: {} lamports ({} per user)", vault_capacity, per_user_cap);
+        msg!("Fee recipient: {}", fee_recipient);
         
         Ok(())
     }
 
     /// Deposit SOL into the vault and receive vault shares
+    ///
+    /// `referrer` is only recorded on a user's first deposit.
     pub fn deposit(
         ctx: Context<Deposit>,
         amount: u64,
+        referrer: Option<Pubkey>,
     ) -> Result<()> {
         // Avoid double mutable/immutable borrow by not holding vault as a mutable reference during CPI
         require!(ctx.accounts.vault.is_active, VaultError::VaultNotActive);
+        ctx.accounts.vault.check_deposit_asset(None)?;
         require!(amount >= ctx.accounts.vault.min_deposit, VaultError::BelowMinDeposit);
         require!(amount <= ctx.accounts.vault.max_deposit, VaultError::AboveMaxDeposit);
+        ctx.accounts.vault.check_deposit_caps(&ctx.accounts.user_account, amount)?;
+        let (user_key, vault_key) = (ctx.accounts.user.key(), ctx.accounts.vault.key());
+        ctx.accounts.user_account.claim(user_key, vault_key)?;
 
         // Calculate shares to mint
-        let shares_to_mint = if ctx.accounts.vault.total_shares == 0 {
-            amount
-        } else {
-            amount
-                .checked_mul(ctx.accounts.vault.total_shares)
-                .unwrap()
-                .checked_div(ctx.accounts.vault.total_deposited)
-                .unwrap()
-        };
+        let shares_to_mint = ctx.accounts.vault.shares_for_deposit(amount, SHARE_DECIMALS)?;
 
         // Transfer SOL from user to vault
         let cpi_context = CpiContext::new(
@@ -83,14 +107,26 @@ pub mod curverider_vault {
         vault.total_shares = vault.total_shares.checked_add(shares_to_mint).unwrap();
 
         // Initialize or update user account
+        if user_account.total_deposited == 0 {
+            user_account.set_referrer(ctx.accounts.user.key(), referrer)?;
+        }
         if user_account.shares == 0 {
-            user_account.owner = ctx.accounts.user.key();
-            user_account.vault = vault.key();
             user_account.deposited_at = Clock::get()?.unix_timestamp;
         }
+        if let Some(referral_account) = referral_account_for(user_account, &mut ctx.accounts.referral_account)? {
+            vault.add_referred_shares(referral_account, shares_to_mint)?;
+        }
         user_account.shares = user_account.shares.checked_add(shares_to_mint).unwrap();
         user_account.total_deposited = user_account.total_deposited.checked_add(amount).unwrap();
 
+        emit!(Deposited {
+            user: ctx.accounts.user.key(),
+            amount,
+            shares_minted: shares_to_mint,
+            sequence: vault.next_sequence()?,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
         msg!("💰 Deposit successful!");
         msg!("Amount: {} lamports", amount);
         msg!("Shares minted: {}", shares_to_mint);
@@ -103,31 +139,45 @@ pub mod curverider_vault {
     pub fn withdraw(
         ctx: Context<Withdraw>,
         shares_to_burn: u64,
+        min_sol_out: u64,
     ) -> Result<()> {
+        ctx.accounts.vault.check_deposit_asset(None)?;
+        let vault_info = ctx.accounts.vault.to_account_info();
+        let user_info = ctx.accounts.user.to_account_info();
+
+        // The vault must stay rent-exempt after paying out, and referral fees owed aren't user funds
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(vault_info.data_len());
+        let available_lamports = vault_info.lamports()
+            .saturating_sub(rent_exempt_minimum)
+            .saturating_sub(ctx.accounts.vault.referral_fees_owed);
+
+        // Validate and record the withdrawal before any lamports move
         let vault = &mut ctx.accounts.vault;
         let user_account = &mut ctx.accounts.user_account;
-        
-        require!(shares_to_burn > 0, VaultError::InvalidAmount);
-        require!(user_account.shares >= shares_to_burn, VaultError::InsufficientShares);
-        
-        // Calculate SOL to return
-        // amount = (shares_to_burn * total_deposited) / total_shares
-        let amount_to_return = shares_to_burn
-            .checked_mul(vault.total_deposited)
-            .unwrap()
-            .checked_div(vault.total_shares)
-            .unwrap();
-        
+        let amount_to_return = vault.settle_withdrawal(user_account, shares_to_burn, available_lamports, min_sol_out)?;
+        if let Some(referral_account) = referral_account_for(user_account, &mut ctx.accounts.referral_account)? {
+            vault.remove_referred_shares(referral_account, shares_to_burn)?;
+        }
+
         // Transfer SOL from vault to user
-        **vault.to_account_info().try_borrow_mut_lamports()? -= amount_to_return;
-        **ctx.accounts.user.to_account_info().try_borrow_mut_lamports()? += amount_to_return;
-        
-        // Update vault state
-        vault.total_deposited = vault.total_deposited.checked_sub(amount_to_return).unwrap();
-        vault.total_shares = vault.total_shares.checked_sub(shares_to_burn).unwrap();
-        
-        // Update user account
-        user_account.shares = user_account.shares.checked_sub(shares_to_burn).unwrap();
+        let vault_lamports = vault_info.lamports();
+        let new_vault_lamports = vault_lamports
+            .checked_sub(amount_to_return)
+            .ok_or(VaultError::InsufficientFunds)?;
+        let new_user_lamports = user_info.lamports()
+            .checked_add(amount_to_return)
+            .ok_or(VaultError::InvalidAmount)?;
+        **vault_info.try_borrow_mut_lamports()? = new_vault_lamports;
+        **user_info.try_borrow_mut_lamports()? = new_user_lamports;
+        require_eq!(vault_lamports - vault_info.lamports(), amount_to_return, VaultError::InvalidAmount);
+
+        emit!(Withdrawn {
+            user: ctx.accounts.user.key(),
+            amount: amount_to_return,
+            shares_burned: shares_to_burn,
+            sequence: vault.next_sequence()?,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         
         msg!("💵 Withdrawal successful!");
         msg!("Shares burned: {}", shares_to_burn);
@@ -137,6 +187,126 @@ pub mod curverider_vault {
         Ok(())
     }
 
+    /// Deposit the vault's SPL mint from the user's ATA into the vault's ATA for shares
+    ///
+    /// `amount` is in the mint's base units; `referrer` is only recorded on a user's first deposit.
+    pub fn deposit_spl(
+        ctx: Context<DepositSpl>,
+        amount: u64,
+        referrer: Option<Pubkey>,
+    ) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        require!(vault.is_active, VaultError::VaultNotActive);
+        vault.check_deposit_asset(Some(&ctx.accounts.deposit_mint.key()))?;
+        require!(amount >= vault.min_deposit, VaultError::BelowMinDeposit);
+        require!(amount <= vault.max_deposit, VaultError::AboveMaxDeposit);
+        vault.check_deposit_caps(&ctx.accounts.user_account, amount)?;
+
+        let shares_to_mint = vault.shares_for_deposit(amount, ctx.accounts.deposit_mint.decimals)?;
+        let (user_key, vault_key) = (ctx.accounts.user.key(), vault.key());
+        ctx.accounts.user_account.claim(user_key, vault_key)?;
+
+        // Transfer tokens from the user's ATA to the vault's
+        let cpi_context = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        );
+        token::transfer(cpi_context, amount)?;
+
+        let vault = &mut ctx.accounts.vault;
+        let user_account = &mut ctx.accounts.user_account;
+
+        vault.total_deposited = vault.total_deposited
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+        vault.total_shares = vault.total_shares
+            .checked_add(shares_to_mint)
+            .ok_or(VaultError::MathOverflow)?;
+
+        if user_account.total_deposited == 0 {
+            user_account.set_referrer(ctx.accounts.user.key(), referrer)?;
+        }
+        if user_account.shares == 0 {
+            user_account.deposited_at = Clock::get()?.unix_timestamp;
+        }
+        if let Some(referral_account) = referral_account_for(user_account, &mut ctx.accounts.referral_account)? {
+            vault.add_referred_shares(referral_account, shares_to_mint)?;
+        }
+        user_account.shares = user_account.shares
+            .checked_add(shares_to_mint)
+            .ok_or(VaultError::MathOverflow)?;
+        user_account.total_deposited = user_account.total_deposited
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+
+        emit!(Deposited {
+            user: ctx.accounts.user.key(),
+            amount,
+            shares_minted: shares_to_mint,
+            sequence: vault.next_sequence()?,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("💰 SPL deposit successful!");
+        msg!("Amount: {} base units of {}", amount, vault.deposit_mint);
+        msg!("Shares minted: {}", shares_to_mint);
+
+        Ok(())
+    }
+
+    /// Burn shares for a proportional cut of the vault's SPL balance, paid to the user's ATA
+    pub fn withdraw_spl(
+        ctx: Context<WithdrawSpl>,
+        shares_to_burn: u64,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        ctx.accounts.vault.check_deposit_asset(Some(&ctx.accounts.deposit_mint.key()))?;
+
+        // Referral fees are owed in lamports, out of the vault account rather than its ATA
+        let available = ctx.accounts.vault_token_account.amount;
+
+        // Validate and record the withdrawal before any tokens move
+        let vault = &mut ctx.accounts.vault;
+        let user_account = &mut ctx.accounts.user_account;
+        let amount_to_return = vault.settle_withdrawal(user_account, shares_to_burn, available, min_amount_out)?;
+        if let Some(referral_account) = referral_account_for(user_account, &mut ctx.accounts.referral_account)? {
+            vault.remove_referred_shares(referral_account, shares_to_burn)?;
+        }
+
+        // The vault PDA signs for its ATA
+        let bump = [vault.vault_bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &bump]];
+        let cpi_context = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                to: ctx.accounts.user_token_account.to_account_info(),
+                authority: vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_context, amount_to_return)?;
+
+        emit!(Withdrawn {
+            user: ctx.accounts.user.key(),
+            amount: amount_to_return,
+            shares_burned: shares_to_burn,
+            sequence: vault.next_sequence()?,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("💵 SPL withdrawal successful!");
+        msg!("Shares burned: {}", shares_to_burn);
+        msg!("Returned: {} base units", amount_to_return);
+        msg!("User remaining shares: {}", user_account.shares);
+
+        Ok(())
+    }
+
     /// Open a new trading position (called by bot/authority)
     pub fn open_position(
         ctx: Context<OpenPosition>,
@@ -148,11 +318,22 @@ pub mod curverider_vault {
     ) -> Result<()> {
         let vault = &mut ctx.accounts.vault;
         let position = &mut ctx.accounts.position;
+        let mint_exposure = &mut ctx.accounts.mint_exposure;
         
         require!(vault.is_active, VaultError::VaultNotActive);
-        require!(amount_sol <= vault.total_deposited, VaultError::InsufficientFunds);
+        // Position sizes and PnL are lamports, which only a SOL vault's books are kept in
+        vault.check_deposit_asset(None)?;
+        if mint_exposure.vault == Pubkey::default() {
+            mint_exposure.vault = vault.key();
+            mint_exposure.token_mint = token_mint;
+            mint_exposure.bump = ctx.bumps.mint_exposure;
+        }
+        mint_exposure.reserve(amount_sol, vault.max_mint_exposure()?)?;
+        vault.reserve_exposure(amount_sol)?;
         
         position.vault = vault.key();
+        position.position_id = vault.next_position_id()?;
+        position.bump = ctx.bumps.position;
         position.token_mint = token_mint;
         position.amount_sol = amount_sol;
         position.entry_price = entry_price;
@@ -185,6 +366,7 @@ pub mod curverider_vault {
         
         require!(position.status == PositionStatus::Open as u8, VaultError::PositionNotOpen);
         require!(position.vault == vault.key(), VaultError::InvalidPosition);
+        vault.check_deposit_asset(None)?;
         
         // Calculate PnL (can be negative)
         let pnl = (amount_received as i64)
@@ -198,27 +380,25 @@ pub mod curverider_vault {
         
         // Update vault statistics
         vault.total_pnl = vault.total_pnl.checked_add(pnl).unwrap();
+        vault.open_exposure = vault.open_exposure.saturating_sub(position.amount_sol);
+        ctx.accounts.mint_exposure.release(position.amount_sol);
         
         if pnl > 0 {
             vault.profitable_trades = vault.profitable_trades.checked_add(1).unwrap();
-            vault.total_deposited = vault.total_deposited
-                .checked_add(pnl as u64)
-                .unwrap();
-        } else {
-            vault.total_deposited = vault.total_deposited
-                .checked_sub((-pnl) as u64)
-                .unwrap();
         }
+        let skimmed = vault.settle_pnl(pnl)?;
         
         msg!("📊 Position closed!");
         msg!("Exit price: {}", exit_price);
         msg!("PnL: {} lamports", pnl);
+        msg!("Skimmed to reserve: {} lamports", skimmed);
         msg!("Vault total PnL: {}", vault.total_pnl);
         
         Ok(())
     }
 
     /// Update vault configuration (authority only)
+    #[allow(clippy::too_many_arguments)]
     pub fn update_vault_config(
         ctx: Context<UpdateVaultConfig>,
         min_deposit: Option<u64>,
@@ -226,6 +406,13 @@ pub mod curverider_vault {
         management_fee_bps: Option<u16>,
         performance_fee_bps: Option<u16>,
         is_active: Option<bool>,
+        vault_capacity: Option<u64>,
+        per_user_cap: Option<u64>,
+        referral_fee_bps: Option<u16>,
+        fee_recipient: Option<Pubkey>,
+        compound_profits: Option<bool>,
+        profit_skim_bps: Option<u16>,
+        max_mint_allocation_bps: Option<u16>,
     ) -> Result<()> {
         let vault = &mut ctx.accounts.vault;
         
@@ -246,29 +433,209 @@ pub mod curverider_vault {
         if let Some(active) = is_active {
             vault.is_active = active;
         }
+        if let Some(capacity) = vault_capacity {
+            vault.vault_capacity = capacity;
+        }
+        if let Some(cap) = per_user_cap {
+            vault.per_user_cap = cap;
+        }
+        if let Some(referral_fee) = referral_fee_bps {
+            require!(referral_fee <= 5000, VaultError::FeeTooHigh); // Max half of claimed fees
+            vault.referral_fee_bps = referral_fee;
+        }
+        if let Some(recipient) = fee_recipient {
+            vault.fee_recipient = recipient;
+        }
+        if let Some(compound) = compound_profits {
+            vault.compound_profits = compound;
+        }
+        if let Some(skim) = profit_skim_bps {
+            require!(skim <= 5000, VaultError::FeeTooHigh); // Max half of each gain
+            vault.profit_skim_bps = skim;
+        }
+        if let Some(allocation) = max_mint_allocation_bps {
+            require!(allocation > 0 && allocation <= 10_000, VaultError::InvalidAmount);
+            vault.max_mint_allocation_bps = allocation;
+        }
         
         msg!("⚙️ Vault configuration updated!");
         
         Ok(())
     }
 
-    /// Claim accumulated fees (authority only)
+    /// Claim accumulated fees to the vault's fee recipient (authority only)
     pub fn claim_fees(
         ctx: Context<ClaimFees>,
         amount: u64,
     ) -> Result<()> {
         let vault = &mut ctx.accounts.vault;
         
-        require!(amount <= vault.total_deposited, VaultError::InsufficientFunds);
+        // Fees are paid in lamports, so only a SOL vault's balance holds any
+        vault.check_deposit_asset(None)?;
+        let vault_info = vault.to_account_info();
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(vault_info.data_len());
+        vault.check_fee_claim(amount, vault_info.lamports().saturating_sub(rent_exempt_minimum))?;
+
+        // Referrers' cut stays in the vault until they claim it
+        let authority_amount = vault.split_fee_claim(amount)?;
         
-        // Transfer SOL from vault to authority
-        **vault.to_account_info().try_borrow_mut_lamports()? -= amount;
-        **ctx.accounts.authority.to_account_info().try_borrow_mut_lamports()? += amount;
+        // Transfer SOL from vault to the fee recipient
+        **vault.to_account_info().try_borrow_mut_lamports()? -= authority_amount;
+        **ctx.accounts.fee_recipient.to_account_info().try_borrow_mut_lamports()? += authority_amount;
         
-        msg!("💰 Fees claimed: {} lamports", amount);
+        msg!("💰 Fees claimed: {} lamports to {}", authority_amount, vault.fee_recipient);
+        msg!("Referral fees reserved: {} lamports", amount - authority_amount);
         
         Ok(())
     }
+
+    /// Compound `amount` of the profit reserve into deposits, raising the share price
+    /// for current shareholders (authority only)
+    pub fn release_profit_reserve(
+        ctx: Context<UpdateVaultConfig>,
+        amount: u64,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        vault.release_profit_reserve(amount)?;
+
+        msg!("📈 Profit reserve released: {} lamports", amount);
+        msg!("Reserve remaining: {} lamports", vault.profit_reserve);
+
+        Ok(())
+    }
+
+    /// Log the vault's NAV and share price and emit them as a `VaultStats` event
+    pub fn get_vault_stats(ctx: Context<GetVaultStats>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        let vault_info = vault.to_account_info();
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(vault_info.data_len());
+        let stats = vault.stats(vault_info.lamports().saturating_sub(rent_exempt_minimum))?;
+
+        msg!("📊 Vault Stats");
+        msg!("Total deposited: {} lamports", stats.total_deposited);
+        msg!("Total shares: {}", stats.total_shares);
+        msg!("Share price (1e9): {}", stats.share_price_scaled_1e9);
+        msg!("Open exposure: {} lamports", stats.total_open_exposure_sol);
+        msg!("Total PnL: {} lamports", stats.total_pnl);
+        msg!("Accrued fees: {} lamports", stats.accrued_fees);
+        msg!("Profit reserve: {} lamports", stats.profit_reserve);
+
+        emit!(stats);
+
+        Ok(())
+    }
+
+    /// Create the referral account a referrer's fees accrue to
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        let referral_account = &mut ctx.accounts.referral_account;
+        referral_account.referrer = ctx.accounts.referrer.key();
+        referral_account.referred_shares = 0;
+        referral_account.fee_per_share_paid = ctx.accounts.vault.referral_fee_per_share;
+        referral_account.accrued_fees = 0;
+        referral_account.bump = ctx.bumps.referral_account;
+
+        msg!("🤝 Referrer registered: {}", referral_account.referrer);
+
+        Ok(())
+    }
+
+    /// Pay a referrer their accrued share of claimed fees
+    pub fn claim_referral_fees(ctx: Context<ClaimReferralFees>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let referral_account = &mut ctx.accounts.referral_account;
+
+        let amount = referral_account.take_accrued(vault)?;
+        require!(amount > 0, VaultError::InvalidAmount);
+
+        let vault_info = vault.to_account_info();
+        let referrer_info = ctx.accounts.referrer.to_account_info();
+        let new_vault_lamports = vault_info.lamports()
+            .checked_sub(amount)
+            .ok_or(VaultError::InsufficientFunds)?;
+        let new_referrer_lamports = referrer_info.lamports()
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+        **vault_info.try_borrow_mut_lamports()? = new_vault_lamports;
+        **referrer_info.try_borrow_mut_lamports()? = new_referrer_lamports;
+
+        msg!("💸 Referral fees claimed: {} lamports", amount);
+
+        Ok(())
+    }
+
+    /// Close a wound-down vault, returning its rent and any residual lamports to the authority
+    ///
+    /// An SPL vault's ATA must be passed and empty; it's closed here too, so a later
+    /// `initialize_vault` can't inherit it.
+    pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        vault.check_closable()?;
+        vault.check_token_account_closable(
+            ctx.accounts.vault_token_account.as_ref().map(|account| account.amount),
+        )?;
+
+        if let Some(vault_token_account) = &ctx.accounts.vault_token_account {
+            let token_program = ctx.accounts.token_program
+                .as_ref()
+                .ok_or(VaultError::MissingVaultTokenAccount)?;
+
+            // The vault PDA signs for its ATA
+            let bump = [vault.vault_bump];
+            let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &bump]];
+            let cpi_context = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                CloseAccount {
+                    account: vault_token_account.to_account_info(),
+                    destination: ctx.accounts.authority.to_account_info(),
+                    authority: vault.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token::close_account(cpi_context)?;
+        }
+
+        let vault_info = vault.to_account_info();
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(vault_info.data_len());
+        let residual = vault_info.lamports().saturating_sub(rent_exempt_minimum);
+
+        msg!("🔒 Vault closed, {} residual lamports swept", residual);
+
+        emit!(VaultClosed {
+            authority: ctx.accounts.authority.key(),
+            residual_lamports: residual,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        // `close = authority` moves every lamport, rent included
+        Ok(())
+    }
+}
+
+/// Prices are lamports per whole token times `PRICE_SCALE`, as the bot encodes them
+pub const PRICE_SCALE: u64 = 1_000_000;
+
+/// Scale of `VaultStats::share_price_scaled_1e9`
+const SHARE_PRICE_SCALE: u64 = 1_000_000_000;
+
+/// Decimals shares are issued at, matching lamports; first deposits in other mints are rescaled to it
+const SHARE_DECIMALS: u8 = 9;
+
+/// Scale of `Vault::referral_fee_per_share`, so small fee claims over many shares don't round to zero
+const REFERRAL_FEE_SCALE: u128 = 1_000_000_000_000;
+
+/// The referral account a deposit or withdrawal must update, checked against the user's referrer
+fn referral_account_for<'a, 'info>(
+    user_account: &UserAccount,
+    referral_account: &'a mut Option<Account<'info, ReferralAccount>>,
+) -> Result<Option<&'a mut ReferralAccount>> {
+    match (user_account.referrer, referral_account.as_mut()) {
+        (None, _) => Ok(None),
+        (Some(referrer), Some(account)) => {
+            require_keys_eq!(account.referrer, referrer, VaultError::InvalidReferrer);
+            Ok(Some(&mut **account))
+        }
+        (Some(_), None) => err!(VaultError::InvalidReferrer),
+    }
 }
 
 // ============================================================================
@@ -293,6 +660,20 @@ pub struct Vault {
     pub management_fee_bps: u16,
     /// Performance fee in basis points (e.g., 2000 = 20%)
     pub performance_fee_bps: u16,
+    /// Maximum total SOL the vault will accept
+    pub vault_capacity: u64,
+    /// Maximum cumulative SOL any one user may deposit
+    pub per_user_cap: u64,
+    /// Portion of claimed fees routed to referrers, in basis points
+    pub referral_fee_bps: u16,
+    /// Shares held by users who deposited with a referrer
+    pub referred_shares: u64,
+    /// Referral fees accrued per referred share, scaled by `REFERRAL_FEE_SCALE`
+    pub referral_fee_per_share: u128,
+    /// Referral fees held in the vault until referrers claim them
+    pub referral_fees_owed: u64,
+    /// SOL currently committed to open positions
+    pub open_exposure: u64,
     /// Whether vault is accepting deposits
     pub is_active: bool,
     /// Total number of trades executed
@@ -303,6 +684,327 @@ pub struct Vault {
     pub total_pnl: i64,
     /// Timestamp when vault was created
     pub created_at: i64,
+    /// SPL mint deposits are denominated in; the default pubkey means native SOL
+    pub deposit_mint: Pubkey,
+    /// Wallet claimed fees are paid to, e.g. a treasury multisig
+    pub fee_recipient: Pubkey,
+    /// Number of the last deposit or withdrawal, so indexers can detect missed events
+    pub sequence: u64,
+    /// Id the next position is seeded with; positions live at
+    /// `[b"position", vault, position_id.to_le_bytes()]`
+    pub position_counter: u64,
+    /// Whether realized gains are added to `total_deposited`; when off they are reserved in full
+    pub compound_profits: bool,
+    /// Share of each realized gain held back in `profit_reserve`, in basis points
+    pub profit_skim_bps: u16,
+    /// Gains held back from compounding; absorbs losses before deposits do
+    pub profit_reserve: u64,
+    /// Most of `total_deposited` open positions in a single mint may hold, in basis points
+    pub max_mint_allocation_bps: u16,
+}
+
+impl Vault {
+    /// Borsh-serialized size, excluding the 8-byte discriminator
+    pub const LEN: usize = 32 // authority
+        + 1 // vault_bump
+        + 8 * 4 // total_deposited, total_shares, min_deposit, max_deposit
+        + 2 * 2 // management_fee_bps, performance_fee_bps
+        + 8 * 2 // vault_capacity, per_user_cap
+        + 2 // referral_fee_bps
+        + 8 // referred_shares
+        + 16 // referral_fee_per_share
+        + 8 * 2 // referral_fees_owed, open_exposure
+        + 1 // is_active
+        + 8 * 4 // total_trades, profitable_trades, total_pnl, created_at
+        + 32 * 2 // deposit_mint, fee_recipient
+        + 8 * 2 // sequence, position_counter
+        + 1 // compound_profits
+        + 2 // profit_skim_bps
+        + 8 // profit_reserve
+        + 2; // max_mint_allocation_bps
+
+    /// Advance the deposit/withdrawal sequence, returning the number for the current event
+    pub fn next_sequence(&mut self) -> Result<u64> {
+        self.sequence = self.sequence
+            .checked_add(1)
+            .ok_or(VaultError::MathOverflow)?;
+        Ok(self.sequence)
+    }
+
+    /// Apply a closed position's `pnl` to deposits, returning how much of a gain
+    /// went to `profit_reserve`
+    ///
+    /// A gain compounds into `total_deposited` less the `profit_skim_bps` skim, or is
+    /// reserved in full when `compound_profits` is off. A loss is taken from the
+    /// reserve before it reduces deposits.
+    pub fn settle_pnl(&mut self, pnl: i64) -> Result<u64> {
+        let amount = pnl.unsigned_abs();
+        if pnl > 0 {
+            let skim = if self.compound_profits {
+                mul_div(amount, self.profit_skim_bps as u64, 10_000)?
+            } else {
+                amount
+            };
+            self.profit_reserve = self.profit_reserve
+                .checked_add(skim)
+                .ok_or(VaultError::MathOverflow)?;
+            self.total_deposited = self.total_deposited
+                .checked_add(amount - skim)
+                .ok_or(VaultError::MathOverflow)?;
+            return Ok(skim);
+        }
+
+        let covered = amount.min(self.profit_reserve);
+        self.profit_reserve -= covered;
+        self.total_deposited = self.total_deposited
+            .checked_sub(amount - covered)
+            .ok_or(VaultError::MathOverflow)?;
+
+        Ok(0)
+    }
+
+    /// Move `amount` of the profit reserve into `total_deposited`
+    ///
+    /// The reserve belongs to shareholders, so it can only be released while
+    /// someone holds shares to receive it.
+    pub fn release_profit_reserve(&mut self, amount: u64) -> Result<()> {
+        require!(amount > 0 && self.total_shares > 0, VaultError::InvalidAmount);
+        require!(amount <= self.profit_reserve, VaultError::InsufficientFunds);
+
+        self.profit_reserve -= amount;
+        self.total_deposited = self.total_deposited
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Reject a fee claim of `amount` larger than the fees accrued in
+    /// `available_lamports`, the vault's balance above rent
+    ///
+    /// Deposits, referral fees owed and the profit reserve are never claimable.
+    pub fn check_fee_claim(&self, amount: u64, available_lamports: u64) -> Result<()> {
+        let accrued_fees = self.stats(available_lamports)?.accrued_fees;
+        require!(amount <= accrued_fees, VaultError::InsufficientFunds);
+
+        Ok(())
+    }
+
+    /// Claim the id for a position being opened and advance the counter
+    pub fn next_position_id(&mut self) -> Result<u64> {
+        let id = self.position_counter;
+        self.position_counter = id
+            .checked_add(1)
+            .ok_or(VaultError::MathOverflow)?;
+        Ok(id)
+    }
+
+    /// Reject a deposit of `amount` that would push the vault past its capacity
+    /// or the user past their cumulative deposit cap
+    pub fn check_deposit_caps(&self, user_account: &UserAccount, amount: u64) -> Result<()> {
+        let vault_total = self.total_deposited
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+        require!(vault_total <= self.vault_capacity, VaultError::VaultAtCapacity);
+
+        let user_total = user_account.total_deposited
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+        require!(user_total <= self.per_user_cap, VaultError::UserCapExceeded);
+
+        Ok(())
+    }
+
+    /// NAV and share price as of now; `available_lamports` is the vault's balance above rent
+    ///
+    /// Open positions are valued at cost: their SOL is still part of `total_deposited`,
+    /// so the share price is `total_deposited / total_shares` and exposure is reported
+    /// alongside it. Anything the vault holds beyond deposits, referral fees owed and
+    /// the profit reserve is fees accrued to the authority.
+    pub fn stats(&self, available_lamports: u64) -> Result<VaultStats> {
+        let share_price_scaled_1e9 = if self.total_shares == 0 {
+            SHARE_PRICE_SCALE
+        } else {
+            mul_div(self.total_deposited, SHARE_PRICE_SCALE, self.total_shares)?
+        };
+        let accrued_fees = available_lamports
+            .saturating_sub(self.total_deposited)
+            .saturating_sub(self.referral_fees_owed)
+            .saturating_sub(self.profit_reserve);
+
+        Ok(VaultStats {
+            total_deposited: self.total_deposited,
+            total_shares: self.total_shares,
+            share_price_scaled_1e9,
+            total_open_exposure_sol: self.open_exposure,
+            total_pnl: self.total_pnl,
+            accrued_fees,
+            profit_reserve: self.profit_reserve,
+        })
+    }
+
+    /// Reject deposits and withdrawals in an asset other than the vault's:
+    /// `mint` is `None` for native SOL
+    pub fn check_deposit_asset(&self, mint: Option<&Pubkey>) -> Result<()> {
+        require_keys_eq!(
+            self.deposit_mint,
+            mint.copied().unwrap_or_default(),
+            VaultError::WrongDepositAsset
+        );
+
+        Ok(())
+    }
+
+    /// Shares minted for a deposit of `amount` base units of an asset with `decimals`
+    ///
+    /// The first deposit sets the share scale at `SHARE_DECIMALS`; later ones are
+    /// priced against the existing share supply.
+    pub fn shares_for_deposit(&self, amount: u64, decimals: u8) -> Result<u64> {
+        if self.total_shares > 0 {
+            return mul_div(amount, self.total_shares, self.total_deposited);
+        }
+
+        let shares = if decimals <= SHARE_DECIMALS {
+            10u64
+                .checked_pow(u32::from(SHARE_DECIMALS - decimals))
+                .and_then(|scale| amount.checked_mul(scale))
+        } else {
+            10u64
+                .checked_pow(u32::from(decimals - SHARE_DECIMALS))
+                .map(|scale| amount / scale)
+        };
+        let shares = shares.ok_or(VaultError::MathOverflow)?;
+        require!(shares > 0, VaultError::InvalidAmount);
+
+        Ok(shares)
+    }
+
+    /// Reject closing while any user still holds shares, referrers are owed fees
+    /// or profits are still held in reserve
+    pub fn check_closable(&self) -> Result<()> {
+        require!(
+            self.total_shares == 0 && self.total_deposited == 0,
+            VaultError::VaultNotEmpty
+        );
+        require!(
+            self.referral_fees_owed == 0 && self.profit_reserve == 0,
+            VaultError::VaultNotEmpty
+        );
+
+        Ok(())
+    }
+
+    /// Reject closing an SPL vault without its ATA, whose balance is `token_balance`,
+    /// or while that ATA still holds tokens
+    pub fn check_token_account_closable(&self, token_balance: Option<u64>) -> Result<()> {
+        if self.deposit_mint == Pubkey::default() {
+            return Ok(());
+        }
+        let token_balance = token_balance.ok_or(VaultError::MissingVaultTokenAccount)?;
+        require!(token_balance == 0, VaultError::VaultNotEmpty);
+
+        Ok(())
+    }
+
+    /// SOL that open positions in any one mint may hold between them
+    pub fn max_mint_exposure(&self) -> Result<u64> {
+        mul_div(self.total_deposited, self.max_mint_allocation_bps as u64, 10_000)
+    }
+
+    /// Commit `amount` of deposits to a new position; only SOL not already deployed
+    /// in open positions can back it
+    pub fn reserve_exposure(&mut self, amount: u64) -> Result<()> {
+        let free = self.total_deposited.saturating_sub(self.open_exposure);
+        require!(amount <= free, VaultError::InsufficientFunds);
+
+        self.open_exposure = self.open_exposure
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Reserve the referrers' cut of a fee claim of `amount`; returns what's left for the authority
+    pub fn split_fee_claim(&mut self, amount: u64) -> Result<u64> {
+        if self.referred_shares == 0 {
+            return Ok(amount);
+        }
+
+        let referral_cut = mul_div(amount, self.referral_fee_bps as u64, 10_000)?;
+        let per_share = (referral_cut as u128) * REFERRAL_FEE_SCALE / self.referred_shares as u128;
+
+        self.referral_fee_per_share = self.referral_fee_per_share
+            .checked_add(per_share)
+            .ok_or(VaultError::MathOverflow)?;
+        self.referral_fees_owed = self.referral_fees_owed
+            .checked_add(referral_cut)
+            .ok_or(VaultError::MathOverflow)?;
+
+        Ok(amount - referral_cut)
+    }
+
+    /// Credit `shares` newly minted to a referred user to their referrer
+    pub fn add_referred_shares(&mut self, referral_account: &mut ReferralAccount, shares: u64) -> Result<()> {
+        referral_account.sync(self)?;
+        referral_account.referred_shares = referral_account.referred_shares
+            .checked_add(shares)
+            .ok_or(VaultError::MathOverflow)?;
+        self.referred_shares = self.referred_shares
+            .checked_add(shares)
+            .ok_or(VaultError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Stop crediting `shares` burned by a referred user to their referrer
+    pub fn remove_referred_shares(&mut self, referral_account: &mut ReferralAccount, shares: u64) -> Result<()> {
+        referral_account.sync(self)?;
+        referral_account.referred_shares = referral_account.referred_shares
+            .checked_sub(shares)
+            .ok_or(VaultError::InsufficientShares)?;
+        self.referred_shares = self.referred_shares
+            .checked_sub(shares)
+            .ok_or(VaultError::InsufficientShares)?;
+
+        Ok(())
+    }
+
+    /// Burn `shares_to_burn` of the user's shares and return the lamports owed
+    ///
+    /// All checks run and all new balances are computed before either account is
+    /// written, so an error leaves share accounting untouched. `available_lamports`
+    /// is what the vault can pay out above its rent-exempt minimum, and the
+    /// withdrawal is rejected if it would pay out less than `min_sol_out`.
+    pub fn settle_withdrawal(
+        &mut self,
+        user_account: &mut UserAccount,
+        shares_to_burn: u64,
+        available_lamports: u64,
+        min_sol_out: u64,
+    ) -> Result<u64> {
+        require!(shares_to_burn > 0, VaultError::InvalidAmount);
+        require!(user_account.shares >= shares_to_burn, VaultError::InsufficientShares);
+
+        // Calculate SOL to return
+        // amount = (shares_to_burn * total_deposited) / total_shares
+        let amount_to_return = mul_div(shares_to_burn, self.total_deposited, self.total_shares)?;
+        require!(amount_to_return >= min_sol_out, VaultError::SlippageExceeded);
+        require!(amount_to_return <= available_lamports, VaultError::InsufficientFunds);
+
+        let total_deposited = self.total_deposited
+            .checked_sub(amount_to_return)
+            .ok_or(VaultError::InsufficientFunds)?;
+        let total_shares = self.total_shares
+            .checked_sub(shares_to_burn)
+            .ok_or(VaultError::InsufficientShares)?;
+        let user_shares = user_account.shares - shares_to_burn;
+
+        self.total_deposited = total_deposited;
+        self.total_shares = total_shares;
+        user_account.shares = user_shares;
+
+        Ok(amount_to_return)
+    }
 }
 
 #[account]
@@ -317,6 +1019,93 @@ pub struct UserAccount {
     pub total_deposited: u64,
     /// Timestamp of first deposit
     pub deposited_at: i64,
+    /// Wallet that referred this user, set on first deposit
+    pub referrer: Option<Pubkey>,
+}
+
+impl UserAccount {
+    /// Borsh-serialized size, excluding the 8-byte discriminator
+    pub const LEN: usize = 32 * 2 // owner, vault
+        + 8 * 3 // shares, total_deposited, deposited_at
+        + 1 + 32; // referrer
+
+    /// Bind a fresh account to `owner` and `vault`, or check an existing one already is
+    ///
+    /// `init_if_needed` hands deposits whatever account sits at the PDA, so this runs
+    /// on every deposit rather than trusting `shares == 0` to mean a new account.
+    pub fn claim(&mut self, owner: Pubkey, vault: Pubkey) -> Result<()> {
+        if self.owner == Pubkey::default() {
+            self.owner = owner;
+            self.vault = vault;
+        }
+        require_keys_eq!(self.owner, owner, VaultError::AccountOwnerMismatch);
+        require_keys_eq!(self.vault, vault, VaultError::AccountOwnerMismatch);
+
+        Ok(())
+    }
+
+    /// Record who referred `owner`; users can't refer themselves
+    pub fn set_referrer(&mut self, owner: Pubkey, referrer: Option<Pubkey>) -> Result<()> {
+        if let Some(referrer) = referrer {
+            require_keys_neq!(referrer, owner, VaultError::SelfReferral);
+        }
+        self.referrer = referrer;
+
+        Ok(())
+    }
+}
+
+#[account]
+pub struct ReferralAccount {
+    /// Wallet that receives the referral fees
+    pub referrer: Pubkey,
+    /// Vault shares currently held by users this referrer brought in
+    pub referred_shares: u64,
+    /// `Vault::referral_fee_per_share` as of the last sync
+    pub fee_per_share_paid: u128,
+    /// Fees accrued and not yet claimed
+    pub accrued_fees: u64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ReferralAccount {
+    /// Borsh-serialized size, excluding the 8-byte discriminator
+    pub const LEN: usize = 32 // referrer
+        + 8 // referred_shares
+        + 16 // fee_per_share_paid
+        + 8 // accrued_fees
+        + 1; // bump
+
+    /// Accrue fees earned by `referred_shares` since the last sync
+    pub fn sync(&mut self, vault: &Vault) -> Result<()> {
+        let per_share = vault.referral_fee_per_share - self.fee_per_share_paid;
+        let earned = (self.referred_shares as u128)
+            .checked_mul(per_share)
+            .ok_or(VaultError::MathOverflow)?
+            / REFERRAL_FEE_SCALE;
+
+        self.accrued_fees = u64::try_from(earned)
+            .ok()
+            .and_then(|earned| self.accrued_fees.checked_add(earned))
+            .ok_or(VaultError::MathOverflow)?;
+        self.fee_per_share_paid = vault.referral_fee_per_share;
+
+        Ok(())
+    }
+
+    /// Sync, then zero and return the accrued fees, releasing them from the vault's reserve
+    pub fn take_accrued(&mut self, vault: &mut Vault) -> Result<u64> {
+        self.sync(vault)?;
+
+        let amount = self.accrued_fees;
+        vault.referral_fees_owed = vault.referral_fees_owed
+            .checked_sub(amount)
+            .ok_or(VaultError::InsufficientFunds)?;
+        self.accrued_fees = 0;
+
+        Ok(amount)
+    }
 }
 
 #[account]
@@ -327,7 +1116,7 @@ pub struct Position {
     pub token_mint: Pubkey,
     /// Amount of SOL invested
     pub amount_sol: u64,
-    /// Entry price (in smallest unit)
+    /// Entry price (lamports per whole token, scaled by `PRICE_SCALE`)
     pub entry_price: u64,
     /// Current price (updated as needed)
     pub current_price: u64,
@@ -343,6 +1132,56 @@ pub struct Position {
     pub closed_at: i64,
     /// Profit/Loss in lamports (can be negative)
     pub pnl: i64,
+    /// Vault counter value this position's PDA is seeded with
+    pub position_id: u64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl Position {
+    /// Borsh-serialized size, excluding the 8-byte discriminator
+    pub const LEN: usize = 32 * 2 // vault, token_mint
+        + 8 * 5 // amount_sol and the four prices
+        + 1 // status
+        + 8 * 3 // opened_at, closed_at, pnl
+        + 8 // position_id
+        + 1; // bump
+}
+
+/// SOL committed to the vault's open positions in one mint
+#[account]
+pub struct MintExposure {
+    /// Vault the positions belong to
+    pub vault: Pubkey,
+    /// Token mint the positions hold
+    pub token_mint: Pubkey,
+    /// Sum of `amount_sol` over the mint's open positions
+    pub open_amount: u64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl MintExposure {
+    /// Borsh-serialized size, excluding the 8-byte discriminator
+    pub const LEN: usize = 32 * 2 // vault, token_mint
+        + 8 // open_amount
+        + 1; // bump
+
+    /// Add a new position of `amount` to the mint, rejecting it if the total would pass `cap`
+    pub fn reserve(&mut self, amount: u64, cap: u64) -> Result<()> {
+        let open_amount = self.open_amount
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+        require!(open_amount <= cap, VaultError::MintAllocationExceeded);
+
+        self.open_amount = open_amount;
+        Ok(())
+    }
+
+    /// Remove a closed position of `amount` from the mint
+    pub fn release(&mut self, amount: u64) {
+        self.open_amount = self.open_amount.saturating_sub(amount);
+    }
 }
 
 #[repr(u8)]
@@ -352,6 +1191,16 @@ pub enum PositionStatus {
     Liquidated = 2,
 }
 
+/// `a * b / denominator` computed in u128, so large vault totals can't overflow the product
+pub fn mul_div(a: u64, b: u64, denominator: u64) -> Result<u64> {
+    let result = (a as u128)
+        .checked_mul(b as u128)
+        .and_then(|product| product.checked_div(denominator as u128))
+        .ok_or(VaultError::MathOverflow)?;
+
+    u64::try_from(result).map_err(|_| error!(VaultError::MathOverflow))
+}
+
 // ============================================================================
 // Context Structures
 // ============================================================================
@@ -362,7 +1211,7 @@ pub struct InitializeVault<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + std::mem::size_of::<Vault>(),
+        space = 8 + Vault::LEN,
         seeds = [b"vault"],
         bump
     )]
@@ -386,11 +1235,19 @@ pub struct Deposit<'info> {
     #[account(
         init_if_needed,
         payer = user,
-        space = 8 + std::mem::size_of::<UserAccount>(),
+        space = 8 + UserAccount::LEN,
         seeds = [b"user", user.key().as_ref()],
         bump
     )]
     pub user_account: Account<'info, UserAccount>,
+
+    /// Required when the user has a referrer
+    #[account(
+        mut,
+        seeds = [b"referral", referral_account.referrer.as_ref()],
+        bump = referral_account.bump
+    )]
+    pub referral_account: Option<Account<'info, ReferralAccount>>,
     
     #[account(mut)]
     pub user: Signer<'info>,
@@ -413,6 +1270,14 @@ pub struct Withdraw<'info> {
         bump
     )]
     pub user_account: Account<'info, UserAccount>,
+
+    /// Required when the user has a referrer
+    #[account(
+        mut,
+        seeds = [b"referral", referral_account.referrer.as_ref()],
+        bump = referral_account.bump
+    )]
+    pub referral_account: Option<Account<'info, ReferralAccount>>,
     
     #[account(mut)]
     pub user: Signer<'info>,
@@ -421,6 +1286,103 @@ pub struct Withdraw<'info> {
 }
 
 #[derive(Accounts)]
+pub struct DepositSpl<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.vault_bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserAccount::LEN,
+        seeds = [b"user", user.key().as_ref()],
+        bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// Required when the user has a referrer
+    #[account(
+        mut,
+        seeds = [b"referral", referral_account.referrer.as_ref()],
+        bump = referral_account.bump
+    )]
+    pub referral_account: Option<Account<'info, ReferralAccount>>,
+
+    pub deposit_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = deposit_mint,
+        associated_token::authority = user
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = deposit_mint,
+        associated_token::authority = vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSpl<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.vault_bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"user", user.key().as_ref()],
+        bump
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// Required when the user has a referrer
+    #[account(
+        mut,
+        seeds = [b"referral", referral_account.referrer.as_ref()],
+        bump = referral_account.bump
+    )]
+    pub referral_account: Option<Account<'info, ReferralAccount>>,
+
+    pub deposit_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = deposit_mint,
+        associated_token::authority = user
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = deposit_mint,
+        associated_token::authority = vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(token_mint: Pubkey)]
 pub struct OpenPosition<'info> {
     #[account(
         mut,
@@ -433,10 +1395,21 @@ pub struct OpenPosition<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + std::mem::size_of::<Position>()
+        space = 8 + Position::LEN,
+        seeds = [b"position", vault.key().as_ref(), &vault.position_counter.to_le_bytes()],
+        bump
     )]
     pub position: Account<'info, Position>,
     
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + MintExposure::LEN,
+        seeds = [b"mint_exposure", vault.key().as_ref(), token_mint.as_ref()],
+        bump
+    )]
+    pub mint_exposure: Account<'info, MintExposure>,
+    
     #[account(mut)]
     pub authority: Signer<'info>,
     
@@ -453,9 +1426,20 @@ pub struct ClosePosition<'info> {
     )]
     pub vault: Account<'info, Vault>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"position", vault.key().as_ref(), &position.position_id.to_le_bytes()],
+        bump = position.bump
+    )]
     pub position: Account<'info, Position>,
     
+    #[account(
+        mut,
+        seeds = [b"mint_exposure", vault.key().as_ref(), position.token_mint.as_ref()],
+        bump = mint_exposure.bump
+    )]
+    pub mint_exposure: Account<'info, MintExposure>,
+    
     pub authority: Signer<'info>,
 }
 
@@ -478,12 +1462,138 @@ pub struct ClaimFees<'info> {
         mut,
         seeds = [b"vault"],
         bump = vault.vault_bump,
-        has_one = authority
+        has_one = authority,
+        has_one = fee_recipient
     )]
     pub vault: Account<'info, Vault>,
     
     #[account(mut)]
     pub authority: Signer<'info>,
+
+    /// CHECK: Only receives lamports; must be the vault's `fee_recipient`
+    #[account(mut)]
+    pub fee_recipient: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.vault_bump,
+        has_one = authority,
+        close = authority
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Required for an SPL vault, and closed with it
+    #[account(
+        mut,
+        associated_token::mint = vault.deposit_mint,
+        associated_token::authority = vault
+    )]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Required with `vault_token_account`
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+#[derive(Accounts)]
+pub struct GetVaultStats<'info> {
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.vault_bump
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.vault_bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init,
+        payer = referrer,
+        space = 8 + ReferralAccount::LEN,
+        seeds = [b"referral", referrer.key().as_ref()],
+        bump
+    )]
+    pub referral_account: Account<'info, ReferralAccount>,
+
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimReferralFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.vault_bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"referral", referrer.key().as_ref()],
+        bump = referral_account.bump,
+        has_one = referrer
+    )]
+    pub referral_account: Account<'info, ReferralAccount>,
+
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct VaultStats {
+    pub total_deposited: u64,
+    pub total_shares: u64,
+    pub share_price_scaled_1e9: u64,
+    pub total_open_exposure_sol: u64,
+    pub total_pnl: i64,
+    pub accrued_fees: u64,
+    pub profit_reserve: u64,
+}
+
+/// A deposit of `amount` in the vault's deposit asset
+#[event]
+pub struct Deposited {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub shares_minted: u64,
+    /// Shared with `Withdrawn`; increases by exactly one per event
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Withdrawn {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub shares_burned: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultClosed {
+    pub authority: Pubkey,
+    pub residual_lamports: u64,
+    pub timestamp: i64,
 }
 
 // ============================================================================
@@ -510,4 +1620,531 @@ pub enum VaultError {
     InvalidPosition,
     #[msg("Fee too high (max 10% mgmt, 30% performance)")]
     FeeTooHigh,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Withdrawal would return less than the requested minimum")]
+    SlippageExceeded,
+    #[msg("Deposit would exceed the vault's capacity")]
+    VaultAtCapacity,
+    #[msg("Deposit would exceed the per-user cap")]
+    UserCapExceeded,
+    #[msg("Users cannot refer themselves")]
+    SelfReferral,
+    #[msg("Referral account does not match the user's referrer")]
+    InvalidReferrer,
+    #[msg("Vault still holds user shares, owed referral fees or reserved profits")]
+    VaultNotEmpty,
+    #[msg("Asset does not match the vault's deposit mint")]
+    WrongDepositAsset,
+    #[msg("Position would push the mint past the vault's allocation cap")]
+    MintAllocationExceeded,
+    #[msg("User account belongs to a different owner or vault")]
+    AccountOwnerMismatch,
+    #[msg("SPL vaults must pass their token account and the token program")]
+    MissingVaultTokenAccount,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault(total_deposited: u64, total_shares: u64) -> Vault {
+        Vault {
+            authority: Pubkey::new_unique(),
+            vault_bump: 255,
+            total_deposited,
+            total_shares,
+            min_deposit: 1_000_000,
+            max_deposit: 100_000_000_000,
+            management_fee_bps: 100,
+            performance_fee_bps: 2000,
+            vault_capacity: u64::MAX,
+            per_user_cap: u64::MAX,
+            referral_fee_bps: 0,
+            referred_shares: 0,
+            referral_fee_per_share: 0,
+            referral_fees_owed: 0,
+            open_exposure: 0,
+            is_active: true,
+            total_trades: 0,
+            profitable_trades: 0,
+            total_pnl: 0,
+            created_at: 0,
+            deposit_mint: Pubkey::default(),
+            fee_recipient: Pubkey::new_unique(),
+            sequence: 0,
+            position_counter: 0,
+            compound_profits: true,
+            profit_skim_bps: 0,
+            profit_reserve: 0,
+            max_mint_allocation_bps: 10_000,
+        }
+    }
+
+    fn user_account(shares: u64) -> UserAccount {
+        UserAccount {
+            owner: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            shares,
+            total_deposited: shares,
+            deposited_at: 0,
+            referrer: None,
+        }
+    }
+
+    #[test]
+    fn test_withdrawal_burns_shares_pro_rata() {
+        let mut vault = vault(2_000_000_000, 1_000_000_000);
+        let mut user = user_account(500_000_000);
+
+        let amount = vault.settle_withdrawal(&mut user, 250_000_000, 2_000_000_000, 0).unwrap();
+
+        assert_eq!(amount, 500_000_000);
+        assert_eq!(vault.total_deposited, 1_500_000_000);
+        assert_eq!(vault.total_shares, 750_000_000);
+        assert_eq!(user.shares, 250_000_000);
+    }
+
+    #[test]
+    fn test_deposit_and_withdrawal_sequence_has_no_gaps() {
+        let mut vault = vault(0, 0);
+        let mut user = user_account(0);
+        let mut sequences = Vec::new();
+
+        for amount in [1_000_000_000, 500_000_000, 2_000_000_000] {
+            let shares = vault.shares_for_deposit(amount, SHARE_DECIMALS).unwrap();
+            vault.total_deposited += amount;
+            vault.total_shares += shares;
+            user.shares += shares;
+            sequences.push(vault.next_sequence().unwrap());
+
+            vault.settle_withdrawal(&mut user, shares / 2, u64::MAX, 0).unwrap();
+            sequences.push(vault.next_sequence().unwrap());
+        }
+
+        assert_eq!(sequences, (1..=6).collect::<Vec<u64>>());
+        assert_eq!(vault.sequence, 6);
+    }
+
+    #[test]
+    fn test_position_pdas_derive_from_the_vault_counter() {
+        let vault_key = Pubkey::find_program_address(&[b"vault"], &crate::ID).0;
+        let position_pda = |id: u64| {
+            Pubkey::find_program_address(&[b"position", vault_key.as_ref(), &id.to_le_bytes()], &crate::ID)
+        };
+        let mut vault = vault(10_000_000_000, 10_000_000_000);
+
+        let mut opened = Vec::new();
+        for _ in 0..2 {
+            let expected = position_pda(vault.position_counter);
+            vault.reserve_exposure(1_000_000_000).unwrap();
+            let id = vault.next_position_id().unwrap();
+            opened.push((id, expected));
+        }
+
+        assert_eq!(opened[0].0, 0);
+        assert_eq!(opened[1].0, 1);
+        assert_eq!(vault.position_counter, 2);
+        assert_ne!(opened[0].1 .0, opened[1].1 .0);
+        for (id, (address, bump)) in opened {
+            assert_eq!(position_pda(id), (address, bump));
+        }
+    }
+
+    #[test]
+    fn test_failed_withdrawal_leaves_shares_unchanged() {
+        let mut vault = vault(2_000_000_000, 1_000_000_000);
+        let mut user = user_account(500_000_000);
+
+        // Trading capital is out in positions: the vault only holds 0.1 SOL spare
+        let result = vault.settle_withdrawal(&mut user, 250_000_000, 100_000_000, 0);
+
+        assert!(result.is_err());
+        assert_eq!(vault.total_deposited, 2_000_000_000);
+        assert_eq!(vault.total_shares, 1_000_000_000);
+        assert_eq!(user.shares, 500_000_000);
+    }
+
+    #[test]
+    fn test_share_math_near_u64_max() {
+        let shares_to_burn = u64::MAX / 4;
+        let mut vault = vault(u64::MAX - 1, u64::MAX / 2);
+        let mut user = user_account(shares_to_burn);
+
+        // The u64 product alone overflows
+        assert!(shares_to_burn.checked_mul(vault.total_deposited).is_none());
+
+        let amount = vault.settle_withdrawal(&mut user, shares_to_burn, u64::MAX, 0).unwrap();
+
+        assert_eq!(amount, 9_223_372_036_854_775_806);
+        assert_eq!(vault.total_deposited, 9_223_372_036_854_775_808);
+        assert_eq!(vault.total_shares, 4_611_686_018_427_387_904);
+        assert_eq!(user.shares, 0);
+
+        // Deposit share pricing against equally large totals
+        assert_eq!(mul_div(10_000_000_000, u64::MAX / 2, u64::MAX / 2).unwrap(), 10_000_000_000);
+    }
+
+    #[test]
+    fn test_mul_div_rejects_truncation() {
+        assert!(mul_div(u64::MAX, u64::MAX, 1).is_err());
+        assert!(mul_div(1, 1, 0).is_err());
+    }
+
+    #[test]
+    fn test_withdrawal_meeting_min_sol_out_succeeds() {
+        let mut vault = vault(2_000_000_000, 1_000_000_000);
+        let mut user = user_account(500_000_000);
+
+        let amount = vault
+            .settle_withdrawal(&mut user, 250_000_000, 2_000_000_000, 500_000_000)
+            .unwrap();
+
+        assert_eq!(amount, 500_000_000);
+        assert_eq!(user.shares, 250_000_000);
+    }
+
+    #[test]
+    fn test_withdrawal_below_min_sol_out_is_rejected() {
+        // A losing close halved the vault since the user quoted 0.5 SOL
+        let mut vault = vault(1_000_000_000, 1_000_000_000);
+        let mut user = user_account(500_000_000);
+
+        let result = vault.settle_withdrawal(&mut user, 250_000_000, 2_000_000_000, 500_000_000);
+
+        assert_eq!(result.unwrap_err(), VaultError::SlippageExceeded.into());
+        assert_eq!(vault.total_deposited, 1_000_000_000);
+        assert_eq!(vault.total_shares, 1_000_000_000);
+        assert_eq!(user.shares, 500_000_000);
+    }
+
+    #[test]
+    fn test_deposit_at_vault_capacity_boundary() {
+        let mut vault = vault(9_000_000_000, 9_000_000_000);
+        vault.vault_capacity = 10_000_000_000;
+        let user = user_account(0);
+
+        assert!(vault.check_deposit_caps(&user, 1_000_000_000).is_ok());
+        assert_eq!(
+            vault.check_deposit_caps(&user, 1_000_000_001).unwrap_err(),
+            VaultError::VaultAtCapacity.into()
+        );
+    }
+
+    #[test]
+    fn test_deposit_at_per_user_cap_boundary() {
+        let mut vault = vault(5_000_000_000, 5_000_000_000);
+        vault.per_user_cap = 3_000_000_000;
+        let user = user_account(2_000_000_000);
+
+        assert!(vault.check_deposit_caps(&user, 1_000_000_000).is_ok());
+        assert_eq!(
+            vault.check_deposit_caps(&user, 1_000_000_001).unwrap_err(),
+            VaultError::UserCapExceeded.into()
+        );
+    }
+
+    fn referral_account(referrer: Pubkey) -> ReferralAccount {
+        ReferralAccount {
+            referrer,
+            referred_shares: 0,
+            fee_per_share_paid: 0,
+            accrued_fees: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_referral_fees_accrue_and_claim() {
+        let mut vault = vault(2_000_000_000, 2_000_000_000);
+        vault.referral_fee_bps = 1000;
+        let mut referral = referral_account(Pubkey::new_unique());
+
+        let mut user = user_account(0);
+        user.set_referrer(user.owner, Some(referral.referrer)).unwrap();
+        vault.add_referred_shares(&mut referral, 1_000_000_000).unwrap();
+
+        // 10% of a 0.1 SOL fee claim is held back for the referrer
+        let authority_amount = vault.split_fee_claim(100_000_000).unwrap();
+        assert_eq!(authority_amount, 90_000_000);
+        assert_eq!(vault.referral_fees_owed, 10_000_000);
+
+        // Shares referred after the claim don't share in it
+        vault.add_referred_shares(&mut referral, 500_000_000).unwrap();
+        assert_eq!(referral.accrued_fees, 10_000_000);
+
+        assert_eq!(referral.take_accrued(&mut vault).unwrap(), 10_000_000);
+        assert_eq!(referral.accrued_fees, 0);
+        assert_eq!(vault.referral_fees_owed, 0);
+        assert_eq!(referral.take_accrued(&mut vault).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_deposit_into_another_users_account_is_rejected() {
+        let (alice, bob, vault_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        // Zero-initialized, as init_if_needed leaves it: the first depositor claims it
+        let mut fresh = UserAccount { owner: Pubkey::default(), vault: Pubkey::default(), ..user_account(0) };
+        fresh.claim(alice, vault_key).unwrap();
+        assert_eq!((fresh.owner, fresh.vault), (alice, vault_key));
+        fresh.claim(alice, vault_key).unwrap();
+
+        // Alice's account, even one emptied back to zero shares, stays hers
+        fresh.shares = 0;
+        assert_eq!(fresh.claim(bob, vault_key).unwrap_err(), VaultError::AccountOwnerMismatch.into());
+        assert_eq!(fresh.claim(alice, Pubkey::new_unique()).unwrap_err(), VaultError::AccountOwnerMismatch.into());
+        assert_eq!(fresh.owner, alice);
+    }
+
+    #[test]
+    fn test_self_referral_is_rejected() {
+        let mut user = user_account(0);
+
+        assert_eq!(
+            user.set_referrer(user.owner, Some(user.owner)).unwrap_err(),
+            VaultError::SelfReferral.into()
+        );
+        assert_eq!(user.referrer, None);
+    }
+
+    #[test]
+    fn test_vault_stats_share_price_matches_recomputation() {
+        // One deposit of 2 SOL at 1:1, then a realized 0.3 SOL win
+        let mut vault = vault(2_000_000_000, 2_000_000_000);
+        vault.total_deposited += 300_000_000;
+        vault.total_pnl = 300_000_000;
+
+        // A second user deposits 1.15 SOL at the new price, then the bot opens a position
+        let shares = mul_div(1_150_000_000, vault.total_shares, vault.total_deposited).unwrap();
+        vault.total_deposited += 1_150_000_000;
+        vault.total_shares += shares;
+        vault.open_exposure += 500_000_000;
+
+        let stats = vault.stats(vault.total_deposited + 25_000_000).unwrap();
+
+        let expected = (vault.total_deposited as f64 / vault.total_shares as f64 * 1e9) as u64;
+        assert!(stats.share_price_scaled_1e9.abs_diff(expected) <= 1);
+        assert_eq!(stats.share_price_scaled_1e9, 1_150_000_000);
+        assert_eq!(stats.total_open_exposure_sol, 500_000_000);
+        assert_eq!(stats.total_pnl, 300_000_000);
+        assert_eq!(stats.accrued_fees, 25_000_000);
+    }
+
+    #[test]
+    fn test_profitable_close_splits_gain_between_skim_and_deposits() {
+        let mut vault = vault(10_000_000_000, 10_000_000_000);
+        vault.profit_skim_bps = 1_000; // 10%
+
+        // 1 SOL in, 1.5 SOL back
+        vault.reserve_exposure(1_000_000_000).unwrap();
+        let skimmed = vault.settle_pnl(500_000_000).unwrap();
+
+        assert_eq!(skimmed, 50_000_000);
+        assert_eq!(vault.profit_reserve, 50_000_000);
+        assert_eq!(vault.total_deposited, 10_450_000_000);
+        // The skim is the vault's, not fees the authority can claim
+        let stats = vault.stats(vault.total_deposited + vault.profit_reserve).unwrap();
+        assert_eq!(stats.accrued_fees, 0);
+        assert_eq!(stats.profit_reserve, 50_000_000);
+
+        // A loss drains the reserve before it touches deposits
+        assert_eq!(vault.settle_pnl(-80_000_000).unwrap(), 0);
+        assert_eq!(vault.profit_reserve, 0);
+        assert_eq!(vault.total_deposited, 10_420_000_000);
+
+        // With compounding off, the whole gain is held back
+        vault.compound_profits = false;
+        assert_eq!(vault.settle_pnl(200_000_000).unwrap(), 200_000_000);
+        assert_eq!(vault.total_deposited, 10_420_000_000);
+        assert_eq!(vault.profit_reserve, 200_000_000);
+    }
+
+    #[test]
+    fn test_positions_cannot_overcommit_deployed_funds() {
+        let mut vault = vault(2_000_000_000, 2_000_000_000);
+
+        vault.reserve_exposure(1_800_000_000).unwrap();
+        assert_eq!(vault.open_exposure, 1_800_000_000);
+
+        // Only 0.2 SOL is still free, though 2 SOL is deposited
+        assert!(vault.reserve_exposure(500_000_000).is_err());
+        assert_eq!(vault.open_exposure, 1_800_000_000);
+
+        vault.reserve_exposure(200_000_000).unwrap();
+        assert_eq!(vault.open_exposure, vault.total_deposited);
+    }
+
+    #[test]
+    fn test_spl_deposit_and_proportional_withdraw() {
+        let usdc = Pubkey::new_unique();
+        let sol_vault = vault(0, 0);
+        let mut vault = vault(0, 0);
+        vault.deposit_mint = usdc;
+
+        // SOL and other mints can't be mixed into a USDC vault, nor USDC into a SOL one
+        assert!(vault.check_deposit_asset(None).is_err());
+        assert!(vault.check_deposit_asset(Some(&Pubkey::new_unique())).is_err());
+        assert!(sol_vault.check_deposit_asset(Some(&usdc)).is_err());
+        vault.check_deposit_asset(Some(&usdc)).unwrap();
+
+        // 100 USDC (6 decimals) opens the vault at 1e9 shares per token
+        let first = vault.shares_for_deposit(100_000_000, 6).unwrap();
+        assert_eq!(first, 100_000_000_000);
+        vault.total_deposited = 100_000_000;
+        vault.total_shares = first;
+
+        // After 50 USDC of gains, 30 USDC buys shares at 1.5 USDC each
+        vault.total_deposited += 50_000_000;
+        let second = vault.shares_for_deposit(30_000_000, 6).unwrap();
+        assert_eq!(second, 20_000_000_000);
+        vault.total_deposited += 30_000_000;
+        vault.total_shares += second;
+
+        let mut user = user_account(second);
+        let available = vault.total_deposited;
+        let amount = vault.settle_withdrawal(&mut user, second / 2, available, 0).unwrap();
+
+        assert_eq!(amount, 15_000_000);
+        assert_eq!(vault.total_deposited, 165_000_000);
+        assert_eq!(user.shares, 10_000_000_000);
+    }
+
+    #[test]
+    fn test_close_requires_empty_vault() {
+        assert!(vault(0, 0).check_closable().is_ok());
+        assert!(vault(0, 1).check_closable().is_err());
+        assert!(vault(1, 0).check_closable().is_err());
+
+        let mut owes_referrers = vault(0, 0);
+        owes_referrers.referral_fees_owed = 1;
+        assert!(owes_referrers.check_closable().is_err());
+
+        let mut holds_reserve = vault(0, 0);
+        holds_reserve.profit_reserve = 1;
+        assert!(holds_reserve.check_closable().is_err());
+    }
+
+    #[test]
+    fn test_spl_vault_closes_only_with_an_empty_token_account() {
+        // A SOL vault has no token account to close
+        assert!(vault(0, 0).check_token_account_closable(None).is_ok());
+
+        let mut spl = vault(0, 0);
+        spl.deposit_mint = Pubkey::new_unique();
+        assert!(spl.check_token_account_closable(None).is_err());
+        assert!(spl.check_token_account_closable(Some(1)).is_err());
+        assert!(spl.check_token_account_closable(Some(0)).is_ok());
+    }
+
+    #[test]
+    fn test_fee_claims_cannot_touch_the_profit_reserve() {
+        let mut vault = vault(10_000_000_000, 10_000_000_000);
+        vault.compound_profits = false;
+        vault.reserve_exposure(1_000_000_000).unwrap();
+        vault.settle_pnl(500_000_000).unwrap();
+
+        // Deposits, the reserve and 1_000 lamports of fees
+        let available = vault.total_deposited + vault.profit_reserve + 1_000;
+        assert!(vault.check_fee_claim(1_000, available).is_ok());
+        assert!(vault.check_fee_claim(1_001, available).is_err());
+        assert!(vault.check_fee_claim(500_000_000, available).is_err());
+
+        // Releasing compounds the reserve into deposits, where claims still can't reach it
+        vault.release_profit_reserve(500_000_000).unwrap();
+        assert_eq!(vault.profit_reserve, 0);
+        assert_eq!(vault.total_deposited, 10_500_000_000);
+        assert!(vault.check_fee_claim(1_001, available).is_err());
+        assert!(vault.release_profit_reserve(1).is_err());
+    }
+
+    #[test]
+    fn test_profit_reserve_needs_shareholders_to_release() {
+        let mut vault = vault(0, 0);
+        vault.profit_reserve = 1_000;
+        assert!(vault.release_profit_reserve(1_000).is_err());
+        assert!(vault.check_closable().is_err());
+    }
+
+    #[test]
+    fn test_empty_vault_share_price_is_one() {
+        let stats = vault(0, 0).stats(0).unwrap();
+
+        assert_eq!(stats.share_price_scaled_1e9, 1_000_000_000);
+    }
+
+    fn serialized_len<T: AccountSerialize>(account: &T) -> usize {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data.len()
+    }
+
+    #[test]
+    fn test_account_len_matches_serialized_size() {
+        let mut user = user_account(1);
+        user.referrer = Some(Pubkey::new_unique());
+        let position = Position {
+            vault: Pubkey::new_unique(),
+            token_mint: Pubkey::new_unique(),
+            amount_sol: 1,
+            entry_price: 1,
+            current_price: 1,
+            take_profit_price: 1,
+            stop_loss_price: 1,
+            status: PositionStatus::Open as u8,
+            opened_at: 1,
+            closed_at: 1,
+            pnl: -1,
+            position_id: 1,
+            bump: 255,
+        };
+
+        assert_eq!(serialized_len(&vault(1, 1)), 8 + Vault::LEN);
+        assert_eq!(serialized_len(&user), 8 + UserAccount::LEN);
+        assert_eq!(serialized_len(&referral_account(Pubkey::new_unique())), 8 + ReferralAccount::LEN);
+        assert_eq!(serialized_len(&position), 8 + Position::LEN);
+        let exposure = MintExposure {
+            vault: Pubkey::new_unique(),
+            token_mint: Pubkey::new_unique(),
+            open_amount: 1,
+            bump: 255,
+        };
+        assert_eq!(serialized_len(&exposure), 8 + MintExposure::LEN);
+    }
+
+    fn mint_exposure() -> MintExposure {
+        MintExposure {
+            vault: Pubkey::new_unique(),
+            token_mint: Pubkey::new_unique(),
+            open_amount: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_mint_allocation_caps_exposure_to_one_mint() {
+        let mut vault = vault(10_000_000_000, 10_000_000_000);
+        vault.max_mint_allocation_bps = 2_500; // 25%
+        let cap = vault.max_mint_exposure().unwrap();
+        assert_eq!(cap, 2_500_000_000);
+
+        // Two 1 SOL positions and a 0.5 SOL one fill the mint's allocation exactly
+        let mut concentrated = mint_exposure();
+        for amount in [1_000_000_000, 1_000_000_000, 500_000_000] {
+            concentrated.reserve(amount, cap).unwrap();
+            vault.reserve_exposure(amount).unwrap();
+        }
+        assert_eq!(concentrated.open_amount, cap);
+
+        let err = concentrated.reserve(1, cap).unwrap_err();
+        assert_eq!(err, VaultError::MintAllocationExceeded.into());
+        assert_eq!(concentrated.open_amount, cap);
+
+        // Another mint still has its own allocation
+        let mut other = mint_exposure();
+        other.reserve(1_000_000_000, cap).unwrap();
+
+        // Closing a position frees room in its mint
+        concentrated.release(500_000_000);
+        concentrated.reserve(500_000_000, cap).unwrap();
+    }
 }
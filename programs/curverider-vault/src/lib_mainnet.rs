@@ -1,6 +1,4 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use anchor_spl::associated_token::AssociatedToken;
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
@@ -71,13 +69,16 @@ pub mod curverider_vault {
     }
 
     /// Create a delegation account (vault) allowing bot to trade on user's behalf
-    /// Users can create multiple vaults with different strategies using vault_index
+    /// Users can create multiple vaults with different strategies using vault_index;
+    /// a nonzero `min_liquidity_sol` keeps the bot out of thinner tokens
     pub fn create_delegation(
         ctx: Context<CreateDelegation>,
         vault_index: u8,
         strategy: u8,
         max_position_size_sol: u64,
         max_concurrent_trades: u8,
+        min_liquidity_sol: u64,
+        performance_fee_bps: u16,
     ) -> Result<()> {
         // Check global pause
         require!(!ctx.accounts.config.is_paused, VaultError::SystemPaused);
@@ -96,6 +97,10 @@ pub mod curverider_vault {
             VaultError::InvalidAmount
         );
         require!(strategy <= 3, VaultError::InvalidStrategy);
+        require!(
+            performance_fee_bps <= MAX_PERFORMANCE_FEE_BPS,
+            VaultError::FeeTooHigh
+        );
 
         delegation.user = ctx.accounts.user.key();
         delegation.bot_authority = ctx.accounts.bot_authority.key();
@@ -113,6 +118,9 @@ pub mod curverider_vault {
         delegation.last_trade_at = 0;
         delegation.bump = ctx.bumps.delegation;
         delegation.position_counter = 0;
+        delegation.min_liquidity_sol = min_liquidity_sol;
+        delegation.performance_fee_bps = performance_fee_bps;
+        delegation.fees_owed = 0;
 
         // Update global stats
         let config = &mut ctx.accounts.config;
@@ -126,6 +134,8 @@ pub mod curverider_vault {
             max_position_size_sol,
             max_concurrent_trades,
             timestamp: delegation.created_at,
+            min_liquidity_sol,
+            performance_fee_bps,
         });
 
         Ok(())
@@ -139,6 +149,7 @@ pub mod curverider_vault {
         max_position_size_sol: Option<u64>,
         max_concurrent_trades: Option<u8>,
         is_active: Option<bool>,
+        min_liquidity_sol: Option<u64>,
     ) -> Result<()> {
         let delegation = &mut ctx.accounts.delegation;
 
@@ -167,14 +178,19 @@ pub mod curverider_vault {
             delegation.is_active = active;
         }
 
+        if let Some(min_liquidity) = min_liquidity_sol {
+            delegation.min_liquidity_sol = min_liquidity;
+        }
+
         emit!(DelegationUpdated {
             user: delegation.user,
-            vault_index: delegation.vault_index,
+            vault_index,
             strategy: delegation.strategy,
             max_position_size_sol: delegation.max_position_size_sol,
             max_concurrent_trades: delegation.max_concurrent_trades,
             is_active: delegation.is_active,
             timestamp: Clock::get()?.unix_timestamp,
+            min_liquidity_sol: delegation.min_liquidity_sol,
         });
 
         Ok(())
@@ -199,7 +215,7 @@ pub mod curverider_vault {
 
         emit!(BotAuthorityChanged {
             user: delegation.user,
-            vault_index: delegation.vault_index,
+            vault_index,
             old_authority,
             new_authority: new_bot_authority,
             timestamp: Clock::get()?.unix_timestamp,
@@ -216,7 +232,7 @@ pub mod curverider_vault {
 
         emit!(DelegationRevoked {
             user: delegation.user,
-            vault_index: delegation.vault_index,
+            vault_index,
             active_trades_remaining: delegation.active_trades,
             timestamp: Clock::get()?.unix_timestamp,
         });
@@ -224,14 +240,58 @@ pub mod curverider_vault {
         Ok(())
     }
 
+    /// User-initiated safety valve: deactivate the delegation and force-liquidate
+    /// the open positions passed in `remaining_accounts`, even while trades are active
+    ///
+    /// PnL of force-liquidated positions is unknown and recorded as zero.
+    pub fn emergency_withdraw<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EmergencyWithdraw<'info>>,
+        vault_index: u8,
+    ) -> Result<()> {
+        let positions = ctx.remaining_accounts;
+        require!(positions.len() <= MAX_BATCH_CLOSE, VaultError::BatchTooLarge);
+
+        let delegation_key = ctx.accounts.delegation.key();
+        let user = ctx.accounts.delegation.user;
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut liquidated: u8 = 0;
+
+        for info in positions {
+            require!(info.is_writable, VaultError::InvalidPosition);
+
+            let mut position: Account<'info, Position> = Account::try_from(info)?;
+            if position.force_liquidate(delegation_key, timestamp)? {
+                liquidated += 1;
+                emit!(PositionLiquidated {
+                    user,
+                    vault_index,
+                    position_id: position.position_id,
+                    token_mint: position.token_mint,
+                    timestamp,
+                });
+            }
+            position.exit(ctx.program_id)?;
+        }
+
+        let delegation = &mut ctx.accounts.delegation;
+        delegation.is_active = false;
+        delegation.record_closes(liquidated, 0, 0)?;
+
+        emit!(EmergencyWithdrawn {
+            user: delegation.user,
+            vault_index,
+            positions_liquidated: liquidated,
+            active_trades_remaining: delegation.active_trades,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
     /// Close delegation account and recover rent (only if no active trades)
     pub fn close_delegation(ctx: Context<CloseDelegation>, vault_index: u8) -> Result<()> {
         let delegation = &ctx.accounts.delegation;
-
-        require!(
-            delegation.active_trades == 0,
-            VaultError::HasActiveTrades
-        );
+        delegation.check_closable()?;
 
         // Update global stats
         let config = &mut ctx.accounts.config;
@@ -239,7 +299,7 @@ pub mod curverider_vault {
 
         emit!(DelegationClosed {
             user: delegation.user,
-            vault_index: delegation.vault_index,
+            vault_index,
             total_trades: delegation.total_trades,
             total_pnl: delegation.total_pnl,
             timestamp: Clock::get()?.unix_timestamp,
@@ -250,17 +310,50 @@ pub mod curverider_vault {
         Ok(())
     }
 
+    /// Restrict the bot to a set of token mints (an empty list allows all)
+    pub fn set_mint_whitelist(
+        ctx: Context<SetMintWhitelist>,
+        vault_index: u8,
+        mints: Vec<Pubkey>,
+    ) -> Result<()> {
+        require!(mints.len() <= MAX_WHITELIST_MINTS, VaultError::WhitelistTooLarge);
+
+        let whitelist = &mut ctx.accounts.mint_whitelist;
+        whitelist.delegation = ctx.accounts.delegation.key();
+        whitelist.mints = mints;
+        whitelist.bump = ctx.bumps.mint_whitelist;
+
+        emit!(MintWhitelistUpdated {
+            user: ctx.accounts.user.key(),
+            vault_index,
+            mint_count: whitelist.mints.len() as u8,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
     /// Bot opens a trading position on behalf of user
     ///
     /// NOTE: This creates a position record. The actual token swap
     /// should be done by the bot via Jupiter/Raydium CPI or separately.
+    ///
+    /// The position PDA is keyed by the bot's `client_order_id`, so resubmitting
+    /// an order that already landed leaves the existing position untouched.
+    #[allow(clippy::too_many_arguments)]
     pub fn open_position(
         ctx: Context<OpenPosition>,
+        client_order_id: u64,
         token_mint: Pubkey,
         amount_sol: u64,
         entry_price: u64,
         take_profit_price: u64,
         stop_loss_price: u64,
+        strategy: u8,
+        entry_confidence_bps: u16,
+        trailing_activation_bps: u16,
+        trailing_distance_bps: u16,
+        liquidity_sol: u64,
     ) -> Result<()> {
         // Check global pause
         require!(!ctx.accounts.config.is_paused, VaultError::SystemPaused);
@@ -268,6 +361,16 @@ pub mod curverider_vault {
         let delegation = &mut ctx.accounts.delegation;
         let position = &mut ctx.accounts.position;
 
+        // A retried order finds the position it already opened
+        if position.is_initialized() {
+            msg!(
+                "Order {} already opened position {}",
+                client_order_id,
+                position.position_id
+            );
+            return Ok(());
+        }
+
         // Validate delegation
         require!(delegation.is_active, VaultError::DelegationNotActive);
         require!(
@@ -279,6 +382,19 @@ pub mod curverider_vault {
             VaultError::PositionTooLarge
         );
         require!(amount_sol > 0, VaultError::InvalidAmount);
+        delegation.check_liquidity(liquidity_sol)?;
+
+        // Validate mint against the user's whitelist, if one has been set
+        let whitelist_info = ctx.accounts.mint_whitelist.to_account_info();
+        let whitelist = if whitelist_info.owner == ctx.program_id && !whitelist_info.data_is_empty() {
+            Some(MintWhitelist::try_deserialize(&mut &whitelist_info.try_borrow_data()?[..])?)
+        } else {
+            None
+        };
+        require!(
+            mint_allowed(whitelist.as_ref(), &token_mint),
+            VaultError::MintNotWhitelisted
+        );
 
         // Validate prices
         require!(entry_price > 0, VaultError::InvalidPrice);
@@ -304,7 +420,10 @@ pub mod curverider_vault {
         position.closed_at = 0;
         position.pnl = 0;
         position.position_id = delegation.position_counter;
+        position.client_order_id = client_order_id;
         position.bump = ctx.bumps.position;
+        position.record_entry_metadata(strategy, entry_confidence_bps)?;
+        position.record_trailing_params(trailing_activation_bps, trailing_distance_bps)?;
 
         // Update delegation stats
         delegation.active_trades = delegation.active_trades.checked_add(1).unwrap();
@@ -319,64 +438,176 @@ pub mod curverider_vault {
 
         emit!(PositionOpened {
             user: delegation.user,
+            vault_index: delegation.vault_index,
             position_id: position.position_id,
+            client_order_id,
             token_mint,
             amount_sol,
             entry_price,
             take_profit_price,
             stop_loss_price,
+            strategy,
+            entry_confidence_bps,
             timestamp: position.opened_at,
+            trailing_activation_bps,
+            trailing_distance_bps,
         });
 
         Ok(())
     }
 
-    /// Bot closes a trading position
+    /// Bot reports the latest price of an open position, raising its high-water
+    /// mark and arming the trailing stop once price reaches the activation level
+    pub fn update_trailing(ctx: Context<UpdateTrailing>, current_price: u64) -> Result<()> {
+        let position = &mut ctx.accounts.position;
+
+        if position.update_trailing(current_price)? {
+            emit!(TrailingStopActivated {
+                user: position.user,
+                position_id: position.position_id,
+                token_mint: position.token_mint,
+                entry_price: position.entry_price,
+                activation_price: current_price,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Bot closes a trading position; `trailing_stop` marks an exit on the armed trailing stop
     pub fn close_position(
         ctx: Context<ClosePosition>,
         exit_price: u64,
         amount_received: u64,
+        trailing_stop: bool,
     ) -> Result<()> {
+        let delegation_key = ctx.accounts.delegation.key();
         let delegation = &mut ctx.accounts.delegation;
         let position = &mut ctx.accounts.position;
 
-        // Validate position state
-        require!(
-            position.status == PositionStatus::Open as u8,
-            VaultError::PositionNotOpen
-        );
-        require!(
-            position.delegation == delegation.key(),
-            VaultError::InvalidPosition
-        );
-
-        // Calculate PnL (can be negative)
-        let pnl = (amount_received as i64)
-            .checked_sub(position.amount_sol as i64)
-            .ok_or(VaultError::MathOverflow)?;
-
-        // Update position
-        position.current_price = exit_price;
-        position.status = PositionStatus::Closed as u8;
-        position.closed_at = Clock::get()?.unix_timestamp;
-        position.pnl = pnl;
+        position.record_exit_trigger(trailing_stop)?;
+        let pnl = position.settle_close(
+            delegation_key,
+            exit_price,
+            amount_received,
+            Clock::get()?.unix_timestamp,
+        )?;
 
         // Update delegation stats
-        delegation.active_trades = delegation.active_trades.checked_sub(1).unwrap();
-        delegation.total_pnl = delegation.total_pnl.checked_add(pnl).unwrap();
-
-        if pnl > 0 {
-            delegation.profitable_trades = delegation.profitable_trades.checked_add(1).unwrap();
-        }
+        delegation.record_closes(1, pnl, u64::from(pnl > 0))?;
+        delegation.accrue_fee(pnl, position.amount_sol)?;
 
         emit!(PositionClosed {
             user: delegation.user,
+            vault_index: delegation.vault_index,
             position_id: position.position_id,
             token_mint: position.token_mint,
             entry_price: position.entry_price,
             exit_price,
             pnl,
+            pnl_bps: position.pnl_bps,
             timestamp: position.closed_at,
+            trailing_stop,
+        });
+
+        Ok(())
+    }
+
+    /// Bot closes several positions of one delegation in a single instruction
+    ///
+    /// Positions are passed writable in `remaining_accounts`, in the same order
+    /// as `exit_prices` and `amounts_received`. Delegation stats are written once.
+    pub fn close_positions_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClosePositionsBatch<'info>>,
+        exit_prices: Vec<u64>,
+        amounts_received: Vec<u64>,
+    ) -> Result<()> {
+        let positions = ctx.remaining_accounts;
+
+        require!(!positions.is_empty(), VaultError::InvalidAmount);
+        require!(positions.len() <= MAX_BATCH_CLOSE, VaultError::BatchTooLarge);
+        require!(
+            exit_prices.len() == positions.len() && amounts_received.len() == positions.len(),
+            VaultError::BatchLengthMismatch
+        );
+
+        let delegation_key = ctx.accounts.delegation.key();
+        let user = ctx.accounts.delegation.user;
+        let vault_index = ctx.accounts.delegation.vault_index;
+        let closed_at = Clock::get()?.unix_timestamp;
+
+        let mut total_pnl: i64 = 0;
+        let mut profitable: u64 = 0;
+        let mut winners: Vec<(i64, u64)> = Vec::with_capacity(positions.len());
+
+        for ((info, &exit_price), &amount_received) in positions
+            .iter()
+            .zip(exit_prices.iter())
+            .zip(amounts_received.iter())
+        {
+            require!(info.is_writable, VaultError::InvalidPosition);
+
+            // Checks program ownership and the Position discriminator
+            let mut position: Account<'info, Position> = Account::try_from(info)?;
+            let pnl = position.settle_close(delegation_key, exit_price, amount_received, closed_at)?;
+
+            total_pnl = total_pnl.checked_add(pnl).ok_or(VaultError::MathOverflow)?;
+            if pnl > 0 {
+                profitable += 1;
+                winners.push((pnl, position.amount_sol));
+            }
+
+            emit!(PositionClosed {
+                user,
+                vault_index,
+                position_id: position.position_id,
+                token_mint: position.token_mint,
+                entry_price: position.entry_price,
+                exit_price,
+                pnl,
+                pnl_bps: position.pnl_bps,
+                timestamp: closed_at,
+                trailing_stop: false,
+            });
+
+            // Persist now so a position listed twice fails as no longer open
+            position.exit(ctx.program_id)?;
+        }
+
+        // positions.len() <= MAX_BATCH_CLOSE, so this fits in u8
+        let delegation = &mut ctx.accounts.delegation;
+        delegation.record_closes(positions.len() as u8, total_pnl, profitable)?;
+        // Fees accrue per winning position, not on the batch's net PnL
+        for (pnl, amount_sol) in winners {
+            delegation.accrue_fee(pnl, amount_sol)?;
+        }
+
+        Ok(())
+    }
+
+    /// User pays the operator the performance fees accrued on their delegation
+    pub fn collect_delegation_fees(ctx: Context<CollectDelegationFees>, vault_index: u8) -> Result<()> {
+        let amount = ctx.accounts.delegation.fees_owed;
+        require!(amount > 0, VaultError::NoFeesOwed);
+
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.user.to_account_info(),
+                to: ctx.accounts.operator.to_account_info(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_context, amount)?;
+
+        ctx.accounts.delegation.fees_owed = 0;
+
+        emit!(DelegationFeesCollected {
+            user: ctx.accounts.user.key(),
+            vault_index,
+            operator: ctx.accounts.operator.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
         });
 
         Ok(())
@@ -408,7 +639,15 @@ pub mod curverider_vault {
 // ============================================================================
 
 const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+/// Prices are lamports per whole token times `PRICE_SCALE`, as the bot encodes them
+pub const PRICE_SCALE: u64 = 1_000_000;
 const MAX_VAULTS_PER_USER: u8 = 10; // Users can have up to 10 vaults (e.g., one per strategy + extras)
+const MAX_BATCH_CLOSE: usize = 8; // Keeps close_positions_batch within the compute budget
+const MAX_WHITELIST_MINTS: usize = 32;
+const MAX_POSITION_STRATEGY: u8 = 4; // Delegation strategies 0-3, plus 4 = ensemble
+const MAX_CONFIDENCE_BPS: u16 = 10_000;
+const MAX_PERFORMANCE_FEE_BPS: u16 = 3_000;
+const MAX_TRAILING_DISTANCE_BPS: u16 = 9_999; // A 100% trail would sit at zero
 
 // ============================================================================
 // Account Structures
@@ -430,6 +669,14 @@ pub struct GlobalConfig {
     pub bump: u8,
 }
 
+impl GlobalConfig {
+    /// Borsh-serialized size, excluding the 8-byte discriminator
+    pub const LEN: usize = 32 * 2 // authority, emergency_authority
+        + 1 // is_paused
+        + 8 * 2 // total_delegations, total_positions
+        + 1; // bump
+}
+
 #[account]
 pub struct DelegationAccount {
     /// User's wallet public key
@@ -464,6 +711,12 @@ pub struct DelegationAccount {
     pub bump: u8,
     /// Counter for position IDs
     pub position_counter: u64,
+    /// Minimum token liquidity the bot may open a position in (lamports, 0 = no floor)
+    pub min_liquidity_sol: u64,
+    /// Operator's share of each profitable close, in basis points
+    pub performance_fee_bps: u16,
+    /// Performance fees accrued but not yet paid to the operator (lamports)
+    pub fees_owed: u64,
 }
 
 #[account]
@@ -476,9 +729,9 @@ pub struct Position {
     pub token_mint: Pubkey,
     /// Amount of SOL invested (lamports)
     pub amount_sol: u64,
-    /// Entry price (scaled by 1e6)
+    /// Entry price (lamports per whole token, scaled by `PRICE_SCALE`)
     pub entry_price: u64,
-    /// Current/exit price (scaled by 1e6)
+    /// Current/exit price (scaled by `PRICE_SCALE`)
     pub current_price: u64,
     /// Take profit target
     pub take_profit_price: u64,
@@ -494,10 +747,261 @@ pub struct Position {
     pub pnl: i64,
     /// Unique position ID within delegation
     pub position_id: u64,
+    /// Bot-supplied order ID the position PDA is derived from
+    pub client_order_id: u64,
+    /// PDA bump seed
+    pub bump: u8,
+    /// Strategy that opened the position (0-3 as for delegations, 4 = ensemble)
+    pub strategy: u8,
+    /// Bot's signal confidence at entry, in basis points
+    pub entry_confidence_bps: u16,
+    /// Realized profit/loss relative to `amount_sol`, in basis points
+    pub pnl_bps: i32,
+    /// Gain over entry that arms the trailing stop, in basis points
+    pub trailing_activation_bps: u16,
+    /// Distance of the trailing stop below `highest_price_seen`, in basis points (0 = none)
+    pub trailing_distance_bps: u16,
+    /// Highest price reported through `update_trailing` (scaled by `PRICE_SCALE`)
+    pub highest_price_seen: u64,
+    /// Whether price has reached the trailing-stop activation level
+    pub trailing_active: bool,
+    /// Whether the bot closed the position on its trailing stop
+    pub closed_by_trailing_stop: bool,
+}
+
+impl DelegationAccount {
+    /// Borsh-serialized size, excluding the 8-byte discriminator
+    pub const LEN: usize = 32 * 2 // user, bot_authority
+        + 2 // vault_index, strategy
+        + 8 // max_position_size_sol
+        + 3 // max_concurrent_trades, is_active, active_trades
+        + 8 * 6 // total_trades .. last_trade_at
+        + 1 // bump
+        + 8 // position_counter
+        + 8 // min_liquidity_sol
+        + 2 // performance_fee_bps
+        + 8; // fees_owed
+
+    /// Reject tokens whose liquidity, as reported by the bot, is below the user's floor
+    pub fn check_liquidity(&self, liquidity_sol: u64) -> Result<()> {
+        require!(
+            liquidity_sol >= self.min_liquidity_sol,
+            VaultError::InsufficientLiquidity
+        );
+
+        Ok(())
+    }
+
+    /// Record `closed` positions with a combined `pnl`, `profitable` of which made money
+    pub fn record_closes(&mut self, closed: u8, pnl: i64, profitable: u64) -> Result<()> {
+        self.active_trades = self.active_trades
+            .checked_sub(closed)
+            .ok_or(VaultError::MathOverflow)?;
+        self.total_pnl = self.total_pnl
+            .checked_add(pnl)
+            .ok_or(VaultError::MathOverflow)?;
+        self.profitable_trades = self.profitable_trades
+            .checked_add(profitable)
+            .ok_or(VaultError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// A delegation can only be closed with no open trades and no uncollected fees
+    pub fn check_closable(&self) -> Result<()> {
+        require!(self.active_trades == 0, VaultError::HasActiveTrades);
+        require!(self.fees_owed == 0, VaultError::FeesOutstanding);
+
+        Ok(())
+    }
+
+    /// Add the performance fee on one close's `pnl` to `fees_owed`; losses owe nothing
+    ///
+    /// `pnl` comes from the bot-reported `amount_received`, which the program can't
+    /// verify, so the fee is charged on at most `amount_sol` of gain. That bounds
+    /// what an inflated report can make the user owe before closing the delegation.
+    pub fn accrue_fee(&mut self, pnl: i64, amount_sol: u64) -> Result<u64> {
+        if pnl <= 0 {
+            return Ok(0);
+        }
+
+        // The taxed gain is at most amount_sol and fee_bps <= 10_000, so the fee fits in u64
+        let taxed = (pnl as u64).min(amount_sol);
+        let fee = (taxed as u128 * u128::from(self.performance_fee_bps) / 10_000) as u64;
+        self.fees_owed = self.fees_owed
+            .checked_add(fee)
+            .ok_or(VaultError::MathOverflow)?;
+
+        Ok(fee)
+    }
+}
+
+impl Position {
+    /// Borsh-serialized size, excluding the 8-byte discriminator
+    pub const LEN: usize = 32 * 3 // delegation, user, token_mint
+        + 8 * 5 // amount_sol and the four prices
+        + 1 // status
+        + 8 * 3 // opened_at, closed_at, pnl
+        + 8 * 2 // position_id, client_order_id
+        + 2 // bump, strategy
+        + 2 // entry_confidence_bps
+        + 4 // pnl_bps
+        + 2 * 2 // trailing_activation_bps, trailing_distance_bps
+        + 8 // highest_price_seen
+        + 2; // trailing_active, closed_by_trailing_stop
+
+    /// Whether `open_position` has filled in this account; a freshly created one is zeroed
+    pub fn is_initialized(&self) -> bool {
+        self.delegation != Pubkey::default()
+    }
+
+    /// Mark an open position of `delegation` liquidated with unknown (zero) PnL;
+    /// returns whether it was open, so already-closed positions are skipped
+    pub fn force_liquidate(&mut self, delegation: Pubkey, closed_at: i64) -> Result<bool> {
+        require!(self.delegation == delegation, VaultError::InvalidPosition);
+
+        if self.status != PositionStatus::Open as u8 {
+            return Ok(false);
+        }
+
+        self.status = PositionStatus::Liquidated as u8;
+        self.closed_at = closed_at;
+        self.pnl = 0;
+        self.pnl_bps = 0;
+
+        Ok(true)
+    }
+
+    /// Tag the position with the strategy and confidence that opened it
+    pub fn record_entry_metadata(&mut self, strategy: u8, entry_confidence_bps: u16) -> Result<()> {
+        require!(strategy <= MAX_POSITION_STRATEGY, VaultError::InvalidStrategy);
+        require!(entry_confidence_bps <= MAX_CONFIDENCE_BPS, VaultError::InvalidConfidence);
+
+        self.strategy = strategy;
+        self.entry_confidence_bps = entry_confidence_bps;
+
+        Ok(())
+    }
+
+    /// Store the trailing stop the bot opened with; the high-water mark starts at entry
+    pub fn record_trailing_params(&mut self, activation_bps: u16, distance_bps: u16) -> Result<()> {
+        require!(distance_bps <= MAX_TRAILING_DISTANCE_BPS, VaultError::InvalidTrailingParams);
+
+        self.trailing_activation_bps = activation_bps;
+        self.trailing_distance_bps = distance_bps;
+        self.highest_price_seen = self.entry_price;
+        self.trailing_active = false;
+
+        Ok(())
+    }
+
+    /// `entry_price * (1 + activation)`, the price that arms the trailing stop
+    pub fn trailing_activation_price(&self) -> u64 {
+        let price = self.entry_price as u128 * (10_000 + self.trailing_activation_bps as u128) / 10_000;
+        price.min(u64::MAX as u128) as u64
+    }
+
+    /// Where the armed trailing stop sits; `None` until it activates
+    pub fn trailing_stop_price(&self) -> Option<u64> {
+        self.trailing_active.then(|| {
+            (self.highest_price_seen as u128 * (10_000 - self.trailing_distance_bps as u128) / 10_000) as u64
+        })
+    }
+
+    /// Record `current_price` for an open position and return whether it just armed the trailing stop
+    pub fn update_trailing(&mut self, current_price: u64) -> Result<bool> {
+        require!(
+            self.status == PositionStatus::Open as u8,
+            VaultError::PositionNotOpen
+        );
+        require!(current_price > 0, VaultError::InvalidPrice);
+
+        self.current_price = current_price;
+        self.highest_price_seen = self.highest_price_seen.max(current_price);
+
+        let activates = !self.trailing_active
+            && self.trailing_distance_bps > 0
+            && current_price >= self.trailing_activation_price();
+        if activates {
+            self.trailing_active = true;
+        }
+
+        Ok(activates)
+    }
+
+    /// Note whether the close is the trailing stop firing, which requires it to be armed
+    pub fn record_exit_trigger(&mut self, trailing_stop: bool) -> Result<()> {
+        require!(!trailing_stop || self.trailing_active, VaultError::TrailingStopNotActive);
+
+        self.closed_by_trailing_stop = trailing_stop;
+
+        Ok(())
+    }
+
+    /// Mark an open position of `delegation` closed and return its PnL in lamports
+    pub fn settle_close(
+        &mut self,
+        delegation: Pubkey,
+        exit_price: u64,
+        amount_received: u64,
+        closed_at: i64,
+    ) -> Result<i64> {
+        require!(
+            self.status == PositionStatus::Open as u8,
+            VaultError::PositionNotOpen
+        );
+        require!(self.delegation == delegation, VaultError::InvalidPosition);
+
+        // Calculate PnL (can be negative)
+        let pnl = i64::try_from(amount_received)
+            .ok()
+            .zip(i64::try_from(self.amount_sol).ok())
+            .and_then(|(received, invested)| received.checked_sub(invested))
+            .ok_or(VaultError::MathOverflow)?;
+
+        self.current_price = exit_price;
+        self.status = PositionStatus::Closed as u8;
+        self.closed_at = closed_at;
+        self.pnl = pnl;
+        self.pnl_bps = pnl_bps(amount_received, self.amount_sol);
+
+        Ok(pnl)
+    }
+}
+
+#[account]
+pub struct MintWhitelist {
+    /// Delegation this whitelist restricts
+    pub delegation: Pubkey,
+    /// Mints the bot may open positions in (empty = all allowed)
+    pub mints: Vec<Pubkey>,
     /// PDA bump seed
     pub bump: u8,
 }
 
+impl MintWhitelist {
+    /// Space for a full whitelist, so later updates never need a realloc
+    pub const SPACE: usize = 8 + 32 + 4 + 32 * MAX_WHITELIST_MINTS + 1;
+}
+
+/// Whether `mint` may be traded under `whitelist`; no whitelist or an empty one allows all
+pub fn mint_allowed(whitelist: Option<&MintWhitelist>, mint: &Pubkey) -> bool {
+    match whitelist {
+        Some(whitelist) => whitelist.mints.is_empty() || whitelist.mints.contains(mint),
+        None => true,
+    }
+}
+
+/// `(amount_received - amount_sol) * 10000 / amount_sol`, clamped to the i32 range
+pub fn pnl_bps(amount_received: u64, amount_sol: u64) -> i32 {
+    if amount_sol == 0 {
+        return 0;
+    }
+
+    let bps = (amount_received as i128 - amount_sol as i128) * 10_000 / amount_sol as i128;
+    bps.clamp(i32::MIN as i128, i32::MAX as i128) as i32
+}
+
 #[repr(u8)]
 #[derive(Clone, Copy, PartialEq)]
 pub enum PositionStatus {
@@ -515,7 +1019,7 @@ pub struct InitializeConfig<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + std::mem::size_of::<GlobalConfig>(),
+        space = 8 + GlobalConfig::LEN,
         seeds = [b"config"],
         bump
     )]
@@ -554,7 +1058,7 @@ pub struct CreateDelegation<'info> {
     #[account(
         init,
         payer = user,
-        space = 8 + std::mem::size_of::<DelegationAccount>(),
+        space = 8 + DelegationAccount::LEN,
         seeds = [b"delegation", user.key().as_ref(), &[vault_index]],
         bump
     )]
@@ -599,6 +1103,21 @@ pub struct ChangeBotAuthority<'info> {
     pub user: Signer<'info>,
 }
 
+#[derive(Accounts)]
+#[instruction(vault_index: u8)]
+pub struct EmergencyWithdraw<'info> {
+    #[account(
+        mut,
+        seeds = [b"delegation", user.key().as_ref(), &[vault_index]],
+        bump = delegation.bump,
+        has_one = user,
+        constraint = delegation.vault_index == vault_index
+    )]
+    pub delegation: Account<'info, DelegationAccount>,
+
+    pub user: Signer<'info>,
+}
+
 #[derive(Accounts)]
 #[instruction(vault_index: u8)]
 pub struct RevokeDelegation<'info> {
@@ -614,6 +1133,34 @@ pub struct RevokeDelegation<'info> {
     pub user: Signer<'info>,
 }
 
+#[derive(Accounts)]
+#[instruction(vault_index: u8)]
+pub struct CollectDelegationFees<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"delegation", user.key().as_ref(), &[vault_index]],
+        bump = delegation.bump,
+        has_one = user,
+        constraint = delegation.vault_index == vault_index
+    )]
+    pub delegation: Account<'info, DelegationAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Fee recipient, pinned to the protocol authority
+    #[account(mut, address = config.authority)]
+    pub operator: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 #[instruction(vault_index: u8)]
 pub struct CloseDelegation<'info> {
@@ -639,6 +1186,33 @@ pub struct CloseDelegation<'info> {
 }
 
 #[derive(Accounts)]
+#[instruction(vault_index: u8)]
+pub struct SetMintWhitelist<'info> {
+    #[account(
+        seeds = [b"delegation", user.key().as_ref(), &[vault_index]],
+        bump = delegation.bump,
+        has_one = user,
+        constraint = delegation.vault_index == vault_index
+    )]
+    pub delegation: Account<'info, DelegationAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = MintWhitelist::SPACE,
+        seeds = [b"mint_whitelist", delegation.key().as_ref()],
+        bump
+    )]
+    pub mint_whitelist: Account<'info, MintWhitelist>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(client_order_id: u64)]
 pub struct OpenPosition<'info> {
     #[account(
         seeds = [b"config"],
@@ -655,18 +1229,25 @@ pub struct OpenPosition<'info> {
     pub delegation: Account<'info, DelegationAccount>,
 
     #[account(
-        init,
+        init_if_needed,
         payer = bot_authority,
-        space = 8 + std::mem::size_of::<Position>(),
+        space = 8 + Position::LEN,
         seeds = [
             b"position",
             delegation.key().as_ref(),
-            &delegation.position_counter.to_le_bytes()
+            &client_order_id.to_le_bytes()
         ],
         bump
     )]
     pub position: Account<'info, Position>,
 
+    /// CHECK: Whitelist PDA for this delegation; uninitialized when the user never set one
+    #[account(
+        seeds = [b"mint_whitelist", delegation.key().as_ref()],
+        bump
+    )]
+    pub mint_whitelist: UncheckedAccount<'info>,
+
     /// CHECK: User's wallet (not signing, just for balance check)
     pub user: AccountInfo<'info>,
 
@@ -691,7 +1272,31 @@ pub struct ClosePosition<'info> {
         seeds = [
             b"position",
             delegation.key().as_ref(),
-            &position.position_id.to_le_bytes()
+            &position.client_order_id.to_le_bytes()
+        ],
+        bump = position.bump,
+        constraint = position.delegation == delegation.key()
+    )]
+    pub position: Account<'info, Position>,
+
+    pub bot_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTrailing<'info> {
+    #[account(
+        seeds = [b"delegation", delegation.user.as_ref(), &[delegation.vault_index]],
+        bump = delegation.bump,
+        has_one = bot_authority
+    )]
+    pub delegation: Account<'info, DelegationAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"position",
+            delegation.key().as_ref(),
+            &position.client_order_id.to_le_bytes()
         ],
         bump = position.bump,
         constraint = position.delegation == delegation.key()
@@ -701,6 +1306,19 @@ pub struct ClosePosition<'info> {
     pub bot_authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct ClosePositionsBatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"delegation", delegation.user.as_ref(), &[delegation.vault_index]],
+        bump = delegation.bump,
+        has_one = bot_authority
+    )]
+    pub delegation: Account<'info, DelegationAccount>,
+
+    pub bot_authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct ClosePositionAccount<'info> {
     #[account(
@@ -708,7 +1326,7 @@ pub struct ClosePositionAccount<'info> {
         seeds = [
             b"position",
             position.delegation.as_ref(),
-            &position.position_id.to_le_bytes()
+            &position.client_order_id.to_le_bytes()
         ],
         bump = position.bump,
         constraint = position.user == user.key(),
@@ -751,6 +1369,8 @@ pub struct DelegationCreated {
     pub max_position_size_sol: u64,
     pub max_concurrent_trades: u8,
     pub timestamp: i64,
+    pub min_liquidity_sol: u64,
+    pub performance_fee_bps: u16,
 }
 
 #[event]
@@ -762,6 +1382,7 @@ pub struct DelegationUpdated {
     pub max_concurrent_trades: u8,
     pub is_active: bool,
     pub timestamp: i64,
+    pub min_liquidity_sol: u64,
 }
 
 #[event]
@@ -773,6 +1394,24 @@ pub struct BotAuthorityChanged {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct EmergencyWithdrawn {
+    pub user: Pubkey,
+    pub vault_index: u8,
+    pub positions_liquidated: u8,
+    pub active_trades_remaining: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegationFeesCollected {
+    pub user: Pubkey,
+    pub vault_index: u8,
+    pub operator: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct DelegationRevoked {
     pub user: Pubkey,
@@ -790,26 +1429,62 @@ pub struct DelegationClosed {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct MintWhitelistUpdated {
+    pub user: Pubkey,
+    pub vault_index: u8,
+    pub mint_count: u8,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct PositionOpened {
     pub user: Pubkey,
+    pub vault_index: u8,
     pub position_id: u64,
+    pub client_order_id: u64,
     pub token_mint: Pubkey,
     pub amount_sol: u64,
     pub entry_price: u64,
     pub take_profit_price: u64,
     pub stop_loss_price: u64,
+    pub strategy: u8,
+    pub entry_confidence_bps: u16,
+    pub timestamp: i64,
+    pub trailing_activation_bps: u16,
+    pub trailing_distance_bps: u16,
+}
+
+#[event]
+pub struct TrailingStopActivated {
+    pub user: Pubkey,
+    pub position_id: u64,
+    pub token_mint: Pubkey,
+    pub entry_price: u64,
+    pub activation_price: u64,
     pub timestamp: i64,
 }
 
 #[event]
 pub struct PositionClosed {
     pub user: Pubkey,
+    pub vault_index: u8,
     pub position_id: u64,
     pub token_mint: Pubkey,
     pub entry_price: u64,
     pub exit_price: u64,
     pub pnl: i64,
+    pub pnl_bps: i32,
+    pub timestamp: i64,
+    pub trailing_stop: bool,
+}
+
+#[event]
+pub struct PositionLiquidated {
+    pub user: Pubkey,
+    pub vault_index: u8,
+    pub position_id: u64,
+    pub token_mint: Pubkey,
     pub timestamp: i64,
 }
 
@@ -854,4 +1529,143 @@ pub enum VaultError {
     HasActiveTrades,
     #[msg("Math overflow")]
     MathOverflow,
+    #[msg("Too many positions in one batch (max 8)")]
+    BatchTooLarge,
+    #[msg("Exit prices and amounts must match the positions passed")]
+    BatchLengthMismatch,
+    #[msg("Token mint is not in this delegation's whitelist")]
+    MintNotWhitelisted,
+    #[msg("Too many mints in whitelist (max 32)")]
+    WhitelistTooLarge,
+    #[msg("Entry confidence must be at most 10000 bps")]
+    InvalidConfidence,
+    #[msg("Trailing distance must be below 10000 bps")]
+    InvalidTrailingParams,
+    #[msg("Trailing stop was never activated for this position")]
+    TrailingStopNotActive,
+    #[msg("Token liquidity is below this delegation's minimum")]
+    InsufficientLiquidity,
+    #[msg("Performance fee must be at most 3000 bps")]
+    FeeTooHigh,
+    #[msg("No performance fees are owed on this delegation")]
+    NoFeesOwed,
+    #[msg("Delegation still owes performance fees")]
+    FeesOutstanding,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delegation(active_trades: u8) -> DelegationAccount {
+        DelegationAccount {
+            user: Pubkey::new_unique(),
+            bot_authority: Pubkey::new_unique(),
+            vault_index: 0,
+            strategy: 0,
+            max_position_size_sol: LAMPORTS_PER_SOL,
+            max_concurrent_trades: 5,
+            is_active: true,
+            active_trades,
+            total_trades: u64::from(active_trades),
+            profitable_trades: 0,
+            total_pnl: 0,
+            total_volume: 0,
+            created_at: 0,
+            last_trade_at: 0,
+            bump: 255,
+            position_counter: u64::from(active_trades),
+            min_liquidity_sol: 0,
+            performance_fee_bps: 0,
+            fees_owed: 0,
+        }
+    }
+
+    fn open_position(delegation: Pubkey, position_id: u64, amount_sol: u64) -> Position {
+        Position {
+            delegation,
+            user: Pubkey::new_unique(),
+            token_mint: Pubkey::new_unique(),
+            amount_sol,
+            entry_price: 1_000_000,
+            current_price: 1_000_000,
+            take_profit_price: 2_000_000,
+            stop_loss_price: 500_000,
+            status: PositionStatus::Open as u8,
+            opened_at: 0,
+            closed_at: 0,
+            pnl: 0,
+            position_id,
+            client_order_id: position_id,
+            bump: 255,
+            strategy: 0,
+            entry_confidence_bps: 0,
+            pnl_bps: 0,
+            trailing_activation_bps: 0,
+            trailing_distance_bps: 0,
+            highest_price_seen: 1_000_000,
+            trailing_active: false,
+            closed_by_trailing_stop: false,
+        }
+    }
+
+    fn serialized_len<T: AccountSerialize>(account: &T) -> usize {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data.len()
+    }
+
+    #[test]
+    fn test_account_len_matches_serialized_size() {
+        let config = GlobalConfig {
+            authority: Pubkey::new_unique(),
+            emergency_authority: Pubkey::new_unique(),
+            is_paused: true,
+            total_delegations: 1,
+            total_positions: 1,
+            bump: 255,
+        };
+        let mut position = open_position(Pubkey::new_unique(), 1, 1);
+        position.pnl_bps = -1;
+
+        assert_eq!(serialized_len(&config), 8 + GlobalConfig::LEN);
+        assert_eq!(serialized_len(&delegation(1)), 8 + DelegationAccount::LEN);
+        assert_eq!(serialized_len(&position), 8 + Position::LEN);
+    }
+
+    fn whitelist(mints: Vec<Pubkey>) -> MintWhitelist {
+        MintWhitelist {
+            delegation: Pubkey::new_unique(),
+            mints,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn test_whitelisted_mint_is_allowed() {
+        let mint = Pubkey::new_unique();
+        let whitelist = whitelist(vec![Pubkey::new_unique(), mint]);
+
+        assert!(mint_allowed(Some(&whitelist), &mint));
+    }
+
+    #[test]
+    fn test_mint_outside_whitelist_is_rejected() {
+        let whitelist = whitelist(vec![Pubkey::new_unique()]);
+
+        assert!(!mint_allowed(Some(&whitelist), &Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_no_whitelist_allows_all() {
+        assert!(mint_allowed(None, &Pubkey::new_unique()));
+        assert!(mint_allowed(Some(&whitelist(Vec::new())), &Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_pnl_bps_clamps_extreme_returns() {
+        assert_eq!(pnl_bps(u64::MAX, 1), i32::MAX);
+        assert_eq!(pnl_bps(0, 1_000), -10_000);
+        assert_eq!(pnl_bps(1_000, 0), 0);
+    }
 }
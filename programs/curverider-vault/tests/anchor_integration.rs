@@ -1,553 +1,306 @@
 use anchor_lang::prelude::*;
-use solana_sdk::signer::Signer;
-use anchor_lang::solana_program::system_program;
-use anchor_lang::prelude::Pubkey;
-use anchor_lang::prelude::ToAccountInfo;
-use anchor_lang::prelude::System;
-use anchor_lang::prelude::AccountMeta;
-use anchor_lang::prelude::Key;
-use anchor_lang::prelude::Program;
+use curverider_vault_client::{
+    claim_fees_ix, close_position_ix, close_vault_ix, decode_position, decode_user_account,
+    decode_vault, deposit_ix, initialize_vault_ix, open_position_ix, position_pda, update_vault_config_ix,
+    user_account_pda, vault_pda, withdraw_ix, VaultConfigUpdate, VaultParams, PROGRAM_ID,
+};
+use solana_program_test::{tokio, BanksClient, BanksClientError, ProgramTest};
+use solana_sdk::{
+    hash::Hash, instruction::Instruction, signature::Keypair, signer::Signer,
+    transaction::Transaction,
+};
+
+const VAULT_PARAMS: VaultParams = VaultParams {
+    min_deposit: 1_000_000,
+    max_deposit: 10_000_000,
+    management_fee_bps: 100,
+    performance_fee_bps: 2000,
+    vault_capacity: u64::MAX,
+    per_user_cap: u64::MAX,
+    deposit_mint: None,
+};
+
+/// Start the program and fund each of `wallets` with 2 SOL
+async fn start(wallets: &[&Keypair]) -> (BanksClient, Hash) {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program("curverider_vault", PROGRAM_ID, None);
 
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
 
+    let fund_ixs: Vec<Instruction> = wallets
+        .iter()
+        .map(|wallet| solana_sdk::system_instruction::transfer(&payer.pubkey(), &wallet.pubkey(), 2_000_000_000))
+        .collect();
+    let fund_tx = Transaction::new_signed_with_payer(&fund_ixs, Some(&payer.pubkey()), &[&payer], recent_blockhash);
+    banks_client.process_transaction(fund_tx).await.unwrap();
 
+    (banks_client, recent_blockhash)
+}
 
-use anchor_lang::prelude::*;
-use solana_program_test::{processor, ProgramTest};
-use std::str::FromStr;
+/// Send `ix` paid for by the first of `signers`
+async fn send(
+    banks_client: &mut BanksClient,
+    recent_blockhash: Hash,
+    ix: Instruction,
+    signers: &[&Keypair],
+) -> std::result::Result<(), BanksClientError> {
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&signers[0].pubkey()), signers, recent_blockhash);
+    banks_client.process_transaction(tx).await
+}
+
+async fn account_data(banks_client: &mut BanksClient, address: Pubkey) -> Vec<u8> {
+    banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .expect("account not found")
+        .data
+}
 
 #[tokio::test]
 async fn test_vault_initialization() {
-    // Setup test context
-    let program_id = Pubkey::from_str("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS").unwrap();
-    let mut program_test = ProgramTest::default();
-    program_test.add_program(
-        "curverider-vault",
-        program_id,
-        None, // Anchor handles the entrypoint automatically
-    );
-    use anchor_lang::InstructionData;
-    use anchor_lang::ToAccountMetas;
-    use solana_program_test::tokio;
-    use solana_sdk::{signature::Keypair, signer::Signer, transaction::Transaction, system_program};
-
-    // Set up test context
-    let program_id = Pubkey::from_str("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS").unwrap();
-    let mut program_test = ProgramTest::default();
-    program_test.add_program(
-        "curverider_vault",
-        program_id,
-        None,
-    );
-
-    // Create authority keypair
     let authority = Keypair::new();
+    let (mut banks_client, recent_blockhash) = start(&[&authority]).await;
 
-    // Start test environment
-    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
-
-    // Derive vault PDA
-    let (vault_pda, vault_bump) = Pubkey::find_program_address(&[b"vault"], &program_id);
-
-    // Build instruction
-    let min_deposit = 1_000_000;
-    let max_deposit = 10_000_000;
-    let management_fee_bps = 100;
-    let performance_fee_bps = 2000;
-    let ix = anchor_lang::solana_program::instruction::Instruction {
-        program_id,
-        accounts: curverider_vault::accounts::InitializeVault {
-            vault: vault_pda,
-            authority: authority.pubkey(),
-            system_program: system_program::ID,
-        }
-        .to_account_metas(None),
-        data: curverider_vault::instruction::InitializeVault {
-            vault_bump,
-            min_deposit,
-            max_deposit,
-            management_fee_bps,
-            performance_fee_bps,
-        }
-        .data(),
-    };
-
-    // Fund authority
-    let fund_ix = solana_sdk::system_instruction::transfer(&payer.pubkey(), &authority.pubkey(), 2_000_000_000);
-    let fund_tx = Transaction::new_signed_with_payer(
-        &[fund_ix],
-        Some(&payer.pubkey()),
-        &[&payer],
-        recent_blockhash,
-    );
-    banks_client.process_transaction(fund_tx).await.unwrap();
-
-    // Send initialize transaction
-    let tx = Transaction::new_signed_with_payer(
-        &[ix],
-        Some(&authority.pubkey()),
-        &[&authority],
-        recent_blockhash,
-    );
-    banks_client.process_transaction(tx).await.unwrap();
-
-    // Fetch and assert vault state
-    let vault_account = banks_client
-        .get_account(vault_pda)
+    send(&mut banks_client, recent_blockhash, initialize_vault_ix(&authority.pubkey(), &authority.pubkey(), &VAULT_PARAMS), &[&authority])
         .await
-        .unwrap()
-        .expect("vault account not found");
-    let vault: curverider_vault::Vault = anchor_lang::AccountDeserialize::try_deserialize(&mut &vault_account.data[..]).unwrap();
+        .unwrap();
+
+    let vault = decode_vault(&account_data(&mut banks_client, vault_pda().0).await).unwrap();
     assert_eq!(vault.authority, authority.pubkey());
-    assert_eq!(vault.min_deposit, min_deposit);
-    assert_eq!(vault.max_deposit, max_deposit);
-    assert_eq!(vault.management_fee_bps, management_fee_bps);
-    assert_eq!(vault.performance_fee_bps, performance_fee_bps);
+    assert_eq!(vault.min_deposit, VAULT_PARAMS.min_deposit);
+    assert_eq!(vault.max_deposit, VAULT_PARAMS.max_deposit);
+    assert_eq!(vault.management_fee_bps, VAULT_PARAMS.management_fee_bps);
+    assert_eq!(vault.performance_fee_bps, VAULT_PARAMS.performance_fee_bps);
     assert!(vault.is_active);
 }
 
 #[tokio::test]
 async fn test_deposit_withdraw() {
-    use anchor_lang::InstructionData;
-    use anchor_lang::ToAccountMetas;
-    use solana_program_test::tokio;
-    use solana_sdk::{signature::Keypair, signer::Signer, transaction::Transaction, system_program};
-
-    let program_id = Pubkey::from_str("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS").unwrap();
-    let mut program_test = ProgramTest::default();
-    program_test.add_program(
-        "curverider-vault",
-        program_id,
-        None,
-    );
-
-    // Create authority and user keypairs
     let authority = Keypair::new();
     let user = Keypair::new();
+    let (mut banks_client, recent_blockhash) = start(&[&authority, &user]).await;
 
-    // Start test environment
-    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
-
-    // Derive vault PDA
-    let (vault_pda, vault_bump) = Pubkey::find_program_address(&[b"vault"], &program_id);
-    let (user_account_pda, _user_bump) = Pubkey::find_program_address(&[b"user", user.pubkey().as_ref()], &program_id);
-
-    // Fund authority and user
-    let fund_ixs = vec![
-        solana_sdk::system_instruction::transfer(&payer.pubkey(), &authority.pubkey(), 2_000_000_000),
-        solana_sdk::system_instruction::transfer(&payer.pubkey(), &user.pubkey(), 2_000_000_000),
-    ];
-    let fund_tx = Transaction::new_signed_with_payer(
-        &fund_ixs,
-        Some(&payer.pubkey()),
-        &[&payer],
-        recent_blockhash,
-    );
-    banks_client.process_transaction(fund_tx).await.unwrap();
-
-    // Initialize vault
-    let min_deposit = 1_000_000;
-    let max_deposit = 10_000_000;
-    let management_fee_bps = 100;
-    let performance_fee_bps = 2000;
-    let ix = anchor_lang::solana_program::instruction::Instruction {
-        program_id,
-        accounts: curverider_vault::accounts::InitializeVault {
-            vault: vault_pda,
-            authority: authority.pubkey(),
-            system_program: system_program::ID,
-        }
-        .to_account_metas(None),
-        data: curverider_vault::instruction::InitializeVault {
-            vault_bump,
-            min_deposit,
-            max_deposit,
-            management_fee_bps,
-            performance_fee_bps,
-        }
-        .data(),
-    };
-    let tx = Transaction::new_signed_with_payer(
-        &[ix],
-        Some(&authority.pubkey()),
-        &[&authority],
-        recent_blockhash,
-    );
-    banks_client.process_transaction(tx).await.unwrap();
+    send(&mut banks_client, recent_blockhash, initialize_vault_ix(&authority.pubkey(), &authority.pubkey(), &VAULT_PARAMS), &[&authority])
+        .await
+        .unwrap();
 
     // Deposit
     let deposit_amount = 2_000_000;
-    let deposit_ix = anchor_lang::solana_program::instruction::Instruction {
-        program_id,
-        accounts: curverider_vault::accounts::Deposit {
-            vault: vault_pda,
-            user_account: user_account_pda,
-            user: user.pubkey(),
-            system_program: system_program::ID,
-        }
-        .to_account_metas(None),
-        data: curverider_vault::instruction::Deposit {
-            amount: deposit_amount,
-        }
-        .data(),
-    };
-    let deposit_tx = Transaction::new_signed_with_payer(
-        &[deposit_ix],
-        Some(&user.pubkey()),
-        &[&user],
-        recent_blockhash,
-    );
-    banks_client.process_transaction(deposit_tx).await.unwrap();
+    send(&mut banks_client, recent_blockhash, deposit_ix(&user.pubkey(), deposit_amount, None), &[&user])
+        .await
+        .unwrap();
 
-    // Fetch and assert vault and user state after deposit
-    let vault_account = banks_client.get_account(vault_pda).await.unwrap().expect("vault not found");
-    let vault: curverider_vault::Vault = anchor_lang::AccountDeserialize::try_deserialize(&mut &vault_account.data[..]).unwrap();
+    let vault = decode_vault(&account_data(&mut banks_client, vault_pda().0).await).unwrap();
     assert_eq!(vault.total_deposited, deposit_amount);
     assert_eq!(vault.total_shares, deposit_amount);
 
-    let user_account = banks_client.get_account(user_account_pda).await.unwrap().expect("user account not found");
-    let user_acc: curverider_vault::UserAccount = anchor_lang::AccountDeserialize::try_deserialize(&mut &user_account.data[..]).unwrap();
+    let user_acc = decode_user_account(&account_data(&mut banks_client, user_account_pda(&user.pubkey())).await).unwrap();
     assert_eq!(user_acc.shares, deposit_amount);
     assert_eq!(user_acc.total_deposited, deposit_amount);
 
     // Withdraw
-    let withdraw_ix = anchor_lang::solana_program::instruction::Instruction {
-        program_id,
-        accounts: curverider_vault::accounts::Withdraw {
-            vault: vault_pda,
-            user_account: user_account_pda,
-            user: user.pubkey(),
-            system_program: system_program::ID,
-        }
-        .to_account_metas(None),
-        data: curverider_vault::instruction::Withdraw {
-            shares_to_burn: deposit_amount,
-        }
-        .data(),
-    };
-    let withdraw_tx = Transaction::new_signed_with_payer(
-        &[withdraw_ix],
-        Some(&user.pubkey()),
-        &[&user],
+    send(
+        &mut banks_client,
         recent_blockhash,
-    );
-    banks_client.process_transaction(withdraw_tx).await.unwrap();
+        withdraw_ix(&user.pubkey(), deposit_amount, deposit_amount, None),
+        &[&user],
+    )
+    .await
+    .unwrap();
 
-    // Fetch and assert vault and user state after withdrawal
-    let vault_account = banks_client.get_account(vault_pda).await.unwrap().expect("vault not found");
-    let vault: curverider_vault::Vault = anchor_lang::AccountDeserialize::try_deserialize(&mut &vault_account.data[..]).unwrap();
+    let vault = decode_vault(&account_data(&mut banks_client, vault_pda().0).await).unwrap();
     assert_eq!(vault.total_deposited, 0);
     assert_eq!(vault.total_shares, 0);
 
-    let user_account = banks_client.get_account(user_account_pda).await.unwrap().expect("user account not found");
-    let user_acc: curverider_vault::UserAccount = anchor_lang::AccountDeserialize::try_deserialize(&mut &user_account.data[..]).unwrap();
+    let user_acc = decode_user_account(&account_data(&mut banks_client, user_account_pda(&user.pubkey())).await).unwrap();
     assert_eq!(user_acc.shares, 0);
 }
 
 #[tokio::test]
-async fn test_trading_logic() {
-    use anchor_lang::InstructionData;
-    use anchor_lang::ToAccountMetas;
-    use solana_program_test::tokio;
-    use solana_sdk::{signature::Keypair, signer::Signer, transaction::Transaction, system_program};
+async fn test_close_vault_after_full_withdrawal() {
+    let authority = Keypair::new();
+    let user = Keypair::new();
+    let (mut banks_client, recent_blockhash) = start(&[&authority, &user]).await;
 
-    let program_id = Pubkey::from_str("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS").unwrap();
-    let mut program_test = ProgramTest::default();
-    program_test.add_program(
-        "curverider-vault",
-        program_id,
-        None,
-    );
+    send(&mut banks_client, recent_blockhash, initialize_vault_ix(&authority.pubkey(), &authority.pubkey(), &VAULT_PARAMS), &[&authority])
+        .await
+        .unwrap();
+
+    let deposit_amount = 2_000_000;
+    send(&mut banks_client, recent_blockhash, deposit_ix(&user.pubkey(), deposit_amount, None), &[&user])
+        .await
+        .unwrap();
 
+    // Shares are still outstanding
+    let result = send(&mut banks_client, recent_blockhash, close_vault_ix(&authority.pubkey(), None), &[&authority]).await;
+    assert!(result.is_err());
+
+    send(&mut banks_client, recent_blockhash, withdraw_ix(&user.pubkey(), deposit_amount, 0, None), &[&user])
+        .await
+        .unwrap();
+    let authority_before = banks_client.get_balance(authority.pubkey()).await.unwrap();
+
+    send(&mut banks_client, recent_blockhash, close_vault_ix(&authority.pubkey(), None), &[&authority])
+        .await
+        .unwrap();
+
+    assert!(banks_client.get_account(vault_pda().0).await.unwrap().is_none());
+    assert!(banks_client.get_balance(authority.pubkey()).await.unwrap() > authority_before);
+}
+
+#[tokio::test]
+async fn test_claimed_fees_go_to_fee_recipient() {
     let authority = Keypair::new();
     let user = Keypair::new();
-    let bot = Keypair::new();
+    let treasury = Keypair::new();
+    let (mut banks_client, recent_blockhash) = start(&[&authority, &user, &treasury]).await;
 
-    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    send(&mut banks_client, recent_blockhash, initialize_vault_ix(&authority.pubkey(), &authority.pubkey(), &VAULT_PARAMS), &[&authority])
+        .await
+        .unwrap();
+    send(&mut banks_client, recent_blockhash, deposit_ix(&user.pubkey(), 2_000_000, None), &[&user])
+        .await
+        .unwrap();
 
-    let (vault_pda, vault_bump) = Pubkey::find_program_address(&[b"vault"], &program_id);
-    let (user_account_pda, _user_bump) = Pubkey::find_program_address(&[b"user", user.pubkey().as_ref()], &program_id);
-    let (position_pda, position_bump) = Pubkey::find_program_address(&[b"position", user.pubkey().as_ref(), &[0]], &program_id);
-
-    // Fund authority, user, and bot
-    let fund_ixs = vec![
-        solana_sdk::system_instruction::transfer(&payer.pubkey(), &authority.pubkey(), 2_000_000_000),
-        solana_sdk::system_instruction::transfer(&payer.pubkey(), &user.pubkey(), 2_000_000_000),
-        solana_sdk::system_instruction::transfer(&payer.pubkey(), &bot.pubkey(), 2_000_000_000),
-    ];
-    let fund_tx = Transaction::new_signed_with_payer(
-        &fund_ixs,
-        Some(&payer.pubkey()),
-        &[&payer],
+    let update = VaultConfigUpdate {
+        fee_recipient: Some(treasury.pubkey()),
+        ..Default::default()
+    };
+    send(&mut banks_client, recent_blockhash, update_vault_config_ix(&authority.pubkey(), &update), &[&authority])
+        .await
+        .unwrap();
+
+    // The old recipient is no longer accepted
+    let result = send(
+        &mut banks_client,
         recent_blockhash,
-    );
-    banks_client.process_transaction(fund_tx).await.unwrap();
+        claim_fees_ix(&authority.pubkey(), &authority.pubkey(), 100_000),
+        &[&authority],
+    )
+    .await;
+    assert!(result.is_err());
 
-    // Initialize vault
-    let min_deposit = 1_000_000;
-    let max_deposit = 10_000_000;
-    let management_fee_bps = 100;
-    let performance_fee_bps = 2000;
-    let ix = anchor_lang::solana_program::instruction::Instruction {
-        program_id,
-        accounts: curverider_vault::accounts::InitializeVault {
-            vault: vault_pda,
-            authority: authority.pubkey(),
-            system_program: system_program::ID,
-        }
-        .to_account_metas(None),
-        data: curverider_vault::instruction::InitializeVault {
-            vault_bump,
-            min_deposit,
-            max_deposit,
-            management_fee_bps,
-            performance_fee_bps,
-        }
-        .data(),
-    };
-    let tx = Transaction::new_signed_with_payer(
-        &[ix],
-        Some(&authority.pubkey()),
+    // Deposits aren't fees: nothing is claimable until lamports beyond them arrive
+    let result = send(
+        &mut banks_client,
+        recent_blockhash,
+        claim_fees_ix(&authority.pubkey(), &treasury.pubkey(), 50_000),
         &[&authority],
+    )
+    .await;
+    assert!(result.is_err());
+
+    let fee_payment = solana_sdk::system_instruction::transfer(&user.pubkey(), &vault_pda().0, 100_000);
+    send(&mut banks_client, recent_blockhash, fee_payment, &[&user])
+        .await
+        .unwrap();
+
+    let treasury_before = banks_client.get_balance(treasury.pubkey()).await.unwrap();
+    let authority_before = banks_client.get_balance(authority.pubkey()).await.unwrap();
+
+    send(
+        &mut banks_client,
         recent_blockhash,
-    );
-    banks_client.process_transaction(tx).await.unwrap();
+        claim_fees_ix(&authority.pubkey(), &treasury.pubkey(), 100_000),
+        &[&authority],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(banks_client.get_balance(treasury.pubkey()).await.unwrap(), treasury_before + 100_000);
+    // The authority only paid the transaction fee
+    assert!(banks_client.get_balance(authority.pubkey()).await.unwrap() < authority_before);
+}
+
+#[tokio::test]
+async fn test_trading_logic() {
+    let authority = Keypair::new();
+    let user = Keypair::new();
+    let (mut banks_client, recent_blockhash) = start(&[&authority, &user]).await;
+
+    send(&mut banks_client, recent_blockhash, initialize_vault_ix(&authority.pubkey(), &authority.pubkey(), &VAULT_PARAMS), &[&authority])
+        .await
+        .unwrap();
 
     // Deposit by user
     let deposit_amount = 2_000_000;
-    let deposit_ix = anchor_lang::solana_program::instruction::Instruction {
-        program_id,
-        accounts: curverider_vault::accounts::Deposit {
-            vault: vault_pda,
-            user_account: user_account_pda,
-            user: user.pubkey(),
-            system_program: system_program::ID,
-        }
-        .to_account_metas(None),
-        data: curverider_vault::instruction::Deposit {
-            amount: deposit_amount,
-        }
-        .data(),
-    };
-    let deposit_tx = Transaction::new_signed_with_payer(
-        &[deposit_ix],
-        Some(&user.pubkey()),
-        &[&user],
-        recent_blockhash,
-    );
-    banks_client.process_transaction(deposit_tx).await.unwrap();
+    send(&mut banks_client, recent_blockhash, deposit_ix(&user.pubkey(), deposit_amount, None), &[&user])
+        .await
+        .unwrap();
 
     // Open position (by authority/bot)
-    let token_mint = Pubkey::new_unique();
     let entry_price = 100_000;
-    let take_profit_price = 120_000;
-    let stop_loss_price = 90_000;
-    let open_position_ix = anchor_lang::solana_program::instruction::Instruction {
-        program_id,
-        accounts: curverider_vault::accounts::OpenPosition {
-            vault: vault_pda,
-            position: position_pda,
-            authority: authority.pubkey(),
-            system_program: system_program::ID,
-        }
-        .to_account_metas(None),
-        data: curverider_vault::instruction::OpenPosition {
-            token_mint,
-            amount_sol: deposit_amount,
-            entry_price,
-            take_profit_price,
-            stop_loss_price,
-        }
-        .data(),
-    };
-    let open_tx = Transaction::new_signed_with_payer(
-        &[open_position_ix],
-        Some(&authority.pubkey()),
-        &[&authority],
-        recent_blockhash,
+    let position_id = decode_vault(&account_data(&mut banks_client, vault_pda().0).await).unwrap().position_counter;
+    let position = position_pda(position_id);
+    let token_mint = Pubkey::new_unique();
+    let open_ix = open_position_ix(
+        &authority.pubkey(),
+        position_id,
+        token_mint,
+        deposit_amount,
+        entry_price,
+        120_000,
+        90_000,
     );
-    banks_client.process_transaction(open_tx).await.unwrap();
+    send(&mut banks_client, recent_blockhash, open_ix, &[&authority])
+        .await
+        .unwrap();
 
-    // Fetch and assert position state
-    let position_account = banks_client.get_account(position_pda).await.unwrap().expect("position not found");
-    let position: curverider_vault::Position = anchor_lang::AccountDeserialize::try_deserialize(&mut &position_account.data[..]).unwrap();
-    assert_eq!(position.amount_sol, deposit_amount);
-    assert_eq!(position.entry_price, entry_price);
-    assert_eq!(position.status, 0); // Open
+    let opened = decode_position(&account_data(&mut banks_client, position).await).unwrap();
+    assert_eq!(opened.amount_sol, deposit_amount);
+    assert_eq!(opened.entry_price, entry_price);
+    assert_eq!(opened.status, 0); // Open
+    assert_eq!(opened.position_id, position_id);
 
     // Close position
-    let exit_price = 110_000;
     let amount_received = deposit_amount + 100_000; // Simulate profit
-    let close_position_ix = anchor_lang::solana_program::instruction::Instruction {
-        program_id,
-        accounts: curverider_vault::accounts::ClosePosition {
-            vault: vault_pda,
-            position: position_pda,
-            authority: authority.pubkey(),
-        }
-        .to_account_metas(None),
-        data: curverider_vault::instruction::ClosePosition {
-            exit_price,
-            amount_received,
-        }
-        .data(),
-    };
-    let close_tx = Transaction::new_signed_with_payer(
-        &[close_position_ix],
-        Some(&authority.pubkey()),
-        &[&authority],
+    send(
+        &mut banks_client,
         recent_blockhash,
-    );
-    banks_client.process_transaction(close_tx).await.unwrap();
+        close_position_ix(&authority.pubkey(), &position, &token_mint, 110_000, amount_received),
+        &[&authority],
+    )
+    .await
+    .unwrap();
 
-    // Fetch and assert vault stats
-    let vault_account = banks_client.get_account(vault_pda).await.unwrap().expect("vault not found");
-    let vault: curverider_vault::Vault = anchor_lang::AccountDeserialize::try_deserialize(&mut &vault_account.data[..]).unwrap();
+    let vault = decode_vault(&account_data(&mut banks_client, vault_pda().0).await).unwrap();
     assert_eq!(vault.total_trades, 1);
     assert_eq!(vault.profitable_trades, 1);
     assert!(vault.total_pnl > 0);
-    // Position should be closed
-    let position_account = banks_client.get_account(position_pda).await.unwrap().expect("position not found");
-    let position: curverider_vault::Position = anchor_lang::AccountDeserialize::try_deserialize(&mut &position_account.data[..]).unwrap();
-    assert_eq!(position.status, 1); // Closed
-    assert_eq!(position.pnl, 100_000);
+
+    let closed = decode_position(&account_data(&mut banks_client, position).await).unwrap();
+    assert_eq!(closed.status, 1); // Closed
+    assert_eq!(closed.pnl, 100_000);
 }
 
 #[tokio::test]
 async fn test_error_cases() {
-    use anchor_lang::InstructionData;
-    use anchor_lang::ToAccountMetas;
-    use solana_program_test::tokio;
-    use solana_sdk::{signature::Keypair, signer::Signer, transaction::Transaction, system_program};
-
-    let program_id = Pubkey::from_str("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS").unwrap();
-    let mut program_test = ProgramTest::default();
-    program_test.add_program(
-        "curverider-vault",
-        program_id,
-        None,
-    );
-
     let authority = Keypair::new();
     let user = Keypair::new();
+    let (mut banks_client, recent_blockhash) = start(&[&authority, &user]).await;
 
-    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
-
-    let (vault_pda, vault_bump) = Pubkey::find_program_address(&[b"vault"], &program_id);
-    let (user_account_pda, _user_bump) = Pubkey::find_program_address(&[b"user", user.pubkey().as_ref()], &program_id);
-
-    // Fund authority and user
-    let fund_ixs = vec![
-        solana_sdk::system_instruction::transfer(&payer.pubkey(), &authority.pubkey(), 2_000_000_000),
-        solana_sdk::system_instruction::transfer(&payer.pubkey(), &user.pubkey(), 2_000_000_000),
-    ];
-    let fund_tx = Transaction::new_signed_with_payer(
-        &fund_ixs,
-        Some(&payer.pubkey()),
-        &[&payer],
-        recent_blockhash,
-    );
-    banks_client.process_transaction(fund_tx).await.unwrap();
-
-    // Initialize vault
-    let min_deposit = 1_000_000;
-    let max_deposit = 10_000_000;
-    let management_fee_bps = 100;
-    let performance_fee_bps = 2000;
-    let ix = anchor_lang::solana_program::instruction::Instruction {
-        program_id,
-        accounts: curverider_vault::accounts::InitializeVault {
-            vault: vault_pda,
-            authority: authority.pubkey(),
-            system_program: system_program::ID,
-        }
-        .to_account_metas(None),
-        data: curverider_vault::instruction::InitializeVault {
-            vault_bump,
-            min_deposit,
-            max_deposit,
-            management_fee_bps,
-            performance_fee_bps,
-        }
-        .data(),
-    };
-    let tx = Transaction::new_signed_with_payer(
-        &[ix],
-        Some(&authority.pubkey()),
-        &[&authority],
-        recent_blockhash,
-    );
-    banks_client.process_transaction(tx).await.unwrap();
+    send(&mut banks_client, recent_blockhash, initialize_vault_ix(&authority.pubkey(), &authority.pubkey(), &VAULT_PARAMS), &[&authority])
+        .await
+        .unwrap();
 
     // Try deposit below minimum
-    let deposit_amount = 100; // Below min_deposit
-    let deposit_ix = anchor_lang::solana_program::instruction::Instruction {
-        program_id,
-        accounts: curverider_vault::accounts::Deposit {
-            vault: vault_pda,
-            user_account: user_account_pda,
-            user: user.pubkey(),
-            system_program: system_program::ID,
-        }
-        .to_account_metas(None),
-        data: curverider_vault::instruction::Deposit {
-            amount: deposit_amount,
-        }
-        .data(),
-    };
-    let deposit_tx = Transaction::new_signed_with_payer(
-        &[deposit_ix],
-        Some(&user.pubkey()),
-        &[&user],
-        recent_blockhash,
-    );
-    let result = banks_client.process_transaction(deposit_tx).await;
+    let result = send(&mut banks_client, recent_blockhash, deposit_ix(&user.pubkey(), 100, None), &[&user]).await;
     assert!(result.is_err());
 
     // Now deposit a valid amount
     let valid_deposit = 2_000_000;
-    let deposit_ix = anchor_lang::solana_program::instruction::Instruction {
-        program_id,
-        accounts: curverider_vault::accounts::Deposit {
-            vault: vault_pda,
-            user_account: user_account_pda,
-            user: user.pubkey(),
-            system_program: system_program::ID,
-        }
-        .to_account_metas(None),
-        data: curverider_vault::instruction::Deposit {
-            amount: valid_deposit,
-        }
-        .data(),
-    };
-    let deposit_tx = Transaction::new_signed_with_payer(
-        &[deposit_ix],
-        Some(&user.pubkey()),
-        &[&user],
-        recent_blockhash,
-    );
-    banks_client.process_transaction(deposit_tx).await.unwrap();
+    send(&mut banks_client, recent_blockhash, deposit_ix(&user.pubkey(), valid_deposit, None), &[&user])
+        .await
+        .unwrap();
 
     // Try to withdraw more shares than owned
-    let withdraw_ix = anchor_lang::solana_program::instruction::Instruction {
-        program_id,
-        accounts: curverider_vault::accounts::Withdraw {
-            vault: vault_pda,
-            user_account: user_account_pda,
-            user: user.pubkey(),
-            system_program: system_program::ID,
-        }
-        .to_account_metas(None),
-        data: curverider_vault::instruction::Withdraw {
-            shares_to_burn: valid_deposit + 1_000_000, // More than owned
-        }
-        .data(),
-    };
-    let withdraw_tx = Transaction::new_signed_with_payer(
-        &[withdraw_ix],
-        Some(&user.pubkey()),
-        &[&user],
+    let result = send(
+        &mut banks_client,
         recent_blockhash,
-    );
-    let result = banks_client.process_transaction(withdraw_tx).await;
+        withdraw_ix(&user.pubkey(), valid_deposit + 1_000_000, 0, None),
+        &[&user],
+    )
+    .await;
     assert!(result.is_err());
 }